@@ -0,0 +1,67 @@
+//! Notices someone using the keyboard or mouse, so a job can hold its clicks while they're at
+//! the computer and only carry on once they've left it alone for a while. The system's idle
+//! time counts our own input too, so input the job sent is told apart by when it was sent
+//! (see [`crate::input::since_sent`]); a cursor that isn't where the job left it counts as well.
+
+use std::time::{Duration, Instant};
+
+/// Whether the system idle time can be read on this platform; elsewhere only cursor movement
+/// is noticed.
+pub const SUPPORTED: bool = cfg!(any(windows, target_os = "linux"));
+
+/// Input the system saw this much before our own last input is taken to be ours.
+const SLACK: Duration = Duration::from_millis(200);
+
+/// Time since the last keyboard or mouse input of any kind, ours included, if the system says.
+pub fn idle() -> Option<Duration> {
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::System::SystemInformation::GetTickCount;
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+        let mut info = LASTINPUTINFO { cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32, dwTime: 0 };
+        if unsafe { GetLastInputInfo(&mut info) } == 0 { return None; }
+        Some(Duration::from_millis(unsafe { GetTickCount() }.wrapping_sub(info.dwTime) as u64))
+    }
+    #[cfg(target_os = "linux")]
+    {
+        // the X11 screensaver extension's count; none without an X server (e.g. on Wayland)
+        use x11rb::connection::Connection;
+        use x11rb::protocol::screensaver::ConnectionExt;
+        let (conn, screen_num) = x11rb::connect(None).ok()?;
+        let root = conn.setup().roots.get(screen_num)?.root;
+        let info = conn.screensaver_query_info(root).ok()?.reply().ok()?;
+        Some(Duration::from_millis(info.ms_since_user_input as u64))
+    }
+    #[cfg(not(any(windows, target_os = "linux")))]
+    { None }
+}
+
+/// When the user last did something, pieced together from repeated looks.
+#[derive(Debug, Default)]
+pub struct Presence {
+    last_seen: Option<Instant>,
+    /// The cursor at the last look.
+    cursor: Option<(i32, i32)>,
+    checked_at: Option<Instant>,
+}
+
+impl Presence {
+    /// Take a look at `now`: the system `idle` time, how long ago we sent input ourselves and
+    /// where the cursor is (each `None` if unknown).
+    pub fn observe(&mut self, now: Instant, idle: Option<Duration>, since_sent: Option<Duration>, cursor: Option<(i32, i32)>) {
+        let sent_since_check = since_sent.is_some_and(|s| self.checked_at.is_none_or(|c| s <= now.duration_since(c)));
+        let moved = !sent_since_check && self.cursor.is_some() && cursor.is_some() && cursor != self.cursor;
+        if moved { self.last_seen = Some(now); }
+        if let Some(idle) = idle.filter(|&i| since_sent.is_none_or(|s| i + SLACK < s)) {
+            let at = now.checked_sub(idle).unwrap_or(now);
+            self.last_seen = Some(self.last_seen.map_or(at, |seen| seen.max(at)));
+        }
+        self.cursor = cursor;
+        self.checked_at = Some(now);
+    }
+
+    /// Whether the user did something within `period` before `now`.
+    pub fn active_within(&self, now: Instant, period: Duration) -> bool {
+        self.last_seen.is_some_and(|seen| now.saturating_duration_since(seen) < period)
+    }
+}
@@ -0,0 +1,185 @@
+//! `bench` subcommand: drives `human_mouse` against a simulated cursor on a simulated clock and
+//! reports what its paths look like (duration, peak speed, curvature, jitter spectrum), so
+//! `HumanMouseSettings` can be tuned against published human-motion figures without moving the
+//! real cursor for hours.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Serialize;
+
+use crate::human_mouse::{plan, Bounds, HumanMouseSettings, PathEvent, PathPoint};
+
+/// Screen the simulated moves are spread over.
+const SCREEN: (i32, i32) = (1920, 1080);
+
+/// Side of the square target each move aims for, about a button's size.
+const TARGET_PX: i32 = 40;
+
+/// Jitter power bands, upper edges in Hz (the last band is open).
+pub const BANDS_HZ: [f32; 3] = [5.0, 10.0, 20.0];
+
+/// Mean and percentiles of one metric over all moves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Stats {
+    pub mean: f32,
+    pub p5: f32,
+    pub p50: f32,
+    pub p95: f32,
+}
+
+impl Stats {
+    fn of(mut values: Vec<f32>) -> Self {
+        if values.is_empty() { return Self::default(); }
+        values.sort_by(f32::total_cmp);
+        let at = |q: f32| values[((values.len() - 1) as f32 * q).round() as usize];
+        Self { mean: values.iter().sum::<f32>() / values.len() as f32, p5: at(0.05), p50: at(0.5), p95: at(0.95) }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Report {
+    pub moves: usize,
+    pub seed: u64,
+    /// From the start of the move to the button going down, ms.
+    pub duration_ms: Stats,
+    /// Fastest stretch of each path, px/s.
+    pub peak_speed: Stats,
+    /// Path length over straight-line distance (1 = a straight line).
+    pub path_ratio: Stats,
+    /// Furthest point off the straight line, as a share of the distance.
+    pub max_deviation: Stats,
+    /// Strongest frequency in the sideways wobble of each path, Hz.
+    pub jitter_peak_hz: Stats,
+    /// Share of all sideways wobble power per band (see [`BANDS_HZ`]).
+    pub jitter_bands: [f32; 4],
+}
+
+/// Metrics of one planned move.
+struct PathMetrics {
+    duration_ms: f32,
+    peak_speed: f32,
+    path_ratio: f32,
+    max_deviation: f32,
+    jitter_peak_hz: f32,
+    band_power: [f32; 4],
+}
+
+/// Simulate `moves` moves between random spots with `settings` (each seeded from `seed`).
+pub fn run(moves: usize, settings: &HumanMouseSettings, seed: u64) -> Report {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut all = Vec::with_capacity(moves);
+    for i in 0..moves {
+        let from = (rng.gen_range(0..SCREEN.0), rng.gen_range(0..SCREEN.1));
+        let to = loop {
+            let to = (rng.gen_range(0..SCREEN.0), rng.gen_range(0..SCREEN.1));
+            if ((to.0 - from.0) as f32).hypot((to.1 - from.1) as f32) >= 50.0 { break to; }
+        };
+        let settings = HumanMouseSettings { rng_seed: Some(seed.wrapping_add(i as u64)), ..settings.clone() };
+        let path = plan(from, to, Some(Bounds::centered(to, TARGET_PX, TARGET_PX)), &settings);
+        all.extend(measure(from, &path));
+    }
+    let mut bands = [0.0; 4];
+    for m in &all { for (b, p) in bands.iter_mut().zip(m.band_power) { *b += p; } }
+    let total: f32 = bands.iter().sum();
+    if total > 0.0 { bands.iter_mut().for_each(|b| *b /= total); }
+    let stat = |f: fn(&PathMetrics) -> f32| Stats::of(all.iter().map(f).collect());
+    Report {
+        moves,
+        seed,
+        duration_ms: stat(|m| m.duration_ms),
+        peak_speed: stat(|m| m.peak_speed),
+        path_ratio: stat(|m| m.path_ratio),
+        max_deviation: stat(|m| m.max_deviation),
+        jitter_peak_hz: stat(|m| m.jitter_peak_hz),
+        jitter_bands: bands,
+    }
+}
+
+fn measure(from: (i32, i32), path: &[PathPoint]) -> Option<PathMetrics> {
+    let samples: Vec<(f32, (i32, i32))> = path.iter().filter(|p| p.event == PathEvent::Move).map(|p| (p.ms, (p.x, p.y))).collect();
+    let down_at = path.iter().find(|p| p.event == PathEvent::Down).map(|p| p.ms);
+    let &(_, end) = samples.last()?;
+    let (dx, dy) = ((end.0 - from.0) as f32, (end.1 - from.1) as f32);
+    let distance = dx.hypot(dy);
+    if samples.len() < 8 || distance < 1.0 { return None; }
+
+    let mut length = 0.0;
+    let mut peak_speed: f32 = 0.0;
+    let mut prev = (0.0, from);
+    for &(t, p) in &samples {
+        let step = ((p.0 - prev.1 .0) as f32).hypot((p.1 - prev.1 .1) as f32);
+        length += step;
+        if t > prev.0 { peak_speed = peak_speed.max(step / (t - prev.0) * 1000.0); }
+        prev = (t, p);
+    }
+
+    // signed distance of each sample from the straight line
+    let (nx, ny) = (-dy / distance, dx / distance);
+    let off: Vec<f32> = samples.iter().map(|&(_, p)| (p.0 - from.0) as f32 * nx + (p.1 - from.1) as f32 * ny).collect();
+    let max_deviation = off.iter().fold(0.0f32, |m, d| m.max(d.abs())) / distance;
+
+    // jitter: what's left of the sideways offset after a short moving average takes the curve out
+    let wobble: Vec<f32> = (0..off.len())
+        .map(|i| {
+            let w = &off[i.saturating_sub(2)..(i + 3).min(off.len())];
+            off[i] - w.iter().sum::<f32>() / w.len() as f32
+        })
+        .collect();
+    let span = samples.last()?.0 - samples[0].0;
+    let dt = span / (samples.len() - 1) as f32 / 1000.0;
+    let (jitter_peak_hz, band_power) = spectrum(&wobble, dt);
+
+    Some(PathMetrics {
+        duration_ms: down_at.unwrap_or(samples.last()?.0),
+        peak_speed,
+        path_ratio: length / distance,
+        max_deviation,
+        jitter_peak_hz,
+        band_power,
+    })
+}
+
+/// Strongest frequency (Hz) of `signal`, sampled every `dt` seconds, and its power per band.
+pub fn spectrum(signal: &[f32], dt: f32) -> (f32, [f32; 4]) {
+    let n = signal.len();
+    let mut bands = [0.0; 4];
+    let (mut peak, mut peak_power) = (0.0, 0.0);
+    if n < 2 || dt <= 0.0 { return (peak, bands); }
+    for k in 1..=n / 2 {
+        let (mut re, mut im) = (0.0f32, 0.0f32);
+        for (i, v) in signal.iter().enumerate() {
+            let a = -2.0 * std::f32::consts::PI * (k * i) as f32 / n as f32;
+            re += v * a.cos();
+            im += v * a.sin();
+        }
+        let power = re * re + im * im;
+        let hz = k as f32 / (n as f32 * dt);
+        bands[BANDS_HZ.iter().position(|&edge| hz < edge).unwrap_or(BANDS_HZ.len())] += power;
+        if power > peak_power { (peak, peak_power) = (hz, power); }
+    }
+    (peak, bands)
+}
+
+/// The report as a table, or as JSON.
+pub fn render(report: &Report, json: bool) -> String {
+    if json {
+        return serde_json::to_string_pretty(report).unwrap_or_default() + "\n";
+    }
+    let mut out = format!("{} simulated moves (seed {})\n", report.moves, report.seed);
+    out += &format!("{:<28} {:>9} {:>9} {:>9} {:>9}\n", "metric", "mean", "p5", "p50", "p95");
+    let rows = [
+        ("duration (ms)", report.duration_ms, 1.0),
+        ("peak speed (px/s)", report.peak_speed, 1.0),
+        ("path / straight length", report.path_ratio, 1.0),
+        ("max deviation (% of dist.)", report.max_deviation, 100.0),
+        ("jitter peak (Hz)", report.jitter_peak_hz, 1.0),
+    ];
+    for (name, s, k) in rows {
+        out += &format!("{:<28} {:>9.2} {:>9.2} {:>9.2} {:>9.2}\n", name, s.mean * k, s.p5 * k, s.p50 * k, s.p95 * k);
+    }
+    let b = report.jitter_bands.map(|p| p * 100.0);
+    out += &format!(
+        "jitter power: <{} Hz {:.0}%, {}–{} Hz {:.0}%, {}–{} Hz {:.0}%, ≥{} Hz {:.0}%\n",
+        BANDS_HZ[0], b[0], BANDS_HZ[0], BANDS_HZ[1], b[1], BANDS_HZ[1], BANDS_HZ[2], b[2], BANDS_HZ[2], b[3],
+    );
+    out
+}
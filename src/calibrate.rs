@@ -0,0 +1,77 @@
+//! Coordinate calibration: the user clicks a few markers in the picker overlay and, at each
+//! click, the picker's idea of the position is paired with where enigo says the cursor is.
+//! A per-axis linear fit of those pairs corrects picked coordinates into injection coordinates.
+
+use serde::{Deserialize, Serialize};
+
+/// Marker positions as fractions of the picker window: four corners (inset) and the center.
+pub const MARKERS: [(f32, f32); 5] = [(0.1, 0.1), (0.9, 0.1), (0.9, 0.9), (0.1, 0.9), (0.5, 0.5)];
+
+/// One calibration click: (picked, injected) position in pixels.
+pub type Sample = ((i32, i32), (i32, i32));
+
+/// `injected = picked * scale + offset`, per axis.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Correction {
+    pub scale_x: f32,
+    pub scale_y: f32,
+    pub offset_x: f32,
+    pub offset_y: f32,
+}
+
+impl Default for Correction {
+    fn default() -> Self { Self { scale_x: 1.0, scale_y: 1.0, offset_x: 0.0, offset_y: 0.0 } }
+}
+
+impl Correction {
+    pub fn apply(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        (
+            (x as f32 * self.scale_x + self.offset_x).round() as i32,
+            (y as f32 * self.scale_y + self.offset_y).round() as i32,
+        )
+    }
+
+    /// Inverse of [`apply`](Self::apply): where a picked point must have been to land at `(x, y)`.
+    pub fn invert(&self, (x, y): (i32, i32)) -> (i32, i32) {
+        (
+            ((x as f32 - self.offset_x) / self.scale_x).round() as i32,
+            ((y as f32 - self.offset_y) / self.scale_y).round() as i32,
+        )
+    }
+
+    /// Least-squares fit of `(picked, injected)` pairs. With too little spread on an axis
+    /// only an offset is fitted there. `None` without samples or for an implausible scale.
+    pub fn fit(samples: &[Sample]) -> Option<Self> {
+        let xs: Vec<(f32, f32)> = samples.iter().map(|&((p, _), (i, _))| (p as f32, i as f32)).collect();
+        let ys: Vec<(f32, f32)> = samples.iter().map(|&((_, p), (_, i))| (p as f32, i as f32)).collect();
+        let (scale_x, offset_x) = fit_axis(&xs)?;
+        let (scale_y, offset_y) = fit_axis(&ys)?;
+        let plausible = |s: f32| (0.25..=4.0).contains(&s);
+        (plausible(scale_x) && plausible(scale_y)).then_some(Self { scale_x, scale_y, offset_x, offset_y })
+    }
+}
+
+fn fit_axis(pairs: &[(f32, f32)]) -> Option<(f32, f32)> {
+    if pairs.is_empty() { return None; }
+    let n = pairs.len() as f32;
+    let mean_p = pairs.iter().map(|p| p.0).sum::<f32>() / n;
+    let mean_i = pairs.iter().map(|p| p.1).sum::<f32>() / n;
+    let var: f32 = pairs.iter().map(|p| (p.0 - mean_p).powi(2)).sum();
+    if var < 1.0 { return Some((1.0, mean_i - mean_p)); }
+    let cov: f32 = pairs.iter().map(|p| (p.0 - mean_p) * (p.1 - mean_i)).sum();
+    let scale = cov / var;
+    Some((scale, mean_i - scale * mean_p))
+}
+
+/// A calibration in progress for one monitor.
+pub struct CalibrationRun {
+    pub monitor_id: u32,
+    pub samples: Vec<Sample>,
+}
+
+impl CalibrationRun {
+    pub fn new(monitor_id: u32) -> Self { Self { monitor_id, samples: Vec::new() } }
+
+    /// The marker to click next, or `None` once all have been clicked.
+    pub fn next_marker(&self) -> Option<(f32, f32)> { MARKERS.get(self.samples.len()).copied() }
+}
@@ -0,0 +1,49 @@
+//! Passphrase encryption for profile files. Argon2id turns the passphrase into a key and
+//! ChaCha20-Poly1305 encrypts the profile JSON, so a wrong passphrase or an altered file fails
+//! to open instead of loading garbage.
+
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+
+/// Written into every encrypted file, so the scheme can change later.
+pub const CIPHER: &str = "argon2id-chacha20poly1305";
+
+/// An encrypted file's contents. Binary fields are base64.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Sealed {
+    pub cipher: String,
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+fn key(passphrase: &str, salt: &[u8]) -> Result<Key, String> {
+    let mut key = Key::default();
+    Argon2::default().hash_password_into(passphrase.as_bytes(), salt, &mut key).map_err(|e| e.to_string())?;
+    Ok(key)
+}
+
+pub fn seal(plain: &[u8], passphrase: &str) -> Result<Sealed, String> {
+    let mut salt = [0u8; 16];
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+    let data = ChaCha20Poly1305::new(&key(passphrase, &salt)?)
+        .encrypt(Nonce::from_slice(&nonce), plain)
+        .map_err(|e| e.to_string())?;
+    Ok(Sealed { cipher: CIPHER.to_string(), salt: STANDARD.encode(salt), nonce: STANDARD.encode(nonce), data: STANDARD.encode(data) })
+}
+
+pub fn open(sealed: &Sealed, passphrase: &str) -> Result<Vec<u8>, String> {
+    if sealed.cipher != CIPHER { return Err(format!("unknown encryption '{}'", sealed.cipher)); }
+    let decode = |field: &str| STANDARD.decode(field).map_err(|e| format!("damaged encrypted file: {}", e));
+    let (salt, nonce, data) = (decode(&sealed.salt)?, decode(&sealed.nonce)?, decode(&sealed.data)?);
+    if nonce.len() != 12 { return Err("damaged encrypted file: bad nonce".to_string()); }
+    ChaCha20Poly1305::new(&key(passphrase, &salt)?)
+        .decrypt(Nonce::from_slice(&nonce), data.as_slice())
+        .map_err(|_| "wrong passphrase, or the file was altered".to_string())
+}
@@ -0,0 +1,268 @@
+//! `--daemon`: run jobs without any window, controlled over a local Unix socket (not available
+//! on Windows yet). Clients send one command per line and get one reply line each, starting
+//! with `ok` or `error`:
+//!
+//! - `load <profile>`: a saved profile's name, or the path of a profile file
+//! - `start`, `stop`; `start <profile>` loads the profile first, stopping a running job
+//! - `step <n or name>`: run that one step once, next to the job if one is running
+//! - `continue`: continue a run cut short by a crash or reboot, from its last checkpoint
+//! - `skip`, `repeat`, `jump <n>`: leave the running step, start it over, or carry on at step n
+//! - `status`: e.g. `ok Running — step 3/7, cycle 12, 428 clicks`
+//! - `quit`: stop the job and shut the daemon down
+//!
+//! E.g. `echo status | nc -U $XDG_RUNTIME_DIR/area_clicker.sock`, or `area_clicker send status`.
+//! The window serves the same commands (but `quit`) when its control socket is turned on.
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+
+use crate::step::StepCommand;
+use crate::{profile, AppState};
+
+/// `$XDG_RUNTIME_DIR/area_clicker.sock`, or the same in a private folder of the temp dir
+/// (created with mode 0700 when the daemon starts).
+pub fn default_socket() -> PathBuf {
+    let dir = dirs::runtime_dir().unwrap_or_else(|| {
+        let user = std::env::var("USER").or_else(|_| std::env::var("LOGNAME")).unwrap_or_else(|_| "user".into());
+        std::env::temp_dir().join(format!("area_clicker-{}", user))
+    });
+    dir.join("area_clicker.sock")
+}
+
+/// Serve commands on `socket` until a client sends `quit`. Any running job is stopped first.
+pub fn run(mut state: AppState, socket: &Path) -> Result<(), String> {
+    let result = serve(socket, &AtomicBool::new(false), true, &mut |line| handle(&mut state, line));
+    state.stop();
+    result
+}
+
+/// One command for the window, and where its reply goes.
+type Request = (String, Sender<Result<String, String>>);
+
+/// The control socket served next to the window: a thread takes the connections and hands
+/// each command to the UI thread, which answers it in [`Remote::answer`].
+pub struct Remote {
+    socket: PathBuf,
+    requests: Receiver<Request>,
+    stop: Arc<AtomicBool>,
+}
+
+impl Remote {
+    /// Serve `socket`; `wake` is called (from the serving thread) for every command.
+    pub fn start(socket: PathBuf, wake: impl Fn() + Send + 'static) -> Self {
+        let (sender, requests) = mpsc::channel::<Request>();
+        let stop = Arc::new(AtomicBool::new(false));
+        {
+            let (socket, stop) = (socket.clone(), Arc::clone(&stop));
+            std::thread::spawn(move || {
+                let mut forward = |line: &str| {
+                    let (reply, answer) = mpsc::channel();
+                    sender.send((line.to_string(), reply)).map_err(|_| "the window closed".to_string())?;
+                    wake();
+                    answer.recv().map_err(|_| "the window closed".to_string())?
+                };
+                if let Err(e) = serve(&socket, &stop, false, &mut forward) { log::error!("{}", e); }
+            });
+        }
+        Self { socket, requests, stop }
+    }
+
+    /// Carry out the commands that came in since the last call.
+    pub fn answer(&self, mut handle: impl FnMut(&str) -> Result<String, String>) {
+        for (line, reply) in self.requests.try_iter() {
+            let _ = reply.send(handle(&line));
+        }
+    }
+}
+
+impl Drop for Remote {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // wake the serving thread from its wait for a connection so it sees the flag
+        let _ = connect(&self.socket);
+    }
+}
+
+/// Send `command` to the daemon or window serving `socket` and return its reply.
+pub fn send(socket: &Path, command: &str) -> Result<String, String> {
+    let mut stream = connect(socket).map_err(|e| format!("Could not connect to {}: {}", socket.display(), e))?;
+    writeln!(stream, "{}", command.trim()).and_then(|_| stream.flush()).map_err(|e| e.to_string())?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply).map_err(|e| e.to_string())?;
+    let reply = reply.trim_end();
+    match reply.split_once(' ').unwrap_or((reply, "")) {
+        ("ok", text) => Ok(text.to_string()),
+        ("error", why) => Err(why.to_string()),
+        _ => Err(format!("unexpected reply '{}'", reply)),
+    }
+}
+
+#[cfg(unix)]
+fn connect(socket: &Path) -> std::io::Result<impl Read + Write> { std::os::unix::net::UnixStream::connect(socket) }
+
+#[cfg(not(unix))]
+fn connect(_socket: &Path) -> std::io::Result<std::net::TcpStream> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "control sockets need a Unix socket on this platform"))
+}
+
+/// Carry out one command line. `Ok` holds the reply text (possibly empty).
+pub fn handle(state: &mut AppState, line: &str) -> Result<String, String> {
+    let line = line.trim();
+    let (cmd, arg) = line.split_once(char::is_whitespace).map_or((line, ""), |(c, a)| (c, a.trim()));
+    match cmd {
+        "load" => {
+            if state.is_running() { return Err("stop the job before loading a profile".into()); }
+            load(state, arg)?;
+            Ok(format!("loaded {}", arg))
+        }
+        "start" => {
+            if !arg.is_empty() {
+                state.stop();
+                load(state, arg)?;
+            }
+            if state.is_running() { return Err("already running".into()); }
+            state.start();
+            if state.is_running() { Ok(String::new()) } else { Err(state.input_error.take().unwrap_or_else(|| "could not start".into())) }
+        }
+        "stop" => {
+            state.stop();
+            Ok(String::new())
+        }
+        "resume" => {
+            if state.resume_after_unlock() { Ok(String::new()) } else { Err("not waiting to resume".into()) }
+        }
+        "continue" => {
+            if state.is_running() { return Err("already running".into()); }
+            let c = state.pending_checkpoint.take().ok_or("no interrupted run to continue")?;
+            state.continue_checkpoint(c);
+            if state.is_running() { Ok(String::new()) } else { Err(state.input_error.take().unwrap_or_else(|| "could not start".into())) }
+        }
+        "skip" => step(state, StepCommand::Skip),
+        "repeat" => step(state, StepCommand::Repeat),
+        "jump" => {
+            let n: usize = arg.parse().ok().filter(|&n| n >= 1).ok_or("usage: jump <step number>")?;
+            step(state, StepCommand::Jump(n - 1))
+        }
+        "step" => {
+            let steps = &state.steps;
+            let i = arg.parse::<usize>().ok().filter(|&n| (1..=steps.len()).contains(&n)).map(|n| n - 1)
+                .or_else(|| steps.iter().position(|s| s.name == arg));
+            let i = i.ok_or_else(|| if arg.is_empty() { "usage: step <step number or name>".to_string() } else { format!("no step '{}'", arg) })?;
+            state.run_step_burst(i)?;
+            Ok(String::new())
+        }
+        "status" => Ok(state.status_text().unwrap_or_else(|| "Idle".into())),
+        "quit" => Err("quit only shuts down --daemon".into()),
+        "" => Err("empty command".into()),
+        _ => Err(format!("unknown command '{}' (load, start, continue, stop, resume, skip, repeat, jump, step, status, quit)", cmd)),
+    }
+}
+
+fn step(state: &AppState, command: StepCommand) -> Result<String, String> {
+    if state.step_command(command) { Ok(String::new()) } else { Err("no steps are running".into()) }
+}
+
+/// A profile by name, or from a file when `arg` looks like a path.
+fn load(state: &mut AppState, arg: &str) -> Result<(), String> {
+    if arg.is_empty() { return Err("usage: load <profile name or file>".into()); }
+    let path = Path::new(arg);
+    if path.extension().is_some_and(|e| e == "json") || path.components().count() > 1 {
+        let mut p = profile::load_file(path).map_err(|e| format!("could not load {}: {}", path.display(), e))?;
+        if p.disarm_events() { log::warn!("{} has event commands; they stay off until turned on in the app", path.display()); }
+        state.apply_profile(p);
+        state.current_profile = None;
+    } else {
+        let store = state.profiles.as_ref().ok_or("no config directory to load profiles from")?;
+        let p = store.load(arg).map_err(|e| format!("could not load profile '{}': {}", arg, e))?;
+        state.apply_profile(p);
+        state.current_profile = Some(arg.to_string());
+        state.restore_position();
+    }
+    Ok(())
+}
+
+/// Command handler of a served socket.
+type Handler<'a> = dyn FnMut(&str) -> Result<String, String> + 'a;
+
+/// Answer one client's commands until it disconnects. True if it asked to quit (and `quit` is
+/// taken; otherwise it goes to `handle` like any other command).
+fn session(handle: &mut Handler, quit: bool, input: impl BufRead, mut out: impl Write) -> bool {
+    for line in input.lines() {
+        let Ok(line) = line else { break };
+        let quit = quit && line.trim() == "quit";
+        let reply = match if quit { Ok(String::new()) } else { handle(&line) } {
+            Ok(text) if text.is_empty() => "ok".to_string(),
+            Ok(text) => format!("ok {}", text),
+            Err(e) => format!("error {}", e),
+        };
+        log::info!("Daemon: {} → {}", line.trim(), reply);
+        if writeln!(out, "{}", reply).and_then(|_| out.flush()).is_err() { break; }
+        if quit { return true; }
+    }
+    false
+}
+
+/// Serve `handle` on `socket` until a client quits (if `quit`) or `stop` is set.
+#[cfg(unix)]
+fn serve(socket: &Path, stop: &AtomicBool, quit: bool, handle: &mut Handler) -> Result<(), String> {
+    let listener = bind_private(socket)?;
+    log::info!("Daemon listening on {}", socket.display());
+    for stream in listener.incoming() {
+        if stop.load(Ordering::Relaxed) { break; }
+        let stream = match stream {
+            Ok(s) => s,
+            Err(e) => { log::warn!("Daemon: connection failed: {}", e); continue; }
+        };
+        let Ok(reader) = stream.try_clone() else { continue };
+        if session(handle, quit, BufReader::new(reader), stream) { break; }
+    }
+    let _ = std::fs::remove_file(socket);
+    log::info!("Daemon stopped");
+    Ok(())
+}
+
+/// Listen on `socket` so that only this user can ever connect: whoever can connect can drive
+/// the mouse. The socket is bound inside a fresh 0700 folder, restricted to 0600 and only then
+/// moved into place. A socket already there is replaced only if it's this user's and dead.
+#[cfg(unix)]
+pub fn bind_private(socket: &Path) -> Result<std::os::unix::net::UnixListener, String> {
+    use std::fs::{DirBuilder, Permissions};
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, MetadataExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let fail = |what: &str, e: std::io::Error| format!("Could not {} {}: {}", what, socket.display(), e);
+    let dir = socket.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    if !dir.exists() { DirBuilder::new().recursive(true).mode(0o700).create(dir).map_err(|e| fail("create the folder of", e))?; }
+    let staging = dir.join(format!(".area_clicker-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&staging);
+    DirBuilder::new().mode(0o700).create(&staging).map_err(|e| fail("prepare", e))?;
+    let result = (|| {
+        let uid = std::fs::metadata(&staging).map_err(|e| fail("prepare", e))?.uid();
+        if let Ok(meta) = std::fs::symlink_metadata(socket) {
+            if UnixStream::connect(socket).is_ok() {
+                return Err(format!("Another daemon is already listening on {}", socket.display()));
+            }
+            if !meta.file_type().is_socket() || meta.uid() != uid {
+                return Err(format!("{} is in the way and isn't a stale socket of this user; remove it by hand", socket.display()));
+            }
+            // left over from a daemon that didn't shut down cleanly
+            std::fs::remove_file(socket).map_err(|e| fail("remove stale", e))?;
+        }
+        let private = staging.join("sock");
+        let listener = UnixListener::bind(&private).map_err(|e| fail("listen on", e))?;
+        std::fs::set_permissions(&private, Permissions::from_mode(0o600)).map_err(|e| fail("restrict", e))?;
+        std::fs::rename(&private, socket).map_err(|e| fail("listen on", e))?;
+        Ok(listener)
+    })();
+    let _ = std::fs::remove_dir_all(&staging);
+    result
+}
+
+/// Named pipes aren't supported yet; the daemon and control socket need a Unix socket.
+#[cfg(not(unix))]
+fn serve(socket: &Path, _stop: &AtomicBool, _quit: bool, _handle: &mut Handler) -> Result<(), String> {
+    Err(format!("Could not listen on {}: control sockets need a Unix socket on this platform", socket.display()))
+}
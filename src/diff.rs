@@ -0,0 +1,85 @@
+//! What loading a profile would change: settings and steps that differ between the current
+//! settings and the incoming ones, shown before unsaved edits get overwritten.
+
+use serde_json::{Map, Value};
+
+use crate::profile::Profile;
+
+/// A setting or step field that differs, with both values as short JSON.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StepChange {
+    /// A step only the incoming profile has; `index` is its position there.
+    Added { index: usize, name: String },
+    /// A step only the current settings have; `index` is its position there.
+    Removed { index: usize, name: String },
+    /// A step both have, matched by name; `index` is its position in the incoming profile.
+    Changed { index: usize, name: String, fields: Vec<FieldChange> },
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProfileDiff {
+    pub settings: Vec<FieldChange>,
+    pub steps: Vec<StepChange>,
+}
+
+impl ProfileDiff {
+    pub fn is_empty(&self) -> bool { self.settings.is_empty() && self.steps.is_empty() }
+}
+
+/// How `to` differs from `from`. Steps are paired by name (the n-th step called "Loot" with
+/// the n-th one in the other list); a renamed step shows as removed and added.
+pub fn profiles(from: &Profile, to: &Profile) -> ProfileDiff {
+    let (Ok(Value::Object(mut a)), Ok(Value::Object(mut b))) = (serde_json::to_value(from), serde_json::to_value(to)) else {
+        return ProfileDiff::default();
+    };
+    let (old_steps, new_steps) = (a.remove("steps"), b.remove("steps"));
+    let as_list = |v: Option<Value>| match v { Some(Value::Array(list)) => list, _ => Vec::new() };
+    ProfileDiff { settings: fields(&a, &b), steps: steps(&as_list(old_steps), &as_list(new_steps)) }
+}
+
+fn steps(old: &[Value], new: &[Value]) -> Vec<StepChange> {
+    let name = |s: &Value| s.get("name").and_then(Value::as_str).unwrap_or_default().to_string();
+    let mut taken = vec![false; old.len()];
+    let mut changes = Vec::new();
+    for (index, step) in new.iter().enumerate() {
+        let found = (0..old.len()).find(|&i| !taken[i] && name(&old[i]) == name(step));
+        let Some(i) = found else {
+            changes.push(StepChange::Added { index, name: name(step) });
+            continue;
+        };
+        taken[i] = true;
+        let (Value::Object(a), Value::Object(b)) = (&old[i], step) else { continue };
+        let fields = fields(a, b);
+        if !fields.is_empty() { changes.push(StepChange::Changed { index, name: name(step), fields }); }
+    }
+    for (index, step) in old.iter().enumerate().filter(|&(i, _)| !taken[i]) {
+        changes.push(StepChange::Removed { index, name: name(step) });
+    }
+    changes
+}
+
+/// Keys whose values differ, in order; a key only one side has shows `—` on the other.
+fn fields(a: &Map<String, Value>, b: &Map<String, Value>) -> Vec<FieldChange> {
+    let mut keys: Vec<&String> = a.keys().chain(b.keys().filter(|k| !a.contains_key(*k))).collect();
+    keys.sort();
+    keys.into_iter()
+        .filter(|k| a.get(*k) != b.get(*k))
+        .map(|k| FieldChange { field: k.replace('_', " "), from: short(a.get(k)), to: short(b.get(k)) })
+        .collect()
+}
+
+/// A value as compact JSON, cut to a readable length.
+fn short(value: Option<&Value>) -> String {
+    const MAX: usize = 60;
+    let Some(value) = value else { return "—".to_string() };
+    let text = value.to_string();
+    if text.chars().count() <= MAX { return text; }
+    text.chars().take(MAX - 1).chain(std::iter::once('…')).collect()
+}
@@ -0,0 +1,147 @@
+//! Event commands: shell commands the job runs when it starts, ends, gives up on a step or
+//! completes every N cycles, e.g. to rotate a VPN or archive logs between cycles.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+
+use crate::step::JobProgress;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event { Start, Finish, Error, Cycle }
+
+impl Event {
+    pub fn label(self) -> &'static str {
+        match self {
+            Event::Start => "start",
+            Event::Finish => "finish",
+            Event::Error => "error",
+            Event::Cycle => "cycle",
+        }
+    }
+}
+
+/// One command line per event; empty = nothing to run. Commands go through the system shell
+/// (`sh -c`, `cmd /V:ON /C`) with the placeholders from [`expand`] filled in.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventCommands {
+    pub on_start: String,
+    pub on_finish: String,
+    pub on_error: String,
+    pub on_cycle: String,
+    /// Run `on_cycle` after every this many completed cycles.
+    pub every_cycles: u32,
+}
+
+impl Default for EventCommands {
+    fn default() -> Self {
+        Self { on_start: String::new(), on_finish: String::new(), on_error: String::new(), on_cycle: String::new(), every_cycles: 1 }
+    }
+}
+
+impl EventCommands {
+    /// The command to run for `event`, if any is due. `cycle` is the number of completed cycles.
+    pub fn command(&self, event: Event, cycle: u32) -> Option<&str> {
+        let cmd = match event {
+            Event::Start => &self.on_start,
+            Event::Finish => &self.on_finish,
+            Event::Error => &self.on_error,
+            Event::Cycle if cycle.is_multiple_of(self.every_cycles.max(1)) => &self.on_cycle,
+            Event::Cycle => return None,
+        };
+        Some(cmd.trim()).filter(|c| !c.is_empty())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        [&self.on_start, &self.on_finish, &self.on_error, &self.on_cycle].iter().all(|c| c.trim().is_empty())
+    }
+}
+
+/// Placeholders and the environment variables their values are passed in.
+const PLACEHOLDERS: [(&str, &str); 6] = [
+    ("{event}", "AREA_CLICKER_EVENT"),
+    ("{clicks}", "AREA_CLICKER_CLICKS"),
+    ("{step}", "AREA_CLICKER_STEP"),
+    ("{cycle}", "AREA_CLICKER_CYCLE"),
+    ("{failed}", "AREA_CLICKER_FAILED"),
+    ("{error}", "AREA_CLICKER_ERROR"),
+];
+
+/// Commands waiting for the runner; further events are dropped while it's this far behind.
+const QUEUE_LEN: usize = 16;
+
+/// Point `{event}`, `{clicks}`, `{step}`, `{cycle}`, `{failed}` and `{error}` at their
+/// variables (see [`env`]). Values never become part of the command line, so a step name or
+/// error text can't run as a command; quote text placeholders against word splitting.
+pub fn expand(cmd: &str) -> String {
+    PLACEHOLDERS.iter().fold(cmd.to_string(), |cmd, (placeholder, var)| {
+        #[cfg(windows)]
+        let reference = format!("!{}!", var);
+        #[cfg(not(windows))]
+        let reference = format!("${{{}}}", var);
+        cmd.replace(placeholder, &reference)
+    })
+}
+
+/// The placeholder values for `event`, as environment variables for the command.
+pub fn env(event: Event, progress: &JobProgress) -> Vec<(&'static str, String)> {
+    let values = [
+        event.label().to_string(),
+        progress.clicks.to_string(),
+        progress.step_name.clone(),
+        progress.cycle.to_string(),
+        progress.failed_steps.to_string(),
+        progress.last_error.clone().unwrap_or_default(),
+    ];
+    PLACEHOLDERS.iter().map(|(_, var)| *var).zip(values).collect()
+}
+
+struct Job {
+    event: Event,
+    cmd: String,
+    env: Vec<(&'static str, String)>,
+}
+
+/// One worker runs the commands in turn, so a slow script doesn't hold up the clicks and a
+/// burst of events can't start a process each at once.
+static QUEUE: Lazy<SyncSender<Job>> = Lazy::new(|| {
+    let (tx, rx) = mpsc::sync_channel::<Job>(QUEUE_LEN);
+    std::thread::spawn(move || {
+        for job in rx { execute(job); }
+    });
+    tx
+});
+
+/// Queue `cmd` for the command worker with `env` set. The exit status and any output end up
+/// in the log; an event is dropped (and logged) while the queue is full.
+pub fn run(event: Event, cmd: String, env: Vec<(&'static str, String)>) {
+    log::info!("Running {} command: {}", event.label(), cmd);
+    match QUEUE.try_send(Job { event, cmd, env }) {
+        Ok(()) => {}
+        Err(TrySendError::Full(job)) => log::warn!("Skipped {} command: {} commands are still waiting", job.event.label(), QUEUE_LEN),
+        Err(TrySendError::Disconnected(job)) => log::error!("Could not run {} command: the command worker is gone", job.event.label()),
+    }
+}
+
+/// Run one queued command and log how it went.
+fn execute(Job { event, cmd, env }: Job) {
+    #[cfg(windows)]
+    let output = Command::new("cmd").args(["/V:ON", "/C", &cmd]).envs(env).output();
+    #[cfg(not(windows))]
+    let output = Command::new("sh").args(["-c", &cmd]).envs(env).output();
+    match output {
+        Ok(out) => {
+            let text = |b: &[u8]| String::from_utf8_lossy(b).trim().to_string();
+            let (stdout, stderr) = (text(&out.stdout), text(&out.stderr));
+            if !stdout.is_empty() { log::info!("{} command output: {}", event.label(), stdout); }
+            if out.status.success() {
+                if !stderr.is_empty() { log::info!("{} command stderr: {}", event.label(), stderr); }
+            } else {
+                log::warn!("{} command failed ({}): {}", event.label(), out.status, stderr);
+            }
+        }
+        Err(e) => log::error!("Could not run {} command: {}", event.label(), e),
+    }
+}
@@ -0,0 +1,83 @@
+//! Click feedback: a short tick sound and/or a dot in the status row that lights up for every
+//! click a job makes, so it can be heard (or seen at a glance) that the job is still alive while
+//! watching another screen.
+
+use serde::{Deserialize, Serialize};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Plays the tick; empty = the system beep.
+#[cfg(windows)]
+pub const DEFAULT_COMMAND: &str = "";
+#[cfg(target_os = "macos")]
+pub const DEFAULT_COMMAND: &str = "afplay /System/Library/Sounds/Tink.aiff";
+#[cfg(not(any(windows, target_os = "macos")))]
+pub const DEFAULT_COMMAND: &str = "paplay /usr/share/sounds/freedesktop/stereo/audio-volume-change.oga";
+
+/// How long the dot stays lit after a click.
+pub const DOT_DURATION: Duration = Duration::from_millis(250);
+
+/// A tick still playing; clicks meanwhile stay silent instead of piling up players.
+static PLAYING: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClickFeedback {
+    /// Play a tick for every click.
+    pub sound: bool,
+    /// Command that plays the tick, run through the system shell; empty = the system beep.
+    pub command: String,
+    /// Light a dot next to the status for every click.
+    pub dot: bool,
+}
+
+impl Default for ClickFeedback {
+    fn default() -> Self {
+        Self { sound: false, command: DEFAULT_COMMAND.to_string(), dot: false }
+    }
+}
+
+impl ClickFeedback {
+    /// Play the tick, if it's on, without waiting for it to finish.
+    pub fn click(&self) {
+        if !self.sound || PLAYING.swap(true, Ordering::AcqRel) { return; }
+        let cmd = self.command.trim().to_string();
+        std::thread::spawn(move || {
+            if cmd.is_empty() {
+                beep();
+            } else {
+                #[cfg(windows)]
+                let status = Command::new("cmd").args(["/C", &cmd]).stdout(Stdio::null()).stderr(Stdio::null()).status();
+                #[cfg(not(windows))]
+                let status = Command::new("sh").args(["-c", &cmd]).stdout(Stdio::null()).stderr(Stdio::null()).status();
+                match status {
+                    Ok(s) if !s.success() => log::debug!("Click sound command failed ({})", s),
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Could not play the click sound: {}", e),
+                }
+            }
+            PLAYING.store(false, Ordering::Release);
+        });
+    }
+}
+
+/// Whether the dot is lit at `now` for a click made `at`.
+pub fn dot_lit(at: Option<Instant>, now: Instant) -> bool {
+    at.is_some_and(|at| now.saturating_duration_since(at) < DOT_DURATION)
+}
+
+fn beep() {
+    #[cfg(windows)]
+    unsafe {
+        use windows_sys::Win32::UI::WindowsAndMessaging::{MessageBeep, MB_OK};
+        MessageBeep(MB_OK);
+    }
+    #[cfg(not(windows))]
+    {
+        // the terminal bell; heard only when started from a terminal
+        use std::io::Write;
+        let mut err = std::io::stderr();
+        let _ = err.write_all(b"\x07").and_then(|_| err.flush());
+    }
+}
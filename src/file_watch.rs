@@ -0,0 +1,78 @@
+//! Notices when the loaded profile's file is edited outside the app, so it can be reloaded.
+
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use parking_lot::Mutex;
+use std::ffi::OsString;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How long the file has to stay quiet before a change counts. Editors often write a file in
+/// several steps (truncate, write, rename), and a half-written profile doesn't parse.
+pub const SETTLE: Duration = Duration::from_millis(300);
+
+pub struct FileWatch {
+    changes: Arc<Changes>,
+    _watcher: RecommendedWatcher,
+}
+
+/// What the watcher thread reports into.
+struct Changes {
+    /// File name of the watched file, as events name it.
+    name: Option<OsString>,
+    /// When the file last changed, until the change is picked up.
+    changed_at: Mutex<Option<Instant>>,
+    wake: Box<dyn Fn() + Send + Sync>,
+}
+
+impl FileWatch {
+    /// Watch `path`, calling `wake` whenever it changes (from the watcher's thread). Its
+    /// directory is watched rather than the file itself, since many editors save by writing a
+    /// new file and renaming it over the old one.
+    pub fn new(path: &Path, wake: impl Fn() + Send + Sync + 'static) -> Result<Self, String> {
+        let changes = Arc::new(Changes {
+            name: path.file_name().map(|n| n.to_os_string()),
+            changed_at: Mutex::new(None),
+            wake: Box::new(wake),
+        });
+        let handler = Arc::clone(&changes);
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| match res {
+            Ok(event) => handler.notice(&event),
+            Err(e) => log::warn!("Profile watch: {}", e),
+        })
+        .map_err(|e| e.to_string())?;
+        let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        watcher.watch(dir, RecursiveMode::NonRecursive).map_err(|e| format!("Can't watch {}: {}", dir.display(), e))?;
+        Ok(Self { changes, _watcher: watcher })
+    }
+
+    /// Take `event` as if the watcher had reported it.
+    #[cfg(test)]
+    pub fn notice(&self, event: &Event) { self.changes.notice(event) }
+
+    /// A change is waiting to settle.
+    pub fn pending(&self) -> bool { self.changes.changed_at.lock().is_some() }
+
+    /// True once per burst of changes, after the file has been quiet for [`SETTLE`].
+    pub fn take_change(&self) -> bool { self.take_change_at(Instant::now()) }
+
+    /// [`FileWatch::take_change`] as of `now`.
+    pub fn take_change_at(&self, now: Instant) -> bool {
+        let mut changed_at = self.changes.changed_at.lock();
+        if changed_at.is_some_and(|at| now.saturating_duration_since(at) >= SETTLE) {
+            *changed_at = None;
+            return true;
+        }
+        false
+    }
+}
+
+impl Changes {
+    fn notice(&self, event: &Event) {
+        let ours = event.paths.iter().any(|p| p.file_name() == self.name.as_deref());
+        if ours && (event.kind.is_create() || event.kind.is_modify()) {
+            *self.changed_at.lock() = Some(Instant::now());
+            (self.wake)();
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Gamepad control. Needs the `gamepad` feature (gilrs); without it no pad is ever seen.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "gamepad")]
+use once_cell::sync::Lazy;
+#[cfg(feature = "gamepad")]
+use parking_lot::Mutex;
+
+/// Stick deflection below this counts as centered, so a worn stick doesn't drift the selection.
+pub const DEAD_ZONE: f32 = 0.2;
+
+/// Pad buttons that can be bound to actions, by position (South is A on an Xbox pad, ✕ on a
+/// PlayStation one).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PadButton {
+    South, East, West, North, LeftBumper, RightBumper, LeftTrigger, RightTrigger,
+    Select, Start, LeftStick, RightStick, DPadUp, DPadDown, DPadLeft, DPadRight,
+}
+
+impl PadButton {
+    pub const ALL: [PadButton; 16] = [
+        PadButton::South, PadButton::East, PadButton::West, PadButton::North,
+        PadButton::LeftBumper, PadButton::RightBumper, PadButton::LeftTrigger, PadButton::RightTrigger,
+        PadButton::Select, PadButton::Start, PadButton::LeftStick, PadButton::RightStick,
+        PadButton::DPadUp, PadButton::DPadDown, PadButton::DPadLeft, PadButton::DPadRight,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PadButton::South => "A / ✕", PadButton::East => "B / ○", PadButton::West => "X / □", PadButton::North => "Y / △",
+            PadButton::LeftBumper => "LB", PadButton::RightBumper => "RB", PadButton::LeftTrigger => "LT", PadButton::RightTrigger => "RT",
+            PadButton::Select => "Select", PadButton::Start => "Start", PadButton::LeftStick => "L3", PadButton::RightStick => "R3",
+            PadButton::DPadUp => "D-pad ↑", PadButton::DPadDown => "D-pad ↓", PadButton::DPadLeft => "D-pad ←", PadButton::DPadRight => "D-pad →",
+        }
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn from_gilrs(button: gilrs::Button) -> Option<Self> {
+        use gilrs::Button;
+        Some(match button {
+            Button::South => PadButton::South, Button::East => PadButton::East,
+            Button::West => PadButton::West, Button::North => PadButton::North,
+            Button::LeftTrigger => PadButton::LeftBumper, Button::RightTrigger => PadButton::RightBumper,
+            Button::LeftTrigger2 => PadButton::LeftTrigger, Button::RightTrigger2 => PadButton::RightTrigger,
+            Button::Select => PadButton::Select, Button::Start => PadButton::Start,
+            Button::LeftThumb => PadButton::LeftStick, Button::RightThumb => PadButton::RightStick,
+            Button::DPadUp => PadButton::DPadUp, Button::DPadDown => PadButton::DPadDown,
+            Button::DPadLeft => PadButton::DPadLeft, Button::DPadRight => PadButton::DPadRight,
+            _ => return None,
+        })
+    }
+}
+
+/// Presses not yet handled by the UI, oldest first.
+#[cfg(feature = "gamepad")]
+static PRESSED: Lazy<Mutex<Vec<PadButton>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Left stick position of the pad moved last, x right and y up, each -1..1.
+#[cfg(feature = "gamepad")]
+static STICK: Lazy<Mutex<(f32, f32)>> = Lazy::new(|| Mutex::new((0.0, 0.0)));
+
+/// Called on every press and stick movement, so the UI can react without polling.
+#[cfg(feature = "gamepad")]
+static WAKE: Lazy<Mutex<Option<Waker>>> = Lazy::new(|| Mutex::new(None));
+
+#[cfg(feature = "gamepad")]
+type Waker = Box<dyn Fn() + Send>;
+
+/// Whether gamepads work in this build.
+pub fn available() -> bool { cfg!(feature = "gamepad") }
+
+/// Start the gamepad listener thread. Safe to call more than once.
+pub fn start_listener() {
+    #[cfg(feature = "gamepad")]
+    {
+        use std::sync::Once;
+        static STARTED: Once = Once::new();
+        STARTED.call_once(|| {
+            std::thread::spawn(|| {
+                let mut pads = match gilrs::Gilrs::new() {
+                    Ok(pads) => pads,
+                    Err(e) => {
+                        log::error!("Gamepad support failed: {}", e);
+                        return;
+                    }
+                };
+                loop {
+                    let Some(event) = pads.next_event_blocking(None) else { continue };
+                    match event.event {
+                        gilrs::EventType::ButtonPressed(b, _) => {
+                            if let Some(b) = PadButton::from_gilrs(b) {
+                                PRESSED.lock().push(b);
+                                wake();
+                            }
+                        }
+                        gilrs::EventType::AxisChanged(axis, value, _) => {
+                            let mut stick = STICK.lock();
+                            match axis {
+                                gilrs::Axis::LeftStickX => stick.0 = value,
+                                gilrs::Axis::LeftStickY => stick.1 = value,
+                                _ => continue,
+                            }
+                            drop(stick);
+                            wake();
+                        }
+                        gilrs::EventType::Connected => log::info!("Gamepad connected: {}", pads.gamepad(event.id).name()),
+                        gilrs::EventType::Disconnected => *STICK.lock() = (0.0, 0.0),
+                        _ => {}
+                    }
+                }
+            });
+        });
+    }
+}
+
+/// Have `wake` called (from the listener thread) whenever a button goes down or a stick moves.
+pub fn on_change(wake: impl Fn() + Send + 'static) {
+    #[cfg(feature = "gamepad")]
+    { *WAKE.lock() = Some(Box::new(wake)); }
+    #[cfg(not(feature = "gamepad"))]
+    { let _ = wake; }
+}
+
+#[cfg(feature = "gamepad")]
+fn wake() {
+    if let Some(wake) = &*WAKE.lock() { wake(); }
+}
+
+/// Drain the button presses that happened since the last call.
+pub fn take_pressed() -> Vec<PadButton> {
+    #[cfg(feature = "gamepad")]
+    { std::mem::take(&mut *PRESSED.lock()) }
+    #[cfg(not(feature = "gamepad"))]
+    { Vec::new() }
+}
+
+/// Left stick position, x right and y up, with the dead zone taken out (and the rest scaled
+/// back to 0..1).
+pub fn stick() -> (f32, f32) {
+    #[cfg(feature = "gamepad")]
+    let (x, y) = *STICK.lock();
+    #[cfg(not(feature = "gamepad"))]
+    let (x, y) = (0.0, 0.0);
+    (dead_zone(x), dead_zone(y))
+}
+
+/// `value` with [`DEAD_ZONE`] taken out.
+pub fn dead_zone(value: f32) -> f32 {
+    if value.abs() < DEAD_ZONE { return 0.0; }
+    value.signum() * (value.abs() - DEAD_ZONE) / (1.0 - DEAD_ZONE)
+}
@@ -0,0 +1,134 @@
+//! Run history: a summary of every run that clicked, kept in a local SQLite database
+//! (`history.sqlite` in the config directory), to answer "how many clicks did profile X do last
+//! week" without a spreadsheet on the side.
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use rusqlite::{params, Connection};
+use std::path::Path;
+use std::time::Duration;
+
+/// One finished (or stopped) run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunRecord {
+    pub started: DateTime<Local>,
+    /// The profile loaded when the run ended; `None` for unsaved settings.
+    pub profile: Option<String>,
+    pub duration: Duration,
+    pub clicks: u32,
+    pub misses: u32,
+    pub failed_steps: u32,
+    pub cycles: u32,
+}
+
+/// Which runs to list; both days are included.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HistoryFilter {
+    pub profile: Option<String>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl HistoryFilter {
+    /// The profile's runs from `days` days ago through today.
+    pub fn last_days(profile: Option<String>, days: u32, today: NaiveDate) -> Self {
+        Self { profile, from: today.checked_sub_days(chrono::Days::new(days as u64)), to: Some(today) }
+    }
+}
+
+pub struct History {
+    conn: Connection,
+}
+
+impl History {
+    /// Open (or create) the database at `path`.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(dir) = path.parent() { std::fs::create_dir_all(dir).map_err(|e| e.to_string())?; }
+        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS runs (
+                id INTEGER PRIMARY KEY,
+                started INTEGER NOT NULL,
+                profile TEXT,
+                duration_ms INTEGER NOT NULL,
+                clicks INTEGER NOT NULL,
+                misses INTEGER NOT NULL,
+                failed_steps INTEGER NOT NULL,
+                cycles INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS runs_started ON runs (started);",
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(Self { conn })
+    }
+
+    pub fn add(&self, run: &RunRecord) -> Result<(), String> {
+        self.conn
+            .execute(
+                "INSERT INTO runs (started, profile, duration_ms, clicks, misses, failed_steps, cycles) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    run.started.timestamp(),
+                    run.profile,
+                    run.duration.as_millis() as i64,
+                    run.clicks,
+                    run.misses,
+                    run.failed_steps,
+                    run.cycles,
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    /// The runs `filter` lets through, newest first.
+    pub fn runs(&self, filter: &HistoryFilter) -> Result<Vec<RunRecord>, String> {
+        let from = filter.from.and_then(day_start).unwrap_or(i64::MIN);
+        let to = filter.to.and_then(|d| d.succ_opt()).and_then(day_start).unwrap_or(i64::MAX);
+        let mut stmt = self.conn
+            .prepare(
+                "SELECT started, profile, duration_ms, clicks, misses, failed_steps, cycles FROM runs
+                 WHERE started >= ?1 AND started < ?2 AND (?3 IS NULL OR profile = ?3)
+                 ORDER BY started DESC, id DESC",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![from, to, filter.profile], |row| {
+                Ok(RunRecord {
+                    started: Local.timestamp_opt(row.get(0)?, 0).single().unwrap_or_default(),
+                    profile: row.get(1)?,
+                    duration: Duration::from_millis(row.get::<_, i64>(2)?.max(0) as u64),
+                    clicks: row.get(3)?,
+                    misses: row.get(4)?,
+                    failed_steps: row.get(5)?,
+                    cycles: row.get(6)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+
+    /// Every profile name that has runs, sorted.
+    pub fn profiles(&self) -> Result<Vec<String>, String> {
+        let mut stmt = self.conn
+            .prepare("SELECT DISTINCT profile FROM runs WHERE profile IS NOT NULL ORDER BY profile")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| row.get(0)).map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+}
+
+/// A day typed as `YYYY-MM-DD`; empty = no limit.
+pub fn parse_day(text: &str) -> Result<Option<NaiveDate>, String> {
+    let text = text.trim();
+    if text.is_empty() { return Ok(None); }
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").map(Some).map_err(|_| format!("'{}' is not a YYYY-MM-DD date", text))
+}
+
+/// Clicks and run time of `runs` added up.
+pub fn totals(runs: &[RunRecord]) -> (u64, Duration) {
+    runs.iter().fold((0, Duration::ZERO), |(clicks, time), r| (clicks + r.clicks as u64, time + r.duration))
+}
+
+/// Local midnight at the start of `day`, as a Unix timestamp.
+fn day_start(day: NaiveDate) -> Option<i64> {
+    Local.from_local_datetime(&day.and_hms_opt(0, 0, 0)?).earliest().map(|t| t.timestamp())
+}
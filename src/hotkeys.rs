@@ -0,0 +1,120 @@
+//! Global hotkeys. Needs the `hooks` feature (rdev); without it nothing is ever held or pressed.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "hooks")]
+use once_cell::sync::Lazy;
+#[cfg(feature = "hooks")]
+use parking_lot::Mutex;
+#[cfg(feature = "hooks")]
+use std::collections::HashSet;
+
+/// Keys that can be bound to global actions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HotKey { F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12, ScrollLock, Pause }
+
+impl HotKey {
+    pub const ALL: [HotKey; 14] = [
+        HotKey::F1, HotKey::F2, HotKey::F3, HotKey::F4, HotKey::F5, HotKey::F6, HotKey::F7,
+        HotKey::F8, HotKey::F9, HotKey::F10, HotKey::F11, HotKey::F12, HotKey::ScrollLock, HotKey::Pause,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HotKey::F1 => "F1", HotKey::F2 => "F2", HotKey::F3 => "F3", HotKey::F4 => "F4",
+            HotKey::F5 => "F5", HotKey::F6 => "F6", HotKey::F7 => "F7", HotKey::F8 => "F8",
+            HotKey::F9 => "F9", HotKey::F10 => "F10", HotKey::F11 => "F11", HotKey::F12 => "F12",
+            HotKey::ScrollLock => "Scroll Lock", HotKey::Pause => "Pause",
+        }
+    }
+
+    #[cfg(feature = "hooks")]
+    fn from_rdev(key: rdev::Key) -> Option<Self> {
+        use rdev::Key;
+        Some(match key {
+            Key::F1 => HotKey::F1, Key::F2 => HotKey::F2, Key::F3 => HotKey::F3, Key::F4 => HotKey::F4,
+            Key::F5 => HotKey::F5, Key::F6 => HotKey::F6, Key::F7 => HotKey::F7, Key::F8 => HotKey::F8,
+            Key::F9 => HotKey::F9, Key::F10 => HotKey::F10, Key::F11 => HotKey::F11, Key::F12 => HotKey::F12,
+            Key::ScrollLock => HotKey::ScrollLock, Key::Pause => HotKey::Pause,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(feature = "hooks")]
+static HELD: Lazy<Mutex<HashSet<HotKey>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+/// Presses not yet handled by the UI, oldest first.
+#[cfg(feature = "hooks")]
+static PRESSED: Lazy<Mutex<Vec<HotKey>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Called on every hotkey press or release, so the UI can react without polling.
+#[cfg(feature = "hooks")]
+static WAKE: Lazy<Mutex<Option<Waker>>> = Lazy::new(|| Mutex::new(None));
+
+#[cfg(feature = "hooks")]
+type Waker = Box<dyn Fn() + Send>;
+
+/// Whether global hotkeys work in this build.
+pub fn available() -> bool { cfg!(feature = "hooks") }
+
+/// Start the global keyboard listener thread. Safe to call more than once.
+pub fn start_listener() {
+    #[cfg(feature = "hooks")]
+    {
+        use std::sync::Once;
+        static STARTED: Once = Once::new();
+        STARTED.call_once(|| {
+            std::thread::spawn(|| {
+                let result = rdev::listen(|event| match event.event_type {
+                    rdev::EventType::KeyPress(k) => {
+                        // key repeat sends more presses while held; only queue the first
+                        if let Some(k) = HotKey::from_rdev(k) {
+                            if HELD.lock().insert(k) {
+                                PRESSED.lock().push(k);
+                                wake();
+                            }
+                        }
+                    }
+                    rdev::EventType::KeyRelease(k) => {
+                        if let Some(k) = HotKey::from_rdev(k) {
+                            HELD.lock().remove(&k);
+                            wake();
+                        }
+                    }
+                    _ => {}
+                });
+                if let Err(e) = result {
+                    log::error!("Global hotkey listener failed: {:?}", e);
+                }
+            });
+        });
+    }
+}
+
+/// Have `wake` called (from the listener thread) whenever a hotkey goes down or up.
+pub fn on_change(wake: impl Fn() + Send + 'static) {
+    #[cfg(feature = "hooks")]
+    { *WAKE.lock() = Some(Box::new(wake)); }
+    #[cfg(not(feature = "hooks"))]
+    { let _ = wake; }
+}
+
+#[cfg(feature = "hooks")]
+fn wake() {
+    if let Some(wake) = &*WAKE.lock() { wake(); }
+}
+
+/// Drain the hotkey presses that happened since the last call.
+pub fn take_pressed() -> Vec<HotKey> {
+    #[cfg(feature = "hooks")]
+    { std::mem::take(&mut *PRESSED.lock()) }
+    #[cfg(not(feature = "hooks"))]
+    { Vec::new() }
+}
+
+/// Whether `key` is currently held down.
+pub fn is_held(key: HotKey) -> bool {
+    #[cfg(feature = "hooks")]
+    { HELD.lock().contains(&key) }
+    #[cfg(not(feature = "hooks"))]
+    { let _ = key; false }
+}
@@ -1,14 +1,31 @@
 use enigo::{MouseControllable, MouseButton};
+use once_cell::sync::Lazy;
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
-use std::{thread, time::Duration};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::sync::Arc;
+use std::{thread, time::{Duration, Instant}};
 
-#[derive(Clone, Copy, Debug)]
+use crate::path_bank::PathBank;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Bounds {
     pub min_x: i32, pub max_x: i32,
     pub min_y: i32, pub max_y: i32,
 }
 impl Bounds {
+    /// `width` × `height` box centered on `(x, y)`.
+    pub fn centered((x, y): (i32, i32), width: i32, height: i32) -> Self {
+        let (w, h) = (width.max(0), height.max(0));
+        Bounds { min_x: x - w / 2, max_x: x - w / 2 + w, min_y: y - h / 2, max_y: y - h / 2 + h }
+    }
+
+    /// Square box of `radius` pixels on each side of `(x, y)`.
+    pub fn around((x, y): (i32, i32), radius: i32) -> Self {
+        let r = radius.max(0);
+        Bounds { min_x: x - r, max_x: x + r, min_y: y - r, max_y: y + r }
+    }
     pub fn clamp(&self, (x, y): (i32, i32)) -> (i32, i32) {
         (x.clamp(self.min_x, self.max_x), y.clamp(self.min_y, self.max_y))
     }
@@ -18,6 +35,7 @@ impl Bounds {
     pub fn nearest_point(&self, (x, y): (i32, i32)) -> (i32, i32) {
         self.clamp((x, y))
     }
+    pub fn center(&self) -> (i32, i32) { ((self.min_x + self.max_x) / 2, (self.min_y + self.max_y) / 2) }
     pub fn width(&self) -> i32 { self.max_x - self.min_x }
     pub fn height(&self) -> i32 { self.max_y - self.min_y }
     pub fn is_valid(&self) -> bool { self.width() > 0 && self.height() > 0 }
@@ -37,9 +55,29 @@ pub struct HumanMouseSettings {
     pub overshoot_chance: f32,        // e.g. 0.25
     /// Max overshoot distance in px.
     pub overshoot_px: f32,            // e.g. 12.0
+    /// The final approach comes in at up to this angle (degrees) off the straight line.
+    pub approach_angle_deg: f32,      // e.g. 30.0
+    /// Share of the last movement that bends into the approach direction.
+    pub approach_frac: f32,           // e.g. 0.3
+    /// Landing falls short of the sampled point, toward where the motion came from, by up to
+    /// this share of the target's size along the motion (Fitts-style undershoot; areas only).
+    pub undershoot: f32,              // e.g. 0.15
+    /// Up to this many tiny corrective moves once near the target, before pressing (0 = none).
+    pub settle_moves: u32,            // e.g. 2
+    /// How far off (px) the cursor is before a corrective move.
+    pub settle_px: i32,               // e.g. 3
+    /// Min & max time the button is held down per click.
+    pub min_hold_ms: u64,             // e.g. 20
+    pub max_hold_ms: u64,             // e.g. 120
     /// Min & max micro-pause durations inserted mid-movement.
     pub min_pause_ms: u64,            // e.g. 15
     pub max_pause_ms: u64,            // e.g. 60
+    /// Drift correction: re-read the real cursor every this many path steps and steer the rest
+    /// of the path by the difference, for pointer acceleration or remote-desktop scaling that
+    /// put the cursor somewhere other than asked. `None` trusts the OS.
+    pub checkpoint_steps: Option<usize>,
+    /// Recorded paths to replay (bent to fit) instead of synthetic curves, for moves long enough.
+    pub path_bank: Option<Arc<PathBank>>,
     /// Seed for reproducible tests. Use None in prod.
     pub rng_seed: Option<u64>,
 }
@@ -53,18 +91,120 @@ impl Default for HumanMouseSettings {
             micro_jitter_hz: 9.0,
             overshoot_chance: 0.25,
             overshoot_px: 12.0,
+            approach_angle_deg: 0.0,
+            approach_frac: 0.3,
+            undershoot: 0.0,
+            settle_moves: 0,
+            settle_px: 3,
+            min_hold_ms: 20,
+            max_hold_ms: 120,
             min_pause_ms: 15,
             max_pause_ms: 60,
+            checkpoint_steps: None,
+            path_bank: None,
             rng_seed: None,
         }
     }
 }
 
+thread_local! {
+    /// The bench's simulated clock: while set, pauses move it on instead of sleeping.
+    static SIMULATED: Cell<Option<Duration>> = const { Cell::new(None) };
+}
+
+/// Real time since the first call, or simulated time inside [`simulate`].
+pub fn clock() -> Duration {
+    static EPOCH: Lazy<Instant> = Lazy::new(Instant::now);
+    SIMULATED.with(|s| s.get()).unwrap_or_else(|| EPOCH.elapsed())
+}
+
+/// Sleep for `d`, or just advance the simulated clock inside [`simulate`].
+fn pause(d: Duration) {
+    match SIMULATED.with(|s| s.get()) {
+        Some(now) => SIMULATED.with(|s| s.set(Some(now + d))),
+        None => thread::sleep(d),
+    }
+}
+
+/// Run `f` on this thread with a simulated clock starting at zero, so paths that would take
+/// minutes to move come back at once.
+pub fn simulate<T>(f: impl FnOnce() -> T) -> T {
+    SIMULATED.with(|s| s.set(Some(Duration::ZERO)));
+    let out = f();
+    SIMULATED.with(|s| s.set(None));
+    out
+}
+
+/// What happens at a [`PathPoint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathEvent { Move, Down, Up }
+
+/// One timed point of a planned move: `ms` after the move starts, the cursor is at `(x, y)`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+pub struct PathPoint {
+    pub ms: f32,
+    pub x: i32,
+    pub y: i32,
+    pub event: PathEvent,
+}
+
+/// Records what a move would do instead of doing it; also the tests' mock mouse.
+pub struct Plan {
+    pub pos: (i32, i32),
+    pub points: Vec<PathPoint>,
+    /// Where a move really puts the cursor, as a share of where it was sent: 1.0 but for a
+    /// remote desktop that scales what it's sent.
+    pub scale: f32,
+}
+
+impl Plan {
+    pub fn at(pos: (i32, i32)) -> Self { Self { pos, points: Vec::new(), scale: 1.0 } }
+
+    fn push(&mut self, event: PathEvent) {
+        let (x, y) = self.pos;
+        self.points.push(PathPoint { ms: clock().as_secs_f32() * 1000.0, x, y, event });
+    }
+}
+
+impl MouseControllable for Plan {
+    fn mouse_move_to(&mut self, x: i32, y: i32) {
+        self.pos = ((x as f32 * self.scale).round() as i32, (y as f32 * self.scale).round() as i32);
+        self.push(PathEvent::Move);
+    }
+    fn mouse_move_relative(&mut self, x: i32, y: i32) { self.mouse_move_to(self.pos.0 + x, self.pos.1 + y); }
+    fn mouse_down(&mut self, _button: MouseButton) { self.push(PathEvent::Down); }
+    fn mouse_up(&mut self, _button: MouseButton) { self.push(PathEvent::Up); }
+    fn mouse_click(&mut self, _button: MouseButton) {
+        self.push(PathEvent::Down);
+        self.push(PathEvent::Up);
+    }
+    fn mouse_scroll_x(&mut self, _length: i32) {}
+    fn mouse_scroll_y(&mut self, _length: i32) {}
+    fn main_display_size(&self) -> (i32, i32) { (0, 0) }
+    fn mouse_location(&self) -> (i32, i32) { self.pos }
+}
+
+/// The path [`human_move_and_click`] would take from `from` to `to`, including the button
+/// press and release, without moving anything or taking any time. Drift correction has no
+/// real cursor to read here, so it never corrects.
+pub fn plan(from: (i32, i32), to: (i32, i32), bounds: Option<Bounds>, settings: &HumanMouseSettings) -> Vec<PathPoint> {
+    let mut plan = Plan::at(from);
+    simulate(|| human_move_and_click(&mut plan, from, to, bounds, settings, MouseButton::Left));
+    plan.points
+}
+
 /// Cosine ease-in-out (smooth velocity bell curve).
 fn ease_in_out(t: f32) -> f32 {
     0.5 - 0.5 * (std::f32::consts::PI * t).cos()
 }
 
+/// Progress along the final approach: like [`ease_in_out`], but with a longer deceleration
+/// into the target than acceleration out of the start.
+fn ease_approach(t: f32) -> f32 {
+    ease_in_out(t.powf(0.75))
+}
+
 /// Cubic Bezier interpolation
 fn cubic_bezier(p0: (f32,f32), p1: (f32,f32), p2: (f32,f32), p3: (f32,f32), t: f32) -> (f32,f32) {
     let u = 1.0 - t;
@@ -82,10 +222,13 @@ fn len((x1,y1):(f32,f32),(x2,y2):(f32,f32)) -> f32 {
     ((x2-x1).hypot(y2-y1)).max(1.0)
 }
 
+/// Control points of a cubic Bezier: (p0, p1, p2, p3).
+type BezierCurve = ((f32,f32),(f32,f32),(f32,f32),(f32,f32));
+
 /// Build a wiggly cubic path with control points roughly perpendicular to the segment.
 fn make_bezier_with_wiggle(
     from: (i32,i32), to: (i32,i32), rng: &mut impl Rng
-) -> ((f32,f32),(f32,f32),(f32,f32),(f32,f32)) {
+) -> BezierCurve {
     let p0 = (from.0 as f32, from.1 as f32);
     let p3 = (to.0 as f32, to.1 as f32);
 
@@ -111,6 +254,95 @@ fn make_bezier_with_wiggle(
     (p0, p1, p2, p3)
 }
 
+/// Where to actually land when aiming for `to` from `from`: pulled back toward `from` by a
+/// random share of the target's extent along the motion, and kept inside `bounds`. Point
+/// targets (no bounds) are hit as given. A move from outside `bounds` is measured from where
+/// it enters them. Callers pick this before checking the point, so it's what gets clicked.
+pub fn landing_point(
+    from: (i32, i32), to: (i32, i32), bounds: Option<Bounds>, settings: &HumanMouseSettings, rng: &mut impl Rng,
+) -> (i32, i32) {
+    let Some(b) = bounds else { return to };
+    let from = if b.contains(from) { from } else { b.nearest_point(from) };
+    let (dx, dy) = ((to.0 - from.0) as f32, (to.1 - from.1) as f32);
+    let d = (dx * dx + dy * dy).sqrt();
+    if d < 1.0 || settings.undershoot <= 0.0 { return to; }
+    let (ux, uy) = (dx / d, dy / d);
+    let extent = ux.abs() * b.width() as f32 + uy.abs() * b.height() as f32;
+    let short = rng.gen_range(0.0..=settings.undershoot) * extent;
+    b.clamp(((to.0 as f32 - ux * short).round() as i32, (to.1 as f32 - uy * short).round() as i32))
+}
+
+/// Unit vector pointing from `to` back along the final approach: the way toward `from`,
+/// turned by a random angle of up to `approach_angle_deg`. `None` for a zero-length move.
+pub fn approach_direction(
+    from: (i32, i32), to: (i32, i32), settings: &HumanMouseSettings, rng: &mut impl Rng,
+) -> Option<(f32, f32)> {
+    let (dx, dy) = ((from.0 - to.0) as f32, (from.1 - to.1) as f32);
+    let d = (dx * dx + dy * dy).sqrt();
+    if d < 1.0 { return None; }
+    let max = settings.approach_angle_deg.to_radians().abs();
+    let angle = if max > 0.0 { rng.gen_range(-max..=max) } else { 0.0 };
+    let (sin, cos) = angle.sin_cos();
+    let (ux, uy) = (dx / d, dy / d);
+    Some((ux * cos - uy * sin, ux * sin + uy * cos))
+}
+
+/// Send the cursor to `(x, y)` shifted by `offset`, the `i`th point of a path. With drift
+/// correction on, the real cursor is re-read at checkpoints (and the `last` point settled) and
+/// the offset to use from then on is returned.
+fn move_checked(
+    enigo: &mut impl MouseControllable, (x, y): (i32, i32), mut offset: (i32, i32), i: usize, last: bool,
+    settings: &HumanMouseSettings,
+) -> (i32, i32) {
+    enigo.mouse_move_to(x + offset.0, y + offset.1);
+    let Some(every) = settings.checkpoint_steps else { return offset };
+    if last {
+        // the end point is what gets clicked: settle it, a few tries at most
+        for _ in 0..CHECKPOINT_TRIES {
+            let before = offset;
+            offset = steer(offset, (x, y), enigo.mouse_location());
+            if offset == before { break; }
+            enigo.mouse_move_to(x + offset.0, y + offset.1);
+        }
+    } else if i.is_multiple_of(every.max(1)) {
+        offset = steer(offset, (x, y), enigo.mouse_location());
+    }
+    offset
+}
+
+/// Play back `(ms, x, y)` points from the path bank on their own timing.
+fn replay(enigo: &mut impl MouseControllable, points: &[(u64, i32, i32)], bounds: Option<Bounds>, settings: &HumanMouseSettings) {
+    let started = clock();
+    let mut offset = (0, 0);
+    for (i, &(ms, x, y)) in points.iter().enumerate() {
+        if let Some(wait) = Duration::from_millis(ms).checked_sub(clock() - started) { pause(wait); }
+        let p = bounds.map_or((x, y), |b| b.clamp((x, y)));
+        offset = move_checked(enigo, p, offset, i, i + 1 == points.len(), settings);
+    }
+}
+
+/// A spot at most `settle_px` off `to` (but not on it), inside `bounds`.
+pub fn settle_point(to: (i32, i32), bounds: Option<Bounds>, settings: &HumanMouseSettings, rng: &mut impl Rng) -> (i32, i32) {
+    let r = settings.settle_px.max(1);
+    let (dx, dy) = loop {
+        let d = (rng.gen_range(-r..=r), rng.gen_range(-r..=r));
+        if d != (0, 0) { break d; }
+    };
+    let p = (to.0 + dx, to.1 + dy);
+    bounds.map_or(p, |b| b.clamp(p))
+}
+
+/// How often the last point of a path is re-sent before drift correction gives up.
+const CHECKPOINT_TRIES: usize = 4;
+
+/// The offset to send with from now on, given where the cursor `actual`ly is while it should
+/// be at `intended`. Drift of a pixel is left alone, since rounding alone causes that much.
+pub fn steer(offset: (i32, i32), intended: (i32, i32), actual: (i32, i32)) -> (i32, i32) {
+    let drift = (actual.0 - intended.0, actual.1 - intended.1);
+    if drift.0.abs() <= 1 && drift.1.abs() <= 1 { return offset; }
+    (offset.0 - drift.0, offset.1 - drift.1)
+}
+
 /// Optionally insert a tiny overshoot point before the true `to`.
 fn maybe_overshoot(to: (i32,i32), from: (i32,i32), settings: &HumanMouseSettings, rng: &mut impl Rng) -> (i32,i32) {
     if rng.gen::<f32>() < settings.overshoot_chance {
@@ -125,15 +357,43 @@ fn maybe_overshoot(to: (i32,i32), from: (i32,i32), settings: &HumanMouseSettings
     to
 }
 
+/// How far (px) the cursor may be off a point target and still count as a hit.
+pub const MISS_TOLERANCE: i32 = 2;
+
+/// Whether a click with the cursor at `at` hit its target: inside `bounds`, or close to `to`
+/// for point clicks.
+pub fn on_target(bounds: Option<Bounds>, to: (i32, i32), at: (i32, i32)) -> bool {
+    match bounds {
+        Some(b) => b.contains(at),
+        None => (at.0 - to.0).abs() <= MISS_TOLERANCE && (at.1 - to.1).abs() <= MISS_TOLERANCE,
+    }
+}
+
 /// Move the mouse like a human: smooth path, velocity bell curve, jitter, pauses, optional overshoot.
+/// Returns where the cursor really was when the button went down (re-read from the backend,
+/// since pointer acceleration can leave it elsewhere).
 pub fn human_move_and_click(
+    enigo: &mut impl MouseControllable,
+    from: (i32,i32),
+    to: (i32,i32),
+    bounds: Option<Bounds>,
+    settings: &HumanMouseSettings,
+    button: MouseButton,
+) -> (i32, i32) {
+    human_move_and_click_while(enigo, from, to, bounds, settings, button, || true).unwrap_or(to)
+}
+
+/// [`human_move_and_click`], asking `go_on` after each part of the move and right before the
+/// press. Stops where it is and returns `None`, without pressing, once `go_on` says no.
+pub fn human_move_and_click_while(
     enigo: &mut impl MouseControllable,
     mut from: (i32,i32),
     to: (i32,i32),
     bounds: Option<Bounds>,
     settings: &HumanMouseSettings,
     button: MouseButton,
-) {
+    go_on: impl Fn() -> bool,
+) -> Option<(i32, i32)> {
     let mut rng: StdRng = match settings.rng_seed {
         Some(seed) => StdRng::seed_from_u64(seed),
         None => StdRng::from_entropy(),
@@ -143,7 +403,8 @@ pub fn human_move_and_click(
     if let Some(b) = bounds {
         if !b.contains(from) {
             let entry = b.nearest_point(from);
-            human_move_inner(enigo, from, entry, None, settings, &mut rng);
+            human_move_inner(enigo, from, entry, None, None, settings, &mut rng);
+            if !go_on() { return None; }
             from = entry;
         }
     }
@@ -151,17 +412,38 @@ pub fn human_move_and_click(
     // Sometimes overshoot a bit, then settle back.
     let over = maybe_overshoot(to, from, settings, &mut rng);
     if over != to {
-        human_move_inner(enigo, from, over, bounds, settings, &mut rng);
+        human_move_inner(enigo, from, over, bounds, None, settings, &mut rng);
+        if !go_on() { return None; }
         // short settle
-        thread::sleep(Duration::from_millis(20 + rng.gen_range(0..20)));
-        human_move_inner(enigo, over, to, bounds, settings, &mut rng);
-    } else {
-        human_move_inner(enigo, from, to, bounds, settings, &mut rng);
+        pause(Duration::from_millis(20 + rng.gen_range(0..20)));
+        from = over;
+    }
+    // When settling, arrive a few px off and correct in one or two small moves.
+    let settle = if settings.settle_moves > 0 { rng.gen_range(1..=settings.settle_moves) } else { 0 };
+    let arrive = if settle > 0 { settle_point(to, bounds, settings, &mut rng) } else { to };
+    let approach = approach_direction(from, arrive, settings, &mut rng);
+    human_move_inner(enigo, from, arrive, bounds, approach, settings, &mut rng);
+    let mut at = arrive;
+    for n in 1..=settle {
+        if !go_on() { return None; }
+        pause(Duration::from_millis(rng.gen_range(settings.min_pause_ms..=settings.max_pause_ms) + 40));
+        let next = if n == settle { to } else { settle_point(to, bounds, settings, &mut rng) };
+        human_move_inner(enigo, at, next, bounds, None, settings, &mut rng);
+        at = next;
     }
 
-    // Human click: press + tiny hold + release with slight randomness
+    if !go_on() { return None; }
+    let landed = enigo.mouse_location();
+    press(enigo, button, settings, &mut rng);
+    Some(landed)
+}
+
+/// Human click: press, hold for a random `min_hold_ms..=max_hold_ms`, release. Down and up
+/// in the same instant is a tell, and some apps ignore such clicks.
+pub fn press(enigo: &mut impl MouseControllable, button: MouseButton, settings: &HumanMouseSettings, rng: &mut impl Rng) {
+    let (lo, hi) = (settings.min_hold_ms.min(settings.max_hold_ms), settings.min_hold_ms.max(settings.max_hold_ms));
     enigo.mouse_down(button);
-    thread::sleep(Duration::from_millis(20 + rng.gen_range(0..50)));
+    pause(Duration::from_millis(rng.gen_range(lo..=hi)));
     enigo.mouse_up(button);
 }
 
@@ -170,11 +452,22 @@ fn human_move_inner(
     from: (i32,i32),
     to: (i32,i32),
     bounds: Option<Bounds>,
+    approach: Option<(f32, f32)>,
     settings: &HumanMouseSettings,
     rng: &mut StdRng,
 ) {
+    if let Some(points) = settings.path_bank.as_ref().and_then(|bank| bank.variant(from, to, rng)) {
+        replay(enigo, &points, bounds, settings);
+        return;
+    }
+
     // Build a bezier-like path with curvature.
-    let (p0, p1, p2, p3) = make_bezier_with_wiggle(from, to, rng);
+    let (p0, p1, mut p2, p3) = make_bezier_with_wiggle(from, to, rng);
+    // The last control point sets the direction the path comes in from.
+    if let Some((ax, ay)) = approach {
+        let reach = settings.approach_frac * len(p0, p3);
+        p2 = (p3.0 + ax * reach, p3.1 + ay * reach);
+    }
     // Approximate duration from average speed (add jitter).
     let distance = len(p0, p3);
     let speed_variation = 1.0 + settings.speed_jitter * rng.gen_range(-1.0..1.0);
@@ -188,13 +481,16 @@ fn human_move_inner(
     // Random chance to insert a tiny pause mid-path (people hesitate).
     let maybe_pause_at = if rng.gen::<f32>() < 0.25 { Some(rng.gen_range(steps/3..(2*steps/3).max(steps/3+1))) } else { None };
 
+    // Added to every position sent, to cancel the drift seen at the last checkpoint
+    let mut offset = (0, 0);
+
     // Jitter parameters
     let jitter_amp = settings.micro_jitter_px;
     let jitter_hz = (settings.micro_jitter_hz * (1.0 + rng.gen_range(-0.2..0.2))).max(1.0);
 
     for i in 0..=steps {
         let raw_t = i as f32 / steps as f32;
-        let t = ease_in_out(raw_t);
+        let t = if approach.is_some() { ease_approach(raw_t) } else { ease_in_out(raw_t) };
 
         let (mut x, mut y) = cubic_bezier(p0, p1, p2, p3, t);
 
@@ -216,17 +512,17 @@ fn human_move_inner(
             (xi, yi) = b.clamp((xi, yi));
         }
 
-        enigo.mouse_move_to(xi, yi);
+        offset = move_checked(enigo, (xi, yi), offset, i, i == steps, settings);
 
         // Mid-path micro-pause
         if let Some(pause_idx) = maybe_pause_at {
             if i == pause_idx {
-                thread::sleep(Duration::from_millis(
+                pause(Duration::from_millis(
                     rng.gen_range(settings.min_pause_ms..=settings.max_pause_ms)
                 ));
             }
         }
 
-        thread::sleep(Duration::from_millis(step_ms as u64));
+        pause(Duration::from_millis(step_ms));
     }
 }
@@ -0,0 +1,120 @@
+//! UI translations. A catalog maps the English text of each UI string to its translation,
+//! so anything not (yet) translated simply shows in English. Besides the built-in catalogs,
+//! `<config>/area_clicker/locales/<code>.json` files show up in the language picker, which
+//! lets people translate without rebuilding:
+//!
+//! ```json
+//! { "name": "Deutsch", "texts": { "Start": "Starten", "{} clicks": "{} Klicks" } }
+//! ```
+//!
+//! `{}` placeholders are filled in order; a translation can use `{0}`, `{1}`… to reorder them.
+
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Catalogs compiled in, besides English: (code, JSON).
+const BUILT_IN: &[(&str, &str)] = &[("de", include_str!("../locales/de.json"))];
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Catalog {
+    /// The language's own name, e.g. "Deutsch".
+    pub name: String,
+    pub texts: HashMap<String, String>,
+}
+
+/// A language the UI can be shown in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Language {
+    pub code: String,
+    pub name: String,
+}
+
+/// (code, catalog) in use; English has an empty catalog.
+static CURRENT: Lazy<RwLock<(String, Catalog)>> = Lazy::new(|| RwLock::new(("en".to_string(), Catalog::default())));
+
+pub fn parse(json: &str) -> Result<Catalog, String> { serde_json::from_str(json).map_err(|e| e.to_string()) }
+
+/// English, the built-in languages, then the catalogs in `dir` (which win over built-in
+/// ones with the same code).
+pub fn available(dir: Option<&Path>) -> Vec<Language> {
+    let mut langs = vec![Language { code: "en".into(), name: "English".into() }];
+    let mut add = |code: &str, name: String| {
+        langs.retain(|l| l.code != code);
+        langs.push(Language { code: code.to_string(), name });
+    };
+    for (code, json) in BUILT_IN {
+        if let Ok(c) = parse(json) { add(code, c.name); }
+    }
+    let entries = dir.and_then(|d| std::fs::read_dir(d).ok()).into_iter().flatten().flatten();
+    for path in entries.map(|e| e.path()).filter(|p| p.extension().is_some_and(|e| e == "json")) {
+        let Some(code) = path.file_stem().and_then(|s| s.to_str()) else { continue };
+        match std::fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|t| parse(&t)) {
+            Ok(c) => add(code, if c.name.is_empty() { code.to_string() } else { c.name }),
+            Err(e) => log::warn!("Ignoring translation {}: {}", path.display(), e),
+        }
+    }
+    langs
+}
+
+/// Show the UI in `code`, looking in `dir` before the built-in catalogs.
+pub fn set_language(code: &str, dir: Option<&Path>) -> Result<(), String> {
+    let catalog = if code == "en" {
+        Catalog::default()
+    } else if let Some(text) = dir.and_then(|d| std::fs::read_to_string(d.join(format!("{}.json", code))).ok()) {
+        parse(&text)?
+    } else {
+        let (_, json) = BUILT_IN.iter().find(|(c, _)| *c == code).ok_or_else(|| format!("No translation for '{}'", code))?;
+        parse(json)?
+    };
+    *CURRENT.write() = (code.to_string(), catalog);
+    Ok(())
+}
+
+/// Code of the language in use, e.g. "en".
+pub fn current() -> String { CURRENT.read().0.clone() }
+
+/// `text` in the current language.
+pub fn translate(text: &str) -> String {
+    CURRENT.read().1.texts.get(text).cloned().unwrap_or_else(|| text.to_string())
+}
+
+/// Put `args` into the `{}` / `{n}` placeholders of `template`.
+pub fn fill(template: &str, args: &[String]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut next = 0;
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let index = after.find('}').map(|end| (&after[..end], end));
+        match index {
+            Some(("", end)) => {
+                out.push_str(args.get(next).map_or("", String::as_str));
+                next += 1;
+                rest = &after[end + 1..];
+            }
+            Some((n, end)) if n.bytes().all(|b| b.is_ascii_digit()) => {
+                out.push_str(n.parse::<usize>().ok().and_then(|i| args.get(i)).map_or("", String::as_str));
+                rest = &after[end + 1..];
+            }
+            _ => {
+                out.push('{');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `tr!("Start")`, `tr!("{} clicks", n)`: translate, then fill in the arguments.
+macro_rules! tr {
+    ($text:literal) => { $crate::i18n::translate($text) };
+    ($text:literal, $($arg:expr),+ $(,)?) => {
+        $crate::i18n::fill(&$crate::i18n::translate($text), &[$($arg.to_string()),+])
+    };
+}
+pub(crate) use tr;
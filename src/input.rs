@@ -0,0 +1,722 @@
+//! Input injection backends: enigo (X11, Windows, macOS) or the `ydotool` CLI, which goes
+//! through uinput and so also works in Wayland sessions where X11 injection does nothing.
+
+use enigo::{Key, KeyboardControllable, MouseButton, MouseControllable};
+use once_cell::sync::OnceCell;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// The latest injection failure the UI hasn't shown yet.
+static FAILURE: Mutex<Option<String>> = parking_lot::const_mutex(None);
+
+/// Log an injection failure and keep it for the UI.
+fn failed(message: String) {
+    log::warn!("{}", message);
+    *FAILURE.lock() = Some(message);
+}
+
+/// The latest injection failure since the last call, if any.
+pub fn take_failure() -> Option<String> { FAILURE.lock().take() }
+
+/// When we last sent any input, to tell it from the user's.
+static LAST_SENT: Mutex<Option<Instant>> = parking_lot::const_mutex(None);
+
+/// How long ago we last sent input, through any backend.
+pub fn since_sent() -> Option<Duration> { LAST_SENT.lock().map(|at| at.elapsed()) }
+
+/// Whether ydotool works here, once [`check_ydotool`]'s probe has finished.
+static YDOTOOL: OnceCell<Result<(), String>> = OnceCell::new();
+
+/// Probe ydotool on a background thread, once per run; `done` is called when the answer is in.
+/// Spawning it takes long enough that the UI shouldn't wait for it.
+pub fn check_ydotool(done: impl FnOnce() + Send + 'static) {
+    static STARTED: AtomicBool = AtomicBool::new(false);
+    if STARTED.swap(true, Ordering::Relaxed) { return; }
+    std::thread::spawn(move || {
+        // a zero-length relative move is a harmless probe that fails without ydotoold
+        let found = match Command::new("ydotool").args(["mousemove", "-x", "0", "-y", "0"]).output() {
+            Ok(out) if out.status.success() => Ok(()),
+            Ok(out) => Err(format!("ydotool failed: {}", String::from_utf8_lossy(&out.stderr).trim())),
+            Err(e) => Err(format!("ydotool not available: {}", e)),
+        };
+        let _ = YDOTOOL.set(found);
+        done();
+    });
+}
+
+/// Whether [`check_ydotool`]'s probe has finished.
+pub fn ydotool_checked() -> bool { YDOTOOL.get().is_some() }
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Backend { Enigo, Ydotool, SendInput, WindowMessages }
+
+impl Backend {
+    /// Backends offered on this platform.
+    #[cfg(windows)]
+    pub const ALL: &'static [Backend] = &[Backend::Enigo, Backend::SendInput, Backend::WindowMessages];
+    #[cfg(not(windows))]
+    pub const ALL: &'static [Backend] = &[Backend::Enigo, Backend::Ydotool];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Backend::Enigo => "enigo (X11/Windows/macOS)",
+            Backend::Ydotool => "ydotool (Wayland)",
+            Backend::SendInput => "SendInput (Windows, absolute)",
+            Backend::WindowMessages => "Window messages (background, Windows)",
+        }
+    }
+
+    /// Whether `mouse_location` reports the real cursor (others echo the last position sent).
+    pub fn reads_cursor(self) -> bool { matches!(self, Backend::Enigo | Backend::SendInput) }
+
+    /// Check that the backend can inject input here; the error says why not. ydotool's answer
+    /// is the cached one from [`check_ydotool`].
+    pub fn check(self) -> Result<(), String> {
+        match self {
+            Backend::Enigo if is_wayland_session() => Err(
+                "This is a Wayland session, where enigo can't move the cursor. Install ydotool (and start ydotoold) and select it as the input backend.".to_string()
+            ),
+            Backend::Enigo => Ok(()),
+            Backend::Ydotool => YDOTOOL.get().cloned().unwrap_or_else(|| Err("Still checking whether ydotool works.".to_string())),
+            Backend::SendInput | Backend::WindowMessages if cfg!(windows) => Ok(()),
+            Backend::SendInput | Backend::WindowMessages => Err(format!("{} is only available on Windows.", self.label())),
+        }
+    }
+
+    /// First backend that works in this session, or enigo if none does.
+    pub fn detect() -> Backend {
+        if is_wayland_session() && Backend::Ydotool.check().is_ok() { Backend::Ydotool } else { Backend::Enigo }
+    }
+}
+
+pub fn is_wayland_session() -> bool {
+    std::env::var("XDG_SESSION_TYPE").is_ok_and(|t| t.eq_ignore_ascii_case("wayland"))
+        || std::env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// The active backend. Implements enigo's traits so the mouse/keyboard code doesn't care which.
+/// The app keeps exactly one, opened when a backend is selected; see `set_input_backend`.
+pub enum Input {
+    Enigo(enigo::Enigo),
+    Unavailable(Unavailable),
+    Ydotool(Ydotool),
+    #[cfg(windows)]
+    SendInput(win::SendInputBackend),
+    #[cfg(windows)]
+    WindowMessages(win::PostMessageBackend),
+}
+
+impl Input {
+    /// Open `backend`; the error says why it can't work here.
+    pub fn new(backend: Backend) -> Result<Self, String> {
+        Ok(match backend {
+            Backend::Enigo => Input::Enigo(open_enigo()?),
+            Backend::Ydotool => Input::Ydotool(Ydotool::default()),
+            #[cfg(windows)]
+            Backend::SendInput => Input::SendInput(win::SendInputBackend::default()),
+            #[cfg(windows)]
+            Backend::WindowMessages => Input::WindowMessages(win::PostMessageBackend::default()),
+            #[cfg(not(windows))]
+            Backend::SendInput | Backend::WindowMessages => return Err(backend.check().unwrap_err()),
+        })
+    }
+
+    /// Stands in for a backend that couldn't be opened: drops all input and reports `reason`.
+    pub fn unavailable(backend: Backend, reason: String) -> Self { Self::unavailable_reporting(backend, reason, Box::new(failed)) }
+
+    /// Like [`Input::unavailable`], but each dropped input goes to `report` instead of the UI.
+    pub fn unavailable_reporting(backend: Backend, reason: String, report: Box<dyn Fn(String) + Send>) -> Self {
+        Input::Unavailable(Unavailable { backend, reason, report })
+    }
+
+    /// Why this backend couldn't be opened, if it couldn't.
+    pub fn error(&self) -> Option<&str> {
+        match self {
+            Input::Unavailable(u) => Some(&u.reason),
+            _ => None,
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        match self {
+            Input::Enigo(_) => Backend::Enigo,
+            Input::Unavailable(u) => u.backend,
+            Input::Ydotool(_) => Backend::Ydotool,
+            #[cfg(windows)]
+            Input::SendInput(_) => Backend::SendInput,
+            #[cfg(windows)]
+            Input::WindowMessages(_) => Backend::WindowMessages,
+        }
+    }
+
+    /// Window messages only: the top-level window (HWND) that receives the clicks.
+    pub fn set_target_window(&mut self, hwnd: isize) {
+        #[cfg(windows)]
+        if let Input::WindowMessages(w) = self { w.hwnd = hwnd; }
+        #[cfg(not(windows))]
+        let _ = hwnd;
+    }
+
+    /// SendInput only: send keys as hardware scan codes instead of virtual-key codes.
+    pub fn set_hardware_like(&mut self, on: bool) {
+        #[cfg(windows)]
+        if let Input::SendInput(s) = self { s.hardware_like = on; }
+        #[cfg(not(windows))]
+        let _ = on;
+    }
+
+    fn mouse(&self) -> &dyn MouseControllable {
+        match self {
+            Input::Enigo(e) => e,
+            Input::Unavailable(u) => u,
+            Input::Ydotool(yd) => yd,
+            #[cfg(windows)]
+            Input::SendInput(s) => s,
+            #[cfg(windows)]
+            Input::WindowMessages(w) => w,
+        }
+    }
+
+    fn mouse_mut(&mut self) -> &mut dyn MouseControllable {
+        *LAST_SENT.lock() = Some(Instant::now());
+        match self {
+            Input::Enigo(e) => e,
+            Input::Unavailable(u) => u,
+            Input::Ydotool(yd) => yd,
+            #[cfg(windows)]
+            Input::SendInput(s) => s,
+            #[cfg(windows)]
+            Input::WindowMessages(w) => w,
+        }
+    }
+
+    fn keyboard_mut(&mut self) -> &mut dyn KeyboardControllable {
+        *LAST_SENT.lock() = Some(Instant::now());
+        match self {
+            Input::Enigo(e) => e,
+            Input::Unavailable(u) => u,
+            Input::Ydotool(yd) => yd,
+            #[cfg(windows)]
+            Input::SendInput(s) => s,
+            #[cfg(windows)]
+            Input::WindowMessages(w) => w,
+        }
+    }
+}
+
+impl MouseControllable for Input {
+    fn mouse_move_to(&mut self, x: i32, y: i32) { self.mouse_mut().mouse_move_to(x, y) }
+    fn mouse_move_relative(&mut self, x: i32, y: i32) { self.mouse_mut().mouse_move_relative(x, y) }
+    fn mouse_down(&mut self, button: MouseButton) { self.mouse_mut().mouse_down(button) }
+    fn mouse_up(&mut self, button: MouseButton) { self.mouse_mut().mouse_up(button) }
+    fn mouse_click(&mut self, button: MouseButton) { self.mouse_mut().mouse_click(button) }
+    fn mouse_scroll_x(&mut self, length: i32) { self.mouse_mut().mouse_scroll_x(length) }
+    fn mouse_scroll_y(&mut self, length: i32) { self.mouse_mut().mouse_scroll_y(length) }
+    fn main_display_size(&self) -> (i32, i32) { self.mouse().main_display_size() }
+    fn mouse_location(&self) -> (i32, i32) { self.mouse().mouse_location() }
+}
+
+impl KeyboardControllable for Input {
+    fn key_sequence(&mut self, sequence: &str) { self.keyboard_mut().key_sequence(sequence) }
+    fn key_down(&mut self, key: Key) { self.keyboard_mut().key_down(key) }
+    fn key_up(&mut self, key: Key) { self.keyboard_mut().key_up(key) }
+    fn key_click(&mut self, key: Key) { self.keyboard_mut().key_click(key) }
+}
+
+/// enigo's X11 side (xdo) silently ignores everything without an X display, so refuse there.
+fn open_enigo() -> Result<enigo::Enigo, String> {
+    if cfg!(target_os = "linux") && std::env::var_os("DISPLAY").is_none() {
+        return Err("DISPLAY is not set; enigo has no X server to send input to.".to_string());
+    }
+    Ok(enigo::Enigo::new())
+}
+
+/// See [`Input::unavailable`].
+pub struct Unavailable {
+    backend: Backend,
+    reason: String,
+    report: Box<dyn Fn(String) + Send>,
+}
+
+impl Unavailable {
+    fn drop_input(&self) { (self.report)(format!("No input sent: {}", self.reason)); }
+}
+
+impl MouseControllable for Unavailable {
+    fn mouse_move_to(&mut self, _x: i32, _y: i32) { self.drop_input(); }
+    fn mouse_move_relative(&mut self, _x: i32, _y: i32) { self.drop_input(); }
+    fn mouse_down(&mut self, _button: MouseButton) { self.drop_input(); }
+    fn mouse_up(&mut self, _button: MouseButton) { self.drop_input(); }
+    fn mouse_click(&mut self, _button: MouseButton) { self.drop_input(); }
+    fn mouse_scroll_x(&mut self, _length: i32) { self.drop_input(); }
+    fn mouse_scroll_y(&mut self, _length: i32) { self.drop_input(); }
+    fn main_display_size(&self) -> (i32, i32) { (0, 0) }
+    fn mouse_location(&self) -> (i32, i32) { (0, 0) }
+}
+
+impl KeyboardControllable for Unavailable {
+    fn key_sequence(&mut self, _sequence: &str) { self.drop_input(); }
+    fn key_down(&mut self, _key: Key) { self.drop_input(); }
+    fn key_up(&mut self, _key: Key) { self.drop_input(); }
+    fn key_click(&mut self, _key: Key) { self.drop_input(); }
+}
+
+/// Drives the `ydotool` CLI (ydotool ≥ 1.0, needs a running `ydotoold`).
+/// ydotool can't read the cursor position, so the last position we moved to is reported.
+#[derive(Default)]
+pub struct Ydotool {
+    pos: (i32, i32),
+}
+
+impl Ydotool {
+    fn run(&self, args: &[String]) {
+        match Command::new("ydotool").args(args).output() {
+            Ok(out) if out.status.success() => {}
+            Ok(out) => failed(format!("ydotool {:?} failed: {}", args, String::from_utf8_lossy(&out.stderr).trim())),
+            Err(e) => failed(format!("Could not run ydotool: {}", e)),
+        }
+    }
+
+    fn click(&self, button: MouseButton, action: u8) {
+        match button_code(button) {
+            Some(code) => self.run(&["click".into(), format!("{:#04x}", action | code)]),
+            None => failed(format!("ydotool backend: unsupported button {:?}", button)),
+        }
+    }
+
+    fn key(&self, key: Key, down: bool, up: bool) {
+        let Some(code) = key_code(key) else {
+            failed(format!("ydotool backend: unsupported key {:?}", key));
+            return;
+        };
+        let mut args = vec!["key".to_string()];
+        if down { args.push(format!("{}:1", code)); }
+        if up { args.push(format!("{}:0", code)); }
+        self.run(&args);
+    }
+}
+
+const DOWN: u8 = 0x40;
+const UP: u8 = 0x80;
+
+impl MouseControllable for Ydotool {
+    fn mouse_move_to(&mut self, x: i32, y: i32) {
+        self.run(&["mousemove".into(), "--absolute".into(), "-x".into(), x.to_string(), "-y".into(), y.to_string()]);
+        self.pos = (x, y);
+    }
+    fn mouse_move_relative(&mut self, x: i32, y: i32) {
+        self.run(&["mousemove".into(), "-x".into(), x.to_string(), "-y".into(), y.to_string()]);
+        self.pos = (self.pos.0 + x, self.pos.1 + y);
+    }
+    fn mouse_down(&mut self, button: MouseButton) { self.click(button, DOWN); }
+    fn mouse_up(&mut self, button: MouseButton) { self.click(button, UP); }
+    fn mouse_click(&mut self, button: MouseButton) { self.click(button, DOWN | UP); }
+    fn mouse_scroll_x(&mut self, length: i32) {
+        self.run(&["mousemove".into(), "--wheel".into(), "-x".into(), length.to_string(), "-y".into(), "0".into()]);
+    }
+    fn mouse_scroll_y(&mut self, length: i32) {
+        // ydotool's wheel is positive = up, enigo's is positive = down
+        self.run(&["mousemove".into(), "--wheel".into(), "-x".into(), "0".into(), "-y".into(), (-length).to_string()]);
+    }
+    fn main_display_size(&self) -> (i32, i32) {
+        display_info::DisplayInfo::all()
+            .ok()
+            .and_then(|d| d.into_iter().find(|d| d.is_primary))
+            .map_or((0, 0), |d| (d.width as i32, d.height as i32))
+    }
+    fn mouse_location(&self) -> (i32, i32) { self.pos }
+}
+
+impl KeyboardControllable for Ydotool {
+    fn key_sequence(&mut self, sequence: &str) { self.run(&["type".into(), sequence.into()]); }
+    fn key_down(&mut self, key: Key) { self.key(key, true, false); }
+    fn key_up(&mut self, key: Key) { self.key(key, false, true); }
+    fn key_click(&mut self, key: Key) { self.key(key, true, true); }
+}
+
+/// ydotool's button numbering (0 = left, 1 = right, 2 = middle, ...).
+pub fn button_code(button: MouseButton) -> Option<u8> {
+    Some(match button {
+        MouseButton::Left => 0x00,
+        MouseButton::Right => 0x01,
+        MouseButton::Middle => 0x02,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        MouseButton::Back => 0x03,
+        #[cfg(any(target_os = "windows", target_os = "linux"))]
+        MouseButton::Forward => 0x04,
+        _ => return None,
+    })
+}
+
+/// Linux input event code (`linux/input-event-codes.h`) for the keys we send.
+pub fn key_code(key: Key) -> Option<u16> {
+    Some(match key {
+        Key::Escape => 1, Key::Backspace => 14, Key::Tab => 15, Key::Return => 28,
+        Key::Control => 29, Key::Shift => 42, Key::Alt => 56, Key::Space => 57,
+        Key::F1 => 59, Key::F2 => 60, Key::F3 => 61, Key::F4 => 62, Key::F5 => 63, Key::F6 => 64,
+        Key::F7 => 65, Key::F8 => 66, Key::F9 => 67, Key::F10 => 68, Key::F11 => 87, Key::F12 => 88,
+        Key::F13 => 183, Key::F14 => 184, Key::F15 => 185,
+        Key::UpArrow => 103, Key::LeftArrow => 105, Key::RightArrow => 106, Key::DownArrow => 108,
+        Key::Layout(c) => return layout_code(c.to_ascii_lowercase()),
+        _ => return None,
+    })
+}
+
+fn layout_code(c: char) -> Option<u16> {
+    const ROWS: [(&str, u16); 4] = [("1234567890", 2), ("qwertyuiop", 16), ("asdfghjkl", 30), ("zxcvbnm", 44)];
+    ROWS.iter().find_map(|(row, first)| row.find(c).map(|i| first + i as u16))
+}
+
+/// Result of the diagnostics self-test.
+#[derive(Clone, Debug)]
+pub struct Diagnostics {
+    pub backend: Backend,
+    pub display_server: &'static str,
+    pub check: Result<(), String>,
+    pub start: (i32, i32),
+    pub target: (i32, i32),
+    pub seen: (i32, i32),
+    /// Whether the cursor moved where we asked; `None` if the backend can't read it back.
+    pub moved: Option<bool>,
+    pub permissions: String,
+}
+
+/// Read the cursor, nudge it by one pixel, read it again and put it back.
+pub fn self_test(input: &mut Input) -> Diagnostics {
+    let backend = input.backend();
+    let check = input.error().map_or_else(|| backend.check(), |e| Err(e.to_string()));
+    self_test_with(input, backend, check)
+}
+
+/// [`self_test`] through any mouse, standing in for `backend`, whose own check came out as `check`.
+pub fn self_test_with(mouse: &mut impl MouseControllable, backend: Backend, check: Result<(), String>) -> Diagnostics {
+    let start = mouse.mouse_location();
+    let target = (start.0 + 1, start.1 + 1);
+    mouse.mouse_move_to(target.0, target.1);
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    let seen = mouse.mouse_location();
+    mouse.mouse_move_to(start.0, start.1);
+    Diagnostics {
+        backend,
+        display_server: display_server(),
+        check,
+        start,
+        target,
+        seen,
+        moved: backend.reads_cursor().then_some(seen == target),
+        permissions: permission_status(backend),
+    }
+}
+
+/// Raw `SendInput` backend: absolute coordinates over the whole virtual desktop, so every
+/// monitor maps exactly, and optionally scan-code keyboard input for apps that ignore
+/// virtual-key events. (The OS still flags all `SendInput` events as injected.)
+#[cfg(windows)]
+mod win {
+    use enigo::{Key, KeyboardControllable, MouseButton, MouseControllable};
+    use windows_sys::Win32::Foundation::{HWND, POINT};
+    use windows_sys::Win32::Graphics::Gdi::ScreenToClient;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::*;
+    use windows_sys::Win32::UI::WindowsAndMessaging::*;
+
+    #[derive(Default)]
+    pub struct SendInputBackend {
+        pub hardware_like: bool,
+    }
+
+    fn send(inputs: &[INPUT]) {
+        let sent = unsafe { SendInput(inputs.len() as u32, inputs.as_ptr(), std::mem::size_of::<INPUT>() as i32) };
+        if sent as usize != inputs.len() {
+            super::failed(format!("SendInput sent {} of {} events (blocked by UIPI or another app?)", sent, inputs.len()));
+        }
+    }
+
+    fn mouse_input(dx: i32, dy: i32, data: u32, flags: MOUSE_EVENT_FLAGS) -> INPUT {
+        INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 { mi: MOUSEINPUT { dx, dy, mouseData: data, dwFlags: flags, time: 0, dwExtraInfo: 0 } },
+        }
+    }
+
+    fn key_input(vk: u16, scan: u16, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 { ki: KEYBDINPUT { wVk: vk, wScan: scan, dwFlags: flags, time: 0, dwExtraInfo: 0 } },
+        }
+    }
+
+    /// Screen pixel → 0..=65535 over the virtual desktop, as `MOUSEEVENTF_VIRTUALDESK` expects.
+    fn normalize(x: i32, y: i32) -> (i32, i32) {
+        let (vx, vy, vw, vh) = unsafe {
+            (
+                GetSystemMetrics(SM_XVIRTUALSCREEN), GetSystemMetrics(SM_YVIRTUALSCREEN),
+                GetSystemMetrics(SM_CXVIRTUALSCREEN), GetSystemMetrics(SM_CYVIRTUALSCREEN),
+            )
+        };
+        let scale = |p: i32, origin: i32, size: i32| (((p - origin) as i64 * 65535) / (size - 1).max(1) as i64) as i32;
+        (scale(x, vx, vw), scale(y, vy, vh))
+    }
+
+    fn button_flags(button: MouseButton) -> Option<(MOUSE_EVENT_FLAGS, MOUSE_EVENT_FLAGS, u32)> {
+        Some(match button {
+            MouseButton::Left => (MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP, 0),
+            MouseButton::Right => (MOUSEEVENTF_RIGHTDOWN, MOUSEEVENTF_RIGHTUP, 0),
+            MouseButton::Middle => (MOUSEEVENTF_MIDDLEDOWN, MOUSEEVENTF_MIDDLEUP, 0),
+            MouseButton::Back => (MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP, XBUTTON1 as u32),
+            MouseButton::Forward => (MOUSEEVENTF_XDOWN, MOUSEEVENTF_XUP, XBUTTON2 as u32),
+            _ => return None,
+        })
+    }
+
+    /// Virtual-key code, and whether it's an extended key (needed with scan codes).
+    fn virtual_key(key: Key) -> Option<(u16, bool)> {
+        Some(match key {
+            Key::Backspace => (0x08, false), Key::Tab => (0x09, false), Key::Return => (0x0D, false),
+            Key::Shift => (0x10, false), Key::Control => (0x11, false), Key::Alt => (0x12, false),
+            Key::Escape => (0x1B, false), Key::Space => (0x20, false),
+            Key::LeftArrow => (0x25, true), Key::UpArrow => (0x26, true),
+            Key::RightArrow => (0x27, true), Key::DownArrow => (0x28, true),
+            Key::F1 => (0x70, false), Key::F2 => (0x71, false), Key::F3 => (0x72, false), Key::F4 => (0x73, false),
+            Key::F5 => (0x74, false), Key::F6 => (0x75, false), Key::F7 => (0x76, false), Key::F8 => (0x77, false),
+            Key::F9 => (0x78, false), Key::F10 => (0x79, false), Key::F11 => (0x7A, false), Key::F12 => (0x7B, false),
+            Key::F13 => (0x7C, false), Key::F14 => (0x7D, false), Key::F15 => (0x7E, false),
+            Key::Layout(c) if c.is_ascii_alphanumeric() => (c.to_ascii_uppercase() as u16, false),
+            Key::Raw(vk) => (vk, false),
+            _ => return None,
+        })
+    }
+
+    impl SendInputBackend {
+        fn key(&self, key: Key, up: bool) {
+            let Some((vk, extended)) = virtual_key(key) else {
+                super::failed(format!("SendInput backend: unsupported key {:?}", key));
+                return;
+            };
+            let mut flags = if up { KEYEVENTF_KEYUP } else { 0 };
+            if extended { flags |= KEYEVENTF_EXTENDEDKEY; }
+            let input = if self.hardware_like {
+                let scan = unsafe { MapVirtualKeyW(vk as u32, MAPVK_VK_TO_VSC) } as u16;
+                key_input(0, scan, flags | KEYEVENTF_SCANCODE)
+            } else {
+                key_input(vk, 0, flags)
+            };
+            send(&[input]);
+        }
+    }
+
+    impl MouseControllable for SendInputBackend {
+        fn mouse_move_to(&mut self, x: i32, y: i32) {
+            let (nx, ny) = normalize(x, y);
+            send(&[mouse_input(nx, ny, 0, MOUSEEVENTF_MOVE | MOUSEEVENTF_ABSOLUTE | MOUSEEVENTF_VIRTUALDESK)]);
+        }
+        fn mouse_move_relative(&mut self, x: i32, y: i32) {
+            let (cx, cy) = self.mouse_location();
+            self.mouse_move_to(cx + x, cy + y);
+        }
+        fn mouse_down(&mut self, button: MouseButton) {
+            if let Some((down, _, data)) = button_flags(button) { send(&[mouse_input(0, 0, data, down)]); }
+        }
+        fn mouse_up(&mut self, button: MouseButton) {
+            if let Some((_, up, data)) = button_flags(button) { send(&[mouse_input(0, 0, data, up)]); }
+        }
+        fn mouse_click(&mut self, button: MouseButton) {
+            if let Some((down, up, data)) = button_flags(button) {
+                send(&[mouse_input(0, 0, data, down), mouse_input(0, 0, data, up)]);
+            }
+        }
+        fn mouse_scroll_x(&mut self, length: i32) {
+            send(&[mouse_input(0, 0, (length * 120) as u32, MOUSEEVENTF_HWHEEL)]);
+        }
+        fn mouse_scroll_y(&mut self, length: i32) {
+            // WHEEL_DELTA per notch; positive is away from the user, enigo's positive is down
+            send(&[mouse_input(0, 0, (-length * 120) as u32, MOUSEEVENTF_WHEEL)]);
+        }
+        fn main_display_size(&self) -> (i32, i32) {
+            unsafe { (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)) }
+        }
+        fn mouse_location(&self) -> (i32, i32) {
+            let mut p = POINT { x: 0, y: 0 };
+            unsafe { GetCursorPos(&mut p) };
+            (p.x, p.y)
+        }
+    }
+
+    /// (HWND, title) of the top-level window under the cursor.
+    pub fn window_under_cursor() -> Option<(isize, String)> {
+        let mut p = POINT { x: 0, y: 0 };
+        let hwnd = unsafe {
+            GetCursorPos(&mut p);
+            GetAncestor(WindowFromPoint(p), GA_ROOT)
+        };
+        if hwnd.is_null() { return None; }
+        let mut buf = [0u16; 256];
+        let len = unsafe { GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32) };
+        Some((hwnd as isize, String::from_utf16_lossy(&buf[..len.max(0) as usize])))
+    }
+
+    const MK_LBUTTON: usize = 0x0001;
+    const MK_RBUTTON: usize = 0x0002;
+    const MK_MBUTTON: usize = 0x0010;
+
+    /// Posts mouse and key messages straight to one window, so the real cursor never moves
+    /// and the window can stay in the background. Positions are screen pixels, converted to
+    /// the client coordinates of whichever child control is under them.
+    #[derive(Default)]
+    pub struct PostMessageBackend {
+        pub hwnd: isize,
+        pos: (i32, i32),
+        held: usize, // MK_* flags for the buttons currently down
+    }
+
+    fn make_lparam(x: i32, y: i32) -> isize { (((y as u16 as u32) << 16) | x as u16 as u32) as i32 as isize }
+
+    impl PostMessageBackend {
+        /// Deepest visible child of the target at `pos`, and `pos` in its client coordinates.
+        fn target_at(&self, (x, y): (i32, i32)) -> Option<(HWND, POINT)> {
+            let root = self.hwnd as HWND;
+            if root.is_null() || unsafe { IsWindow(root) } == 0 {
+                super::failed("Window messages: the target window is gone; pick it again".to_string());
+                return None;
+            }
+            let mut hwnd = root;
+            let mut pt = POINT { x, y };
+            unsafe { ScreenToClient(hwnd, &mut pt) };
+            loop {
+                let child = unsafe { ChildWindowFromPointEx(hwnd, pt, CWP_SKIPINVISIBLE | CWP_SKIPDISABLED | CWP_SKIPTRANSPARENT) };
+                if child.is_null() || child == hwnd { break; }
+                hwnd = child;
+                pt = POINT { x, y };
+                unsafe { ScreenToClient(hwnd, &mut pt) };
+            }
+            Some((hwnd, pt))
+        }
+
+        fn post_mouse(&self, msg: u32) {
+            if let Some((hwnd, pt)) = self.target_at(self.pos) {
+                unsafe { PostMessageW(hwnd, msg, self.held, make_lparam(pt.x, pt.y)) };
+            }
+        }
+
+        fn post_key(&self, key: Key, msg: u32) {
+            match (virtual_key(key), self.target_at(self.pos)) {
+                (Some((vk, _)), Some((hwnd, _))) => { unsafe { PostMessageW(hwnd, msg, vk as usize, 0) }; }
+                (None, _) => super::failed(format!("Window messages: unsupported key {:?}", key)),
+                _ => {}
+            }
+        }
+
+        fn button(&mut self, button: MouseButton, down: bool) {
+            let (down_msg, up_msg, mk) = match button {
+                MouseButton::Left => (WM_LBUTTONDOWN, WM_LBUTTONUP, MK_LBUTTON),
+                MouseButton::Right => (WM_RBUTTONDOWN, WM_RBUTTONUP, MK_RBUTTON),
+                MouseButton::Middle => (WM_MBUTTONDOWN, WM_MBUTTONUP, MK_MBUTTON),
+                _ => { super::failed(format!("Window messages: unsupported button {:?}", button)); return; }
+            };
+            if down {
+                self.held |= mk;
+                self.post_mouse(down_msg);
+            } else {
+                self.held &= !mk;
+                self.post_mouse(up_msg);
+            }
+        }
+
+        fn wheel(&self, msg: u32, notches: i32) {
+            // wheel messages carry screen coordinates and the delta in the high word
+            if let Some((hwnd, _)) = self.target_at(self.pos) {
+                let wparam = (((notches * 120) as i16 as u16 as usize) << 16) | self.held;
+                unsafe { PostMessageW(hwnd, msg, wparam, make_lparam(self.pos.0, self.pos.1)) };
+            }
+        }
+    }
+
+    impl MouseControllable for PostMessageBackend {
+        fn mouse_move_to(&mut self, x: i32, y: i32) {
+            self.pos = (x, y);
+            self.post_mouse(WM_MOUSEMOVE);
+        }
+        fn mouse_move_relative(&mut self, x: i32, y: i32) { self.mouse_move_to(self.pos.0 + x, self.pos.1 + y); }
+        fn mouse_down(&mut self, button: MouseButton) { self.button(button, true); }
+        fn mouse_up(&mut self, button: MouseButton) { self.button(button, false); }
+        fn mouse_click(&mut self, button: MouseButton) {
+            self.button(button, true);
+            self.button(button, false);
+        }
+        fn mouse_scroll_x(&mut self, length: i32) { self.wheel(WM_MOUSEHWHEEL, length); }
+        fn mouse_scroll_y(&mut self, length: i32) { self.wheel(WM_MOUSEWHEEL, -length); }
+        fn main_display_size(&self) -> (i32, i32) {
+            unsafe { (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN)) }
+        }
+        fn mouse_location(&self) -> (i32, i32) { self.pos }
+    }
+
+    impl KeyboardControllable for PostMessageBackend {
+        fn key_sequence(&mut self, sequence: &str) {
+            if let Some((hwnd, _)) = self.target_at(self.pos) {
+                for u in sequence.encode_utf16() {
+                    unsafe { PostMessageW(hwnd, WM_CHAR, u as usize, 0) };
+                }
+            }
+        }
+        fn key_down(&mut self, key: Key) { self.post_key(key, WM_KEYDOWN); }
+        fn key_up(&mut self, key: Key) { self.post_key(key, WM_KEYUP); }
+        fn key_click(&mut self, key: Key) {
+            self.post_key(key, WM_KEYDOWN);
+            self.post_key(key, WM_KEYUP);
+        }
+    }
+
+    impl KeyboardControllable for SendInputBackend {
+        fn key_sequence(&mut self, sequence: &str) {
+            let inputs: Vec<INPUT> = sequence
+                .encode_utf16()
+                .flat_map(|u| [key_input(0, u, KEYEVENTF_UNICODE), key_input(0, u, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP)])
+                .collect();
+            send(&inputs);
+        }
+        fn key_down(&mut self, key: Key) { self.key(key, false); }
+        fn key_up(&mut self, key: Key) { self.key(key, true); }
+        fn key_click(&mut self, key: Key) {
+            self.key(key, false);
+            self.key(key, true);
+        }
+    }
+}
+
+/// Top-level window under the cursor, as (HWND, title). Windows only.
+pub fn window_under_cursor() -> Option<(isize, String)> {
+    #[cfg(windows)]
+    { win::window_under_cursor() }
+    #[cfg(not(windows))]
+    { None }
+}
+
+pub fn display_server() -> &'static str {
+    if cfg!(target_os = "windows") { return "Windows"; }
+    if cfg!(target_os = "macos") { return "macOS (Quartz)"; }
+    if is_wayland_session() { return "Wayland"; }
+    if std::env::var_os("DISPLAY").is_some() { "X11" } else { "unknown (no DISPLAY or WAYLAND_DISPLAY)" }
+}
+
+fn permission_status(backend: Backend) -> String {
+    if cfg!(target_os = "macos") {
+        return "Needs Accessibility access (System Settings → Privacy & Security → Accessibility); can't be checked from here.".to_string();
+    }
+    if cfg!(target_os = "windows") {
+        return "No special permission needed, but clicks into elevated (admin) windows need this app elevated too.".to_string();
+    }
+    match backend {
+        Backend::Ydotool => match std::fs::OpenOptions::new().write(true).open("/dev/uinput") {
+            Ok(_) => "/dev/uinput is writable.".to_string(),
+            Err(e) => format!("/dev/uinput not writable ({}); ydotoold must run as a user that can write it.", e),
+        },
+        Backend::Enigo => match std::env::var("DISPLAY") {
+            Ok(d) => format!("Using X server DISPLAY={}.", d),
+            Err(_) => "DISPLAY is not set; enigo has no X server to talk to.".to_string(),
+        },
+        Backend::SendInput | Backend::WindowMessages => format!("{} is Windows-only.", backend.label()),
+    }
+}
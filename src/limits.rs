@@ -0,0 +1,110 @@
+//! Safety limits: app-wide caps on click rate, clicks per job and run time. Every job obeys
+//! them whatever its own settings say, so a typo like a 0.01 s interval can't set off
+//! thousands of clicks.
+
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::i18n::tr;
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// `None` = no limit.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SafetyLimits {
+    /// Hold clicks while the session is locked or the screensaver is on.
+    pub pause_when_locked: bool,
+    /// After an unlock, wait for the user to resume instead of carrying on by itself.
+    pub confirm_unlock: bool,
+    /// Hold clicks while someone uses the keyboard or mouse, until they've left it alone for
+    /// `idle_secs`.
+    pub pause_when_active: bool,
+    pub idle_secs: u32,
+    /// Most clicks in any 60 s; clicks beyond that wait.
+    pub per_minute: Option<u32>,
+    /// A job is stopped after this many clicks.
+    pub max_clicks: Option<u32>,
+    /// A job is stopped after running this long.
+    pub max_minutes: Option<u32>,
+}
+
+impl Default for SafetyLimits {
+    fn default() -> Self {
+        Self {
+            pause_when_locked: true, confirm_unlock: false, pause_when_active: false, idle_secs: 30,
+            per_minute: None, max_clicks: None, max_minutes: None,
+        }
+    }
+}
+
+impl SafetyLimits {
+    /// Why a job that has made `clicks` clicks may not click again, if it may not.
+    pub fn clicks_exceeded(&self, clicks: u32) -> Option<String> {
+        let max = self.max_clicks.filter(|&max| clicks >= max)?;
+        Some(format!("safety limit: {} clicks reached", max))
+    }
+
+    /// Why a job running for `elapsed` has to stop, if it has to.
+    pub fn run_time_exceeded(&self, elapsed: Duration) -> Option<String> {
+        let max = self.max_minutes.filter(|&max| elapsed >= Duration::from_secs(max as u64 * 60))?;
+        Some(format!("safety limit: {} min run time reached", max))
+    }
+
+    /// What the limits will cut short for a job that would click up to `rate` times a minute,
+    /// `clicks` times in all and run for up to `secs` (`None` = until stopped).
+    pub fn warnings(&self, rate: f32, clicks: Option<u32>, secs: Option<f32>) -> Vec<String> {
+        let mut out = Vec::new();
+        if let Some(max) = self.per_minute.filter(|&max| rate > max as f32) {
+            out.push(tr!("Up to {} clicks a minute; the safety limit slows this to {}.", rate.round(), max));
+        }
+        match (self.max_clicks, clicks) {
+            (Some(max), Some(n)) if n > max => out.push(tr!("{} clicks planned; the safety limit stops the job after {}.", n, max)),
+            (Some(max), None) => out.push(tr!("Runs until stopped; the safety limit stops it after {} clicks.", max)),
+            _ => {}
+        }
+        match (self.max_minutes, secs) {
+            (Some(max), Some(s)) if s > max as f32 * 60.0 => {
+                out.push(tr!("May run {} min; the safety limit stops the job after {} min.", (s / 60.0).ceil(), max));
+            }
+            (Some(max), None) => out.push(tr!("Runs until stopped; the safety limit stops it after {} min.", max)),
+            _ => {}
+        }
+        out
+    }
+}
+
+/// Clicks of every job in this session, so the per-minute cap holds across jobs and bursts.
+pub static SESSION_RATE: Lazy<Arc<Mutex<RateLimiter>>> = Lazy::new(Arc::default);
+
+/// Sliding one-minute window of click times.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    recent: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    /// How long to hold off a click at `now` so no minute holds more than `per_minute` clicks.
+    pub fn delay(&mut self, per_minute: u32, now: Instant) -> Duration {
+        self.forget_before(now);
+        let per_minute = per_minute.max(1) as usize;
+        if self.recent.len() < per_minute { return Duration::ZERO; }
+        // the click that has to drop out of the window first
+        let oldest = self.recent[self.recent.len() - per_minute];
+        (oldest + MINUTE).saturating_duration_since(now)
+    }
+
+    pub fn record(&mut self, at: Instant) {
+        self.forget_before(at);
+        self.recent.push_back(at);
+    }
+
+    /// Drop clicks more than a minute before `now`.
+    fn forget_before(&mut self, now: Instant) {
+        while self.recent.front().is_some_and(|&t| now.duration_since(t) >= MINUTE) { self.recent.pop_front(); }
+    }
+}
@@ -0,0 +1,132 @@
+//! Notices a locked session or running screensaver, so jobs hold their clicks instead of
+//! clicking into the lock screen (where they fail, or end up in the password box).
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Blocked { Locked, Screensaver }
+
+impl Blocked {
+    pub fn label(self) -> &'static str {
+        match self {
+            Blocked::Locked => "session locked",
+            Blocked::Screensaver => "screensaver on",
+        }
+    }
+}
+
+/// Whether locking can be told on this platform at all.
+pub const SUPPORTED: bool = cfg!(any(windows, target_os = "linux"));
+
+/// Tells what keeps input from reaching the desktop, for the length of one job: the
+/// connections it needs are opened on the first look and kept, not reopened every check.
+#[derive(Default)]
+pub struct LockWatch {
+    #[cfg(target_os = "linux")]
+    linux: Option<linux::Watch>,
+}
+
+impl LockWatch {
+    /// What blocks input right now, if anything. Errs on the side of `None` when the state
+    /// can't be read.
+    pub fn blocked(&mut self) -> Option<Blocked> {
+        #[cfg(windows)]
+        { win::blocked() }
+        #[cfg(target_os = "linux")]
+        { self.linux.get_or_insert_with(linux::Watch::open).blocked() }
+        #[cfg(not(any(windows, target_os = "linux")))]
+        { None }
+    }
+}
+
+/// logind's D-Bus object path for session `id`: like systemd's `bus_label_escape`, anything but
+/// letters (and digits after the first character) becomes `_xx`.
+pub fn session_path(id: &str) -> String {
+    let mut path = "/org/freedesktop/login1/session/".to_string();
+    if id.is_empty() { path.push('_'); }
+    for (i, b) in id.bytes().enumerate() {
+        if b.is_ascii_alphabetic() || (i > 0 && b.is_ascii_digit()) {
+            path.push(b as char);
+        } else {
+            path.push_str(&format!("_{:02x}", b));
+        }
+    }
+    path
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::screensaver::{ConnectionExt, State};
+    use x11rb::rust_connection::RustConnection;
+
+    use super::Blocked;
+
+    pub struct Watch {
+        /// This session's object on logind. Most desktop lock screens set its `LockedHint` (not
+        /// every standalone X11 locker does; those usually start the screensaver too). The
+        /// proxy follows property changes, so reading it doesn't go back to the bus.
+        logind: Option<zbus::blocking::Proxy<'static>>,
+        /// X connection and root window for the screensaver extension; `None` without an X
+        /// server (e.g. on Wayland).
+        x11: Option<(RustConnection, u32)>,
+    }
+
+    impl Watch {
+        pub fn open() -> Self {
+            let logind = || -> zbus::Result<zbus::blocking::Proxy<'static>> {
+                let path = match std::env::var("XDG_SESSION_ID") {
+                    Ok(id) => super::session_path(&id),
+                    Err(_) => "/org/freedesktop/login1/session/auto".to_string(),
+                };
+                let conn = zbus::blocking::Connection::system()?;
+                zbus::blocking::Proxy::new_owned(conn, "org.freedesktop.login1", path, "org.freedesktop.login1.Session")
+            };
+            let x11 = || -> Option<(RustConnection, u32)> {
+                let (conn, screen_num) = x11rb::connect(None).ok()?;
+                let root = conn.setup().roots.get(screen_num)?.root;
+                Some((conn, root))
+            };
+            Self {
+                logind: logind().map_err(|e| log::debug!("No logind lock state: {}", e)).ok(),
+                x11: x11(),
+            }
+        }
+
+        pub fn blocked(&self) -> Option<Blocked> {
+            if let Some(logind) = &self.logind {
+                if logind.get_property::<bool>("LockedHint").unwrap_or(false) { return Some(Blocked::Locked); }
+            }
+            let (conn, root) = self.x11.as_ref()?;
+            let info = conn.screensaver_query_info(*root).ok()?.reply().ok()?;
+            (info.state == u8::from(State::ON)).then_some(Blocked::Screensaver)
+        }
+    }
+}
+
+#[cfg(windows)]
+mod win {
+    use windows_sys::Win32::System::StationsAndDesktops::{
+        CloseDesktop, GetUserObjectInformationW, OpenInputDesktop, DESKTOP_READOBJECTS, UOI_NAME,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{SystemParametersInfoW, SPI_GETSCREENSAVERRUNNING};
+
+    use super::Blocked;
+
+    pub fn blocked() -> Option<Blocked> {
+        unsafe {
+            let mut running = 0i32;
+            let ok = SystemParametersInfoW(SPI_GETSCREENSAVERRUNNING, 0, &mut running as *mut i32 as *mut core::ffi::c_void, 0);
+            if ok != 0 && running != 0 { return Some(Blocked::Screensaver); }
+
+            // while locked, the input desktop is Winlogon's, which we may not even open
+            let desktop = OpenInputDesktop(0, 0, DESKTOP_READOBJECTS);
+            if desktop.is_null() { return Some(Blocked::Locked); }
+            let mut name = [0u16; 64];
+            let mut needed = 0u32;
+            let ok = GetUserObjectInformationW(desktop, UOI_NAME, name.as_mut_ptr().cast(), (name.len() * 2) as u32, &mut needed);
+            CloseDesktop(desktop);
+            if ok == 0 { return None; }
+            let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+            (!String::from_utf16_lossy(&name[..len]).eq_ignore_ascii_case("Default")).then_some(Blocked::Locked)
+        }
+    }
+}
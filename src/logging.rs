@@ -0,0 +1,153 @@
+//! Logging backend for the `log` macros: every record goes to stderr, into a ring buffer
+//! shown by the in-app log panel, and (when enabled) into a size-rotated log file.
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Lines kept for the log panel.
+pub const PANEL_LINES: usize = 500;
+/// A log file is rotated once it grows past this size...
+pub const MAX_FILE_BYTES: u64 = 5 * 1024 * 1024;
+/// ...keeping this many old files (`<name>.1` is the newest).
+pub const KEEP_FILES: usize = 3;
+
+/// One formatted record, for the log panel.
+#[derive(Clone, Debug)]
+pub struct Line {
+    pub level: Level,
+    pub text: String,
+}
+
+/// An append-only log file that moves itself aside when it gets too big.
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    max_bytes: u64,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, max_bytes: u64) -> io::Result<Self> {
+        if let Some(dir) = path.parent() { std::fs::create_dir_all(dir)?; }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self { path: path.to_path_buf(), file, len, max_bytes })
+    }
+
+    pub fn path(&self) -> &Path { &self.path }
+
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.len > 0 && self.len + line.len() as u64 + 1 > self.max_bytes { self.rotate()?; }
+        writeln!(self.file, "{}", line)?;
+        self.len += line.len() as u64 + 1;
+        Ok(())
+    }
+
+    /// `<name>` becomes `<name>.1`, `.1` becomes `.2`, and so on; the oldest is dropped.
+    fn rotate(&mut self) -> io::Result<()> {
+        let numbered = |n: usize| {
+            let mut name = self.path.clone().into_os_string();
+            name.push(format!(".{}", n));
+            PathBuf::from(name)
+        };
+        for n in (1..KEEP_FILES).rev() {
+            let from = numbered(n);
+            if from.exists() { std::fs::rename(from, numbered(n + 1))?; }
+        }
+        std::fs::rename(&self.path, numbered(1))?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+struct Logger {
+    lines: Mutex<VecDeque<Line>>,
+    file: Mutex<Option<RotatingFile>>,
+}
+
+static LOGGER: Lazy<Logger> = Lazy::new(|| Logger { lines: Mutex::new(VecDeque::new()), file: Mutex::new(None) });
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        // other crates (eframe, winit, ...) only get through with warnings and errors
+        metadata.level() <= log::max_level()
+            && (metadata.target().starts_with(env!("CARGO_CRATE_NAME")) || metadata.level() <= Level::Warn)
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) { return; }
+        let text = format!("{} {:<5} {}", timestamp(SystemTime::now()), record.level(), record.args());
+        eprintln!("{}", text);
+        if let Some(file) = self.file.lock().as_mut() {
+            if let Err(e) = file.write_line(&text) { eprintln!("Could not write log file: {}", e); }
+        }
+        let mut lines = self.lines.lock();
+        if lines.len() >= PANEL_LINES { lines.pop_front(); }
+        lines.push_back(Line { level: record.level(), text });
+    }
+
+    fn flush(&self) {
+        if let Some(file) = self.file.lock().as_mut() { let _ = file.file.flush(); }
+    }
+}
+
+/// Install the logger at `level` and log panics before the default panic output.
+pub fn init(level: LevelFilter) {
+    if log::set_logger(&*LOGGER).is_err() { return; }
+    log::set_max_level(level);
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        log::error!("{}", info);
+        default_hook(info);
+    }));
+}
+
+pub fn level() -> LevelFilter { log::max_level() }
+
+pub fn set_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
+
+/// Start writing to `path` (rotated at [`MAX_FILE_BYTES`]), or stop writing with `None`.
+pub fn set_file(path: Option<&Path>) -> io::Result<()> {
+    let file = path.map(|p| RotatingFile::open(p, MAX_FILE_BYTES)).transpose()?;
+    *LOGGER.file.lock() = file;
+    Ok(())
+}
+
+/// The file currently written to, if any.
+pub fn file() -> Option<PathBuf> { LOGGER.file.lock().as_ref().map(|f| f.path().to_path_buf()) }
+
+/// `logs/area_clicker.log` in the config folder.
+pub fn default_file() -> Option<PathBuf> {
+    crate::portable::config_dir().map(|d| d.join("logs").join("area_clicker.log"))
+}
+
+/// The most recent lines, oldest first.
+pub fn recent() -> Vec<Line> { LOGGER.lines.lock().iter().cloned().collect() }
+
+pub fn clear_recent() { LOGGER.lines.lock().clear(); }
+
+/// UTC wall-clock time as `YYYY-MM-DD HH:MM:SS`.
+pub fn timestamp(t: SystemTime) -> String {
+    let secs = t.duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // civil-from-days (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, rem / 3600, rem % 3600 / 60, rem % 60)
+}
@@ -1,588 +1,9586 @@
+mod activity;
+mod bench;
+mod calibrate;
+mod crypt;
+mod daemon;
+mod diff;
+mod events;
+mod feedback;
+mod file_watch;
+mod history;
+mod gamepad;
+mod hotkeys;
+mod i18n;
+mod input;
+mod limits;
+mod lock;
 mod human_mouse;
+mod logging;
+mod path_bank;
+mod ocr;
+mod pacing;
+mod portable;
+mod power;
+mod profile;
+mod quiet;
+mod sampler;
+mod screenshot;
+mod setup;
+mod stats;
+mod step;
+mod task;
+mod template;
+mod theme;
+mod triggers;
+mod tutorial;
+mod viewer;
+mod voice;
+mod watch;
 
 use eframe::{egui, egui::{Color32, Pos2, Rect, Sense, WindowLevel}};
 use enigo::MouseControllable;
 use once_cell::sync::Lazy;
 use parking_lot::Mutex;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
-use crate::human_mouse::{HumanMouseSettings, Bounds, human_move_and_click};
+use std::time::Instant;
+use crate::calibrate::{CalibrationRun, Correction};
+use crate::events::{Event, EventCommands};
+use crate::feedback::ClickFeedback;
+use crate::file_watch::FileWatch;
+use crate::gamepad::PadButton;
+use crate::history::{History, HistoryFilter, RunRecord};
+use crate::hotkeys::HotKey;
+use crate::i18n::tr;
+use crate::human_mouse::Bounds;
+use crate::input::{Backend, Diagnostics, Input};
+use crate::limits::SafetyLimits;
+use crate::path_bank::{PathBank, Recorder};
+use crate::pacing::{Fatigue, WarmUp};
+use crate::quiet::QuietWindow;
+use crate::profile::{Checkpoint, ForbiddenZone, Profile, ProfileStore, Recovery, SequencePosition, StepDraft};
+use crate::stats::RunStats;
+use crate::sampler::{center_band, resample_outside, sample_point, ClickPoint, Zone};
+use crate::setup::{Page as SetupPage, Setup, TestClick};
+use crate::watch::{Refocus, WaitFor, WatchKind};
+use crate::theme::{Theme, ThemeMode};
+use crate::triggers::{MidiBinding, MidiInput, Note, TriggerAction, Triggers};
+use crate::tutorial::{Stage as TutorialStage, Tutorial};
+use crate::viewer::ViewerSettings;
+use crate::voice::Said;
+use crate::step::{color_distance, grid_cells, run_steps, ClickStep, DryRun, DryRunEvent, GridSweepStep, JobProgress, KeyTapStep, SingleAreaStep, Step, StepCommand, StepCtx, WaitImageStep, RESAMPLE_ATTEMPTS};
 
 use clap::Parser;
+use serde::{Deserialize, Serialize};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Total number of random clicks to perform before stopping (0 = infinite)
-    #[arg(long = "clicks", default_value_t = 0)]
-    clicks: u32,
+    #[arg(long = "clicks")]
+    clicks: Option<u32>,
 
     /// Optional min delay between clicks in ms
-    #[arg(long = "min-delay-ms", default_value_t = 75)]
-    min_delay_ms: u64,
+    #[arg(long = "min-delay-ms")]
+    min_delay_ms: Option<u64>,
 
     /// Optional max delay between clicks in ms
-    #[arg(long = "max-delay-ms", default_value_t = 250)]
-    max_delay_ms: u64,
-}
+    #[arg(long = "max-delay-ms")]
+    max_delay_ms: Option<u64>,
 
-// Click operation utilities
+    /// Click area as two corners in physical pixels: x1,y1,x2,y2
+    #[arg(long = "bounds", value_parser = parse_bounds, allow_hyphen_values = true)]
+    bounds: Option<Bounds>,
 
-/// Moved to separate module
+    /// Load settings from this profile file instead of the last used profile
+    #[arg(long = "profile")]
+    profile: Option<std::path::PathBuf>,
 
-#[derive(Clone, Copy, Debug)]
-enum ClickButton { Left, Right }
+    /// Start the job right away (after a short countdown)
+    #[arg(long = "autostart")]
+    autostart: bool,
 
+    /// Job mode to use, overriding the profile
+    #[arg(long = "mode", value_enum)]
+    mode: Option<JobMode>,
 
+    /// Before the first click, wait for a window whose title matches this regex
+    #[arg(long = "wait-window", conflicts_with = "wait_process")]
+    wait_window: Option<String>,
 
-struct ClickJob {
-    running: Arc<AtomicBool>,
-    #[allow(dead_code)] // Used through Arc clone in spawn
-    config: Arc<Mutex<ClickConfig>>,
+    /// Before the first click, wait for a process whose name matches this regex
+    #[arg(long = "wait-process")]
+    wait_process: Option<String>,
+
+    /// Give up waiting after this many seconds (0 = wait forever)
+    #[arg(long = "wait-timeout")]
+    wait_timeout: Option<u32>,
+
+    /// Stop the job when the waited-for window/process goes away
+    #[arg(long = "stop-when-gone")]
+    stop_when_gone: bool,
+
+    /// Log verbosity: off, error, warn, info, debug or trace
+    #[arg(long = "log-level", default_value = "info")]
+    log_level: log::LevelFilter,
+
+    /// Also write the log to this file (rotated when it gets large)
+    #[arg(long = "log-file")]
+    log_file: Option<std::path::PathBuf>,
+
+    /// Run without a window, controlled over a local socket (load, start, continue, stop, resume, skip, repeat, jump, step, status, quit)
+    #[arg(long = "daemon")]
+    daemon: bool,
+
+    /// Control socket (Unix only for now) for --daemon, `send` and the window; defaults to area_clicker.sock in the runtime dir
+    #[arg(long = "socket")]
+    socket: Option<std::path::PathBuf>,
+
+    /// Keep profiles and settings in config/ beside the executable (also on when a portable.flag file is there)
+    #[arg(long = "portable")]
+    portable: bool,
 }
 
-#[derive(Clone, Debug)]
-struct ClickConfig {
-    bounds: Option<Bounds>,
-    button: ClickButton,
-    min_secs: f32,
-    max_secs: f32,
-    finite_clicks: Option<u32>,  // None for infinite, Some(n) for n clicks
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Print the connected displays (id, name, origin, size, scale) and exit
+    ListDisplays {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Open only the area picker, print the picked bounds as JSON and exit (Esc cancels)
+    Pick,
+    /// Write all profiles and app-wide settings to one file and exit
+    Export {
+        path: PathBuf,
+    },
+    /// Merge a file written by `export` into the stored settings and exit
+    Import {
+        path: PathBuf,
+    },
+    /// Print the path a human-like move and click would take as JSON (nothing is moved), then exit
+    Simulate {
+        /// Start position: x,y
+        #[arg(long, value_parser = parse_point, allow_hyphen_values = true)]
+        from: (i32, i32),
+        /// Target position: x,y
+        #[arg(long, value_parser = parse_point, allow_hyphen_values = true)]
+        to: (i32, i32),
+        /// Target area (x1,y1,x2,y2) the path stays in once inside; the click may land anywhere in it
+        #[arg(long, value_parser = parse_bounds, allow_hyphen_values = true)]
+        area: Option<Bounds>,
+        /// Seed, for the same path every time
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Send one command (e.g. "start Farm", "step 2", "stop") to the daemon or window on the control socket, print the reply and exit
+    Send {
+        #[arg(required = true)]
+        command: Vec<String>,
+    },
+    /// Have the system start the headless runner (--daemon --profile <file> --autostart) at logon or daily: a Task Scheduler task on Windows, a systemd user unit elsewhere; then exit
+    InstallTask {
+        /// Saved profile name, or a profile file
+        profile: String,
+        /// Start every day at this time (HH:MM) instead of at logon
+        #[arg(long, value_parser = parse_time)]
+        at: Option<u16>,
+        /// Task or unit name; defaults to area_clicker-<profile>
+        #[arg(long)]
+        name: Option<String>,
+        /// Print the task or units instead of installing them
+        #[arg(long)]
+        print: bool,
+    },
+    /// Simulate mouse moves (nothing is moved) and print statistics of the paths, then exit
+    Bench {
+        /// Number of moves to simulate
+        #[arg(long, default_value_t = 1000)]
+        moves: usize,
+        /// Seed for the random spots and paths
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
 }
 
-static ENIGO: Lazy<Mutex<enigo::Enigo>> = Lazy::new(|| Mutex::new(enigo::Enigo::new()));
+fn parse_point(s: &str) -> Result<(i32, i32), String> {
+    let (x, y) = s.split_once(',').ok_or("expected x,y")?;
+    let parse = |p: &str| p.trim().parse::<i32>().map_err(|e| format!("'{}': {}", p.trim(), e));
+    Ok((parse(x)?, parse(y)?))
+}
 
-impl ClickJob {
-    fn spawn(config: Arc<Mutex<ClickConfig>>) -> Self {
-        use std::sync::atomic::AtomicBool;
-        use std::sync::atomic::Ordering;
-        use std::sync::{Arc};
-        use std::time::Duration;
-        use rand::Rng;
-        use enigo::{MouseButton};
+fn parse_time(s: &str) -> Result<u16, String> {
+    quiet::parse_time(s).ok_or_else(|| format!("'{}' is not a HH:MM time", s.trim()))
+}
 
-        let running = Arc::new(AtomicBool::new(true));
-        let running_clone = Arc::clone(&running);
-        let config_clone = Arc::clone(&config);
+fn parse_bounds(s: &str) -> Result<Bounds, String> {
+    let v: Vec<i32> = s
+        .split(',')
+        .map(|p| p.trim().parse::<i32>().map_err(|e| format!("'{}': {}", p.trim(), e)))
+        .collect::<Result<_, _>>()?;
+    let [x1, y1, x2, y2] = v[..] else { return Err("expected x1,y1,x2,y2".to_string()) };
+    Ok(Bounds { min_x: x1.min(x2), max_x: x1.max(x2), min_y: y1.min(y2), max_y: y1.max(y2) })
+}
 
-        eprintln!("Starting click job with config: {:?}", config.lock());
+// Click operation utilities
 
-        std::thread::spawn(move || {
-            let mut rng = rand::thread_rng();
-            let mut last_pos: Option<(i32,i32)> = None;
-            let mut clicks_remaining = config_clone.lock().finite_clicks;
-
-            loop {
-                if !running_clone.load(Ordering::Relaxed) { break; }
-                
-                // Check if we've completed our finite clicks
-                if let Some(0) = clicks_remaining {
-                    running_clone.store(false, Ordering::Relaxed);
-                    break;
-                }
-
-                let cfg = config_clone.lock().clone();
-                let Some(b) = cfg.bounds else {
-                    std::thread::sleep(Duration::from_millis(200));
-                    continue;
-                };
-                if !b.is_valid() {
-                    std::thread::sleep(Duration::from_millis(200));
-                    continue;
-                }
+/// Moved to separate module
 
-                // pick random point inside box
-                let x = rng.gen_range(b.min_x..=b.max_x);
-                let y = rng.gen_range(b.min_y..=b.max_y);
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum ClickButton { Left, Right }
 
-                // human-style move & click
-                {
-                    let mut en = ENIGO.lock();
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
+enum JobMode { Single, Sequence, GridSweep, AntiAfk }
 
-                    // starting point: last known, or “outside the square” so we can test re-entry
-                    let from = last_pos.unwrap_or((b.min_x - 40, b.min_y - 40));
+/// How a sequence goes through its steps each cycle.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+enum StepOrder {
+    /// Each step makes all its clicks before the next one starts.
+    #[default]
+    InOrder,
+    /// One click of each step in turn, until every step has made its clicks.
+    Interleave,
+    /// Each click goes to a step drawn at random by the steps' weights; a cycle makes as many
+    /// clicks as the steps' counts add up to.
+    Weighted,
+}
 
-                    // minimal rect adapter for the helper
-                    // map your ClickButton -> enigo::MouseButton
-                    let button = match cfg.button {
-                        ClickButton::Left => MouseButton::Left,
-                        ClickButton::Right => MouseButton::Right,
-                    };
+impl StepOrder {
+    const ALL: [StepOrder; 3] = [StepOrder::InOrder, StepOrder::Interleave, StepOrder::Weighted];
 
-                    // run the human move & click
-                    human_move_and_click(
-                        &mut *en,
-                        from,
-                        (x, y),
-                        Some(Bounds { min_x: b.min_x, min_y: b.min_y, max_x: b.max_x, max_y: b.max_y }),
-                        &HumanMouseSettings::default(),
-                        button,
-                    );
-                }
+    fn label(self) -> &'static str {
+        match self {
+            StepOrder::InOrder => "In order",
+            StepOrder::Interleave => "Interleaved",
+            StepOrder::Weighted => "Weighted random",
+        }
+    }
+}
 
-                // remember where we ended up
-                last_pos = Some((x, y));
+/// Harmless keys offered for anti-AFK taps.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum AfkKey { Shift, Control, F15, Space }
 
-                // Update click counter if we're using finite clicks
-                if let Some(ref mut remaining) = clicks_remaining {
-                    *remaining = remaining.saturating_sub(1);
-                }
+impl AfkKey {
+    const ALL: [AfkKey; 4] = [AfkKey::Shift, AfkKey::Control, AfkKey::F15, AfkKey::Space];
 
-                // sleep random between min..max (seconds), while checking stop flag
-                let (min_s, max_s) = if cfg.min_secs <= cfg.max_secs {
-                    (cfg.min_secs, cfg.max_secs)
-                } else { (cfg.max_secs, cfg.min_secs) };
-                let wait = rng.gen_range(min_s..=max_s).max(0.01);
-                let ms = (wait * 1000.0) as u64;
-                for _ in 0..ms/50 {
-                    if !running_clone.load(Ordering::Relaxed) { break; }
-                    std::thread::sleep(Duration::from_millis(50));
-                }
-                if ms % 50 != 0 { std::thread::sleep(Duration::from_millis(ms % 50)); }
-            }
-        });
+    fn label(self) -> &'static str {
+        match self {
+            AfkKey::Shift => "Shift",
+            AfkKey::Control => "Ctrl",
+            AfkKey::F15 => "F15",
+            AfkKey::Space => "Space",
+        }
+    }
 
-        Self { running, config }
+    fn to_enigo(self) -> enigo::Key {
+        match self {
+            AfkKey::Shift => enigo::Key::Shift,
+            AfkKey::Control => enigo::Key::Control,
+            AfkKey::F15 => enigo::Key::F15,
+            AfkKey::Space => enigo::Key::Space,
+        }
     }
-    fn stop(&self) { self.running.store(false, Ordering::Relaxed); }
 }
 
-// -------------- Display Info --------------
-#[derive(Clone, Debug)]
-struct Monitor {
-    #[allow(dead_code)]
-    id: u32,
-    name: String,
-    origin_px: (i32, i32),
-    size_px: (i32, i32),
-    #[allow(dead_code)]
-    scale_factor: f32,
+/// Grid sweep: click every cell of the selected area once.
+#[derive(Clone, Copy, Debug)]
+struct GridSweep {
+    cols: u32,
+    rows: u32,
+    serpentine: bool, // reverse every other row so the cursor snakes instead of jumping back
 }
 
+/// Periodic key tap that keeps an idle session alive.
+#[derive(Clone, Copy, Debug)]
+struct AntiAfk {
+    key: AfkKey,
+    min_secs: f32,
+    max_secs: f32,
+}
 
-fn query_monitors() -> Vec<Monitor> {
-    match display_info::DisplayInfo::all() {
-        Ok(displays) if !displays.is_empty() => {
-            displays
-                .into_iter()
-                .map(|d| Monitor {
-                    id: d.id,
-                    // v0.4.x has no `.name`; make a friendly one
-                    name: if d.is_primary {
-                        format!("Display {} (Primary)", d.id)
-                    } else {
-                        format!("Display {}", d.id)
-                    },
-                    origin_px: (d.x, d.y),                            // i32
-                    size_px: (d.width as i32, d.height as i32),       // u32 -> i32
-                    scale_factor: d.scale_factor as f32,              // usually f32 already
-                })
-                .collect()
-        }
-        _ => {
-            // Fallback: single main display using Enigo
-            let en = enigo::Enigo::new();
-            let (w, h) = en.main_display_size();
-            vec![Monitor {
-                id: 0,
-                name: "Main display".to_string(),
-                origin_px: (0, 0),
-                size_px: (w as i32, h as i32),
-                scale_factor: 1.0,
-            }]
+/// What a sequence step clicks.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum StepTarget {
+    /// Random point in the step's `bounds`, weighted by its `zones`.
+    Area,
+    Point(ClickPoint),
+    /// Offset from wherever the previous click landed, e.g. an entry of a context menu.
+    Offset { dx: i32, dy: i32, jitter: i32 },
+    /// No click: save a screenshot of the step's `bounds` (or every screen) instead.
+    Screenshot { full_screen: bool },
+    /// No click: watch `bounds` until the step's `image` shows up, then branch.
+    WaitImage(WaitImage),
+}
+
+/// Where a sequence goes after a wait-for-image step.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum Branch {
+    /// On to the next step.
+    Continue,
+    /// Skip the step after this one.
+    SkipNext,
+    /// Stop the job.
+    Abort,
+    /// Go to step n (0-based) of the sequence.
+    Jump(usize),
+}
+
+impl Branch {
+    fn label(self) -> String {
+        match self {
+            Branch::Continue => tr!("continue"),
+            Branch::SkipNext => tr!("skip next step"),
+            Branch::Abort => tr!("stop the job"),
+            Branch::Jump(n) => tr!("go to step {}", n + 1),
         }
     }
 }
 
+/// Settings of a wait-for-image step.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct WaitImage {
+    timeout_secs: u32,
+    /// How far (0–255) each color channel may be off and still match.
+    tolerance: u8,
+    on_found: Branch,
+    on_timeout: Branch,
+}
 
-fn union_rect(monitors: &[Monitor]) -> (i32, i32, i32, i32) {
-    let mut min_x = i32::MAX;
-    let mut min_y = i32::MAX;
-    let mut max_x = i32::MIN;
-    let mut max_y = i32::MIN;
-    for m in monitors {
-        min_x = min_x.min(m.origin_px.0);
-        min_y = min_y.min(m.origin_px.1);
-        max_x = max_x.max(m.origin_px.0 + m.size_px.0);
-        max_y = max_y.max(m.origin_px.1 + m.size_px.1);
+impl Default for WaitImage {
+    fn default() -> Self {
+        Self { timeout_secs: 30, tolerance: 16, on_found: Branch::Continue, on_timeout: Branch::Abort }
     }
-    if monitors.is_empty() {
-        (0, 0, 0, 0)
-    } else {
-        (min_x, min_y, max_x, max_y)
+}
+
+/// What a step's post-click check looks at.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+enum Check {
+    /// The pixel at (x, y) has about this color.
+    Pixel { x: i32, y: i32, color: [u8; 3] },
+    /// The verify image shows up somewhere in `region`.
+    Image { region: Bounds },
+}
+
+/// Check made after each click of a step; failed clicks are retried with backoff.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct Verify {
+    check: Check,
+    /// Template PNG for [`Check::Image`].
+    image: String,
+    /// How far (0–255) each color channel may be off and still match.
+    tolerance: u8,
+    /// Wait after a click before checking, so the UI can react.
+    delay_ms: u64,
+    retries: u32,
+    /// Wait before the first retry; doubles for each further one.
+    backoff_ms: u64,
+}
+
+impl Default for Verify {
+    fn default() -> Self {
+        Self {
+            check: Check::Pixel { x: 0, y: 0, color: [0, 0, 0] },
+            image: String::new(),
+            tolerance: 16,
+            delay_ms: 300,
+            retries: 2,
+            backoff_ms: 500,
+        }
     }
 }
 
-#[derive(Clone, Copy, PartialEq)]
-enum DisplayChoice {
-    All,
-    One(usize), // index into monitors
+/// What a step does when it can't run: bad bounds, failed check, missing window or text.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct OnError {
+    /// Tries again this many times first, a second apart.
+    retries: u32,
+    then: Branch,
 }
 
-// -------------- UI State --------------
-struct AppState {
-    // Picker state
-    picking_area: bool,
-    drag_start: Option<Pos2>,
-    drag_end: Option<Pos2>,
+impl Default for OnError {
+    fn default() -> Self { Self { retries: 0, then: Branch::Continue } }
+}
 
-    // Display state
-    monitors: Vec<Monitor>,
-    display_choice: DisplayChoice,
+/// The kinds of step the sequence editor can make.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+enum StepKind {
+    #[default]
+    Click,
+    Screenshot,
+    WaitImage,
+}
 
-    // Config inputs
-    bounds_inputs: [i32; 4], // min_x, max_x, min_y, max_y
-    click_button_left: bool,
-    min_secs: f32,
-    max_secs: f32,
-    use_finite_clicks: bool,
-    num_clicks: u32,
+impl StepKind {
+    const ALL: [StepKind; 3] = [StepKind::Click, StepKind::Screenshot, StepKind::WaitImage];
 
-    // Engine
-    job: Option<ClickJob>,
-    config: Arc<Mutex<ClickConfig>>,
+    fn label(self) -> String {
+        match self {
+            StepKind::Click => tr!("Click"),
+            StepKind::Screenshot => tr!("Screenshot"),
+            StepKind::WaitImage => tr!("Wait for image"),
+        }
+    }
 }
 
-impl Default for AppState {
-    fn default() -> Self {
-        let monitors = query_monitors();
-        Self {
-            picking_area: false,
-            drag_start: None,
-            drag_end: None,
+/// One entry of a click sequence.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(remote = "Self")]
+struct SequenceStep {
+    name: String,
+    bounds: Bounds,
+    button: ClickButton,
+    clicks: u32,
+    min_secs: f32,
+    max_secs: f32,
+    zones: Vec<Zone>, // weighted sub-regions of `bounds`; empty = uniform
+    target: StepTarget,
+    /// Library area `bounds` is kept in sync with; `bounds` is the fallback if it's missing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    area: Option<String>,
+    /// Word or phrase to find by OCR inside `bounds` at run time; the click goes on the text.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    /// Template PNG a wait-for-image step looks for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    /// Check after every click; `None` = clicks are trusted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    verify: Option<Verify>,
+    #[serde(default)]
+    on_error: OnError,
+    /// Color tag (sRGB) for the step list, timeline and overlays; `None` = by position.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    color: Option<[u8; 3]>,
+    /// How often the step is drawn relative to the others in weighted order; 0 leaves it out.
+    #[serde(default = "default_weight")]
+    weight: f32,
+    /// Global hotkey that runs just this step, job or no job.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hotkey: Option<HotKey>,
+    /// Step template whose click settings this step follows; edits to it are copied here.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+    /// Free-text comment (why the step is there), shown as its tooltip in the step list.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    note: String,
+}
 
-            monitors,
-            display_choice: DisplayChoice::All,
+fn default_weight() -> f32 { 1.0 }
 
-            bounds_inputs: [100, 400, 100, 400],
-            click_button_left: true,
-            min_secs: 2.0,
-            max_secs: 4.5,
-            use_finite_clicks: false,
-            num_clicks: 100,
+impl Serialize for SequenceStep {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> { SequenceStep::serialize(self, s) }
+}
 
-            job: None,
-            config: Arc::new(Mutex::new(ClickConfig{
-                bounds: Some(Bounds{min_x:100, max_x:400, min_y:100, max_y:400}),
-                button: ClickButton::Left,
-                min_secs: 2.0,
-                max_secs: 4.5,
-                finite_clicks: None,
-            })),
+/// Steps saved before per-step failure policies branched through `verify.on_fail`; that
+/// becomes `on_error.then` unless the step already has an `on_error`.
+impl<'de> Deserialize<'de> for SequenceStep {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let value = serde_json::Value::deserialize(d)?;
+        let on_fail = value.pointer("/verify/on_fail").filter(|_| value.get("on_error").is_none()).cloned();
+        let mut step = SequenceStep::deserialize(value).map_err(D::Error::custom)?;
+        if let Some(then) = on_fail {
+            step.on_error.then = serde_json::from_value(then).map_err(D::Error::custom)?;
         }
+        Ok(step)
     }
 }
 
-impl AppState {
-    fn start(&mut self) {
-        if self.job.is_some() { return; }
-        let mut cfg = self.config.lock();
-        cfg.button = if self.click_button_left { ClickButton::Left } else { ClickButton::Right };
-        cfg.min_secs = self.min_secs;
-        cfg.max_secs = self.max_secs;
-        cfg.finite_clicks = if self.use_finite_clicks { Some(self.num_clicks) } else { None };
-        cfg.bounds = Some(Bounds{
-            min_x: self.bounds_inputs[0],
-            max_x: self.bounds_inputs[1],
-            min_y: self.bounds_inputs[2],
-            max_y: self.bounds_inputs[3],
-        });
-        drop(cfg);
-        self.job = Some(ClickJob::spawn(Arc::clone(&self.config)));
+impl Default for SequenceStep {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            bounds: Bounds { min_x: 0, max_x: 0, min_y: 0, max_y: 0 },
+            button: ClickButton::Left,
+            clicks: 1,
+            min_secs: 0.5,
+            max_secs: 1.5,
+            zones: Vec::new(),
+            target: StepTarget::Area,
+            area: None,
+            text: None,
+            image: None,
+            verify: None,
+            on_error: OnError::default(),
+            color: None,
+            weight: default_weight(),
+            hotkey: None,
+            template: None,
+            note: String::new(),
+        }
     }
+}
 
-    fn stop(&mut self) {
-        if let Some(job) = &self.job { job.stop(); }
-        self.job = None;
+/// Colors handed out to new steps in turn; also the fallback for untagged ones.
+const STEP_COLORS: [[u8; 3]; 8] = [
+    [230, 90, 80], [80, 170, 230], [110, 200, 90], [240, 180, 60],
+    [180, 110, 220], [60, 200, 190], [240, 130, 180], [170, 170, 170],
+];
+
+impl SequenceStep {
+    /// The step's color tag, or the palette color for list position `i`.
+    fn color(&self, i: usize) -> Color32 {
+        let [r, g, b] = self.color.unwrap_or(STEP_COLORS[i % STEP_COLORS.len()]);
+        Color32::from_rgb(r, g, b)
     }
 
-    fn pause(&mut self) {
-        if let Some(job) = &self.job { job.stop(); }
-        self.job = None;
+    /// Expected run time: clicks × mean interval.
+    fn expected_secs(&self) -> f32 { self.secs_range()[1] }
+
+    /// False for steps that only look at the screen (screenshots, waits).
+    fn clicks_mouse(&self) -> bool { !matches!(self.target, StepTarget::Screenshot { .. } | StepTarget::WaitImage(_)) }
+
+    /// Shortest, expected and longest run time. A wait-for-image step is put at half its timeout.
+    fn secs_range(&self) -> [f32; 3] {
+        if let StepTarget::WaitImage(w) = self.target {
+            let t = w.timeout_secs as f32;
+            return [0.0, t / 2.0, t];
+        }
+        let n = self.clicks as f32;
+        [n * self.min_secs.min(self.max_secs), n * (self.min_secs + self.max_secs) / 2.0, n * self.min_secs.max(self.max_secs)]
     }
 
-    fn refresh_monitors(&mut self) {
-        self.monitors = query_monitors();
-        // Clamp selection if out-of-range
-        if let DisplayChoice::One(i) = self.display_choice {
-            if i >= self.monitors.len() {
-                self.display_choice = DisplayChoice::All;
-            }
+    /// Point every `Jump(n)` branch at `map(n)`; `None` turns the jump into `Continue`.
+    fn remap_jumps(&mut self, map: impl Fn(usize) -> Option<usize>) {
+        let mut branches = vec![&mut self.on_error.then];
+        if let StepTarget::WaitImage(w) = &mut self.target { branches.extend([&mut w.on_found, &mut w.on_timeout]); }
+        for branch in branches {
+            if let Branch::Jump(n) = *branch { *branch = map(n).map_or(Branch::Continue, Branch::Jump); }
         }
     }
 
-    fn enter_picker(&mut self, ctx: &egui::Context) {
-        self.drag_start = None;
-        self.drag_end = None;
-        self.picking_area = true;
+    /// The same step with each screen position it holds (bounds, click point, verify check)
+    /// passed through `map`. The library area link is dropped, as the area itself stays put.
+    fn moved(&self, map: impl Fn((i32, i32)) -> (i32, i32)) -> Self {
+        let rect = |b: Bounds| {
+            let ((x0, y0), (x1, y1)) = (map((b.min_x, b.min_y)), map((b.max_x, b.max_y)));
+            Bounds { min_x: x0.min(x1), max_x: x0.max(x1), min_y: y0.min(y1), max_y: y0.max(y1) }
+        };
+        let mut step = self.clone();
+        step.bounds = rect(self.bounds);
+        step.area = None;
+        if let StepTarget::Point(p) = &mut step.target { (p.x, p.y) = map((p.x, p.y)); }
+        match step.verify.as_mut().map(|v| &mut v.check) {
+            Some(Check::Pixel { x, y, .. }) => (*x, *y) = map((*x, *y)),
+            Some(Check::Image { region }) => *region = rect(*region),
+            None => {}
+        }
+        step
+    }
 
-        // choose target rectangle in PHYSICAL pixels
-        let (origin_px, size_px) = match self.display_choice {
-            DisplayChoice::All => {
-                let (min_x, min_y, max_x, max_y) = union_rect(&self.monitors);
-                ((min_x, min_y), (max_x - min_x, max_y - min_y))
-            }
-            DisplayChoice::One(i) => {
-                if let Some(m) = self.monitors.get(i) {
-                    (m.origin_px, m.size_px)
-                } else {
-                    // fallback: union
-                    let (min_x, min_y, max_x, max_y) = union_rect(&self.monitors);
-                    ((min_x, min_y), (max_x - min_x, max_y - min_y))
+    /// One-line description for the step list, e.g. "Loot — 5x Left [10..90]x[20..80] 0.5-1.5s".
+    fn summary(&self) -> String {
+        let zones = if self.zones.is_empty() { String::new() } else { tr!(" · {} zones", self.zones.len()) };
+        let target = match self.target {
+            StepTarget::Area => {
+                let area = match &self.area {
+                    Some(name) => format!("@{}", name),
+                    None => format!("[{}..{}]x[{}..{}]", self.bounds.min_x, self.bounds.max_x, self.bounds.min_y, self.bounds.max_y),
+                };
+                match &self.text {
+                    Some(text) => tr!("“{}” in {}", text, area),
+                    None => area,
                 }
             }
+            StepTarget::Point(p) => format!("({}, {})±{}", p.x, p.y, p.jitter),
+            StepTarget::Offset { dx, dy, jitter } => tr!("last click {} ±{}", format!("{:+},{:+}", dx, dy), jitter),
+            StepTarget::Screenshot { full_screen: true } => tr!("full screen"),
+            StepTarget::Screenshot { full_screen: false } => {
+                format!("[{}..{}]x[{}..{}]", self.bounds.min_x, self.bounds.max_x, self.bounds.min_y, self.bounds.max_y)
+            }
+            StepTarget::WaitImage(w) => {
+                let image = self.image.as_deref().map_or_else(|| tr!("(no image)"), |p| image_name(p).to_string());
+                return tr!(
+                    "{} — wait for {} in [{}..{}]x[{}..{}] ≤{}s: found → {}, timeout → {}",
+                    self.name, image, self.bounds.min_x, self.bounds.max_x, self.bounds.min_y, self.bounds.max_y,
+                    w.timeout_secs, w.on_found.label(), w.on_timeout.label(),
+                );
+            }
         };
-
-        // convert to LOGICAL points for egui/eframe viewport commands
-        let ppp = ctx.pixels_per_point().max(0.1);
-        let inner = egui::vec2(size_px.0 as f32 / ppp, size_px.1 as f32 / ppp);
-        let outer = egui::pos2(origin_px.0 as f32 / ppp, origin_px.1 as f32 / ppp);
-
-        ctx.send_viewport_cmd(egui::ViewportCommand::Transparent(true));
-        ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
-        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(WindowLevel::AlwaysOnTop));
-        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(outer));
-        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(inner));
-        // Note: using borderless large window; not true OS fullscreen to avoid monitor switching quirks.
-    }
-
-    fn exit_picker(&mut self, ctx: &egui::Context) {
-        self.picking_area = false;
-        // restore a comfy window
-        ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(true));
-        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(WindowLevel::Normal));
-        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(520.0, 380.0)));
+        let action = match self.target {
+            StepTarget::Screenshot { .. } => tr!("screenshot"),
+            _ => format!("{:?}", self.button),
+        };
+        let verify = if self.verify.is_some() { tr!(" · verified") } else { String::new() };
+        let on_error = match self.on_error {
+            e if e == OnError::default() => String::new(),
+            OnError { retries: 0, then } => tr!(" · on error: {}", then.label()),
+            OnError { retries, then } => tr!(" · on error: retry {}×, then {}", retries, then.label()),
+        };
+        let hotkey = self.hotkey.map_or(String::new(), |k| format!(" · ⌨ {}", k.label()));
+        let template = self.template.as_ref().map_or(String::new(), |t| tr!(" · template {}", t));
+        format!(
+            "{} — {}x {} {} {:.1}-{:.1}s{}{}{}{}{}",
+            self.name, self.clicks, action, target, self.min_secs, self.max_secs, zones, verify, on_error, hotkey, template,
+        )
     }
+}
 
-    /// Convert current drag (logical points in current window) into PHYSICAL pixel bounds,
-    /// offset by the selected monitor or union origin.
-    fn set_bounds_from_drag(&mut self, ppp: f32, origin_px: (i32, i32)) {
-        if let (Some(a), Some(b)) = (self.drag_start, self.drag_end) {
-            let to_px = |p: Pos2| ((p.x * ppp).round() as i32, (p.y * ppp).round() as i32);
-            let (ax, ay) = to_px(a);
-            let (bx, by) = to_px(b);
-
-            let min_x = ax.min(bx) + origin_px.0;
-            let max_x = ax.max(bx) + origin_px.0;
-            let min_y = ay.min(by) + origin_px.1;
-            let max_y = ay.max(by) + origin_px.1;
+/// The in-place editor under an opened step row; true if anything changed. Wait-for-image
+/// steps have nothing to edit here.
+fn inline_step_ui(ui: &mut egui::Ui, step: &mut SequenceStep) -> bool {
+    if matches!(step.target, StepTarget::WaitImage(_)) { return false; }
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.add_space(24.0);
+        let shots = matches!(step.target, StepTarget::Screenshot { .. });
+        ui.label(if shots { tr!("Shots") } else { tr!("Clicks") });
+        changed |= ui.add(egui::DragValue::new(&mut step.clicks).speed(1.0).clamp_range(1..=1000000)).changed();
+        ui.label(tr!("every"));
+        changed |= ui.add(egui::DragValue::new(&mut step.min_secs).speed(0.1).clamp_range(0.0..=f32::MAX)).changed();
+        ui.label(tr!("to"));
+        changed |= ui.add(egui::DragValue::new(&mut step.max_secs).speed(0.1).clamp_range(0.0..=f32::MAX).suffix(" s")).changed();
+        if !shots {
+            changed |= ui.radio_value(&mut step.button, ClickButton::Left, tr!("Left")).changed();
+            changed |= ui.radio_value(&mut step.button, ClickButton::Right, tr!("Right")).changed();
+        }
+    });
+    changed
+}
 
-            self.bounds_inputs = [min_x, max_x, min_y, max_y];
-            self.config.lock().bounds = Some(Bounds{min_x, max_x, min_y, max_y});
-            eprintln!("Selected bounds (px): x=[{}..{}], y=[{}..{}]", min_x, max_x, min_y, max_y);
+/// A profile diff as lists: changed settings, then steps added, removed or changed.
+fn diff_ui(ui: &mut egui::Ui, diff: &diff::ProfileDiff) {
+    let field = |ui: &mut egui::Ui, f: &diff::FieldChange| {
+        ui.horizontal(|ui| {
+            ui.label(&f.field);
+            ui.colored_label(Color32::LIGHT_RED, &f.from);
+            ui.label("→");
+            ui.colored_label(Color32::LIGHT_GREEN, &f.to);
+        });
+    };
+    if !diff.settings.is_empty() {
+        ui.strong(tr!("Settings"));
+        for f in &diff.settings { field(ui, f); }
+    }
+    if !diff.steps.is_empty() {
+        ui.strong(tr!("Steps"));
+        for change in &diff.steps {
+            match change {
+                diff::StepChange::Added { index, name } => { ui.colored_label(Color32::LIGHT_GREEN, tr!("+ {}. {} (new)", index + 1, name)); }
+                diff::StepChange::Removed { index, name } => { ui.colored_label(Color32::LIGHT_RED, tr!("− {}. {} (removed)", index + 1, name)); }
+                diff::StepChange::Changed { index, name, fields } => {
+                    ui.label(tr!("~ {}. {}", index + 1, name));
+                    ui.indent(("diff_step", *index), |ui| for f in fields { field(ui, f); });
+                }
+            }
         }
     }
 }
 
+/// Combo box for a wait-for-image branch; jumps pick one of the `steps` steps.
+fn branch_ui(ui: &mut egui::Ui, id: &str, branch: &mut Branch, steps: usize) {
+    let jump_to = match *branch { Branch::Jump(n) => n, _ => 0 };
+    egui::ComboBox::from_id_source(id)
+        .selected_text(match *branch { Branch::Jump(_) => tr!("go to step"), b => b.label() })
+        .show_ui(ui, |ui| {
+            for b in [Branch::Continue, Branch::SkipNext, Branch::Abort] {
+                ui.selectable_value(branch, b, b.label());
+            }
+            if ui.selectable_label(matches!(branch, Branch::Jump(_)), tr!("go to step")).clicked() {
+                *branch = Branch::Jump(jump_to);
+            }
+        });
+    if let Branch::Jump(n) = branch {
+        let mut number = *n + 1;
+        ui.add(egui::DragValue::new(&mut number).clamp_range(1..=steps.max(1)));
+        *n = number - 1;
+    }
+}
+
+/// Self-test results, as shown under Diagnostics and in the setup wizard.
+fn diagnostics_grid(ui: &mut egui::Ui, d: &Diagnostics) {
+    egui::Grid::new("diagnostics").num_columns(2).show(ui, |ui| {
+        ui.label(tr!("Backend")); ui.label(i18n::translate(d.backend.label())); ui.end_row();
+        ui.label(tr!("Display server")); ui.label(d.display_server); ui.end_row();
+        ui.label(tr!("Backend check"));
+        match &d.check {
+            Ok(()) => ui.colored_label(Color32::LIGHT_GREEN, tr!("OK")),
+            Err(e) => ui.colored_label(Color32::LIGHT_RED, e),
+        };
+        ui.end_row();
+        ui.label(tr!("Cursor"));
+        ui.label(tr!("read {}, moved to {}, read back {}", format!("{:?}", d.start), format!("{:?}", d.target), format!("{:?}", d.seen)));
+        ui.end_row();
+        ui.label(tr!("Move test"));
+        match d.moved {
+            Some(true) => ui.colored_label(Color32::LIGHT_GREEN, tr!("cursor followed")),
+            Some(false) => ui.colored_label(Color32::LIGHT_RED, tr!("cursor did NOT move — input is not being injected")),
+            None => ui.label(tr!("not verifiable (backend can't read the cursor back)")),
+        };
+        ui.end_row();
+        ui.label(tr!("Permissions")); ui.label(&d.permissions); ui.end_row();
+    });
+}
+
+/// File name part of an image path, for labels.
+fn image_name(path: &str) -> &str {
+    std::path::Path::new(path).file_name().and_then(|n| n.to_str()).unwrap_or(path)
+}
+
+/// Changes the step list's bulk edit makes to every selected step; each one is opt-in.
+#[derive(Clone, Debug)]
+struct BulkEdit {
+    set_interval: bool,
+    min_secs: f32,
+    max_secs: f32,
+    set_button: bool,
+    button: ClickButton,
+    set_clicks: bool,
+    clicks: u32,
+}
+
+impl Default for BulkEdit {
+    fn default() -> Self {
+        Self {
+            set_interval: false,
+            min_secs: 0.5,
+            max_secs: 1.5,
+            set_button: false,
+            button: ClickButton::Left,
+            set_clicks: false,
+            clicks: 5,
+        }
+    }
+}
+
+impl BulkEdit {
+    fn is_empty(&self) -> bool { !(self.set_interval || self.set_button || self.set_clicks) }
+
+    fn apply(&self, step: &mut SequenceStep) {
+        if self.set_interval {
+            step.min_secs = self.min_secs.min(self.max_secs);
+            step.max_secs = self.min_secs.max(self.max_secs);
+        }
+        if self.set_button { step.button = self.button; }
+        if self.set_clicks { step.clicks = self.clicks.max(1); }
+    }
+}
+
+/// How the step list's "Clone with offset" moves the copies: by a fixed offset, or from one
+/// monitor onto another.
+#[derive(Clone, Debug)]
+struct CloneOffset {
+    onto_monitor: bool,
+    dx: i32,
+    dy: i32,
+    from: usize, // indices into the monitor list
+    to: usize,
+    profile_name: String,
+}
+
+impl Default for CloneOffset {
+    fn default() -> Self {
+        Self { onto_monitor: false, dx: 0, dy: 0, from: 0, to: 1, profile_name: String::new() }
+    }
+}
+
+/// What a new step starts with in the editor; app-wide, not part of profiles.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct StepDefaults {
+    clicks: u32,
+    min_secs: f32,
+    max_secs: f32,
+    button: ClickButton,
+    /// Name of a step added without one; `{n}` is its number. Empty = "Step {n}" in the UI language.
+    name: String,
+}
+
+impl Default for StepDefaults {
+    fn default() -> Self {
+        Self { clicks: 5, min_secs: 0.5, max_secs: 1.5, button: ClickButton::Left, name: String::new() }
+    }
+}
+
+impl StepDefaults {
+    /// The name of unnamed step number `n` (1-based).
+    fn step_name(&self, n: usize) -> String {
+        match self.name.trim() {
+            "" => tr!("Step {}", n),
+            pattern => pattern.replace("{n}", &n.to_string()),
+        }
+    }
+}
+
+/// A named building block for steps: how a step clicks, but not where. Kept in a library shared
+/// by all profiles; steps inserted from it can stay linked and follow its edits.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct StepTemplate {
+    button: ClickButton,
+    clicks: u32,
+    min_secs: f32,
+    max_secs: f32,
+    zones: Vec<Zone>,
+    on_error: OnError,
+    weight: f32,
+    color: Option<[u8; 3]>,
+}
+
+impl StepTemplate {
+    fn of(step: &SequenceStep) -> Self {
+        Self {
+            button: step.button,
+            clicks: step.clicks,
+            min_secs: step.min_secs,
+            max_secs: step.max_secs,
+            zones: step.zones.clone(),
+            on_error: step.on_error,
+            weight: step.weight,
+            color: step.color,
+        }
+    }
+
+    /// Give `step` the template's settings; its name and target stay.
+    fn apply(&self, step: &mut SequenceStep) {
+        step.button = self.button;
+        step.clicks = self.clicks;
+        step.min_secs = self.min_secs;
+        step.max_secs = self.max_secs;
+        step.zones = self.zones.clone();
+        step.on_error = self.on_error;
+        step.weight = self.weight;
+        if self.color.is_some() { step.color = self.color; }
+    }
+}
+
+/// Byte ranges of `text` matching `needle`, case-insensitively and without overlaps.
+fn match_ranges(text: &str, needle: &str) -> Vec<std::ops::Range<usize>> {
+    let needle: Vec<char> = needle.chars().flat_map(char::to_lowercase).collect();
+    if needle.is_empty() { return Vec::new(); }
+    let mut ranges = Vec::new();
+    let mut skip_to = 0;
+    for (start, _) in text.char_indices() {
+        if start < skip_to { continue; }
+        let mut matched = 0; // chars of `needle` matched so far
+        for (i, c) in text[start..].char_indices() {
+            let lower: Vec<char> = c.to_lowercase().collect();
+            if !needle[matched..].starts_with(&lower) { break; }
+            matched += lower.len();
+            if matched == needle.len() {
+                skip_to = start + i + c.len_utf8();
+                ranges.push(start..skip_to);
+                break;
+            }
+        }
+    }
+    ranges
+}
+
+/// `text` with the `ranges` highlighted, for a label.
+fn highlighted(text: &str, ranges: &[std::ops::Range<usize>], ui: &egui::Ui) -> egui::text::LayoutJob {
+    let mut job = egui::text::LayoutJob::default();
+    let plain = egui::TextFormat { color: ui.visuals().text_color(), ..Default::default() };
+    let marked = egui::TextFormat { background: Color32::from_rgb(120, 100, 20), color: Color32::WHITE, ..Default::default() };
+    let mut at = 0;
+    for r in ranges {
+        job.append(&text[at..r.start], 0.0, plain.clone());
+        job.append(&text[r.clone()], 0.0, marked.clone());
+        at = r.end;
+    }
+    job.append(&text[at..], 0.0, plain);
+    job
+}
+
+/// (min, mean, max) seconds to run `steps` `cycles` times, from each step's interval range.
+fn sequence_duration(steps: &[SequenceStep], cycles: u32) -> [f32; 3] {
+    let per_cycle = steps.iter().fold([0.0; 3], |[lo, mid, hi], s| {
+        let [a, b, c] = s.secs_range();
+        [lo + a, mid + b, hi + c]
+    });
+    per_cycle.map(|t| t * cycles as f32)
+}
+
+/// "45s", "12m 30s", "2h 05m".
+fn format_duration(secs: f32) -> String {
+    let secs = secs.max(0.0).round() as u64;
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// The setup wizard's test click on its thread, and the flag that stops it.
+type TestClickJob = (Arc<AtomicBool>, std::thread::JoinHandle<Result<TestClick, String>>);
+
+struct ClickJob {
+    running: Arc<AtomicBool>,
+    progress: Arc<Mutex<JobProgress>>,
+    reported: bool, // its statistics were taken for the results window
+    #[allow(dead_code)] // Used through Arc clone in spawn
+    config: Arc<Mutex<ClickConfig>>,
+}
+
+#[derive(Clone, Debug)]
+struct ClickConfig {
+    mode: JobMode,
+    bounds: Option<Bounds>,
+    point: Option<ClickPoint>,   // Single mode: click a point instead of `bounds`
+    button: ClickButton,
+    min_secs: f32,
+    max_secs: f32,
+    finite_clicks: Option<u32>,  // None for infinite, Some(n) for n clicks
+    steps: Vec<SequenceStep>,
+    cycles: Option<u32>,         // None for infinite, Some(n) for n passes over `steps`
+    step_order: StepOrder,       // how each cycle goes through `steps`
+    anti_afk: Option<AntiAfk>,   // key taps sent while the job waits between clicks
+    grid: GridSweep,
+    fatigue: Option<Fatigue>,    // None = constant pace for the whole run
+    warm_up: Option<WarmUp>,     // slower first clicks
+    quiet_hours: Vec<QuietWindow>, // daily windows in which clicks and key taps wait
+    keep_awake: bool,            // inhibit system sleep for as long as the job runs
+    reclick_misses: bool,        // click once more when the cursor ended up off target
+    correct_drift: bool,         // re-read the cursor along the path and steer out drift
+    settle_before_click: bool,   // a tiny correction or two near the target before pressing
+    vary_approach: bool,         // come in at an angle and land a little short of the aim
+    min_hold_ms: u64,            // the button is held a random time in this range per click
+    max_hold_ms: u64,
+    path_bank: Option<Arc<PathBank>>, // recorded paths replayed instead of synthetic ones
+    hold_to_run: Option<HotKey>, // only click while this key is held (dead man's switch)
+    forbidden: Vec<Bounds>,      // never click inside these; points are resampled
+    wait_for: Option<WaitFor>,   // start condition checked before the first click
+    refocus: Option<Refocus>,    // window brought to the front before each step
+    screenshot_every: Option<u32>, // full-screen screenshot every n clicks
+    screenshot_dir: PathBuf,     // where screenshots (and screenshot steps) are saved
+    events: Option<EventCommands>, // shell commands run on start/finish/error/cycle
+    limits: SafetyLimits,        // app-wide caps every job obeys
+    feedback: ClickFeedback,     // tick sound for every click
+    reload: bool,                // settings were reloaded; rebuild the steps after this cycle
+    step_command: Option<StepCommand>, // skip/repeat/jump asked for while running; taken by the runner
+    start_at: (usize, u32),      // step index and cycles already done a sequence starts from
+    checkpoint: Option<(ProfileStore, Checkpoint)>, // saved at each cycle boundary, on top of the counts carried in
+}
+
+impl Default for ClickConfig {
+    fn default() -> Self {
+        Self {
+            mode: JobMode::Single,
+            bounds: Some(Bounds{min_x:100, max_x:400, min_y:100, max_y:400}),
+            point: None,
+            button: ClickButton::Left,
+            min_secs: 2.0,
+            max_secs: 4.5,
+            finite_clicks: None,
+            steps: Vec::new(),
+            cycles: None,
+            step_order: StepOrder::InOrder,
+            anti_afk: None,
+            grid: GridSweep { cols: 4, rows: 4, serpentine: true },
+            fatigue: None,
+            warm_up: None,
+            quiet_hours: Vec::new(),
+            keep_awake: false,
+            reclick_misses: false,
+            correct_drift: false,
+            settle_before_click: false,
+            vary_approach: false,
+            min_hold_ms: 20,
+            max_hold_ms: 120,
+            path_bank: None,
+            hold_to_run: None,
+            forbidden: Vec::new(),
+            wait_for: None,
+            refocus: None,
+            screenshot_every: None,
+            screenshot_dir: PathBuf::new(),
+            events: None,
+            limits: SafetyLimits::default(),
+            feedback: ClickFeedback::default(),
+            reload: false,
+            step_command: None,
+            start_at: (0, 0),
+            checkpoint: None,
+        }
+    }
+}
+
+/// Clicks sampled for the "where would it click?" preview.
+const PREVIEW_CLICKS: usize = 500;
+
+/// Screen shown around the targets in the click preview, px.
+const PREVIEW_MARGIN: i32 = 20;
+
+/// Something a job clicks into, for the click preview.
+#[derive(Clone, Debug, PartialEq)]
+enum ClickTarget {
+    Area(Bounds, Vec<Zone>),
+    Point(ClickPoint),
+}
+
+impl ClickTarget {
+    fn sample(&self, rng: &mut impl rand::Rng) -> (i32, i32) {
+        match self {
+            ClickTarget::Area(b, zones) => sample_point(*b, zones, rng),
+            ClickTarget::Point(p) => p.sample(rng),
+        }
+    }
+
+    /// Everything the target's points can land on.
+    fn extent(&self) -> Bounds {
+        match self {
+            ClickTarget::Area(b, _) => *b,
+            ClickTarget::Point(p) => Bounds::around((p.x, p.y), p.jitter),
+        }
+    }
+}
+
+/// What a job with `cfg` clicks into, each with the index of its step (0 outside sequences).
+/// Offset and text steps are left out: where they click is only known while running.
+fn click_targets(cfg: &ClickConfig) -> Vec<(usize, ClickTarget)> {
+    let area = cfg.bounds.filter(|b| b.is_valid());
+    match cfg.mode {
+        JobMode::Single => match (cfg.point, area) {
+            (Some(p), _) => vec![(0, ClickTarget::Point(p))],
+            (None, Some(b)) => vec![(0, ClickTarget::Area(b, Vec::new()))],
+            (None, None) => Vec::new(),
+        },
+        JobMode::GridSweep => area
+            .map(|b| grid_cells(b, cfg.grid.cols, cfg.grid.rows, cfg.grid.serpentine))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|cell| (0, ClickTarget::Area(cell, Vec::new())))
+            .collect(),
+        JobMode::Sequence => cfg.steps
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| match s.target {
+                StepTarget::Area if s.text.is_none() && s.bounds.is_valid() => Some((i, ClickTarget::Area(s.bounds, s.zones.clone()))),
+                StepTarget::Point(p) => Some((i, ClickTarget::Point(p))),
+                _ => None,
+            })
+            .collect(),
+        JobMode::AntiAfk => Vec::new(),
+    }
+}
+
+/// A simulated click: the index of its step, and the point.
+type SimClick = (usize, (i32, i32));
+
+/// `n` clicks spread evenly over `targets` the way a job draws them, forbidden zones included.
+/// Returns the points with their step index, and how many draws found no allowed point.
+fn simulate_clicks(targets: &[(usize, ClickTarget)], forbidden: &[Bounds], n: usize, rng: &mut impl rand::Rng) -> (Vec<SimClick>, usize) {
+    let mut points = Vec::with_capacity(n);
+    let mut skipped = 0;
+    for (step, target) in targets.iter().cycle().take(if targets.is_empty() { 0 } else { n }) {
+        match resample_outside(forbidden, RESAMPLE_ATTEMPTS, || target.sample(rng)) {
+            Some(p) => points.push((*step, p)),
+            None => skipped += 1,
+        }
+    }
+    (points, skipped)
+}
+
+/// Simulated clicks over a screenshot of the screen around them.
+struct ClickPreview {
+    /// The part of the screen shown, px.
+    area: Bounds,
+    /// `None` if the screen couldn't be captured; the points are shown on a plain background.
+    texture: Option<egui::TextureHandle>,
+    points: Vec<SimClick>,
+    skipped: usize,
+    forbidden: Vec<Bounds>,
+}
+
+/// Where clicks and key taps are injected: the one input driver, opened by `set_input_backend`
+/// and replaced when the user picks another backend.
+static INPUT: Lazy<Mutex<Input>> =
+    Lazy::new(|| Mutex::new(Input::unavailable(Backend::Enigo, "input is not set up yet".to_string())));
+
+/// How often the status bar reads the cursor position and the color under it.
+const READOUT_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+/// Time for the picker overlay to go away before the screen under a pick is read.
+const PICK_SAMPLE_DELAY: std::time::Duration = std::time::Duration::from_millis(300);
+
+/// Time to switch to the target app between `--autostart` and the first click.
+const AUTOSTART_DELAY: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// How fast a fully tilted stick moves the selection, px per second.
+const NUDGE_SPEED: f32 = 400.0;
+
+/// How long the overlay marks a click picked from the history list.
+const FLASH_DURATION: std::time::Duration = std::time::Duration::from_millis(2000);
+
+/// How often the working state is written to the recovery file.
+const AUTOSAVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Turn the config into the list of steps the runner iterates over.
+fn build_steps(cfg: &ClickConfig) -> (Vec<Box<dyn Step>>, Option<u32>) {
+    match cfg.mode {
+        JobMode::Single => (vec![Box::new(SingleAreaStep::new(cfg.finite_clicks))], Some(1)),
+        JobMode::Sequence => (
+            cfg.steps.iter().cloned().map(|s| match s.target {
+                StepTarget::WaitImage(wait) => Box::new(WaitImageStep::new(s, wait)) as Box<dyn Step>,
+                _ => Box::new(ClickStep::new(s)) as Box<dyn Step>,
+            }).collect(),
+            cfg.cycles,
+        ),
+        JobMode::GridSweep => (vec![Box::new(GridSweepStep::new(cfg.bounds, cfg.grid))], Some(1)),
+        JobMode::AntiAfk => (vec![Box::new(KeyTapStep)], None),
+    }
+}
+
+impl ClickConfig {
+    /// The job a step hotkey starts: `step` once, with this config's pacing, targets and limits
+    /// but without its start condition, hold key, anti-AFK taps, shell commands or checkpoints.
+    fn burst(&self, step: SequenceStep) -> Self {
+        Self {
+            mode: JobMode::Sequence,
+            steps: vec![step],
+            cycles: Some(1),
+            step_order: StepOrder::InOrder,
+            anti_afk: None,
+            hold_to_run: None,
+            wait_for: None,
+            screenshot_every: None,
+            events: None,
+            reload: false,
+            step_command: None,
+            start_at: (0, 0),
+            checkpoint: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// Run the job `cfg` describes as a dry run (see [`DryRun`]): in virtual time, without clicking.
+fn dry_run(cfg: ClickConfig) -> DryRunReport {
+    let (mut steps, cycles) = build_steps(&cfg);
+    let step_names = steps.iter().map(|s| s.name().to_string()).collect();
+    let running = AtomicBool::new(true);
+    let config = Mutex::new(cfg);
+    let mut ctx = StepCtx::new(&running, &config).dry();
+    if ctx.wait_for_start() { run_steps(&mut steps, cycles, &mut ctx); }
+    let progress = ctx.progress.lock().clone();
+    DryRunReport { run: ctx.dry_run.take().unwrap_or_default(), step_names, clicks: progress.clicks, failed_steps: progress.failed_steps }
+}
+
+/// What a dry run of the current settings did, for the report window.
+struct DryRunReport {
+    run: DryRun,
+    step_names: Vec<String>,
+    clicks: u32,
+    failed_steps: u32,
+}
+
+impl DryRunReport {
+    fn cycles(&self) -> usize { self.run.entries.iter().filter(|e| e.event == DryRunEvent::CycleEnd).count() }
+
+    /// Why the run ended early, if it did.
+    fn stopped(&self) -> Option<&str> {
+        self.run.entries.iter().rev().find_map(|e| match &e.event {
+            DryRunEvent::Stopped(why) => Some(why.as_str()),
+            _ => None,
+        })
+    }
+}
+
+/// A dry run event for the schedule list.
+fn dry_run_event_text(event: &DryRunEvent) -> String {
+    match event {
+        DryRunEvent::Step => tr!("step starts"),
+        DryRunEvent::Click((x, y)) => tr!("click at ({}, {})", x, y),
+        DryRunEvent::Screenshot => tr!("screenshot"),
+        DryRunEvent::Assumed(what) => tr!("assumed: {}", what),
+        DryRunEvent::Failed(why) => tr!("failed: {}", why),
+        DryRunEvent::CycleEnd => tr!("cycle done"),
+        DryRunEvent::Stopped(why) => tr!("stopped: {}", why),
+    }
+}
+
+/// Virtual time of a dry run event, e.g. `0:02:05.3`.
+fn format_virtual_time(ms: u64) -> String {
+    format!("{}:{:02}:{:02}.{}", ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000 / 100)
+}
+
+impl ClickJob {
+    fn spawn(config: Arc<Mutex<ClickConfig>>) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let running_clone = Arc::clone(&running);
+        let config_clone = Arc::clone(&config);
+        let progress = Arc::new(Mutex::new(JobProgress::default()));
+        let progress_clone = Arc::clone(&progress);
+
+        log::info!("Starting {:?} job", config.lock().mode);
+        log::debug!("Job config: {:?}", config.lock());
+
+        std::thread::spawn(move || {
+            let (mut steps, cycles) = build_steps(&config_clone.lock());
+            let keep_awake = config_clone.lock().keep_awake;
+            let _awake = keep_awake.then(power::KeepAwake::new).and_then(|r| {
+                r.map_err(|e| log::warn!("The system may go to sleep during the job: {}", e)).ok()
+            });
+            let mut ctx = StepCtx::new(&running_clone, &config_clone).with_progress(progress_clone);
+            ctx.fire(Event::Start);
+            if ctx.wait_for_start() { run_steps(&mut steps, cycles, &mut ctx); }
+            // the run got to end, so there's nothing to continue
+            let checkpoint = config_clone.lock().checkpoint.clone();
+            if let Some(Err(e)) = checkpoint.map(|(store, _)| store.clear_checkpoint()) {
+                log::warn!("Could not remove the checkpoint: {}", e);
+            }
+            if ctx.is_running() {
+                log::info!("Job finished after {} clicks", ctx.clicks);
+            } else {
+                log::info!("Job stopped after {} clicks", ctx.clicks);
+            }
+            ctx.fire(Event::Finish);
+            viewer::set_target(None);
+            running_clone.store(false, Ordering::Relaxed);
+        });
+
+        Self { running, progress, reported: false, config }
+    }
+    fn stop(&self) { self.running.store(false, Ordering::Relaxed); }
+}
+
+// -------------- Display Info --------------
+#[derive(Clone, Debug)]
+#[derive(Serialize)]
+struct Monitor {
+    id: u32,
+    name: String,
+    origin_px: (i32, i32),
+    size_px: (i32, i32),
+    scale_factor: f32,
+}
+
+impl Monitor {
+    fn contains(&self, (x, y): (i32, i32)) -> bool {
+        x >= self.origin_px.0 && x < self.origin_px.0 + self.size_px.0
+            && y >= self.origin_px.1 && y < self.origin_px.1 + self.size_px.1
+    }
+}
+
+/// Where physical point `p` on monitor `from` lands on monitor `to`: same relative position,
+/// scaled when the two differ in resolution.
+fn remap_to_monitor((x, y): (i32, i32), from: &Monitor, to: &Monitor) -> (i32, i32) {
+    let axis = |v: i32, from_origin: i32, from_size: i32, to_origin: i32, to_size: i32| {
+        to_origin + ((v - from_origin) as i64 * to_size as i64 / from_size.max(1) as i64) as i32
+    };
+    (
+        axis(x, from.origin_px.0, from.size_px.0, to.origin_px.0, to.size_px.0),
+        axis(y, from.origin_px.1, from.size_px.1, to.origin_px.1, to.size_px.1),
+    )
+}
+
+/// A monitor's own scale factor, or `ppp` when it doesn't report a usable one.
+fn monitor_scale(m: &Monitor, ppp: f32) -> f32 {
+    if m.scale_factor <= 0.0 || (m.scale_factor - ppp).abs() < 0.01 { ppp } else { m.scale_factor }
+}
+
+/// Where monitor `m` sits in the picker window (logical points), the window's top-left being
+/// at `origin_px`. Measured from the monitor's true origin: monitors before it in the same row
+/// (or column) count with their own scale factor, any remaining distance with `ppp`, so a
+/// differently scaled monitor left of or above it doesn't shift it.
+fn monitor_rect(m: &Monitor, ppp: f32, origin_px: (i32, i32), monitors: &[Monitor]) -> Rect {
+    let start = |along: fn((i32, i32)) -> i32, across: fn((i32, i32)) -> i32| {
+        let mut px = along(m.origin_px) - along(origin_px);
+        let mut points = 0.0;
+        let mut counted: Vec<(i32, i32)> = Vec::new();
+        for n in monitors {
+            let (lo, hi) = (along(n.origin_px), along(n.origin_px) + along(n.size_px));
+            let before = lo >= along(origin_px) && hi <= along(m.origin_px);
+            let beside = across(n.origin_px) < across(m.origin_px) + across(m.size_px)
+                && across(m.origin_px) < across(n.origin_px) + across(n.size_px);
+            // two monitors stacked beside this one cover the same stretch; count it once
+            if !before || !beside || counted.iter().any(|&(a, b)| lo < b && a < hi) { continue; }
+            counted.push((lo, hi));
+            px -= hi - lo;
+            points += (hi - lo) as f32 / monitor_scale(n, ppp);
+        }
+        points + px as f32 / ppp
+    };
+    let scale = monitor_scale(m, ppp);
+    Rect::from_min_size(
+        Pos2::new(start(|t| t.0, |t| t.1), start(|t| t.1, |t| t.0)),
+        egui::vec2(m.size_px.0 as f32 / scale, m.size_px.1 as f32 / scale),
+    )
+}
+
+/// Convert a point in the picker window (logical points) to physical screen pixels.
+///
+/// The window-wide `ppp` only matches the monitor the window is "on"; with mixed scale
+/// factors, a point on another monitor is re-measured from that monitor's own origin with
+/// its own `scale_factor`. The monitor is found in window coordinates (see [`monitor_rect`]),
+/// which keeps monitors left of or above the primary one (negative origins) in place.
+fn logical_to_physical(p: Pos2, ppp: f32, origin_px: (i32, i32), monitors: &[Monitor]) -> (i32, i32) {
+    let on = monitors.iter().map(|m| (m, monitor_rect(m, ppp, origin_px, monitors))).find(|(_, r)| {
+        p.x >= r.min.x && p.x < r.max.x && p.y >= r.min.y && p.y < r.max.y
+    });
+    let Some((m, rect)) = on else {
+        return (origin_px.0 + (p.x * ppp).round() as i32, origin_px.1 + (p.y * ppp).round() as i32);
+    };
+    let scale = monitor_scale(m, ppp);
+    let x = m.origin_px.0 + ((p.x - rect.min.x) * scale).round() as i32;
+    let y = m.origin_px.1 + ((p.y - rect.min.y) * scale).round() as i32;
+    (
+        x.clamp(m.origin_px.0, m.origin_px.0 + m.size_px.0 - 1),
+        y.clamp(m.origin_px.1, m.origin_px.1 + m.size_px.1 - 1),
+    )
+}
+
+/// Inverse of [`logical_to_physical`]: where physical pixel `px` lies in the picker window.
+fn physical_to_logical(px: (i32, i32), ppp: f32, origin_px: (i32, i32), monitors: &[Monitor]) -> Pos2 {
+    let Some(m) = monitors.iter().find(|m| m.contains(px)) else {
+        return Pos2::new((px.0 - origin_px.0) as f32 / ppp, (px.1 - origin_px.1) as f32 / ppp);
+    };
+    let scale = monitor_scale(m, ppp);
+    monitor_rect(m, ppp, origin_px, monitors).min
+        + egui::vec2((px.0 - m.origin_px.0) as f32 / scale, (px.1 - m.origin_px.1) as f32 / scale)
+}
+
+/// Monitor list for `list-displays`: an aligned text table, or a JSON array.
+fn displays_table(monitors: &[Monitor], json: bool) -> String {
+    if json {
+        return serde_json::to_string_pretty(monitors).unwrap_or_default() + "\n";
+    }
+    let mut out = format!("{:>4}  {:<22} {:>13}  {:>11}  {:>5}\n", "id", "name", "origin", "size", "scale");
+    for m in monitors {
+        out += &format!(
+            "{:>4}  {:<22} {:>13}  {:>11}  {:>5}\n",
+            m.id,
+            m.name,
+            format!("{},{}", m.origin_px.0, m.origin_px.1),
+            format!("{}x{}", m.size_px.0, m.size_px.1),
+            m.scale_factor,
+        );
+    }
+    out
+}
+
+
+/// Live cursor position and the color under it, shown in the status bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct CursorReadout {
+    pos: (i32, i32),
+    color: Option<[u8; 3]>, // None if the screen can't be read here
+}
+
+/// Logical points either side of an edge of the picker's rectangle that grab it.
+const GRIP_MARGIN: f32 = 6.0;
+
+/// The edges of the picker's rectangle a drag moves; all four move the whole rectangle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Grip {
+    left: bool,
+    right: bool,
+    top: bool,
+    bottom: bool,
+}
+
+impl Grip {
+    const MOVE: Grip = Grip { left: true, right: true, top: true, bottom: true };
+
+    fn cursor(self) -> egui::CursorIcon {
+        use egui::CursorIcon as C;
+        match (self.left || self.right, self.top || self.bottom) {
+            _ if self == Grip::MOVE => C::Move,
+            (true, true) if self.left == self.top => C::ResizeNwSe,
+            (true, true) => C::ResizeNeSw,
+            (true, false) => C::ResizeHorizontal,
+            _ => C::ResizeVertical,
+        }
+    }
+}
+
+/// What pressing at `p` grabs of `rect`: an edge or corner near it, the whole rectangle
+/// inside it, or nothing outside.
+fn grip_at(rect: Rect, p: Pos2) -> Option<Grip> {
+    if !rect.expand(GRIP_MARGIN).contains(p) { return None; }
+    let near = |a: f32, b: f32| (a - b).abs() <= GRIP_MARGIN;
+    // on a narrow rectangle both edges are near; take the closer one
+    let (left, right) = (near(p.x, rect.min.x), near(p.x, rect.max.x));
+    let (top, bottom) = (near(p.y, rect.min.y), near(p.y, rect.max.y));
+    let left = left && !(right && p.x - rect.min.x > rect.max.x - p.x);
+    let top = top && !(bottom && p.y - rect.min.y > rect.max.y - p.y);
+    let g = Grip { left, right: right && !left, top, bottom: bottom && !top };
+    Some(if g == (Grip { left: false, right: false, top: false, bottom: false }) { Grip::MOVE } else { g })
+}
+
+/// `[x, y, width, height]` with the gripped edges moved by `(dx, dy)` px.
+fn adjust_entry([x, y, w, h]: [i32; 4], g: Grip, (dx, dy): (i32, i32)) -> [i32; 4] {
+    let (mut x0, mut y0, mut x1, mut y1) = (x, y, x + w, y + h);
+    if g.left { x0 += dx; }
+    if g.right { x1 += dx; }
+    if g.top { y0 += dy; }
+    if g.bottom { y1 += dy; }
+    [x0.min(x1), y0.min(y1), (x1 - x0).abs(), (y1 - y0).abs()]
+}
+
+/// `end` moved so the drag from `start` spans a square as large as its longer side.
+fn square_corner(start: Pos2, end: Pos2) -> Pos2 {
+    let d = end - start;
+    let side = d.x.abs().max(d.y.abs());
+    start + egui::vec2(side.copysign(d.x), side.copysign(d.y))
+}
+
+/// A small filled square of `color`.
+fn color_swatch(ui: &mut egui::Ui, [r, g, b]: [u8; 3]) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(14.0, 14.0), Sense::hover());
+    ui.painter().rect_filled(rect, 2.0, Color32::from_rgb(r, g, b));
+    ui.painter().rect_stroke(rect, 2.0, ui.visuals().widgets.noninteractive.bg_stroke);
+}
+
+/// The displays to pick on; errors if neither display_info nor the input backend can size one.
+fn query_monitors() -> Result<Vec<Monitor>, String> {
+    match display_info::DisplayInfo::all() {
+        Ok(displays) if !displays.is_empty() => {
+            Ok(displays
+                .into_iter()
+                .map(|d| Monitor {
+                    id: d.id,
+                    // v0.4.x has no `.name`; make a friendly one
+                    name: if d.is_primary {
+                        format!("Display {} (Primary)", d.id)
+                    } else {
+                        format!("Display {}", d.id)
+                    },
+                    origin_px: (d.x, d.y),                            // i32
+                    size_px: (d.width as i32, d.height as i32),       // u32 -> i32
+                    scale_factor: d.scale_factor,                     // already f32
+                })
+                .collect())
+        }
+        listed => {
+            // Fallback: single main display as the input backend sees it. The app's backend may
+            // not be open yet (it starts out unavailable), so open enigo just for this.
+            let input = INPUT.lock();
+            let (w, h) = if input.error().is_none() {
+                input.main_display_size()
+            } else {
+                drop(input);
+                Input::new(Backend::Enigo)?.main_display_size()
+            };
+            if w <= 0 || h <= 0 {
+                return Err(match listed {
+                    Err(e) => format!("Could not list displays: {}", e),
+                    Ok(_) => "No displays found.".to_string(),
+                });
+            }
+            Ok(vec![Monitor {
+                id: 0,
+                name: "Main display".to_string(),
+                origin_px: (0, 0),
+                size_px: (w, h),
+                scale_factor: 1.0,
+            }])
+        }
+    }
+}
+
+
+fn union_rect(monitors: &[Monitor]) -> (i32, i32, i32, i32) {
+    let mut min_x = i32::MAX;
+    let mut min_y = i32::MAX;
+    let mut max_x = i32::MIN;
+    let mut max_y = i32::MIN;
+    for m in monitors {
+        min_x = min_x.min(m.origin_px.0);
+        min_y = min_y.min(m.origin_px.1);
+        max_x = max_x.max(m.origin_px.0 + m.size_px.0);
+        max_y = max_y.max(m.origin_px.1 + m.size_px.1);
+    }
+    if monitors.is_empty() {
+        (0, 0, 0, 0)
+    } else {
+        (min_x, min_y, max_x, max_y)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DisplayChoice {
+    All,
+    One(usize), // index into monitors
+}
+
+/// Passphrase dialog for encrypted profiles.
+#[derive(Default)]
+struct PassPrompt {
+    /// Profile to unlock; `None` when choosing a passphrase for the current profile.
+    unlock: Option<String>,
+    passphrase: String,
+    repeat: String,
+    error: Option<String>,
+}
+
+/// A profile load held back because it would overwrite unsaved changes; the user sees `diff`
+/// and accepts or rejects it.
+struct PendingLoad {
+    profile: Profile,
+    target: LoadTarget,
+    diff: diff::ProfileDiff,
+}
+
+enum LoadTarget {
+    /// Switch to stored profile `name`.
+    Named { name: String, passphrase: Option<String> },
+    /// Take the current profile's file as edited on disk.
+    Reload,
+}
+
+// -------------- UI State --------------
+struct AppState {
+    // Picker state
+    picking_area: bool,
+    compact: bool, // tiny always-on-top control bar instead of the full window
+    size_before_compact: Option<egui::Vec2>, // full window's size, given back when leaving the bar
+    pick_only: bool, // `pick` subcommand: the picker is the whole app
+    flash: Option<((i32, i32), Instant)>, // overlay marks this click (physical px) until the deadline
+    picking_color: bool, // picker takes one click and samples the color there
+    color_pick: Option<((i32, i32), Instant)>, // sample this pixel once the overlay is gone
+    template_pick_at: Option<Instant>, // save the picked area as a template once the overlay is gone
+    drag_start: Option<Pos2>,
+    drag_end: Option<Pos2>,
+    pick_entry: [i32; 4], // x, y, width, height typed into the picker's panel (physical px)
+    adjusting: Option<(Grip, [i32; 4], (i32, i32))>, // picker: grip dragged, and the area and pointer (px) when it was grabbed
+
+    // Display state
+    monitors: Vec<Monitor>,
+    display_choice: DisplayChoice,
+    corrections: HashMap<u32, Correction>, // picker → injection coordinates, per monitor id
+    calibration: Option<CalibrationRun>,   // picker is showing calibration markers
+    input_backend: Backend,
+    input_error: Option<String>, // why the selected backend can't inject input here
+    hardware_like_input: bool,   // SendInput: scan codes instead of virtual keys
+    target_window: Option<(isize, String)>, // window messages: (HWND, title)
+    window_pick_at: Option<Instant>,        // grab the window under the cursor at this time
+    diagnostics: Option<Diagnostics>,
+    setup: Option<Setup>, // first-run wizard, while it's open
+    test_click: Option<TestClickJob>, // the wizard's, while it runs
+    ydotool_checked: bool, // whether the backend was chosen again once the ydotool probe finished
+    tutorial: Option<Tutorial>, // tutorial overlay, while it's running
+
+    // Config inputs
+    bounds_inputs: [i32; 4], // min_x, max_x, min_y, max_y
+    use_point: bool,
+    point_inputs: [i32; 3],  // x, y, jitter
+    follow_cursor: bool,     // re-center the area on the cursor at every start
+    follow_size: [i32; 2],   // width, height
+    use_fixed_pick_size: bool, // the picker places a box of `fixed_pick_size` instead of dragging one
+    fixed_pick_size: [i32; 2],
+    click_button_left: bool,
+    min_secs: f32,
+    max_secs: f32,
+    use_finite_clicks: bool,
+    num_clicks: u32,
+
+    // Sequence editor
+    mode: JobMode,
+    steps: Vec<SequenceStep>,
+    step_filter: String, // narrows the step list; not saved
+    selected_steps: BTreeSet<usize>, // step indices ticked for bulk edit
+    expanded_steps: BTreeSet<usize>, // step rows opened for editing in place
+    step_cursor: Option<usize>, // keyboard-selected row of the step list
+    confirm_start: bool, // starting by hand shows a summary first
+    confirming_start: bool,
+    raise_for_start: bool, // the summary was asked for; bring the full window to the front
+    scroll_to_cursor: bool,
+    focus_step_editor: bool, // put keyboard focus on the editor's first field next frame
+    areas: BTreeMap<String, Bounds>, // named area library, shared by all profiles
+    forbidden: Vec<ForbiddenZone>, // app-wide, not part of profiles
+    limits: SafetyLimits, // app-wide too
+    feedback: ClickFeedback, // app-wide too
+    triggers: Triggers, // app-wide too
+    control_socket: PathBuf, // where the window serves commands when `triggers.remote` is on
+    remote: Option<daemon::Remote>,
+    midi: Option<MidiInput>,
+    midi_error: Option<(PathBuf, String)>, // device that failed to open, not tried again until changed
+    last_note: Option<Note>, // shown to help with binding notes
+    voice: Option<voice::Listener>,
+    voice_error: Option<String>, // the recognizer isn't started again until asked to
+    last_heard: Option<String>,
+    viewer_settings: ViewerSettings, // app-wide too
+    viewer: Option<viewer::Viewer>,
+    viewer_error: Option<String>, // the viewer isn't started again until its settings change
+    forbidden_name_input: String,
+    click_preview: Option<ClickPreview>,
+    dry_run_report: Option<DryRunReport>,
+    run_stats: Option<RunStats>, // per-step statistics of the job that just ended
+    stats_path: String,          // CSV file the statistics are exported to
+    stats_result: Option<Result<String, String>>,
+    history_open: bool,
+    history_filter: HistoryFilter,
+    history_days: [String; 2], // the filter's first and last day as typed, YYYY-MM-DD
+    history_runs: Option<Result<Vec<RunRecord>, String>>, // the filtered runs; None = query again
+    area_name_input: String,
+    step_templates: BTreeMap<String, StepTemplate>, // step template library, shared by all profiles
+    template_name_input: String,
+    template_linked: bool, // steps inserted from a template follow its later edits
+    step_area: Option<String>, // library area for the step being edited; None = use the selection
+    bulk: BulkEdit,
+    clone_offset: CloneOffset,
+    editing_step_idx: Option<usize>,
+    step_defaults: StepDefaults, // app-wide
+    step_name: String,
+    step_clicks: u32,
+    step_min_secs: f32,
+    step_max_secs: f32,
+    step_button_left: bool,
+    step_zones: Vec<Zone>,
+    step_color: [u8; 3],
+    step_use_offset: bool,
+    step_offset: [i32; 3], // dx, dy, jitter
+    step_kind: StepKind,
+    step_shot_full: bool,
+    step_image: String, // template for wait-for-image steps
+    step_wait: WaitImage,
+    step_use_verify: bool,
+    step_verify: Verify,
+    step_on_error: OnError,
+    step_weight: f32,
+    step_hotkey: Option<HotKey>,
+    step_text: String, // OCR text target; empty = click anywhere in the area
+    step_note: String,
+    sequence_notes: String, // what the sequence is for, saved with the profile
+    use_finite_cycles: bool,
+    num_cycles: u32,
+    step_order: StepOrder,
+    use_start_at: bool, // next start carries on at `start_at` instead of the top
+    start_at: SequencePosition,
+
+    // Grid sweep
+    grid_cols: u32,
+    grid_rows: u32,
+    grid_serpentine: bool,
+
+    // Pacing
+    use_fatigue: bool,
+    fatigue: Fatigue,
+    use_warm_up: bool,
+    warm_up: WarmUp,
+
+    // Quiet hours
+    use_quiet_hours: bool,
+    quiet_hours: Vec<QuietWindow>,
+
+    // Hold-to-run
+    use_hold_to_run: bool,
+    hold_key: HotKey,
+
+    keep_awake: bool, // hold off system sleep while a job runs
+    reclick_misses: bool, // re-click once when a click lands outside its target
+    correct_drift: bool,  // steer mouse paths back when the cursor drifts from where it was sent
+    settle_before_click: bool, // small corrective moves near the target before each click
+    vary_approach: bool, // approach from a varied angle and land slightly short of the aim
+    min_hold_ms: u64, // how long the button stays down per click, picked at random in range
+    max_hold_ms: u64,
+    replay_paths: bool, // move along recorded paths from the path bank
+    path_bank: Arc<PathBank>, // app-wide, recorded in trainer mode
+    path_recorder: Option<Recorder>, // trainer mode is on
+    paths_recorded: usize, // kept since the recorder was started
+
+    // Start condition
+    use_wait_for: bool,
+    wait_for: WaitFor,
+
+    // Refocus the target window
+    use_refocus: bool,
+    refocus: Refocus,
+
+    // Screenshots
+    use_screenshot_every: bool,
+    screenshot_every: u32,
+    screenshot_dir: String, // empty = screenshot::default_dir()
+
+    // Event commands
+    use_events: bool,
+    events: EventCommands,
+
+    // Global hotkeys
+    start_key: HotKey,
+    capture_key: HotKey,
+    skip_key: Option<HotKey>,
+    repeat_key: Option<HotKey>,
+    capture_radius: i32,
+    capture_as_step: bool,
+    // Gamepad
+    pad_start: Option<PadButton>,
+    pad_pause: Option<PadButton>,
+    pad_stop: Option<PadButton>,
+    pad_nudge: bool, // left stick moves the selection
+    nudge_carry: (f32, f32), // fractions of a pixel the stick has moved the selection by
+    nudged_at: Instant,
+    pick_template: bool,
+
+    // Anti-AFK
+    use_anti_afk: bool,
+    afk_key: AfkKey,
+    afk_min_secs: f32,
+    afk_max_secs: f32,
+
+    // Profiles
+    profiles: Option<ProfileStore>, // None when there's no config directory (and in tests)
+    profile_names: Vec<String>,
+    current_profile: Option<String>,
+    profile_file: Option<PathBuf>, // loaded with --profile rather than from the store
+    profile_watch: Option<(PathBuf, Option<FileWatch>)>, // the profile's file; no watch if it couldn't be set up
+    changed_profile: Option<Profile>, // edited on disk, waiting for Reload or Ignore
+    saved_settings: Option<serde_json::Value>, // the settings as last loaded or saved; None = the defaults
+    pending_load: Option<PendingLoad>,
+    auto_reload: bool,
+    profile_name_input: String,
+    bundle_path: String, // settings export/import file
+    bundle_result: Option<Result<String, String>>,
+    profile_passphrase: Option<String>, // the current profile is saved encrypted with this
+    pass_prompt: Option<PassPrompt>,
+    profile_locked: bool, // the profile is read-only
+    unlocked: bool,       // ...but was unlocked for editing this session
+    unlock_prompt: bool,
+
+    languages: Vec<i18n::Language>, // offered in the language picker
+    theme: Theme,
+    applied_theme: Option<(Theme, Option<eframe::Theme>)>, // what the visuals were last built from
+
+    // Status bar
+    show_cursor_readout: bool,
+    cursor_readout: Option<CursorReadout>, // None while the input driver can't read the cursor
+    cursor_read_at: Instant,
+    pixel_preview: Option<((i32, i32), [u8; 3])>, // color now at the pixel check's point
+    pixel_preview_at: Instant,
+
+    // Autosave / crash recovery
+    pending_recovery: Option<Recovery>, // left by a crashed session, waiting for restore/discard
+    pending_checkpoint: Option<Checkpoint>, // a run cut short, waiting for continue/discard
+    continued: Option<Checkpoint>, // counts carried into the next start
+    autosave_at: Instant,
+    autosaved: String, // last JSON written, to skip unchanged saves
+
+    // Engine
+    job: Option<ClickJob>,
+    bursts: Vec<(String, ClickJob)>, // steps started by their hotkeys, by step name
+    autostart_at: Option<Instant>, // pending `--autostart`
+    window_title: String, // last title sent to the viewport
+    log_path: Option<std::path::PathBuf>, // where "Write to file" logs; default location if None
+    log_error: Option<String>,
+    config: Arc<Mutex<ClickConfig>>,
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        let monitors = query_monitors().unwrap_or_else(|e| {
+            log::warn!("{}", e);
+            Vec::new()
+        });
+        Self {
+            picking_area: false,
+            compact: false,
+            size_before_compact: None,
+            pick_only: false,
+            flash: None,
+            picking_color: false,
+            color_pick: None,
+            template_pick_at: None,
+            drag_start: None,
+            drag_end: None,
+            pick_entry: [0; 4],
+            adjusting: None,
+
+            monitors,
+            display_choice: DisplayChoice::All,
+            corrections: HashMap::new(),
+            calibration: None,
+            input_backend: Backend::Enigo,
+            input_error: None,
+            hardware_like_input: false,
+            target_window: None,
+            window_pick_at: None,
+            diagnostics: None,
+            setup: None,
+            test_click: None,
+            ydotool_checked: false,
+            tutorial: None,
+
+            bounds_inputs: [100, 400, 100, 400],
+            use_point: false,
+            point_inputs: [250, 250, 0],
+            follow_cursor: false,
+            follow_size: [100, 100],
+            use_fixed_pick_size: false,
+            fixed_pick_size: [100, 100],
+            click_button_left: true,
+            min_secs: 2.0,
+            max_secs: 4.5,
+            use_finite_clicks: false,
+            num_clicks: 100,
+
+            mode: JobMode::Single,
+            steps: Vec::new(),
+            step_filter: String::new(),
+            selected_steps: BTreeSet::new(),
+            expanded_steps: BTreeSet::new(),
+            step_cursor: None,
+            confirm_start: true,
+            confirming_start: false,
+            raise_for_start: false,
+            scroll_to_cursor: false,
+            focus_step_editor: false,
+            areas: BTreeMap::new(),
+            forbidden: Vec::new(),
+            limits: SafetyLimits::default(),
+            feedback: ClickFeedback::default(),
+            triggers: Triggers::default(),
+            control_socket: daemon::default_socket(),
+            remote: None,
+            midi: None,
+            midi_error: None,
+            last_note: None,
+            voice: None,
+            voice_error: None,
+            last_heard: None,
+            viewer_settings: ViewerSettings::default(),
+            viewer: None,
+            viewer_error: None,
+            forbidden_name_input: String::new(),
+            click_preview: None,
+            dry_run_report: None,
+            run_stats: None,
+            stats_path: dirs::home_dir().unwrap_or_default().join("area_clicker-stats.csv").display().to_string(),
+            stats_result: None,
+            history_open: false,
+            history_filter: HistoryFilter::default(),
+            history_days: Default::default(),
+            history_runs: None,
+            area_name_input: String::new(),
+            step_templates: BTreeMap::new(),
+            template_name_input: String::new(),
+            template_linked: true,
+            step_area: None,
+            bulk: BulkEdit::default(),
+            clone_offset: CloneOffset::default(),
+            editing_step_idx: None,
+            step_defaults: StepDefaults::default(),
+            step_name: String::new(),
+            step_clicks: StepDefaults::default().clicks,
+            step_min_secs: StepDefaults::default().min_secs,
+            step_max_secs: StepDefaults::default().max_secs,
+            step_button_left: true,
+            step_zones: Vec::new(),
+            step_color: STEP_COLORS[0],
+            step_use_offset: false,
+            step_offset: [0, 30, 0],
+            step_kind: StepKind::Click,
+            step_shot_full: false,
+            step_image: String::new(),
+            step_wait: WaitImage::default(),
+            step_use_verify: false,
+            step_verify: Verify::default(),
+            step_on_error: OnError::default(),
+            step_weight: 1.0,
+            step_hotkey: None,
+            step_text: String::new(),
+            step_note: String::new(),
+            sequence_notes: String::new(),
+            use_finite_cycles: true,
+            num_cycles: 1,
+            step_order: StepOrder::InOrder,
+            use_start_at: false,
+            start_at: SequencePosition::default(),
+
+            grid_cols: 4,
+            grid_rows: 4,
+            grid_serpentine: true,
+
+            use_fatigue: false,
+            fatigue: Fatigue::default(),
+            use_warm_up: false,
+            warm_up: WarmUp::default(),
+
+            use_quiet_hours: false,
+            quiet_hours: Vec::new(),
+
+            use_hold_to_run: false,
+            hold_key: HotKey::F9,
+
+            keep_awake: true,
+            reclick_misses: false,
+            correct_drift: false,
+            settle_before_click: false,
+            vary_approach: false,
+            min_hold_ms: 20,
+            max_hold_ms: 120,
+            replay_paths: false,
+            path_bank: Arc::new(PathBank::default()),
+            path_recorder: None,
+            paths_recorded: 0,
+
+            use_wait_for: false,
+            wait_for: WaitFor::default(),
+            use_refocus: false,
+            refocus: Refocus::default(),
+
+            use_screenshot_every: false,
+            screenshot_every: 100,
+            screenshot_dir: String::new(),
+
+            use_events: false,
+            events: EventCommands::default(),
+
+            start_key: HotKey::F6,
+            capture_key: HotKey::F8,
+            skip_key: None,
+            repeat_key: None,
+            capture_radius: 5,
+            pad_start: None,
+            pad_pause: None,
+            pad_stop: None,
+            pad_nudge: false,
+            nudge_carry: (0.0, 0.0),
+            nudged_at: Instant::now(),
+            capture_as_step: false,
+            pick_template: false,
+
+            use_anti_afk: false,
+            afk_key: AfkKey::Shift,
+            afk_min_secs: 30.0,
+            afk_max_secs: 90.0,
+
+            profiles: None,
+            profile_names: Vec::new(),
+            current_profile: None,
+            profile_file: None,
+            profile_watch: None,
+            changed_profile: None,
+            saved_settings: None,
+            pending_load: None,
+            auto_reload: false,
+            profile_name_input: String::new(),
+            bundle_path: dirs::home_dir().unwrap_or_default().join("area_clicker-settings.json").display().to_string(),
+            bundle_result: None,
+            profile_passphrase: None,
+            pass_prompt: None,
+            profile_locked: false,
+            unlocked: false,
+            unlock_prompt: false,
+
+            languages: Vec::new(),
+            theme: Theme::default(),
+            applied_theme: None,
+
+            show_cursor_readout: true,
+            cursor_readout: None,
+            cursor_read_at: Instant::now(),
+            pixel_preview: None,
+            pixel_preview_at: Instant::now(),
+
+            pending_recovery: None,
+            pending_checkpoint: None,
+            continued: None,
+            autosave_at: Instant::now(),
+            autosaved: String::new(),
+
+            job: None,
+            bursts: Vec::new(),
+            autostart_at: None,
+            window_title: String::new(),
+            log_path: None,
+            log_error: None,
+            config: Arc::new(Mutex::new(ClickConfig::default())),
+        }
+    }
+}
+
+impl AppState {
+    /// Default state plus the profile store, with the last used profile loaded.
+    fn new() -> Self {
+        let mut state = Self { profiles: ProfileStore::open_default(), ..Self::default() };
+        state.load_app_settings();
+        state.set_input_backend(Backend::detect());
+        if let Some(name) = state.profiles.as_ref().and_then(|p| p.last_used()) {
+            state.load_profile(&name);
+        }
+        state.pending_recovery = state.profiles.as_ref().and_then(|p| p.load_recovery());
+        state.pending_checkpoint = state.profiles.as_ref().and_then(|p| p.load_checkpoint());
+        if state.profile_names.is_empty() && state.profiles.as_ref().is_some_and(|p| !p.setup_done()) {
+            state.setup = Some(Setup::default());
+        }
+        state
+    }
+
+    /// Read the app-wide settings (everything but the profiles themselves) from the store.
+    fn load_app_settings(&mut self) {
+        self.corrections = self.profiles.as_ref().map(|p| p.load_calibration()).unwrap_or_default();
+        self.areas = self.profiles.as_ref().map(|p| p.load_areas()).unwrap_or_default();
+        self.step_templates = self.profiles.as_ref().map(|p| p.load_step_templates()).unwrap_or_default();
+        self.forbidden = self.profiles.as_ref().map(|p| p.load_forbidden()).unwrap_or_default();
+        self.update_forbidden();
+        self.limits = self.profiles.as_ref().map(|p| p.load_limits()).unwrap_or_default();
+        self.feedback = self.profiles.as_ref().map(|p| p.load_feedback()).unwrap_or_default();
+        self.triggers = self.profiles.as_ref().map(|p| p.load_triggers()).unwrap_or_default();
+        self.viewer_settings = self.profiles.as_ref().map(|p| p.load_viewer()).unwrap_or_default();
+        self.viewer = None;
+        self.step_defaults = self.profiles.as_ref().map(|p| p.load_step_defaults()).unwrap_or_default();
+        self.reset_step_draft();
+        self.path_bank = Arc::new(self.profiles.as_ref().map(|p| p.load_path_bank()).unwrap_or_default());
+        self.auto_reload = self.profiles.as_ref().is_some_and(|p| p.auto_reload());
+        self.confirm_start = self.profiles.as_ref().is_none_or(|p| p.confirm_start());
+        self.config.lock().limits = self.limits;
+        self.config.lock().feedback = self.feedback.clone();
+        self.refresh_profiles();
+        let locales = self.profiles.as_ref().map(|p| p.locales_dir());
+        if let Some(code) = self.profiles.as_ref().and_then(|p| p.language()) {
+            if let Err(e) = i18n::set_language(&code, locales.as_deref()) { log::warn!("{}", e); }
+        }
+        self.languages = i18n::available(locales.as_deref());
+        self.theme = self.profiles.as_ref().map(|p| p.load_theme()).unwrap_or_default();
+    }
+
+    /// Apply command line overrides on top of whatever profile was loaded.
+    fn apply_args(&mut self, args: &Args) {
+        if let Some(path) = &args.profile {
+            match profile::load_file(path) {
+                Ok(mut p) => {
+                    if p.disarm_events() { log::warn!("{} has event commands; they stay off until turned on", path.display()); }
+                    self.apply_profile(p);
+                    self.current_profile = None;
+                    self.profile_file = Some(path.clone());
+                    self.profile_passphrase = None;
+                    self.mark_saved();
+                }
+                Err(e) => log::error!("Could not load profile {}: {}", path.display(), e),
+            }
+        }
+        if let Some(mode) = args.mode { self.mode = mode; }
+        if let Some(b) = args.bounds {
+            self.bounds_inputs = [b.min_x, b.max_x, b.min_y, b.max_y];
+            self.use_point = false;
+            self.follow_cursor = false;
+        }
+        if let Some(n) = args.clicks {
+            self.use_finite_clicks = n > 0;
+            if n > 0 { self.num_clicks = n; }
+        }
+        if let Some(ms) = args.min_delay_ms { self.min_secs = ms as f32 / 1000.0; }
+        if let Some(ms) = args.max_delay_ms { self.max_secs = ms as f32 / 1000.0; }
+        if args.autostart { self.autostart_at = Some(Instant::now() + AUTOSTART_DELAY); }
+        if args.log_file.is_some() { self.log_path = args.log_file.clone(); }
+        if let Some(socket) = &args.socket { self.control_socket = socket.clone(); }
+        let wait = args.wait_window.as_ref().map(|p| (WatchKind::Window, p))
+            .or(args.wait_process.as_ref().map(|p| (WatchKind::Process, p)));
+        if let Some((kind, pattern)) = wait {
+            self.use_wait_for = true;
+            self.wait_for.kind = kind;
+            self.wait_for.pattern = pattern.clone();
+        }
+        if let Some(secs) = args.wait_timeout { self.wait_for.timeout_secs = secs; }
+        if args.stop_when_gone { self.wait_for.stop_when_gone = true; }
+    }
+
+    /// Take the window under the cursor as the window-messages target once the pick delay is up.
+    fn poll_window_pick(&mut self, ctx: &egui::Context) {
+        let Some(at) = self.window_pick_at else { return };
+        if Instant::now() < at {
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+            return;
+        }
+        self.window_pick_at = None;
+        self.target_window = input::window_under_cursor();
+        match &self.target_window {
+            Some((hwnd, title)) => {
+                log::info!("Target window: {:?} ({:#x})", title, hwnd);
+                INPUT.lock().set_target_window(*hwnd);
+            }
+            None => log::warn!("No window under the cursor"),
+        }
+    }
+
+    /// Read the color of the pixel picked with the color picker, now that the overlay is gone,
+    /// into the step's pixel check.
+    fn poll_color_pick(&mut self, ctx: &egui::Context) {
+        let Some((px, at)) = self.color_pick else { return };
+        if Instant::now() < at {
+            ctx.request_repaint_after(at - Instant::now());
+            return;
+        }
+        self.color_pick = None;
+        match screenshot::pixel(px.0, px.1) {
+            Ok(color) => {
+                log::info!("Picked color #{:02X}{:02X}{:02X} at ({}, {})", color[0], color[1], color[2], px.0, px.1);
+                self.step_use_verify = true;
+                self.step_verify.check = Check::Pixel { x: px.0, y: px.1, color };
+            }
+            Err(e) => log::error!("Could not sample the pixel: {}", e),
+        }
+    }
+
+    /// Save the area just picked as the step's reference image, now that the overlay is gone,
+    /// and check for it after each click.
+    fn poll_template_pick(&mut self, ctx: &egui::Context) {
+        let Some(at) = self.template_pick_at else { return };
+        if Instant::now() < at {
+            ctx.request_repaint_after(at - Instant::now());
+            return;
+        }
+        self.template_pick_at = None;
+        let Some(path) = self.capture_template() else { return };
+        self.step_use_verify = true;
+        self.step_verify.check = Check::Image { region: self.current_bounds() };
+        self.step_verify.image = path;
+    }
+
+    /// Start the job once the `--autostart` countdown has run out.
+    fn poll_autostart(&mut self, ctx: &egui::Context) {
+        let Some(at) = self.autostart_at else { return };
+        if Instant::now() >= at {
+            self.autostart_at = None;
+            self.start();
+        } else {
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+        }
+    }
+
+    fn to_recovery(&self) -> Recovery {
+        Recovery {
+            profile: self.current_profile.clone(),
+            settings: self.to_profile(),
+            draft: StepDraft {
+                editing: self.editing_step_idx,
+                name: self.step_name.clone(),
+                clicks: self.step_clicks,
+                min_secs: self.step_min_secs,
+                max_secs: self.step_max_secs,
+                button_left: self.step_button_left,
+                zones: self.step_zones.clone(),
+                use_offset: self.step_use_offset,
+                offset: self.step_offset,
+                kind: self.step_kind,
+                shot_full_screen: self.step_shot_full,
+                image: self.step_image.clone(),
+                wait: self.step_wait,
+                use_verify: self.step_use_verify,
+                verify: self.step_verify.clone(),
+                on_error: self.step_on_error,
+                text: self.step_text.clone(),
+                area: self.step_area.clone(),
+                color: Some(self.step_color),
+                weight: self.step_weight,
+                hotkey: self.step_hotkey,
+                note: self.step_note.clone(),
+            },
+        }
+    }
+
+    fn restore_recovery(&mut self, r: Recovery) {
+        self.apply_profile(r.settings);
+        self.current_profile = r.profile.filter(|n| self.profile_names.contains(n));
+        let d = r.draft;
+        self.editing_step_idx = d.editing.filter(|&i| i < self.steps.len());
+        self.step_name = d.name;
+        self.step_clicks = d.clicks;
+        self.step_min_secs = d.min_secs;
+        self.step_max_secs = d.max_secs;
+        self.step_button_left = d.button_left;
+        self.step_zones = d.zones;
+        self.step_use_offset = d.use_offset;
+        self.step_offset = d.offset;
+        self.step_kind = d.kind;
+        self.step_shot_full = d.shot_full_screen;
+        self.step_image = d.image;
+        self.step_wait = d.wait;
+        self.step_use_verify = d.use_verify;
+        self.step_verify = d.verify;
+        self.step_on_error = d.on_error;
+        self.step_text = d.text;
+        self.step_area = d.area.filter(|n| self.areas.contains_key(n));
+        if let Some(c) = d.color { self.step_color = c; }
+        self.step_weight = d.weight;
+        self.step_hotkey = d.hotkey;
+        self.step_note = d.note;
+    }
+
+    /// Write the working state to the recovery file if it changed. Held off while a
+    /// previous session's recovery is still waiting for an answer.
+    fn autosave(&mut self) {
+        self.autosave_at = Instant::now();
+        let Some(store) = &self.profiles else { return };
+        if self.pending_recovery.is_some() || self.profile_passphrase.is_some() { return; }
+        let text = match serde_json::to_string_pretty(&self.to_recovery()) {
+            Ok(t) => t,
+            Err(e) => { log::warn!("Autosave failed: {}", e); return; }
+        };
+        if text == self.autosaved { return; }
+        match store.save_recovery(&text) {
+            Ok(()) => self.autosaved = text,
+            Err(e) => log::warn!("Autosave failed: {}", e),
+        }
+    }
+
+    fn recovery_ui(&mut self, ui: &mut egui::Ui) {
+        if self.pending_recovery.is_none() { return; }
+        ui.horizontal(|ui| {
+            ui.colored_label(Color32::YELLOW, tr!("The last session didn't exit cleanly. Restore its unsaved work?"));
+            if ui.button(tr!("Restore")).clicked() {
+                if let Some(r) = self.pending_recovery.take() { self.restore_recovery(r); }
+            }
+            if ui.button(tr!("Discard")).clicked() { self.pending_recovery = None; }
+        });
+    }
+
+    /// Continue the run a checkpoint was saved for: its settings, from the cycle after the last
+    /// one it finished, with its click counts carried over.
+    fn continue_checkpoint(&mut self, c: Checkpoint) {
+        self.apply_profile(c.settings.clone());
+        self.current_profile = c.profile.clone().filter(|n| self.profile_names.contains(n));
+        self.use_start_at = true;
+        self.start_at = SequencePosition { step: 1, cycle: c.cycle + 1 };
+        log::info!("Continuing the interrupted run from cycle {}", c.cycle + 1);
+        self.continued = Some(c);
+        self.start();
+    }
+
+    fn checkpoint_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(c) = &self.pending_checkpoint else { return };
+        let text = tr!("A sequence run was cut short after {} cycles ({} clicks). Continue it?", c.cycle, c.clicks);
+        ui.horizontal(|ui| {
+            ui.colored_label(Color32::YELLOW, text);
+            if ui.button(tr!("Continue")).clicked() {
+                if let Some(c) = self.pending_checkpoint.take() { self.continue_checkpoint(c); }
+            }
+            if ui.button(tr!("Discard")).clicked() {
+                self.pending_checkpoint = None;
+                if let Some(Err(e)) = self.profiles.as_ref().map(|p| p.clear_checkpoint()) {
+                    log::warn!("Could not remove the checkpoint: {}", e);
+                }
+            }
+        });
+    }
+
+    fn to_profile(&self) -> Profile {
+        Profile {
+            bounds: self.bounds_inputs,
+            use_point: self.use_point,
+            point: self.point_inputs,
+            follow_cursor: self.follow_cursor,
+            follow_size: self.follow_size,
+            use_fixed_pick_size: self.use_fixed_pick_size,
+            fixed_pick_size: self.fixed_pick_size,
+            click_button_left: self.click_button_left,
+            min_secs: self.min_secs,
+            max_secs: self.max_secs,
+            use_finite_clicks: self.use_finite_clicks,
+            num_clicks: self.num_clicks,
+            mode: self.mode,
+            steps: self.steps.clone(),
+            use_finite_cycles: self.use_finite_cycles,
+            num_cycles: self.num_cycles,
+            step_order: self.step_order,
+            notes: self.sequence_notes.clone(),
+            grid_cols: self.grid_cols,
+            grid_rows: self.grid_rows,
+            grid_serpentine: self.grid_serpentine,
+            use_fatigue: self.use_fatigue,
+            fatigue: self.fatigue,
+            use_warm_up: self.use_warm_up,
+            warm_up: self.warm_up,
+            use_quiet_hours: self.use_quiet_hours,
+            quiet_hours: self.quiet_hours.clone(),
+            use_hold_to_run: self.use_hold_to_run,
+            hold_key: self.hold_key,
+            keep_awake: self.keep_awake,
+            reclick_misses: self.reclick_misses,
+            correct_drift: self.correct_drift,
+            settle_before_click: self.settle_before_click,
+            vary_approach: self.vary_approach,
+            min_hold_ms: self.min_hold_ms,
+            max_hold_ms: self.max_hold_ms,
+            replay_paths: self.replay_paths,
+            use_wait_for: self.use_wait_for,
+            wait_for: self.wait_for.clone(),
+            use_refocus: self.use_refocus,
+            refocus: self.refocus.clone(),
+            use_screenshot_every: self.use_screenshot_every,
+            screenshot_every: self.screenshot_every,
+            screenshot_dir: self.screenshot_dir.clone(),
+            use_events: self.use_events,
+            events: self.events.clone(),
+            start_key: self.start_key,
+            capture_key: self.capture_key,
+            skip_key: self.skip_key,
+            repeat_key: self.repeat_key,
+            capture_radius: self.capture_radius,
+            pad_start: self.pad_start,
+            pad_pause: self.pad_pause,
+            pad_stop: self.pad_stop,
+            pad_nudge: self.pad_nudge,
+            capture_as_step: self.capture_as_step,
+            pick_template: self.pick_template,
+            use_anti_afk: self.use_anti_afk,
+            afk_key: self.afk_key,
+            afk_min_secs: self.afk_min_secs,
+            afk_max_secs: self.afk_max_secs,
+            locked: self.profile_locked,
+        }
+    }
+
+    fn apply_profile(&mut self, p: Profile) {
+        self.bounds_inputs = p.bounds;
+        self.use_point = p.use_point;
+        self.point_inputs = p.point;
+        self.follow_cursor = p.follow_cursor;
+        self.follow_size = p.follow_size;
+        self.use_fixed_pick_size = p.use_fixed_pick_size;
+        self.fixed_pick_size = p.fixed_pick_size;
+        self.click_button_left = p.click_button_left;
+        self.min_secs = p.min_secs;
+        self.max_secs = p.max_secs;
+        self.use_finite_clicks = p.use_finite_clicks;
+        self.num_clicks = p.num_clicks;
+        self.mode = p.mode;
+        self.steps = p.steps;
+        self.editing_step_idx = None;
+        self.selected_steps.clear();
+        self.expanded_steps.clear();
+        self.step_cursor = None;
+        self.sync_areas();
+        self.sync_templates();
+        self.step_color = STEP_COLORS[self.steps.len() % STEP_COLORS.len()];
+        self.use_finite_cycles = p.use_finite_cycles;
+        self.num_cycles = p.num_cycles;
+        self.step_order = p.step_order;
+        self.sequence_notes = p.notes;
+        self.grid_cols = p.grid_cols;
+        self.grid_rows = p.grid_rows;
+        self.grid_serpentine = p.grid_serpentine;
+        self.use_fatigue = p.use_fatigue;
+        self.fatigue = p.fatigue;
+        self.use_warm_up = p.use_warm_up;
+        self.warm_up = p.warm_up;
+        self.use_quiet_hours = p.use_quiet_hours;
+        self.quiet_hours = p.quiet_hours;
+        self.use_hold_to_run = p.use_hold_to_run;
+        self.hold_key = p.hold_key;
+        self.keep_awake = p.keep_awake;
+        self.reclick_misses = p.reclick_misses;
+        self.correct_drift = p.correct_drift;
+        self.settle_before_click = p.settle_before_click;
+        self.vary_approach = p.vary_approach;
+        (self.min_hold_ms, self.max_hold_ms) = (p.min_hold_ms, p.max_hold_ms);
+        self.replay_paths = p.replay_paths;
+        self.use_wait_for = p.use_wait_for;
+        self.wait_for = p.wait_for;
+        self.use_refocus = p.use_refocus;
+        self.refocus = p.refocus;
+        self.use_screenshot_every = p.use_screenshot_every;
+        self.screenshot_every = p.screenshot_every;
+        self.screenshot_dir = p.screenshot_dir;
+        self.use_events = p.use_events;
+        self.events = p.events;
+        self.start_key = p.start_key;
+        self.capture_key = p.capture_key;
+        self.skip_key = p.skip_key;
+        self.repeat_key = p.repeat_key;
+        self.capture_radius = p.capture_radius;
+        self.pad_start = p.pad_start;
+        self.pad_pause = p.pad_pause;
+        self.pad_stop = p.pad_stop;
+        self.pad_nudge = p.pad_nudge;
+        self.capture_as_step = p.capture_as_step;
+        self.pick_template = p.pick_template;
+        self.use_anti_afk = p.use_anti_afk;
+        self.afk_key = p.afk_key;
+        self.afk_min_secs = p.afk_min_secs;
+        self.afk_max_secs = p.afk_max_secs;
+        self.profile_locked = p.locked;
+        self.unlocked = false;
+    }
+
+    fn refresh_profiles(&mut self) {
+        self.profile_names = self.profiles.as_ref().map(|p| p.list()).unwrap_or_default();
+    }
+
+    /// Load a stored profile, or ask for its passphrase first if it's encrypted.
+    fn load_profile(&mut self, name: &str) {
+        let Some(store) = &self.profiles else { return };
+        if store.is_encrypted(name) {
+            self.pass_prompt = Some(PassPrompt { unlock: Some(name.to_string()), ..PassPrompt::default() });
+            return;
+        }
+        match store.load(name) {
+            Ok(p) => self.use_profile(name, p, None),
+            Err(e) => log::error!("Could not load profile '{}': {}", name, e),
+        }
+    }
+
+    fn unlock_profile(&mut self, name: &str, passphrase: String) -> Result<(), String> {
+        let Some(store) = &self.profiles else { return Ok(()) };
+        let p = store.load_with(name, Some(&passphrase)).map_err(|e| tr!("Could not unlock: {}", e))?;
+        self.use_profile(name, p, Some(passphrase));
+        Ok(())
+    }
+
+    /// Switch to profile `name`, unless that would overwrite unsaved changes: then its diff is
+    /// shown first.
+    fn use_profile(&mut self, name: &str, p: Profile, passphrase: Option<String>) {
+        if self.has_unsaved_changes() {
+            let diff = diff::profiles(&self.to_profile(), &p);
+            if !diff.is_empty() {
+                self.pending_load = Some(PendingLoad { profile: p, target: LoadTarget::Named { name: name.to_string(), passphrase }, diff });
+                return;
+            }
+        }
+        self.switch_profile(name, p, passphrase);
+    }
+
+    fn switch_profile(&mut self, name: &str, p: Profile, passphrase: Option<String>) {
+        self.apply_profile(p);
+        self.current_profile = Some(name.to_string());
+        self.profile_file = None;
+        self.profile_passphrase = passphrase;
+        self.restore_position();
+        self.mark_saved();
+        if let Some(Err(e)) = self.profiles.as_ref().map(|s| s.set_last_used(name)) {
+            log::warn!("Could not remember last profile: {}", e);
+        }
+    }
+
+    /// Remember the current settings as the saved ones.
+    fn mark_saved(&mut self) { self.saved_settings = serde_json::to_value(self.to_profile()).ok(); }
+
+    /// True if the settings were edited since they were last loaded or saved.
+    fn has_unsaved_changes(&self) -> bool {
+        let saved = match &self.saved_settings {
+            Some(v) => Some(v.clone()),
+            None => serde_json::to_value(AppState::default().to_profile()).ok(),
+        };
+        serde_json::to_value(self.to_profile()).ok() != saved
+    }
+
+    /// The "unsaved changes" dialog of a held-back load: what it would change, load or keep.
+    fn pending_load_ui(&mut self, ctx: &egui::Context) {
+        let Some(pending) = &self.pending_load else { return };
+        let (mut accept, mut reject) = (false, false);
+        let title = match &pending.target {
+            LoadTarget::Named { name, .. } => tr!("Load '{}' over unsaved changes?", name),
+            LoadTarget::Reload => tr!("Reload over unsaved changes?"),
+        };
+        egui::Window::new(title)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(tr!("Loading replaces your current settings. It changes:"));
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| diff_ui(ui, &pending.diff));
+                ui.separator();
+                ui.horizontal(|ui| {
+                    accept = ui.button(tr!("Load")).clicked();
+                    reject = ui.button(tr!("Keep my changes")).clicked();
+                });
+            });
+        if reject {
+            if let Some(PendingLoad { target: LoadTarget::Reload, .. }) = self.pending_load.take() { self.changed_profile = None; }
+        }
+        if !accept { return; }
+        let Some(PendingLoad { profile, target, .. }) = self.pending_load.take() else { return };
+        match target {
+            LoadTarget::Named { name, passphrase } => self.switch_profile(&name, profile, passphrase),
+            LoadTarget::Reload => self.reload_profile(profile),
+        }
+    }
+
+    /// False while a locked profile hasn't been unlocked: its settings can't be edited.
+    fn editable(&self) -> bool { !self.profile_locked || self.unlocked }
+
+    /// Make the current profile read-only and save it that way.
+    fn lock_profile(&mut self) {
+        let Some(name) = self.current_profile.clone() else { return };
+        self.profile_locked = true;
+        self.unlocked = false;
+        self.editing_step_idx = None;
+        self.save_profile_as(&name);
+    }
+
+    /// Lock controls next to the profile picker.
+    fn lock_ui(&mut self, ui: &mut egui::Ui) {
+        if !self.profile_locked {
+            if self.current_profile.is_some()
+                && ui.button(tr!("Lock")).on_hover_text(tr!("Save this profile read-only; editing it needs an explicit unlock")).clicked()
+            {
+                self.lock_profile();
+            }
+        } else if !self.unlocked {
+            ui.colored_label(Color32::YELLOW, tr!("Read-only"));
+            if ui.button(tr!("Unlock…")).clicked() { self.unlock_prompt = true; }
+        } else {
+            if ui.button(tr!("Lock again")).on_hover_text(tr!("Back to read-only; edits so far are kept")).clicked() {
+                self.unlocked = false;
+                self.editing_step_idx = None;
+            }
+            if ui.button(tr!("Remove lock")).on_hover_text(tr!("Save afterwards to keep the profile unlocked for good")).clicked() {
+                self.profile_locked = false;
+                self.unlocked = false;
+            }
+        }
+    }
+
+    /// Ask before a locked profile is opened up for editing.
+    fn unlock_prompt_ui(&mut self, ctx: &egui::Context) {
+        if !self.unlock_prompt { return; }
+        let (mut unlock, mut cancel) = (false, ctx.input(|i| i.key_pressed(egui::Key::Escape)));
+        let name = self.current_profile.clone().unwrap_or_else(|| tr!("(unsaved)"));
+        egui::Window::new(tr!("Unlock '{}'?", name))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(tr!("This profile is locked so it isn't changed by accident. Whatever you save affects everyone who runs it."));
+                ui.horizontal(|ui| {
+                    unlock = ui.button(tr!("Unlock for editing")).clicked();
+                    cancel |= ui.button(tr!("Cancel")).clicked();
+                });
+            });
+        if unlock { self.unlocked = true; }
+        if unlock || cancel { self.unlock_prompt = false; }
+    }
+
+    /// Re-save the current profile encrypted with `passphrase`, or in plain text with `None`.
+    fn set_profile_passphrase(&mut self, passphrase: Option<String>) {
+        let (Some(store), Some(name)) = (&self.profiles, &self.current_profile) else { return };
+        if let Err(e) = store.save_with(name, &self.to_profile(), passphrase.as_deref()) {
+            log::error!("Could not save profile '{}': {}", name, e);
+            return;
+        }
+        // the recovery file would hold the same settings in plain text
+        if passphrase.is_some() {
+            if let Err(e) = store.clear_recovery() { log::warn!("Could not remove recovery file: {}", e); }
+            self.autosaved.clear();
+        }
+        self.profile_passphrase = passphrase;
+        self.mark_saved();
+    }
+
+    /// Ask for the passphrase of an encrypted profile, or for a new one to encrypt with.
+    fn pass_prompt_ui(&mut self, ctx: &egui::Context) {
+        let Some(prompt) = &mut self.pass_prompt else { return };
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) { self.pass_prompt = None; return; }
+        let (mut ok, mut cancel) = (false, false);
+        let title = if prompt.unlock.is_some() { tr!("Encrypted profile") } else { tr!("Encrypt profile") };
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                match &prompt.unlock {
+                    Some(name) => { ui.label(tr!("Profile '{}' is encrypted. Enter its passphrase to load it.", name)); }
+                    None => { ui.label(tr!("The profile is saved encrypted and asks for this passphrase whenever it's loaded. A forgotten passphrase can't be recovered.")); }
+                }
+                let field = ui.add(egui::TextEdit::singleline(&mut prompt.passphrase).password(true).hint_text(tr!("passphrase")));
+                let mut entered = field.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                let ready = match prompt.unlock {
+                    Some(_) => !prompt.passphrase.is_empty(),
+                    None => {
+                        let repeat = ui.add(egui::TextEdit::singleline(&mut prompt.repeat).password(true).hint_text(tr!("repeat passphrase")));
+                        entered = repeat.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        if !prompt.repeat.is_empty() && prompt.repeat != prompt.passphrase {
+                            ui.colored_label(Color32::YELLOW, tr!("The passphrases don't match."));
+                        }
+                        !prompt.passphrase.is_empty() && prompt.repeat == prompt.passphrase
+                    }
+                };
+                if let Some(e) = &prompt.error { ui.colored_label(Color32::LIGHT_RED, e); }
+                ui.horizontal(|ui| {
+                    let label = if prompt.unlock.is_some() { tr!("Unlock") } else { tr!("Encrypt") };
+                    ok = ui.add_enabled(ready, egui::Button::new(label)).clicked() || (ready && entered);
+                    cancel = ui.button(tr!("Cancel")).clicked();
+                });
+            });
+        if cancel { self.pass_prompt = None; }
+        if !ok { return; }
+        let Some(PassPrompt { unlock, passphrase, .. }) = self.pass_prompt.take() else { return };
+        match unlock {
+            Some(name) => {
+                if let Err(e) = self.unlock_profile(&name, passphrase) {
+                    self.pass_prompt = Some(PassPrompt { unlock: Some(name), error: Some(e), ..PassPrompt::default() });
+                }
+            }
+            None => self.set_profile_passphrase(Some(passphrase)),
+        }
+    }
+
+    /// File the current settings were loaded from, if any.
+    fn profile_path(&self) -> Option<PathBuf> {
+        match &self.current_profile {
+            Some(name) => self.profiles.as_ref().and_then(|s| s.path(name).ok()),
+            None => self.profile_file.clone(),
+        }
+    }
+
+    /// Keep watching the current profile's file across loads, renames and "save as".
+    fn sync_profile_watch(&mut self, ctx: &egui::Context) {
+        let path = self.profile_path();
+        if self.profile_watch.as_ref().map(|(p, _)| p) == path.as_ref() { return; }
+        self.changed_profile = None;
+        self.profile_watch = path.map(|p| {
+            let ctx = ctx.clone();
+            let watch = FileWatch::new(&p, move || ctx.request_repaint()).map_err(|e| log::warn!("Not watching {} for changes: {}", p.display(), e)).ok();
+            (p, watch)
+        });
+    }
+
+    /// Pick up edits made to the profile's file outside the app.
+    fn poll_profile_file(&mut self, ctx: &egui::Context) {
+        self.sync_profile_watch(ctx);
+        let Some((path, Some(watch))) = &self.profile_watch else { return };
+        if !watch.pending() { return; }
+        if !watch.take_change() {
+            ctx.request_repaint_after(file_watch::SETTLE);
+            return;
+        }
+        let path = path.clone();
+        let mut p = match profile::load_file_with(&path, self.profile_passphrase.as_deref()) {
+            Ok(p) => p,
+            Err(e) => {
+                log::warn!("Not reloading {}: {}", path.display(), e);
+                return;
+            }
+        };
+        // commands that were already on stay on; new ones wait for the user
+        if !(self.use_events && p.events == self.events) && p.disarm_events() {
+            log::warn!("{} has new event commands; they stay off until turned on", path.display());
+        }
+        // our own saves end up here too
+        if serde_json::to_value(&p).ok() == serde_json::to_value(self.to_profile()).ok() { return; }
+        log::info!("{} changed on disk", path.display());
+        // unsaved edits are never reloaded over without asking
+        if self.auto_reload && !self.has_unsaved_changes() { self.reload_profile(p); } else { self.changed_profile = Some(p); }
+    }
+
+    /// Apply a profile re-read from disk. A running job takes the new settings right away;
+    /// a sequence switches to the new steps after the current cycle.
+    fn reload_profile(&mut self, p: Profile) {
+        self.changed_profile = None;
+        self.apply_profile(p);
+        self.mark_saved();
+        if self.is_running() {
+            let mut cfg = self.config.lock();
+            self.write_config(&mut cfg);
+            cfg.reload = true;
+        }
+        log::info!("Profile reloaded");
+    }
+
+    /// Reload the file changed on disk, after showing the diff if there are unsaved changes.
+    fn review_reload(&mut self) {
+        let Some(p) = self.changed_profile.take() else { return };
+        if !self.has_unsaved_changes() {
+            self.reload_profile(p);
+            return;
+        }
+        let diff = diff::profiles(&self.to_profile(), &p);
+        // kept until the dialog is answered, so "Keep my changes" can drop it
+        self.changed_profile = Some(p.clone());
+        self.pending_load = Some(PendingLoad { profile: p, target: LoadTarget::Reload, diff });
+    }
+
+    fn reload_ui(&mut self, ui: &mut egui::Ui) {
+        if self.changed_profile.is_none() { return; }
+        ui.horizontal(|ui| {
+            ui.colored_label(Color32::YELLOW, tr!("The profile's file was changed outside the app."));
+            if ui.button(tr!("Reload")).clicked() { self.review_reload(); }
+            if ui.button(tr!("Ignore")).clicked() { self.changed_profile = None; }
+            self.auto_reload_ui(ui);
+        });
+    }
+
+    fn auto_reload_ui(&mut self, ui: &mut egui::Ui) {
+        let toggled = ui.checkbox(&mut self.auto_reload, tr!("Reload automatically"))
+            .on_hover_text(tr!("Reload the profile whenever its file is edited outside the app. A running sequence switches over between cycles."))
+            .changed();
+        if !toggled { return; }
+        if let Some(Err(e)) = self.profiles.as_ref().map(|p| p.set_auto_reload(self.auto_reload)) {
+            log::warn!("Could not remember the auto-reload setting: {}", e);
+        }
+        if self.auto_reload { self.review_reload(); }
+    }
+
+    /// Switch the UI language and remember it for next time.
+    fn set_language(&mut self, code: &str) {
+        let locales = self.profiles.as_ref().map(|p| p.locales_dir());
+        if let Err(e) = i18n::set_language(code, locales.as_deref()) {
+            log::error!("{}", e);
+            return;
+        }
+        if let Some(Err(e)) = self.profiles.as_ref().map(|p| p.set_language(code)) {
+            log::warn!("Could not remember the language: {}", e);
+        }
+    }
+
+    fn language_ui(&mut self, ui: &mut egui::Ui) {
+        if self.languages.len() < 2 { return; }
+        let current = i18n::current();
+        let name = self.languages.iter().find(|l| l.code == current).map_or(current.as_str(), |l| l.name.as_str());
+        let mut picked = None;
+        egui::ComboBox::from_id_source("language")
+            .selected_text(format!("🌐 {}", name))
+            .show_ui(ui, |ui| {
+                for l in &self.languages {
+                    if ui.selectable_label(l.code == current, &l.name).clicked() { picked = Some(l.code.clone()); }
+                }
+            });
+        if let Some(code) = picked { self.set_language(&code); }
+    }
+
+    /// Rebuild the visuals when the theme or the desktop's light/dark setting changed.
+    fn apply_theme(&mut self, ctx: &egui::Context, system: Option<eframe::Theme>) {
+        if self.applied_theme == Some((self.theme, system)) { return; }
+        self.theme.apply(ctx, system);
+        self.applied_theme = Some((self.theme, system));
+    }
+
+    fn theme_ui(&mut self, ui: &mut egui::Ui) {
+        let before = self.theme;
+        egui::ComboBox::from_id_source("theme")
+            .selected_text(format!("🎨 {}", i18n::translate(self.theme.mode.label())))
+            .show_ui(ui, |ui| {
+                for mode in ThemeMode::ALL {
+                    ui.selectable_value(&mut self.theme.mode, mode, i18n::translate(mode.label()));
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let mut custom = self.theme.accent.is_some();
+                    if ui.checkbox(&mut custom, tr!("Accent")).changed() {
+                        self.theme.accent = custom.then_some([0x90, 0xd1, 0xff]);
+                    }
+                    if let Some(accent) = &mut self.theme.accent { ui.color_edit_button_srgb(accent); }
+                });
+            });
+        if self.theme == before { return; }
+        if let Some(Err(e)) = self.profiles.as_ref().map(|p| p.save_theme(&self.theme)) {
+            log::warn!("Could not save the theme: {}", e);
+        }
+    }
+
+    /// Save the current settings under `name` and switch to it.
+    fn save_profile_as(&mut self, name: &str) {
+        let Some(store) = &self.profiles else { return };
+        match store.save_with(name, &self.to_profile(), self.profile_passphrase.as_deref()) {
+            Ok(()) => {
+                if let Err(e) = store.set_last_used(name) {
+                    log::warn!("Could not remember last profile: {}", e);
+                }
+                self.current_profile = Some(name.to_string());
+                self.mark_saved();
+            }
+            Err(e) => log::error!("Could not save profile '{}': {}", name, e),
+        }
+        self.refresh_profiles();
+    }
+
+    fn rename_profile(&mut self, from: &str, to: &str) {
+        let Some(store) = &self.profiles else { return };
+        match store.rename(from, to) {
+            Ok(()) => self.current_profile = Some(to.to_string()),
+            Err(e) => log::error!("Could not rename profile '{}': {}", from, e),
+        }
+        self.refresh_profiles();
+    }
+
+    fn duplicate_profile(&mut self, from: &str, to: &str) {
+        let Some(store) = &self.profiles else { return };
+        if let Err(e) = store.duplicate(from, to) {
+            log::error!("Could not duplicate profile '{}': {}", from, e);
+        }
+        self.refresh_profiles();
+    }
+
+    fn delete_profile(&mut self, name: &str) {
+        let Some(store) = &self.profiles else { return };
+        match store.delete(name) {
+            Ok(()) => self.current_profile = None,
+            Err(e) => log::error!("Could not delete profile '{}': {}", name, e),
+        }
+        self.refresh_profiles();
+    }
+
+    fn start(&mut self) {
+        if self.is_running() { return; }
+        // the job's own moves must not end up in the bank
+        self.stop_path_recording();
+        if self.input_backend == Backend::WindowMessages && self.target_window.is_none() {
+            self.input_error = Some("Pick a target window before starting.".to_string());
+            return;
+        }
+        if self.follow_cursor && !self.use_point && matches!(self.mode, JobMode::Single | JobMode::GridSweep) {
+            let pos = INPUT.lock().mouse_location();
+            let b = Bounds::centered(pos, self.follow_size[0], self.follow_size[1]);
+            self.bounds_inputs = [b.min_x, b.max_x, b.min_y, b.max_y];
+        }
+        let checkpoint = self.checkpoint_base();
+        let mut cfg = self.config.lock();
+        self.write_config(&mut cfg);
+        cfg.reload = false;
+        cfg.step_command = None;
+        cfg.checkpoint = checkpoint;
+        drop(cfg);
+        self.job = Some(ClickJob::spawn(Arc::clone(&self.config)));
+        if self.mode == JobMode::Sequence {
+            // the start position is used up; stopping again records a new one
+            self.use_start_at = false;
+            self.store_position(None);
+        }
+    }
+
+    /// What a starting sequence checkpoints on top of: the counts of a continued run, or none.
+    /// Runs of encrypted profiles aren't checkpointed, as that would put their settings on
+    /// disk in plain text.
+    fn checkpoint_base(&mut self) -> Option<(ProfileStore, Checkpoint)> {
+        let carried = self.continued.take().unwrap_or_default();
+        if self.mode != JobMode::Sequence || self.profile_passphrase.is_some() { return None; }
+        let store = self.profiles.clone()?;
+        Some((store, Checkpoint { profile: self.current_profile.clone(), settings: self.to_profile(), ..carried }))
+    }
+
+    /// Before a running sequence is stopped, remember where it was so the next start can
+    /// carry on from there.
+    fn remember_position(&mut self) {
+        let Some(job) = self.job.as_ref().filter(|_| self.is_running()) else { return };
+        if job.config.lock().mode != JobMode::Sequence { return; }
+        let at = {
+            let p = job.progress.lock();
+            (p.step_count > 0).then_some(SequencePosition { step: p.step + 1, cycle: p.cycle })
+        };
+        let Some(at) = at else { return };
+        log::info!("Sequence stopped at step {}, cycle {}", at.step, at.cycle);
+        self.start_at = at;
+        self.use_start_at = true;
+        self.store_position(Some(at));
+    }
+
+    /// Save the current profile's stopped position, or forget it with `None`.
+    fn store_position(&self, at: Option<SequencePosition>) {
+        let (Some(store), Some(name)) = (&self.profiles, &self.current_profile) else { return };
+        let mut positions = store.load_positions();
+        let changed = match at {
+            Some(at) => positions.insert(name.clone(), at) != Some(at),
+            None => positions.remove(name).is_some(),
+        };
+        if !changed { return; }
+        if let Err(e) = store.save_positions(&positions) { log::warn!("Could not save the sequence position: {}", e); }
+    }
+
+    /// Offer the position the current profile's sequence was last stopped at, if any.
+    fn restore_position(&mut self) {
+        let at = self.profiles.as_ref().zip(self.current_profile.as_ref()).and_then(|(s, n)| s.load_positions().get(n).copied());
+        self.use_start_at = at.is_some();
+        if let Some(at) = at { self.start_at = at; }
+    }
+
+    /// Copy the job settings into the engine's config.
+    fn write_config(&self, cfg: &mut ClickConfig) {
+        cfg.mode = self.mode;
+        cfg.steps = self.steps.clone();
+        cfg.cycles = if self.use_finite_cycles { Some(self.num_cycles) } else { None };
+        cfg.step_order = if self.mode == JobMode::Sequence { self.step_order } else { StepOrder::InOrder };
+        cfg.start_at = match self.mode {
+            JobMode::Sequence if self.use_start_at => (self.start_at.step.saturating_sub(1), self.start_at.cycle.saturating_sub(1)),
+            _ => (0, 0),
+        };
+        cfg.grid = GridSweep { cols: self.grid_cols, rows: self.grid_rows, serpentine: self.grid_serpentine };
+        cfg.fatigue = self.use_fatigue.then_some(self.fatigue);
+        cfg.warm_up = self.use_warm_up.then_some(self.warm_up);
+        cfg.quiet_hours = self.active_quiet_hours();
+        cfg.keep_awake = self.keep_awake;
+        cfg.reclick_misses = self.reclick_misses;
+        cfg.correct_drift = self.correct_drift;
+        cfg.settle_before_click = self.settle_before_click;
+        cfg.vary_approach = self.vary_approach;
+        (cfg.min_hold_ms, cfg.max_hold_ms) = (self.min_hold_ms.min(self.max_hold_ms), self.min_hold_ms.max(self.max_hold_ms));
+        cfg.path_bank = (self.replay_paths && !self.path_bank.is_empty()).then(|| Arc::clone(&self.path_bank));
+        cfg.hold_to_run = (self.use_hold_to_run && hotkeys::available()).then_some(self.hold_key);
+        cfg.wait_for = self.use_wait_for.then(|| self.wait_for.clone());
+        cfg.refocus = self.use_refocus.then(|| self.refocus.clone());
+        cfg.screenshot_every = self.use_screenshot_every.then_some(self.screenshot_every.max(1));
+        cfg.screenshot_dir = self.screenshot_dir();
+        cfg.events = (self.use_events && !self.events.is_empty()).then(|| self.events.clone());
+        cfg.anti_afk = (self.use_anti_afk || self.mode == JobMode::AntiAfk).then_some(AntiAfk {
+            key: self.afk_key,
+            min_secs: self.afk_min_secs,
+            max_secs: self.afk_max_secs,
+        });
+        cfg.button = if self.click_button_left { ClickButton::Left } else { ClickButton::Right };
+        cfg.min_secs = self.min_secs;
+        cfg.max_secs = self.max_secs;
+        cfg.finite_clicks = if self.use_finite_clicks { Some(self.num_clicks) } else { None };
+        cfg.bounds = Some(self.current_bounds());
+        cfg.point = self.current_point();
+    }
+
+    /// What Start is about to do, as (label, value) rows for the confirmation dialog.
+    fn start_summary(&self) -> Vec<(&'static str, String)> {
+        let display_of = |(x, y): (i32, i32)| {
+            self.monitors.iter().find(|m| m.contains((x, y))).map_or(tr!("off-screen"), |m| m.name.clone())
+        };
+        let area = |b: Bounds| format!("[{}..{}]x[{}..{}]", b.min_x, b.max_x, b.min_y, b.max_y);
+        let interval = |min: f32, max: f32, clicks: u32| {
+            format!("{} – {}", format_duration(min * clicks as f32), format_duration(max * clicks as f32))
+        };
+        let button = if self.click_button_left { tr!("Left") } else { tr!("Right") };
+        let target = match self.current_point() {
+            Some(p) => tr!("point ({}, {}) ±{}", p.x, p.y, p.jitter),
+            None if self.follow_cursor => tr!("{}×{} box around the cursor", self.follow_size[0], self.follow_size[1]),
+            None => area(self.current_bounds()),
+        };
+        let where_ = self.current_point().map_or_else(|| self.current_bounds().center(), |p| (p.x, p.y));
+
+        let mut rows = vec![
+            ("Profile", self.current_profile.clone().unwrap_or_else(|| tr!("(unsaved settings)"))),
+            ("Mode", format!("{:?}", self.mode)),
+        ];
+        match self.mode {
+            JobMode::Single => {
+                rows.push(("Display", display_of(where_)));
+                rows.push(("Target", target));
+                rows.push(("Button", button));
+                if self.use_finite_clicks {
+                    rows.push(("Clicks", self.num_clicks.to_string()));
+                    rows.push(("Duration", interval(self.min_secs, self.max_secs, self.num_clicks)));
+                } else {
+                    rows.push(("Clicks", tr!("until stopped")));
+                }
+            }
+            JobMode::Sequence => {
+                let mut displays: Vec<String> = self.steps.iter()
+                    .filter_map(|s| match s.target {
+                        StepTarget::Area | StepTarget::Screenshot { full_screen: false } | StepTarget::WaitImage(_) => Some(s.bounds.center()),
+                        StepTarget::Point(p) => Some((p.x, p.y)),
+                        StepTarget::Offset { .. } | StepTarget::Screenshot { full_screen: true } => None,
+                    })
+                    .map(display_of)
+                    .collect();
+                displays.sort();
+                displays.dedup();
+                rows.push(("Display", displays.join(", ")));
+                let cycles = if self.use_finite_cycles { self.num_cycles.to_string() } else { tr!("until stopped") };
+                rows.push(("Steps", tr!("{} steps, {} cycles", self.steps.len(), cycles)));
+                match self.step_order {
+                    StepOrder::InOrder => {}
+                    StepOrder::Interleave => rows.push(("Order", tr!("interleaved, one click of each step in turn"))),
+                    StepOrder::Weighted => rows.push(("Order", tr!("weighted random, one step drawn per click"))),
+                }
+                let clicking: Vec<&SequenceStep> = self.steps.iter().filter(|s| s.clicks_mouse()).collect();
+                let lefts = clicking.iter().filter(|s| s.button == ClickButton::Left).count();
+                rows.push(("Buttons", tr!("Left × {} steps, Right × {} steps", lefts, clicking.len() - lefts)));
+                let per_cycle: u32 = clicking.iter().map(|s| s.clicks).sum();
+                if self.use_finite_cycles {
+                    let [lo, mid, hi] = sequence_duration(&self.steps, self.num_cycles);
+                    rows.push(("Clicks", (per_cycle * self.num_cycles).to_string()));
+                    rows.push(("Duration", tr!("{} – {}, typically {}", format_duration(lo), format_duration(hi), format_duration(mid))));
+                } else {
+                    rows.push(("Clicks", tr!("{} per cycle", per_cycle)));
+                }
+            }
+            JobMode::GridSweep => {
+                let cells = self.grid_cols.max(1) * self.grid_rows.max(1);
+                rows.push(("Display", display_of(where_)));
+                rows.push(("Target", tr!("{}×{} grid over {}", self.grid_cols, self.grid_rows, area(self.current_bounds()))));
+                rows.push(("Button", button));
+                rows.push(("Clicks", cells.to_string()));
+                rows.push(("Duration", interval(self.min_secs, self.max_secs, cells)));
+            }
+            JobMode::AntiAfk => {
+                rows.push(("Key", tr!("{} every {}–{}s, until stopped", self.afk_key.label(), format!("{:.0}", self.afk_min_secs), format!("{:.0}", self.afk_max_secs))));
+            }
+        }
+        if self.use_wait_for {
+            let gone = if self.wait_for.stop_when_gone { tr!(", stops when it's gone") } else { String::new() };
+            rows.push(("Waits for", format!("{}{}", self.wait_for.describe(), gone)));
+        }
+        let quiet_hours = self.active_quiet_hours();
+        if !quiet_hours.is_empty() {
+            rows.push(("Pauses", quiet_hours.iter().map(QuietWindow::label).collect::<Vec<_>>().join(", ")));
+        }
+        if self.use_screenshot_every || self.steps_take_screenshots() {
+            let every = if self.use_screenshot_every { tr!("every {} clicks, ", self.screenshot_every.max(1)) } else { String::new() };
+            rows.push(("Screenshots", tr!("{}to {}", every, self.screenshot_dir().display())));
+        }
+        if self.use_refocus {
+            let when = if self.refocus.every_click { tr!("every click") } else { tr!("each step") };
+            rows.push(("Refocuses", tr!("“{}” before {}", self.refocus.pattern, when)));
+        }
+        if self.use_events && !self.events.is_empty() {
+            let e = &self.events;
+            let mut on: Vec<String> = [("start", &e.on_start), ("finish", &e.on_finish), ("error", &e.on_error)]
+                .iter()
+                .filter(|(_, c)| !c.trim().is_empty())
+                .map(|(name, _)| name.to_string())
+                .collect();
+            if !e.on_cycle.trim().is_empty() { on.push(tr!("every {} cycles", e.every_cycles.max(1))); }
+            rows.push(("Commands", tr!("on {}", on.join(", "))));
+        }
+        rows.push(("Input", i18n::translate(self.input_backend.label())));
+        rows
+    }
+
+    /// The summary shown by Start, with Start/Cancel.
+    fn confirm_start_ui(&mut self, ctx: &egui::Context) {
+        if !self.confirming_start { return; }
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) { self.confirming_start = false; return; }
+        let mut start = false;
+        let mut cancel = false;
+        egui::Window::new(tr!("Start this job?"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                egui::Grid::new("start_summary").num_columns(2).striped(true).show(ui, |ui| {
+                    for (label, value) in self.start_summary() {
+                        ui.strong(i18n::translate(label));
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                });
+                for w in self.limit_warnings() { ui.colored_label(Color32::YELLOW, format!("⚠ {}", w)); }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    start = ui.button(tr!("Start")).clicked();
+                    cancel = ui.button(tr!("Cancel")).clicked();
+                    let mut ask = self.confirm_start;
+                    if ui.checkbox(&mut ask, tr!("Ask every time")).changed() { self.set_confirm_start(ask); }
+                });
+            });
+        if start || cancel { self.confirming_start = false; }
+        if start { self.start(); }
+    }
+
+    /// Start because someone asked for it by hand (button, hotkey, gamepad, voice or MIDI): with
+    /// the summary first if that's turned on. `--autostart` and the control socket call
+    /// [`Self::start`] directly.
+    fn request_start(&mut self) {
+        if self.confirm_start && !self.is_running() {
+            self.confirming_start = true;
+            self.raise_for_start = true;
+        } else {
+            self.start();
+        }
+    }
+
+    /// Turn the start summary on or off and remember it for next time.
+    fn set_confirm_start(&mut self, on: bool) {
+        self.confirm_start = on;
+        if let Some(Err(e)) = self.profiles.as_ref().map(|p| p.set_confirm_start(on)) {
+            log::warn!("Could not remember the start summary setting: {}", e);
+        }
+    }
+
+    /// Sample clicks for the current settings and capture the screen they fall on.
+    fn open_click_preview(&mut self, ctx: &egui::Context) {
+        let mut cfg = ClickConfig::default();
+        self.write_config(&mut cfg);
+        let forbidden: Vec<Bounds> = self.forbidden.iter().filter(|z| z.enabled).map(|z| z.bounds).collect();
+        let targets = click_targets(&cfg);
+        let (points, skipped) = simulate_clicks(&targets, &forbidden, PREVIEW_CLICKS, &mut rand::thread_rng());
+        let Some(area) = targets.iter().map(|(_, t)| t.extent()).reduce(|a, b| Bounds {
+            min_x: a.min_x.min(b.min_x), max_x: a.max_x.max(b.max_x),
+            min_y: a.min_y.min(b.min_y), max_y: a.max_y.max(b.max_y),
+        }) else {
+            log::warn!("Nothing to preview: the current settings don't click anywhere fixed");
+            return;
+        };
+        let area = Bounds {
+            min_x: area.min_x - PREVIEW_MARGIN, max_x: area.max_x + PREVIEW_MARGIN + 1,
+            min_y: area.min_y - PREVIEW_MARGIN, max_y: area.max_y + PREVIEW_MARGIN + 1,
+        };
+        let texture = match screenshot::capture(Some(area)) {
+            Ok(c) => {
+                let image = egui::ColorImage::from_rgb([c.width as usize, c.height as usize], &c.rgb);
+                Some(ctx.load_texture("click_preview", image, egui::TextureOptions::LINEAR))
+            }
+            Err(e) => { log::warn!("Click preview without a screenshot: {}", e); None }
+        };
+        self.click_preview = Some(ClickPreview { area, texture, points, skipped, forbidden });
+    }
+
+    /// The click preview window: the sampled points as dots (in their step's color in a
+    /// sequence), forbidden zones in red.
+    fn click_preview_ui(&mut self, ctx: &egui::Context) {
+        let Some(preview) = &self.click_preview else { return };
+        let mut open = true;
+        egui::Window::new(tr!("Where would it click?"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let a = preview.area;
+                let scale = (480.0 / a.width() as f32).min(360.0 / a.height() as f32).min(1.0);
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(a.width() as f32 * scale, a.height() as f32 * scale), Sense::hover());
+                let painter = ui.painter_at(rect);
+                let to_screen = |(x, y): (i32, i32)| rect.min + egui::vec2((x - a.min_x) as f32 * scale, (y - a.min_y) as f32 * scale);
+                match &preview.texture {
+                    Some(t) => { painter.image(t.id(), rect, Rect::from_min_max(Pos2::ZERO, egui::pos2(1.0, 1.0)), Color32::WHITE); }
+                    None => { painter.rect_filled(rect, 0.0, Color32::from_gray(30)); }
+                }
+                for f in &preview.forbidden {
+                    let r = Rect::from_two_pos(to_screen((f.min_x, f.min_y)), to_screen((f.max_x, f.max_y)));
+                    painter.rect_filled(r, 0.0, Color32::from_rgba_unmultiplied(200, 0, 0, 60));
+                    painter.rect_stroke(r, 0.0, egui::Stroke { width: 1.5, color: Color32::RED });
+                }
+                for &(step, p) in &preview.points {
+                    let color = match self.mode {
+                        JobMode::Sequence => self.steps.get(step).map_or(Color32::YELLOW, |s| s.color(step)),
+                        _ => Color32::YELLOW,
+                    };
+                    painter.circle_filled(to_screen(p), 2.0, color);
+                }
+                ui.label(tr!("{} simulated clicks in [{}..{}]x[{}..{}]", preview.points.len(), a.min_x, a.max_x, a.min_y, a.max_y));
+                if preview.skipped > 0 {
+                    ui.colored_label(Color32::YELLOW, tr!("⚠ {} draws found no point outside the forbidden zones", preview.skipped));
+                }
+            });
+        if !open { self.click_preview = None; }
+    }
+
+    /// Dry-run the current settings; conditions are taken as met and the safety limits apply.
+    fn start_dry_run(&mut self) {
+        let mut cfg = ClickConfig::default();
+        self.write_config(&mut cfg);
+        cfg.forbidden = self.forbidden.iter().filter(|z| z.enabled).map(|z| z.bounds).collect();
+        cfg.limits = self.limits;
+        let report = dry_run(cfg);
+        log::info!("Dry run: {} cycles, {} clicks in {} of virtual time", report.cycles(), report.clicks, format_duration(report.run.now_ms as f32 / 1000.0));
+        self.dry_run_report = Some(report);
+    }
+
+    /// The dry run report: totals, clicks and time per step, and the full schedule.
+    fn dry_run_ui(&mut self, ctx: &egui::Context) {
+        let Some(report) = &self.dry_run_report else { return };
+        let mut open = true;
+        egui::Window::new(tr!("Dry run"))
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                ui.label(tr!(
+                    "{} cycles, {} clicks, {} of virtual time",
+                    report.cycles(),
+                    report.clicks,
+                    format_duration(report.run.now_ms as f32 / 1000.0),
+                ));
+                if report.failed_steps > 0 { ui.colored_label(Color32::LIGHT_RED, tr!("{} steps failed", report.failed_steps)); }
+                if let Some(why) = report.stopped() { ui.colored_label(Color32::YELLOW, tr!("Ended early: {}", why)); }
+                egui::Grid::new("dry_run_steps").num_columns(3).striped(true).show(ui, |ui| {
+                    ui.strong(tr!("Step"));
+                    ui.strong(tr!("Clicks"));
+                    ui.strong(tr!("Time"));
+                    ui.end_row();
+                    for (name, (clicks, ms)) in report.step_names.iter().zip(report.run.per_step(report.step_names.len())) {
+                        ui.label(name);
+                        ui.label(clicks.to_string());
+                        ui.label(format_duration(ms as f32 / 1000.0));
+                        ui.end_row();
+                    }
+                });
+                ui.collapsing(tr!("Schedule"), |ui| {
+                    let entries = &report.run.entries;
+                    let row_height = ui.text_style_height(&egui::TextStyle::Body);
+                    egui::ScrollArea::vertical().max_height(300.0).show_rows(ui, row_height, entries.len(), |ui, rows| {
+                        for e in &entries[rows] {
+                            ui.label(format!(
+                                "{}  {}  {}  {}",
+                                format_virtual_time(e.at_ms),
+                                tr!("cycle {}", e.cycle),
+                                e.step_name,
+                                dry_run_event_text(&e.event),
+                            ));
+                        }
+                    });
+                });
+            });
+        if !open { self.dry_run_report = None; }
+    }
+
+    /// Keep the statistics of the job that ended for the results window, once per job. Jobs
+    /// that never clicked (or failed) have nothing to show.
+    fn take_run_stats(&mut self) {
+        let Some(job) = self.job.as_mut().filter(|j| !j.reported) else { return };
+        job.reported = true;
+        let stats = job.progress.lock().run_stats(Instant::now());
+        if stats.clicks == 0 && stats.failed_steps == 0 { return; }
+        self.record_run(&stats);
+        self.run_stats = Some(stats);
+        self.stats_result = None;
+    }
+
+    /// Add the run that just ended to the run history.
+    fn record_run(&mut self, stats: &RunStats) {
+        let Some(store) = &self.profiles else { return };
+        let run = RunRecord {
+            started: chrono::Local::now() - chrono::TimeDelta::from_std(stats.duration).unwrap_or_default(),
+            profile: self.current_profile.clone(),
+            duration: stats.duration,
+            clicks: stats.clicks,
+            misses: stats.misses,
+            failed_steps: stats.failed_steps,
+            cycles: stats.cycles,
+        };
+        if let Err(e) = History::open(&store.history_path()).and_then(|h| h.add(&run)) {
+            log::warn!("Could not add the run to the history: {}", e);
+        }
+        self.history_runs = None;
+    }
+
+    /// Past runs, filtered by profile and date, with their totals.
+    fn run_history_ui(&mut self, ctx: &egui::Context) {
+        if !self.history_open { return; }
+        let mut open = true;
+        egui::Window::new(tr!("History"))
+            .open(&mut open)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                let Some(store) = self.profiles.clone() else {
+                    ui.label(tr!("No config directory found; profiles can't be stored."));
+                    return;
+                };
+                let mut changed = false;
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Profile:"));
+                    let selected = self.history_filter.profile.clone().unwrap_or_else(|| tr!("All"));
+                    egui::ComboBox::from_id_source("history_profile").selected_text(selected).show_ui(ui, |ui| {
+                        changed |= ui.selectable_value(&mut self.history_filter.profile, None, tr!("All")).changed();
+                        // deleted profiles still have their runs
+                        let mut names: BTreeSet<String> = self.profile_names.iter().cloned().collect();
+                        names.extend(History::open(&store.history_path()).and_then(|h| h.profiles()).unwrap_or_default());
+                        for name in names {
+                            changed |= ui.selectable_value(&mut self.history_filter.profile, Some(name.clone()), &name).changed();
+                        }
+                    });
+                    ui.label(tr!("From"));
+                    changed |= ui.add(egui::TextEdit::singleline(&mut self.history_days[0]).hint_text("YYYY-MM-DD").desired_width(90.0)).changed();
+                    ui.label(tr!("to"));
+                    changed |= ui.add(egui::TextEdit::singleline(&mut self.history_days[1]).hint_text("YYYY-MM-DD").desired_width(90.0)).changed();
+                    if ui.button(tr!("Last 7 days")).clicked() {
+                        let today = chrono::Local::now().date_naive();
+                        let week = HistoryFilter::last_days(None, 6, today);
+                        self.history_days = [week.from, week.to].map(|d| d.map(|d| d.to_string()).unwrap_or_default());
+                        changed = true;
+                    }
+                });
+                if changed {
+                    match (history::parse_day(&self.history_days[0]), history::parse_day(&self.history_days[1])) {
+                        (Ok(from), Ok(to)) => {
+                            self.history_filter.from = from;
+                            self.history_filter.to = to;
+                            self.history_runs = None;
+                        }
+                        (Err(e), _) | (_, Err(e)) => self.history_runs = Some(Err(e)),
+                    }
+                }
+                let filter = &self.history_filter;
+                let runs = self.history_runs.get_or_insert_with(|| History::open(&store.history_path()).and_then(|h| h.runs(filter)));
+                let runs = match runs {
+                    Ok(runs) => runs,
+                    Err(e) => {
+                        ui.colored_label(Color32::LIGHT_RED, e.as_str());
+                        return;
+                    }
+                };
+                let (clicks, time) = history::totals(runs);
+                ui.label(tr!("{} runs, {} clicks, {}", runs.len(), clicks, format_duration(time.as_secs_f32())));
+                egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                    egui::Grid::new("history_runs").num_columns(6).striped(true).show(ui, |ui| {
+                        for h in [tr!("Started"), tr!("Profile"), tr!("Clicks"), tr!("Misses"), tr!("Failed"), tr!("Time")] { ui.strong(h); }
+                        ui.end_row();
+                        for r in runs.iter() {
+                            ui.label(r.started.format("%Y-%m-%d %H:%M").to_string());
+                            ui.label(r.profile.as_deref().unwrap_or("–"));
+                            ui.label(r.clicks.to_string());
+                            ui.label(r.misses.to_string());
+                            ui.label(r.failed_steps.to_string());
+                            ui.label(format_duration(r.duration.as_secs_f32()));
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        if !open { self.history_open = false; }
+    }
+
+    /// Clicks, intervals, misses, retries and time per step of the run that just ended.
+    fn run_stats_ui(&mut self, ctx: &egui::Context) {
+        let Some(stats) = &self.run_stats else { return };
+        let mut open = true;
+        egui::Window::new(tr!("Run statistics"))
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(520.0)
+            .show(ctx, |ui| {
+                ui.label(tr!("{} clicks in {}", stats.clicks, format_duration(stats.duration.as_secs_f32())));
+                if stats.failed_steps > 0 { ui.colored_label(Color32::LIGHT_RED, tr!("{} steps failed", stats.failed_steps)); }
+                if stats.steps.len() > 1 {
+                    if let Some(name) = stats.bottleneck() { ui.label(tr!("Most time spent in: {}", name)); }
+                }
+                let secs = |v: Option<f64>| v.map_or("–".to_string(), |v| format!("{:.2} s", v));
+                egui::Grid::new("run_stats_steps").num_columns(6).striped(true).show(ui, |ui| {
+                    for h in [tr!("Step"), tr!("Clicks"), tr!("Interval"), tr!("Misses"), tr!("Retries"), tr!("Time")] { ui.strong(h); }
+                    ui.end_row();
+                    for (name, s) in &stats.steps {
+                        ui.label(name);
+                        ui.label(s.clicks.to_string());
+                        ui.label(match s.stdev_interval() {
+                            Some(sd) => format!("{} ± {:.2}", secs(s.mean_interval()), sd),
+                            None => secs(None),
+                        });
+                        ui.label(s.misses.to_string());
+                        ui.label(s.retries.to_string());
+                        ui.label(format_duration(s.time.as_secs_f32()));
+                        ui.end_row();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.stats_path).hint_text(tr!("file")).desired_width(280.0));
+                    if ui.button(tr!("Export CSV")).clicked() {
+                        let path = PathBuf::from(self.stats_path.trim());
+                        self.stats_result = Some(match stats.save_csv(&path) {
+                            Ok(()) => Ok(tr!("Saved to {}", path.display())),
+                            Err(e) => Err(tr!("Could not write {}: {}", path.display(), e)),
+                        });
+                    }
+                });
+                match &self.stats_result {
+                    Some(Ok(done)) => { ui.label(done); }
+                    Some(Err(e)) => { ui.colored_label(Color32::LIGHT_RED, e); }
+                    None => {}
+                }
+            });
+        if !open { self.run_stats = None; }
+    }
+
+    fn tutorial_mark(&mut self, stage: TutorialStage, rect: Rect) {
+        if let Some(t) = &mut self.tutorial { t.mark(stage, rect); }
+    }
+
+    /// Dim everything but the current stage's widget and explain it in a callout.
+    fn tutorial_ui(&mut self, ctx: &egui::Context) {
+        let running = self.is_running();
+        let Some(t) = &mut self.tutorial else { return };
+        if running { t.advance(TutorialStage::Start); }
+        let (stage, target) = (t.stage, t.target.take());
+
+        let screen = ctx.screen_rect();
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("tutorial")));
+        let dim = Color32::from_black_alpha(140);
+        let Some(r) = target.map(|r| r.expand(4.0)) else {
+            painter.rect_filled(screen, 0.0, dim);
+            self.tutorial_callout(ctx, stage, None);
+            return;
+        };
+        for band in [
+            Rect::from_min_max(screen.min, egui::pos2(screen.max.x, r.min.y)),
+            Rect::from_min_max(egui::pos2(screen.min.x, r.max.y), screen.max),
+            Rect::from_min_max(egui::pos2(screen.min.x, r.min.y), egui::pos2(r.min.x, r.max.y)),
+            Rect::from_min_max(egui::pos2(r.max.x, r.min.y), egui::pos2(screen.max.x, r.max.y)),
+        ] {
+            painter.rect_filled(band, 0.0, dim);
+        }
+        painter.rect_stroke(r, 4.0, egui::Stroke::new(2.0, Color32::YELLOW));
+        self.tutorial_callout(ctx, stage, Some(r));
+    }
+
+    /// The text box under the highlighted widget (or centered when there's none).
+    fn tutorial_callout(&mut self, ctx: &egui::Context, stage: TutorialStage, near: Option<Rect>) {
+        let mut area = egui::Area::new(egui::Id::new("tutorial_callout")).order(egui::Order::Tooltip).constrain(true);
+        area = match near {
+            Some(r) => area.fixed_pos(r.left_bottom() + egui::vec2(0.0, 8.0)),
+            None => area.anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO),
+        };
+        let (mut next, mut end) = (false, false);
+        area.show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_max_width(300.0);
+                if let Some(i) = TutorialStage::STEPS.iter().position(|&s| s == stage) {
+                    ui.strong(tr!("Tutorial {} of {}", i + 1, TutorialStage::STEPS.len()));
+                }
+                ui.label(i18n::translate(stage.text()));
+                ui.horizontal(|ui| {
+                    if stage == TutorialStage::Done {
+                        end = ui.button(tr!("Close")).clicked();
+                    } else {
+                        next = ui.button(tr!("Next")).clicked();
+                        end = ui.button(tr!("End tutorial")).clicked();
+                    }
+                });
+            });
+        });
+        if end || ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.tutorial = None;
+        } else if let (true, Some(t)) = (next, &mut self.tutorial) {
+            t.advance(stage);
+        }
+    }
+
+    /// Close the setup wizard for good (until reopened from Diagnostics).
+    fn finish_setup(&mut self) {
+        self.setup = None;
+        if let Some((running, _)) = self.test_click.take() { running.store(false, Ordering::Relaxed); }
+        if let Some(Err(e)) = self.profiles.as_ref().map(|p| p.set_setup_done()) {
+            log::warn!("Could not remember that setup is done: {}", e);
+        }
+    }
+
+    /// Fire the wizard's test click once its countdown is up, then mark where it was aimed. The
+    /// click goes through the same checks as a job's (forbidden zones, safety limits, lock
+    /// screen, quiet hours), on its own thread so waiting on them doesn't hold up the window.
+    fn poll_test_click(&mut self, ctx: &egui::Context) {
+        if self.test_click.as_ref().is_some_and(|(_, job)| job.is_finished()) {
+            let Some((_, job)) = self.test_click.take() else { return };
+            let result = job.join().unwrap_or_else(|_| Err("the test click failed".to_string()));
+            let Some(setup) = &mut self.setup else { return };
+            match result {
+                Ok(click) => {
+                    log::info!("Test click at ({}, {}), cursor now at {:?}", click.target.0, click.target.1, click.seen);
+                    setup.click = Some(click);
+                    self.flash_click(click.target, ctx);
+                }
+                Err(e) => {
+                    log::warn!("No test click: {}", e);
+                    setup.refused = Some(e);
+                }
+            }
+            return;
+        }
+        if self.test_click.is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+            return;
+        }
+        let Some(at) = self.setup.as_ref().and_then(|s| s.click_at) else { return };
+        if Instant::now() < at {
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+            return;
+        }
+        let target = self.current_point().map_or_else(|| self.current_bounds().center(), |p| (p.x, p.y));
+        let Some(setup) = &mut self.setup else { return };
+        setup.click_at = None;
+        let mut cfg = ClickConfig::default();
+        self.write_config(&mut cfg);
+        cfg.forbidden = self.forbidden.iter().filter(|z| z.enabled).map(|z| z.bounds).collect();
+        cfg.limits = self.limits;
+        let running = Arc::new(AtomicBool::new(true));
+        let flag = Arc::clone(&running);
+        let repaint = ctx.clone();
+        let job = std::thread::spawn(move || {
+            let config = Mutex::new(cfg);
+            let mut step = StepCtx::new(&flag, &config);
+            step.click_at(target, None, ClickButton::Left);
+            viewer::set_target(None);
+            repaint.request_repaint();
+            if step.clicks == 0 {
+                let why = step.progress.lock().last_error.clone();
+                return Err(why.unwrap_or_else(|| tr!("({}, {}) is in a forbidden zone.", target.0, target.1)));
+            }
+            let input = INPUT.lock();
+            Ok(TestClick { target, seen: input.backend().reads_cursor().then(|| input.mouse_location()) })
+        });
+        self.test_click = Some((running, job));
+        ctx.request_repaint_after(std::time::Duration::from_millis(200));
+    }
+
+    fn setup_ui(&mut self, ctx: &egui::Context) {
+        let Some(page) = self.setup.as_ref().map(|s| s.page) else { return };
+        let has_target = self.current_point().is_some() || self.current_bounds().is_valid();
+        let input_ok = self.diagnostics.as_ref().is_some_and(|d| d.check.is_ok() && d.moved != Some(false));
+        let (mut next, mut back, mut skip, mut pick) = (false, false, false, false);
+        egui::Window::new(tr!("Welcome to Area Clicker"))
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(tr!("Step {} of {}: {}", page.index() + 1, SetupPage::ALL.len(), i18n::translate(page.title())));
+                ui.separator();
+                match page {
+                    SetupPage::Permissions => {
+                        ui.label(tr!("Area Clicker moves the mouse for you. First, check that it's allowed to."));
+                        if ui.button(tr!("Run self-test")).clicked() { self.diagnostics = Some(input::self_test(&mut INPUT.lock())); }
+                        if let Some(d) = &self.diagnostics { diagnostics_grid(ui, d); }
+                        if self.diagnostics.is_some() && !input_ok {
+                            ui.colored_label(Color32::LIGHT_RED, tr!("Input isn't getting through. Fix the permissions listed above, or pick another input backend, then run the test again."));
+                        }
+                    }
+                    SetupPage::Displays => {
+                        ui.label(tr!("Found {} display(s):", self.monitors.len()));
+                        for m in &self.monitors {
+                            ui.label(format!("• {} — {}×{} at ({}, {}), {:.0}%", m.name, m.size_px.0, m.size_px.1, m.origin_px.0, m.origin_px.1, m.scale_factor * 100.0));
+                        }
+                        ui.horizontal(|ui| self.display_ui(ui));
+                    }
+                    SetupPage::Area => {
+                        ui.label(tr!("Drag a rectangle over something harmless to click, such as an empty part of the desktop, or click a single point."));
+                        pick = ui.button(tr!("Pick (drag a rectangle, or click a point)")).clicked();
+                        match self.current_point() {
+                            Some(p) => { ui.monospace(tr!("Point ({}, {})", p.x, p.y)); }
+                            None if has_target => {
+                                let b = self.current_bounds();
+                                ui.monospace(tr!("Area x=[{}..{}], y=[{}..{}]", b.min_x, b.max_x, b.min_y, b.max_y));
+                            }
+                            None => {}
+                        }
+                    }
+                    SetupPage::TestClick => {
+                        let Some(setup) = &mut self.setup else { return };
+                        ui.label(tr!("One left click will be sent to the middle of your selection. Keep your hands off the mouse until it's done."));
+                        match setup.click_at {
+                            Some(at) => { ui.label(tr!("Clicking in {} s…", at.saturating_duration_since(Instant::now()).as_secs() + 1)); }
+                            None if self.test_click.is_some() => { ui.label(tr!("Clicking…")); }
+                            None => {
+                                if ui.button(tr!("Click once")).clicked() {
+                                    setup.reset_click();
+                                    setup.click_at = Some(Instant::now() + setup::TEST_CLICK_DELAY);
+                                }
+                            }
+                        }
+                        if let Some(why) = &setup.refused {
+                            ui.colored_label(Color32::LIGHT_RED, tr!("The click wasn't sent: {}", why));
+                        }
+                        if let Some(click) = setup.click {
+                            match click.landed() {
+                                Some(true) => { ui.colored_label(Color32::LIGHT_GREEN, tr!("The cursor reached ({}, {}).", click.target.0, click.target.1)); }
+                                Some(false) => { ui.colored_label(Color32::LIGHT_RED, tr!("The cursor ended up at {} instead of {}.", format!("{:?}", click.seen.unwrap_or_default()), format!("{:?}", click.target))); }
+                                None => { ui.label(tr!("This input backend can't read the cursor back, so only you can tell.")); }
+                            }
+                            ui.horizontal(|ui| {
+                                ui.label(tr!("Did it land on the yellow marker?"));
+                                if ui.selectable_label(setup.confirmed == Some(true), tr!("Yes")).clicked() { setup.confirmed = Some(true); }
+                                if ui.selectable_label(setup.confirmed == Some(false), tr!("No")).clicked() { setup.confirmed = Some(false); }
+                            });
+                            if setup.confirmed == Some(false) {
+                                ui.label(tr!("Calibrating the display usually fixes clicks that land off target (Calibrate… next to the display picker). Scaled displays are the usual cause."));
+                            }
+                        }
+                    }
+                    SetupPage::Profile => {
+                        let Some(setup) = &mut self.setup else { return };
+                        ui.label(tr!("Save these settings as a profile that clicks your test area, so you can start from it next time."));
+                        ui.horizontal(|ui| {
+                            ui.label(tr!("Name"));
+                            ui.text_edit_singleline(&mut setup.profile_name);
+                        });
+                    }
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    back = ui.add_enabled(page.back().is_some(), egui::Button::new(tr!("Back"))).clicked();
+                    let ready = self.setup.as_ref().is_some_and(|s| s.can_advance(has_target, input_ok));
+                    let label = if page.next().is_some() { tr!("Next") } else { tr!("Save and finish") };
+                    next = ui.add_enabled(ready, egui::Button::new(label)).clicked();
+                    skip = ui.button(tr!("Skip setup")).clicked();
+                });
+            });
+        if pick {
+            if let Some(s) = &mut self.setup { s.reset_click(); }
+            self.enter_picker(ctx);
+        }
+        if skip {
+            self.finish_setup();
+            return;
+        }
+        let Some(setup) = &mut self.setup else { return };
+        if back {
+            if let Some(p) = page.back() { setup.go(p); }
+        } else if next {
+            match page.next() {
+                Some(p) => {
+                    setup.go(p);
+                    if p == SetupPage::Displays { self.refresh_monitors(); }
+                }
+                None => {
+                    let name = setup.profile_name.trim().to_string();
+                    self.mode = JobMode::Single;
+                    self.save_profile_as(&name);
+                    self.finish_setup();
+                }
+            }
+        }
+    }
+
+    /// "Running — step 3/7, cycle 12, 428 clicks", or `None` when no job has been started.
+    fn status_text(&self) -> Option<String> {
+        let job = self.job.as_ref()?;
+        let state = if job.running.load(Ordering::Relaxed) { tr!("Running") } else { tr!("Stopped") };
+        Some(format!("{} — {}", state, job.progress.lock().summary()))
+    }
+
+    /// Mirror the job status into the window title so it's visible in the taskbar.
+    /// (Taskbar progress bars aren't exposed by eframe/winit, so the title is all we have.)
+    fn update_window_title(&mut self, ctx: &egui::Context) {
+        let title = match self.status_text() {
+            Some(status) => tr!("Area Clicker — {}", status),
+            None => "Area Clicker".to_string(),
+        };
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+        if self.is_running() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(500));
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.job.as_ref().is_some_and(|j| j.running.load(Ordering::Relaxed))
+    }
+
+    fn stop(&mut self) {
+        self.remember_position();
+        self.take_run_stats();
+        self.autostart_at = None;
+        if let Some(job) = &self.job { job.stop(); }
+        self.job = None;
+        for (_, burst) in self.bursts.drain(..) { burst.stop(); }
+    }
+
+    /// Run step `i` once on its own thread, next to the job if one is running (its hotkey or
+    /// an external trigger asked for it). A step still busy from an earlier request isn't
+    /// started again.
+    fn run_step_burst(&mut self, i: usize) -> Result<(), String> {
+        let step = self.steps.get(i).cloned().ok_or_else(|| format!("no step {}", i + 1))?;
+        self.bursts.retain(|(_, burst)| burst.running.load(Ordering::Relaxed));
+        if self.bursts.iter().any(|(name, _)| *name == step.name) {
+            return Err(format!("step '{}' is still running", step.name));
+        }
+        if self.input_backend == Backend::WindowMessages && self.target_window.is_none() {
+            return Err("pick a target window first".to_string());
+        }
+        self.stop_path_recording();
+        let mut cfg = self.config.lock().clone();
+        self.write_config(&mut cfg);
+        log::info!("Running step '{}' on its own", step.name);
+        let name = step.name.clone();
+        self.bursts.push((name, ClickJob::spawn(Arc::new(Mutex::new(cfg.burst(step))))));
+        Ok(())
+    }
+
+    fn pause(&mut self) {
+        self.remember_position();
+        self.take_run_stats();
+        if let Some(job) = &self.job { job.stop(); }
+        self.job = None;
+    }
+
+    fn current_bounds(&self) -> Bounds {
+        Bounds {
+            min_x: self.bounds_inputs[0],
+            max_x: self.bounds_inputs[1],
+            min_y: self.bounds_inputs[2],
+            max_y: self.bounds_inputs[3],
+        }
+    }
+
+    /// The point target, if the selection is in point mode.
+    fn current_point(&self) -> Option<ClickPoint> {
+        let [x, y, jitter] = self.point_inputs;
+        self.use_point.then_some(ClickPoint { x, y, jitter })
+    }
+
+    /// Add a step from the editor fields, or overwrite the one being edited.
+    fn save_step(&mut self) {
+        let mut step = self.draft_step();
+        match self.editing_step_idx.take() {
+            Some(i) if i < self.steps.len() => {
+                // an edit that leaves the template's settings alone keeps the step linked
+                let template = self.steps[i].template.take();
+                step.template = template.filter(|n| self.step_templates.get(n).is_none_or(|t| *t == StepTemplate::of(&step)));
+                self.steps[i] = step;
+            }
+            _ => self.steps.push(step),
+        }
+        self.reset_step_draft();
+    }
+
+    /// The step the editor fields describe.
+    fn draft_step(&self) -> SequenceStep {
+        let name = if self.step_name.trim().is_empty() {
+            self.step_defaults.step_name(self.editing_step_idx.unwrap_or(self.steps.len()) + 1)
+        } else {
+            self.step_name.trim().to_string()
+        };
+        let step = SequenceStep {
+            name,
+            bounds: self.current_bounds(),
+            button: if self.step_button_left { ClickButton::Left } else { ClickButton::Right },
+            clicks: self.step_clicks,
+            min_secs: self.step_min_secs,
+            max_secs: self.step_max_secs,
+            zones: self.step_zones.clone(),
+            target: match self.step_kind {
+                StepKind::Screenshot => StepTarget::Screenshot { full_screen: self.step_shot_full },
+                StepKind::WaitImage => StepTarget::WaitImage(self.step_wait),
+                StepKind::Click if self.step_use_offset => {
+                    let [dx, dy, jitter] = self.step_offset;
+                    StepTarget::Offset { dx, dy, jitter }
+                }
+                StepKind::Click => self.current_point().map_or(StepTarget::Area, StepTarget::Point),
+            },
+            area: None,
+            text: None,
+            image: (self.step_kind == StepKind::WaitImage).then(|| self.step_image.trim().to_string()),
+            verify: (self.step_kind == StepKind::Click && self.step_use_verify).then(|| self.step_verify.clone()),
+            on_error: self.step_on_error,
+            color: Some(self.step_color),
+            weight: self.step_weight,
+            hotkey: self.step_hotkey,
+            template: None,
+            note: self.step_note.trim_end().to_string(),
+        };
+        let mut step = self.link_area(step, self.step_area.clone());
+        // text targets search the step's area; points and offsets have none
+        let text = self.step_text.trim();
+        step.text = (step.target == StepTarget::Area && !text.is_empty()).then(|| text.to_string());
+        step
+    }
+
+    /// Clear the step editor for the next new step, which starts from the step defaults.
+    fn reset_step_draft(&mut self) {
+        let d = &self.step_defaults;
+        self.step_clicks = d.clicks;
+        self.step_min_secs = d.min_secs;
+        self.step_max_secs = d.max_secs;
+        self.step_button_left = d.button == ClickButton::Left;
+        self.step_name.clear();
+        self.step_text.clear();
+        self.step_note.clear();
+        self.step_color = STEP_COLORS[self.steps.len() % STEP_COLORS.len()];
+    }
+
+    /// Point an area step at library area `name`, taking its bounds. Other targets and
+    /// unknown names stay unlinked.
+    fn link_area(&self, mut step: SequenceStep, name: Option<String>) -> SequenceStep {
+        step.area = name.filter(|n| step.target == StepTarget::Area && self.areas.contains_key(n));
+        if let Some(b) = step.area.as_ref().and_then(|n| self.areas.get(n)) { step.bounds = *b; }
+        step
+    }
+
+    /// Store the current selection as library area `name` (replacing it) and update its steps.
+    fn save_area(&mut self, name: &str) {
+        let name = name.trim();
+        if name.is_empty() { return; }
+        self.areas.insert(name.to_string(), self.current_bounds());
+        self.sync_areas();
+        self.persist_areas();
+    }
+
+    /// Remove library area `name`; its steps keep their last bounds.
+    fn delete_area(&mut self, name: &str) {
+        if self.areas.remove(name).is_none() { return; }
+        for step in self.steps.iter_mut().filter(|s| s.area.as_deref() == Some(name)) { step.area = None; }
+        if self.step_area.as_deref() == Some(name) { self.step_area = None; }
+        self.persist_areas();
+    }
+
+    /// Store the editor's click settings as step template `name`, replacing it, and update the
+    /// steps that follow it.
+    fn save_template(&mut self, name: &str) {
+        let name = name.trim();
+        if name.is_empty() { return; }
+        self.step_templates.insert(name.to_string(), StepTemplate::of(&self.draft_step()));
+        self.sync_templates();
+        self.persist_templates();
+    }
+
+    /// Remove step template `name`; its steps keep their settings and are unlinked.
+    fn delete_template(&mut self, name: &str) {
+        if self.step_templates.remove(name).is_none() { return; }
+        for step in self.steps.iter_mut().filter(|s| s.template.as_deref() == Some(name)) { step.template = None; }
+        self.persist_templates();
+    }
+
+    /// Add a step from template `name` at the editor's target, linked to it if `template_linked`.
+    fn insert_template(&mut self, name: &str) {
+        let Some(template) = self.step_templates.get(name) else { return };
+        let mut step = self.draft_step();
+        template.apply(&mut step);
+        if self.step_name.trim().is_empty() { step.name = format!("{} {}", name, self.steps.len() + 1); }
+        step.template = self.template_linked.then(|| name.to_string());
+        self.steps.push(step);
+        self.reset_step_draft();
+    }
+
+    /// Copy template settings into every step linked to a template in the library.
+    fn sync_templates(&mut self) {
+        for step in &mut self.steps {
+            if let Some(t) = step.template.as_ref().and_then(|n| self.step_templates.get(n)) { t.apply(step); }
+        }
+    }
+
+    fn persist_templates(&self) {
+        let Some(store) = &self.profiles else { return };
+        if let Err(e) = store.save_step_templates(&self.step_templates) { log::error!("Could not save step templates: {}", e); }
+    }
+
+    /// Copy library bounds into every step linked to an area.
+    fn sync_areas(&mut self) {
+        for step in &mut self.steps {
+            if let Some(b) = step.area.as_ref().and_then(|n| self.areas.get(n)) { step.bounds = *b; }
+        }
+    }
+
+    /// Hand the enabled forbidden zones to the (possibly running) job and save the list.
+    fn update_forbidden(&mut self) {
+        self.config.lock().forbidden = self.forbidden.iter().filter(|z| z.enabled).map(|z| z.bounds).collect();
+        let Some(store) = &self.profiles else { return };
+        if let Err(e) = store.save_forbidden(&self.forbidden) { log::error!("Could not save forbidden zones: {}", e); }
+    }
+
+    fn persist_areas(&self) {
+        let Some(store) = &self.profiles else { return };
+        if let Err(e) = store.save_areas(&self.areas) { log::error!("Could not save area library: {}", e); }
+    }
+
+    /// Load a step into the editor fields (and the selection inputs) for editing.
+    fn edit_step(&mut self, i: usize) {
+        let Some(s) = self.steps.get(i) else { return; };
+        self.step_name = s.name.clone();
+        self.step_clicks = s.clicks;
+        self.step_min_secs = s.min_secs;
+        self.step_max_secs = s.max_secs;
+        self.step_button_left = s.button == ClickButton::Left;
+        self.step_zones = s.zones.clone();
+        self.step_area = s.area.clone();
+        self.step_text = s.text.clone().unwrap_or_default();
+        self.step_use_verify = s.verify.is_some();
+        self.step_on_error = s.on_error;
+        self.step_weight = s.weight;
+        self.step_hotkey = s.hotkey;
+        self.step_note = s.note.clone();
+        if let Some(v) = &s.verify { self.step_verify = v.clone(); }
+        self.step_color = s.color.unwrap_or(STEP_COLORS[i % STEP_COLORS.len()]);
+        self.bounds_inputs = [s.bounds.min_x, s.bounds.max_x, s.bounds.min_y, s.bounds.max_y];
+        self.use_point = matches!(s.target, StepTarget::Point(_));
+        self.step_use_offset = matches!(s.target, StepTarget::Offset { .. });
+        self.step_kind = StepKind::Click;
+        match s.target {
+            StepTarget::Area => {}
+            StepTarget::Point(p) => self.point_inputs = [p.x, p.y, p.jitter],
+            StepTarget::Offset { dx, dy, jitter } => self.step_offset = [dx, dy, jitter],
+            StepTarget::Screenshot { full_screen } => {
+                self.step_kind = StepKind::Screenshot;
+                self.step_shot_full = full_screen;
+            }
+            StepTarget::WaitImage(w) => {
+                self.step_kind = StepKind::WaitImage;
+                self.step_wait = w;
+                self.step_image = s.image.clone().unwrap_or_default();
+            }
+        }
+        self.editing_step_idx = Some(i);
+    }
+
+    /// After step `i` was changed in the list: drop its template link if it no longer matches,
+    /// and carry the change into the editor if the step is open there.
+    fn step_edited_inline(&mut self, i: usize) {
+        let Some(step) = self.steps.get_mut(i) else { return };
+        step.template = step.template.take().filter(|n| self.step_templates.get(n).is_none_or(|t| *t == StepTemplate::of(step)));
+        if self.editing_step_idx == Some(i) {
+            self.step_clicks = step.clicks;
+            self.step_min_secs = step.min_secs;
+            self.step_max_secs = step.max_secs;
+            self.step_button_left = step.button == ClickButton::Left;
+        }
+    }
+
+    /// Apply the bulk edit to every selected step.
+    fn apply_bulk_edit(&mut self) {
+        for &i in &self.selected_steps {
+            if let Some(step) = self.steps.get_mut(i) { self.bulk.apply(step); }
+        }
+        log::info!("Bulk-edited {} steps", self.selected_steps.len());
+    }
+
+    /// Copies of every step, shifted by the clone offset or moved onto its target monitor;
+    /// `None` if a chosen monitor is gone.
+    fn offset_copies(&self) -> Option<Vec<SequenceStep>> {
+        let c = &self.clone_offset;
+        if !c.onto_monitor {
+            return Some(self.steps.iter().map(|s| s.moved(|(x, y)| (x + c.dx, y + c.dy))).collect());
+        }
+        let (from, to) = (self.monitors.get(c.from)?, self.monitors.get(c.to)?);
+        Some(self.steps.iter().map(|s| s.moved(|p| remap_to_monitor(p, from, to))).collect())
+    }
+
+    /// Append moved copies of the whole sequence. Their jumps point at the other copies, and
+    /// they get no step hotkeys, which would clash with the originals'.
+    fn append_offset_copies(&mut self) {
+        let Some(mut copies) = self.offset_copies() else { return };
+        let n = self.steps.len();
+        for step in &mut copies {
+            step.name = tr!("{} copy", step.name);
+            step.hotkey = None;
+            step.remap_jumps(|j| Some(j + n));
+        }
+        self.steps.extend(copies);
+        log::info!("Appended {} moved copies of the sequence", n);
+    }
+
+    /// Save the current settings with moved copies in place of the steps as new profile `name`;
+    /// the open profile stays as it is.
+    fn save_offset_copies(&mut self, name: &str) {
+        let (Some(store), Some(steps)) = (&self.profiles, self.offset_copies()) else { return };
+        if store.list().iter().any(|p| p == name) {
+            log::warn!("Not cloning the sequence: profile '{}' already exists", name);
+            return;
+        }
+        let profile = Profile { steps, locked: false, ..self.to_profile() };
+        match store.save(name, &profile) {
+            Ok(()) => log::info!("Saved the moved sequence as profile '{}'", name),
+            Err(e) => log::error!("Could not save profile '{}': {}", name, e),
+        }
+        self.refresh_profiles();
+    }
+
+    /// Indices of the steps the list filter lets through.
+    fn visible_steps(&self) -> Vec<usize> {
+        let filter = self.step_filter.trim();
+        (0..self.steps.len())
+            .filter(|&i| filter.is_empty() || !match_ranges(&self.steps[i].summary(), filter).is_empty())
+            .collect()
+    }
+
+    /// Swap steps `a` and `b`, carrying the editor, cursor and bulk selection along.
+    fn swap_steps(&mut self, a: usize, b: usize) {
+        if a >= self.steps.len() || b >= self.steps.len() { return; }
+        self.steps.swap(a, b);
+        self.remap_jumps(|i| Some(if i == a { b } else if i == b { a } else { i }));
+        let swapped = |i: Option<usize>| i.map(|i| if i == a { b } else if i == b { a } else { i });
+        self.editing_step_idx = swapped(self.editing_step_idx);
+        self.step_cursor = swapped(self.step_cursor);
+        for set in [&mut self.selected_steps, &mut self.expanded_steps] {
+            let (has_a, has_b) = (set.remove(&a), set.remove(&b));
+            if has_a { set.insert(b); }
+            if has_b { set.insert(a); }
+        }
+    }
+
+    /// Keep jumps (wait-for-image branches, error policies) pointing at the same steps after the list changed. `map`
+    /// gives a step's new index, or `None` if it's gone (the jump becomes "continue").
+    fn remap_jumps(&mut self, map: impl Fn(usize) -> Option<usize>) {
+        for step in &mut self.steps { step.remap_jumps(&map); }
+    }
+
+    /// Insert a copy of step `i` right after it and put the cursor on the copy.
+    fn duplicate_step(&mut self, i: usize) {
+        let Some(step) = self.steps.get(i) else { return };
+        let mut copy = step.clone();
+        copy.name = tr!("{} copy", step.name);
+        self.steps.insert(i + 1, copy);
+        let shifted = |j: usize| if j > i { j + 1 } else { j };
+        self.remap_jumps(|j| Some(shifted(j)));
+        self.editing_step_idx = self.editing_step_idx.map(shifted);
+        self.selected_steps = self.selected_steps.iter().map(|&j| shifted(j)).collect();
+        self.expanded_steps = self.expanded_steps.iter().map(|&j| shifted(j)).collect();
+        self.step_cursor = Some(i + 1);
+    }
+
+    /// Step list keys: ↑/↓ move the cursor, Ctrl+↑/↓ move the step, Enter edits (and focuses
+    /// the editor, so Tab walks its fields from the top), Delete removes, Ctrl+D duplicates.
+    /// Ignored while any widget has focus: there the keys are egui's focus navigation, and
+    /// Escape, which drops the focus, hands them back to the list.
+    fn step_list_keys(&mut self, ctx: &egui::Context) {
+        use egui::{Key, Modifiers};
+        if ctx.wants_keyboard_input() || self.steps.is_empty() || !self.editable() { return; }
+        let visible = self.visible_steps();
+        let pos = self.step_cursor.and_then(|c| visible.iter().position(|&i| i == c));
+        let (up, down, move_up, move_down, enter, delete, duplicate) = ctx.input_mut(|i| (
+            i.consume_key(Modifiers::NONE, Key::ArrowUp),
+            i.consume_key(Modifiers::NONE, Key::ArrowDown),
+            i.consume_key(Modifiers::COMMAND, Key::ArrowUp),
+            i.consume_key(Modifiers::COMMAND, Key::ArrowDown),
+            i.consume_key(Modifiers::NONE, Key::Enter),
+            i.consume_key(Modifiers::NONE, Key::Delete),
+            i.consume_key(Modifiers::COMMAND, Key::D),
+        ));
+        if up || down {
+            let next = match pos {
+                Some(p) if up => p.saturating_sub(1),
+                Some(p) => (p + 1).min(visible.len().saturating_sub(1)),
+                None if up => visible.len().saturating_sub(1),
+                None => 0,
+            };
+            self.step_cursor = visible.get(next).copied();
+            self.scroll_to_cursor = true;
+        }
+        let Some(c) = self.step_cursor.filter(|&c| c < self.steps.len()) else { return };
+        if move_up && c > 0 { self.swap_steps(c - 1, c); self.scroll_to_cursor = true; }
+        if move_down && c + 1 < self.steps.len() { self.swap_steps(c, c + 1); self.scroll_to_cursor = true; }
+        if enter {
+            self.edit_step(c);
+            self.focus_step_editor = true;
+        }
+        if duplicate { self.duplicate_step(c); self.scroll_to_cursor = true; }
+        if delete {
+            self.remove_step(c);
+            self.step_cursor = (!self.steps.is_empty()).then(|| c.min(self.steps.len() - 1));
+        }
+    }
+
+    fn remove_step(&mut self, i: usize) {
+        if i >= self.steps.len() { return; }
+        self.steps.remove(i);
+        self.remap_jumps(|j| match j.cmp(&i) {
+            std::cmp::Ordering::Less => Some(j),
+            std::cmp::Ordering::Equal => None,
+            std::cmp::Ordering::Greater => Some(j - 1),
+        });
+        self.step_cursor = match self.step_cursor {
+            Some(c) if c > i => Some(c - 1),
+            Some(c) if c == i => None,
+            c => c,
+        };
+        for set in [&mut self.selected_steps, &mut self.expanded_steps] {
+            *set = set.iter().filter(|&&s| s != i).map(|&s| if s > i { s - 1 } else { s }).collect();
+        }
+        match self.editing_step_idx {
+            Some(e) if e == i => self.editing_step_idx = None,
+            Some(e) if e > i => self.editing_step_idx = Some(e - 1),
+            _ => {}
+        }
+    }
+
+    /// Handle global hotkey presses queued since the last frame.
+    fn handle_hotkeys(&mut self) {
+        for key in hotkeys::take_pressed() {
+            if key == self.start_key {
+                if self.is_running() { self.stop(); } else { self.request_start(); }
+            }
+            if key == self.capture_key { self.capture_cursor(); }
+            if Some(key) == self.skip_key { self.step_command(StepCommand::Skip); }
+            if Some(key) == self.repeat_key { self.step_command(StepCommand::Repeat); }
+            for i in 0..self.steps.len() {
+                if self.steps[i].hotkey != Some(key) { continue; }
+                if let Err(e) = self.run_step_burst(i) { log::warn!("Step hotkey {}: {}", key.label(), e); }
+            }
+        }
+    }
+
+    /// Handle gamepad presses queued since the last frame, and move the selection with the
+    /// left stick if that's turned on.
+    fn handle_gamepad(&mut self, ctx: &egui::Context) {
+        for button in gamepad::take_pressed() {
+            if Some(button) == self.pad_start {
+                if self.is_running() { self.stop(); } else { self.request_start(); }
+            }
+            if Some(button) == self.pad_pause {
+                if self.is_running() { self.pause(); } else { self.request_start(); }
+            }
+            if Some(button) == self.pad_stop { self.stop(); }
+        }
+        let (x, y) = if self.pad_nudge { gamepad::stick() } else { (0.0, 0.0) };
+        // a long frame (the window was in the background) shouldn't make the selection jump
+        let secs = self.nudged_at.elapsed().as_secs_f32().min(0.1);
+        self.nudged_at = Instant::now();
+        if (x, y) != (0.0, 0.0) {
+            self.nudge_selection((x * NUDGE_SPEED * secs, -y * NUDGE_SPEED * secs));
+            ctx.request_repaint_after(std::time::Duration::from_millis(16));
+        }
+    }
+
+    /// Move the selection (area or point) by `by` px. Fractions carry over to the next call.
+    fn nudge_selection(&mut self, by: (f32, f32)) {
+        self.nudge_carry.0 += by.0;
+        self.nudge_carry.1 += by.1;
+        let (dx, dy) = (self.nudge_carry.0.trunc(), self.nudge_carry.1.trunc());
+        self.nudge_carry = (self.nudge_carry.0 - dx, self.nudge_carry.1 - dy);
+        let (dx, dy) = (dx as i32, dy as i32);
+        if (dx, dy) == (0, 0) { return; }
+        if self.use_point {
+            self.point_inputs[0] += dx;
+            self.point_inputs[1] += dy;
+            self.config.lock().point = self.current_point();
+        } else {
+            let [min_x, max_x, min_y, max_y] = self.bounds_inputs;
+            self.bounds_inputs = [min_x + dx, max_x + dx, min_y + dy, max_y + dy];
+            self.config.lock().bounds = Some(self.current_bounds());
+        }
+    }
+
+    /// Put a small box (or the point, in point mode) at the live cursor position,
+    /// or record it as a new step.
+    fn capture_cursor(&mut self) {
+        if !self.editable() {
+            log::info!("Not capturing the cursor: the profile is locked");
+            return;
+        }
+        let pos = INPUT.lock().mouse_location();
+        if self.use_point {
+            self.set_point(pos);
+        } else {
+            let b = Bounds::around(pos, self.capture_radius);
+            self.bounds_inputs = [b.min_x, b.max_x, b.min_y, b.max_y];
+            self.config.lock().bounds = Some(b);
+        }
+        log::info!("Captured cursor at ({}, {})", pos.0, pos.1);
+        if self.capture_as_step && self.mode == JobMode::Sequence {
+            self.save_step();
+        }
+    }
+
+    fn refresh_monitors(&mut self) {
+        self.monitors = query_monitors().unwrap_or_else(|e| {
+            log::warn!("{}", e);
+            Vec::new()
+        });
+        // Clamp selection if out-of-range
+        if let DisplayChoice::One(i) = self.display_choice {
+            if i >= self.monitors.len() {
+                self.display_choice = DisplayChoice::All;
+            }
+        }
+    }
+
+    /// Target display picker with a refresh button.
+    fn display_ui(&mut self, ui: &mut egui::Ui) {
+        ui.label(tr!("Target display:"));
+        egui::ComboBox::from_id_source("display_select")
+            .selected_text(match self.display_choice {
+                DisplayChoice::All => tr!("All displays"),
+                DisplayChoice::One(i) => self.monitors.get(i)
+                    .map(|m| m.name.clone())
+                    .unwrap_or_else(|| tr!("Unknown")),
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.display_choice, DisplayChoice::All, tr!("All displays"));
+                for (i, m) in self.monitors.iter().enumerate() {
+                    ui.selectable_value(&mut self.display_choice, DisplayChoice::One(i), &m.name);
+                }
+            });
+
+        if ui.button(tr!("↻ Refresh")).clicked() {
+            self.refresh_monitors();
+        }
+    }
+
+    fn enter_picker(&mut self, ctx: &egui::Context) {
+        self.autosave(); // in case the picker takes the app down
+        self.drag_start = None;
+        self.drag_end = None;
+        self.adjusting = None;
+        self.picking_area = true;
+        let b = self.current_bounds();
+        self.pick_entry = [b.min_x, b.min_y, b.max_x - b.min_x, b.max_y - b.min_y];
+
+        // choose target rectangle in PHYSICAL pixels
+        let (origin_px, size_px) = match self.display_choice {
+            DisplayChoice::All => {
+                let (min_x, min_y, max_x, max_y) = union_rect(&self.monitors);
+                ((min_x, min_y), (max_x - min_x, max_y - min_y))
+            }
+            DisplayChoice::One(i) => {
+                if let Some(m) = self.monitors.get(i) {
+                    (m.origin_px, m.size_px)
+                } else {
+                    // fallback: union
+                    let (min_x, min_y, max_x, max_y) = union_rect(&self.monitors);
+                    ((min_x, min_y), (max_x - min_x, max_y - min_y))
+                }
+            }
+        };
+
+        // convert to LOGICAL points for egui/eframe viewport commands
+        let ppp = ctx.pixels_per_point().max(0.1);
+        let mut inner = egui::vec2(size_px.0 as f32 / ppp, size_px.1 as f32 / ppp);
+        if self.display_choice == DisplayChoice::All {
+            // reach every monitor as laid out in window coordinates, mixed scale factors included
+            for m in &self.monitors {
+                inner = inner.max(monitor_rect(m, ppp, origin_px, &self.monitors).max.to_vec2());
+            }
+        }
+        let outer = egui::pos2(origin_px.0 as f32 / ppp, origin_px.1 as f32 / ppp);
+
+        ctx.send_viewport_cmd(egui::ViewportCommand::Transparent(true));
+        ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(WindowLevel::AlwaysOnTop));
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(outer));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(inner));
+        // Note: using borderless large window; not true OS fullscreen to avoid monitor switching quirks.
+    }
+
+    fn enter_compact(&mut self, ctx: &egui::Context) {
+        self.compact = true;
+        self.size_before_compact = ctx.input(|i| i.viewport().inner_rect).map(|r| r.size());
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(WindowLevel::AlwaysOnTop));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(460.0, 36.0)));
+    }
+
+    fn exit_compact(&mut self, ctx: &egui::Context) {
+        self.compact = false;
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(WindowLevel::Normal));
+        let size = self.size_before_compact.take().unwrap_or(egui::vec2(740.0, 560.0));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+    }
+
+    fn exit_picker(&mut self, ctx: &egui::Context) {
+        self.picking_area = false;
+        self.picking_color = false;
+        self.calibration = None;
+        self.flash = None;
+        // restore a comfy window
+        ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(true));
+        ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(WindowLevel::Normal));
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(740.0, 560.0)));
+    }
+
+    /// Picked area (or a zero-size area at the picked point) as JSON, for the `pick` subcommand.
+    fn picked_json(&self) -> String {
+        let b = match self.current_point() {
+            Some(p) => Bounds { min_x: p.x, max_x: p.x, min_y: p.y, max_y: p.y },
+            None => self.current_bounds(),
+        };
+        serde_json::to_string(&b).unwrap_or_default()
+    }
+
+    /// Drag or click done: back to the main window, or print and quit in `pick` mode.
+    /// An area was picked: also save it as the step's reference image if asked to, then finish.
+    fn finish_area_pick(&mut self, ctx: &egui::Context) {
+        if self.pick_template && self.mode == JobMode::Sequence && !self.pick_only {
+            self.template_pick_at = Some(Instant::now() + PICK_SAMPLE_DELAY);
+        }
+        self.finish_picking(ctx);
+    }
+
+    fn finish_picking(&mut self, ctx: &egui::Context) {
+        if self.pick_only {
+            println!("{}", self.picked_json());
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        } else {
+            self.exit_picker(ctx);
+            if let Some(t) = &mut self.tutorial { t.advance(TutorialStage::PickArea); }
+        }
+    }
+
+    /// Where the physical point `px` shows on the picker overlay. Calibration is undone, so a
+    /// point from the main window's fields sits where the user would have picked it.
+    fn overlay_pos(&self, px: (i32, i32), ppp: f32, screen_rect: Rect) -> Pos2 {
+        let picked = self.monitors
+            .iter()
+            .find(|m| m.contains(px))
+            .and_then(|m| self.corrections.get(&m.id))
+            .map_or(px, |c| c.invert(px));
+        screen_rect.min + physical_to_logical(picked, ppp, self.picker_origin_px(), &self.monitors).to_vec2()
+    }
+
+    /// Panel on the picker overlay for typing the area exactly; the typed area is outlined
+    /// live, and a drag fills the fields in. True once the typed area is confirmed.
+    fn pick_entry_ui(&mut self, ctx: &egui::Context, painter: &egui::Painter, screen_rect: Rect) -> bool {
+        let ppp = ctx.pixels_per_point().max(0.1);
+        if let (Some(a), Some(b)) = (self.drag_start, self.drag_end) {
+            let origin_px = self.picker_origin_px();
+            let ((ax, ay), (bx, by)) = (self.picked_px(a, ppp, origin_px), self.picked_px(b, ppp, origin_px));
+            self.pick_entry = [ax.min(bx), ay.min(by), (ax - bx).abs(), (ay - by).abs()];
+        } else {
+            painter.rect_stroke(self.pick_entry_rect(ppp, screen_rect), 0.0, egui::Stroke { width: 2.0, color: Color32::YELLOW });
+        }
+        let mut confirmed = false;
+        // above the picker's own click-and-drag area, so the fields get the clicks
+        egui::Area::new(egui::Id::new("pick_entry"))
+            .order(egui::Order::Tooltip)
+            .fixed_pos(screen_rect.min + egui::vec2(16.0, 16.0))
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(tr!("Drag a rectangle, click a point, or type the area (px):"));
+                    ui.weak(tr!("Drag the yellow area or its edges to adjust it."));
+                    ui.horizontal(|ui| {
+                        let [x, y, w, h] = &mut self.pick_entry;
+                        ui.label("x");
+                        ui.add(egui::DragValue::new(x));
+                        ui.label("y");
+                        ui.add(egui::DragValue::new(y));
+                        ui.label(tr!("width"));
+                        ui.add(egui::DragValue::new(w).clamp_range(1..=100_000));
+                        ui.label(tr!("height"));
+                        ui.add(egui::DragValue::new(h).clamp_range(1..=100_000));
+                    });
+                    confirmed = ui.button(tr!("Use this area")).on_hover_text(tr!("Or press Enter")).clicked();
+                });
+            });
+        confirmed || ctx.input(|i| i.key_pressed(egui::Key::Enter))
+    }
+
+    /// The typed (or adjusted) area on the picker overlay.
+    fn pick_entry_rect(&self, ppp: f32, screen_rect: Rect) -> Rect {
+        let [x, y, w, h] = self.pick_entry;
+        Rect::from_two_pos(self.overlay_pos((x, y), ppp, screen_rect), self.overlay_pos((x + w, y + h), ppp, screen_rect))
+    }
+
+    /// Physical pixel origin of the picker window (selected monitor or union of all).
+    fn picker_origin_px(&self) -> (i32, i32) {
+        match self.display_choice {
+            DisplayChoice::All => {
+                let (min_x, min_y, _max_x, _max_y) = union_rect(&self.monitors);
+                (min_x, min_y)
+            }
+            DisplayChoice::One(i) => {
+                self.monitors.get(i).map(|m| m.origin_px).unwrap_or((0, 0))
+            }
+        }
+    }
+
+    /// Switch the selection to point mode at `(x, y)` (physical px), keeping the jitter.
+    fn set_point(&mut self, (x, y): (i32, i32)) {
+        self.use_point = true;
+        self.point_inputs[0] = x;
+        self.point_inputs[1] = y;
+        self.config.lock().point = self.current_point();
+        log::info!("Selected point (px): ({}, {}) ±{}", x, y, self.point_inputs[2]);
+    }
+
+    /// Physical position of a picker point, with the monitor's calibration applied.
+    fn picked_px(&self, p: Pos2, ppp: f32, origin_px: (i32, i32)) -> (i32, i32) {
+        let px = logical_to_physical(p, ppp, origin_px, &self.monitors);
+        self.monitors
+            .iter()
+            .find(|m| m.contains(px))
+            .and_then(|m| self.corrections.get(&m.id))
+            .map_or(px, |c| c.apply(px))
+    }
+
+    fn set_input_backend(&mut self, backend: Backend) {
+        let mut input = INPUT.lock();
+        if input.backend() != backend || input.error().is_some() {
+            *input = Input::new(backend).unwrap_or_else(|e| Input::unavailable(backend, e));
+        }
+        input.set_hardware_like(self.hardware_like_input);
+        if let Some((hwnd, _)) = &self.target_window { input.set_target_window(*hwnd); }
+        self.input_error = input.error().map(str::to_string).or_else(|| backend.check().err());
+        drop(input);
+        self.input_backend = backend;
+        if let Some(e) = &self.input_error { log::warn!("Input backend: {}", e); }
+    }
+
+    /// Once the background ydotool probe is done, detect the backend again (it may be ydotool
+    /// after all) or refresh the selected one's error.
+    fn poll_ydotool_check(&mut self) {
+        if self.ydotool_checked || !input::ydotool_checked() { return; }
+        self.ydotool_checked = true;
+        let backend = if self.input_backend == Backend::Enigo { Backend::detect() } else { self.input_backend };
+        self.set_input_backend(backend);
+    }
+
+    fn start_calibration(&mut self, ctx: &egui::Context) {
+        let DisplayChoice::One(i) = self.display_choice else { return };
+        let Some(m) = self.monitors.get(i) else { return };
+        let run = CalibrationRun::new(m.id);
+        self.enter_picker(ctx);
+        self.calibration = Some(run);
+    }
+
+    /// Record one calibration click: where the picker thinks it is vs. where enigo reports the cursor.
+    fn add_calibration_sample(&mut self, picked: (i32, i32), injected: (i32, i32), ctx: &egui::Context) {
+        let Some(run) = &mut self.calibration else { return };
+        run.samples.push((picked, injected));
+        if run.next_marker().is_some() { return; }
+        let id = run.monitor_id;
+        match Correction::fit(&run.samples) {
+            Some(c) => {
+                log::info!("Calibrated display {}: {:?}", id, c);
+                self.corrections.insert(id, c);
+                self.save_calibration();
+            }
+            None => log::warn!("Calibration of display {} failed: the clicks didn't line up", id),
+        }
+        self.exit_picker(ctx);
+    }
+
+    fn reset_calibration(&mut self, monitor_id: u32) {
+        self.corrections.remove(&monitor_id);
+        self.save_calibration();
+    }
+
+    fn save_calibration(&self) {
+        let Some(store) = &self.profiles else { return };
+        if let Err(e) = store.save_calibration(&self.corrections) {
+            log::error!("Could not save calibration: {}", e);
+        }
+    }
+
+    /// Convert a click in the picker (logical points) into a PHYSICAL pixel point target.
+    fn set_point_from_click(&mut self, p: Pos2, ppp: f32, origin_px: (i32, i32)) {
+        self.set_point(self.picked_px(p, ppp, origin_px));
+    }
+
+    /// Convert current drag (logical points in current window) into PHYSICAL pixel bounds,
+    /// offset by the selected monitor or union origin.
+    fn set_bounds_from_drag(&mut self, ppp: f32, origin_px: (i32, i32)) {
+        if let (Some(a), Some(b)) = (self.drag_start, self.drag_end) {
+            let (ax, ay) = self.picked_px(a, ppp, origin_px);
+            let (bx, by) = self.picked_px(b, ppp, origin_px);
+
+            let min_x = ax.min(bx);
+            let max_x = ax.max(bx);
+            let min_y = ay.min(by);
+            let max_y = ay.max(by);
+
+            self.set_picked_bounds(Bounds { min_x, max_x, min_y, max_y });
+        }
+    }
+
+    /// Make `b` (physical px) the selected area.
+    fn set_picked_bounds(&mut self, b: Bounds) {
+        self.bounds_inputs = [b.min_x, b.max_x, b.min_y, b.max_y];
+        self.use_point = false;
+        let mut cfg = self.config.lock();
+        cfg.bounds = Some(b);
+        cfg.point = None;
+        drop(cfg);
+        log::info!("Selected bounds (px): x=[{}..{}], y=[{}..{}]", b.min_x, b.max_x, b.min_y, b.max_y);
+    }
+}
+
+impl AppState {
+    fn profiles_ui(&mut self, ui: &mut egui::Ui) {
+        ui.add_enabled_ui(self.profiles.is_some(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("Profile:"));
+                let mut chosen = None;
+                egui::ComboBox::from_id_source("profile_select")
+                    .selected_text(match (&self.current_profile, &self.profile_passphrase) {
+                        (Some(name), Some(_)) => format!("🔒 {}", name),
+                        (Some(name), None) => name.clone(),
+                        (None, _) => tr!("(unsaved)"),
+                    })
+                    .show_ui(ui, |ui| {
+                        for name in &self.profile_names {
+                            let selected = self.current_profile.as_deref() == Some(name);
+                            if ui.selectable_label(selected, name).clicked() { chosen = Some(name.clone()); }
+                        }
+                    });
+                if let Some(name) = chosen { self.load_profile(&name); }
+
+                if let Some(current) = self.current_profile.clone() {
+                    if ui.button(tr!("Save")).on_hover_text(tr!("Overwrite this profile with the current settings")).clicked() {
+                        self.save_profile_as(&current);
+                    }
+                }
+                self.lock_ui(ui);
+
+                ui.separator();
+                ui.add(egui::TextEdit::singleline(&mut self.profile_name_input).hint_text(tr!("name")).desired_width(100.0));
+                let name = self.profile_name_input.trim().to_string();
+                let name_ok = profile::valid_name(&name) && !self.profile_names.contains(&name);
+                if ui.add_enabled(name_ok, egui::Button::new(tr!("New"))).on_hover_text(tr!("Save the current settings as a new profile")).clicked() {
+                    self.save_profile_as(&name);
+                    self.profile_name_input.clear();
+                }
+                if let Some(current) = self.current_profile.clone() {
+                    if ui.add_enabled(name_ok, egui::Button::new(tr!("Rename"))).clicked() {
+                        self.rename_profile(&current, &name);
+                        self.profile_name_input.clear();
+                    }
+                    if ui.add_enabled(name_ok, egui::Button::new(tr!("Duplicate"))).clicked() {
+                        self.duplicate_profile(&current, &name);
+                        self.profile_name_input.clear();
+                    }
+                    if ui.button(tr!("Delete")).clicked() { self.delete_profile(&current); }
+                    if self.profile_passphrase.is_some() {
+                        if ui.button(tr!("🔓 Decrypt")).on_hover_text(tr!("Save this profile in plain text again")).clicked() {
+                            self.set_profile_passphrase(None);
+                        }
+                    } else if ui.button(tr!("🔒 Encrypt…"))
+                        .on_hover_text(tr!("Save this profile encrypted with a passphrase, asked for whenever it's loaded"))
+                        .clicked()
+                    {
+                        self.pass_prompt = Some(PassPrompt::default());
+                    }
+                }
+                if self.profile_watch.is_some() {
+                    ui.separator();
+                    self.auto_reload_ui(ui);
+                }
+            });
+        }).response.on_disabled_hover_text(tr!("No config directory found; profiles can't be stored."));
+    }
+
+    fn diagnostics_ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(tr!("Diagnostics")).show(ui, |ui| {
+            let run = ui.add_enabled(!self.is_running(), egui::Button::new(tr!("Run self-test")))
+                .on_hover_text(tr!("Reads the cursor, moves it by one pixel and back, and checks it followed"))
+                .on_disabled_hover_text(tr!("Stop the job first."));
+            if run.clicked() { self.diagnostics = Some(input::self_test(&mut INPUT.lock())); }
+            if ui.button(tr!("Setup wizard…")).on_hover_text(tr!("Walk through the first-run setup again")).clicked() {
+                self.setup = Some(Setup::default());
+            }
+            if let Some(d) = &self.diagnostics { diagnostics_grid(ui, d); }
+        });
+    }
+
+    /// Export every profile and app-wide setting to one file, or import such a file.
+    fn backup_ui(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(tr!("Backup")).show(ui, |ui| {
+            if portable::active() {
+                if let Some(dir) = portable::config_dir() { ui.label(tr!("Portable mode: settings are kept in {}", dir.display())); }
+            }
+            ui.add_enabled_ui(self.profiles.is_some(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.bundle_path).hint_text(tr!("file")).desired_width(280.0));
+                    let export = ui.button(tr!("Export everything"))
+                        .on_hover_text(tr!("Profiles (with their hotkeys and human-like settings), area library, forbidden zones, safety limits, theme and language. Display calibration is left out."));
+                    if export.clicked() { self.bundle_result = Some(self.export_settings()); }
+                    let import = ui.add_enabled(!self.is_running(), egui::Button::new(tr!("Import")))
+                        .on_hover_text(tr!("Profiles, areas and step templates with the same name are replaced"))
+                        .on_disabled_hover_text(tr!("Stop the job first."));
+                    if import.clicked() { self.bundle_result = Some(self.import_settings()); }
+                });
+                match &self.bundle_result {
+                    Some(Ok(done)) => { ui.label(done); }
+                    Some(Err(e)) => { ui.colored_label(Color32::LIGHT_RED, e); }
+                    None => {}
+                }
+            }).response.on_disabled_hover_text(tr!("No config directory found; profiles can't be stored."));
+        });
+    }
+
+    fn export_settings(&self) -> Result<String, String> {
+        let store = self.profiles.as_ref().ok_or_else(|| tr!("No config directory found; profiles can't be stored."))?;
+        let path = PathBuf::from(self.bundle_path.trim());
+        let bundle = store.export_bundle().map_err(|e| tr!("Could not export: {}", e))?;
+        profile::save_bundle(&path, &bundle).map_err(|e| tr!("Could not write {}: {}", path.display(), e))?;
+        Ok(tr!("Exported {} profiles to {}", bundle.profiles.len() + bundle.encrypted.len(), path.display()))
+    }
+
+    /// Merge a settings file into the store and switch to what it contains.
+    fn import_settings(&mut self) -> Result<String, String> {
+        let store = self.profiles.as_ref().ok_or_else(|| tr!("No config directory found; profiles can't be stored."))?;
+        let path = PathBuf::from(self.bundle_path.trim());
+        let bundle = profile::load_bundle(&path).map_err(|e| tr!("Could not read {}: {}", path.display(), e))?;
+        store.import_bundle(&bundle).map_err(|e| tr!("Could not import: {}", e))?;
+        self.load_app_settings();
+        if let Some(name) = bundle.last_profile.as_deref().filter(|n| bundle.profiles.contains_key(*n)) {
+            self.load_profile(name);
+        }
+        let imported = tr!("Imported {} profiles from {}", bundle.profiles.len() + bundle.encrypted.len(), path.display());
+        if bundle.profiles.values().any(|p| p.use_events && !p.events.is_empty()) {
+            return Ok(format!("{} {}", imported, tr!("Their commands are off until you turn them on.")));
+        }
+        Ok(imported)
+    }
+
+    fn controls_ui(&mut self, ui: &mut egui::Ui) {
+        let start = ui.button(tr!("Start"));
+        self.tutorial_mark(TutorialStage::Start, start.rect);
+        if start.clicked() { self.request_start(); }
+        if ui.button(tr!("Pause")).clicked() { self.pause(); }
+        if ui.button(tr!("Stop")).clicked() { self.stop(); }
+    }
+
+    fn status_ui(&self, ui: &mut egui::Ui) {
+        if let Some(status) = self.status_text() {
+            let hold = self.config.lock().hold_to_run;
+            match hold {
+                Some(k) if self.is_running() && !hotkeys::is_held(k) => {
+                    ui.label(tr!("Status: Waiting — hold {} to click", k.label()));
+                    ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+                }
+                _ => {
+                    ui.horizontal(|ui| {
+                        self.click_dot_ui(ui);
+                        ui.label(tr!("Status: {}", status));
+                    });
+                    self.resume_ui(ui);
+                    self.step_controls_ui(ui);
+                    self.next_click_ui(ui);
+                    self.last_error_ui(ui);
+                }
+            }
+        } else {
+            match self.autostart_at {
+                Some(at) => {
+                    let left = at.saturating_duration_since(Instant::now()).as_secs_f32().ceil();
+                    ui.label(tr!("Status: Starting in {} s…", left));
+                }
+                None => { ui.label(tr!("Status: Stopped")); }
+            }
+        }
+    }
+
+    /// Let a job that paused for the lock screen carry on, if it waits for that.
+    fn resume_after_unlock(&self) -> bool {
+        let Some(job) = &self.job else { return false };
+        std::mem::take(&mut job.progress.lock().await_resume)
+    }
+
+    fn resume_ui(&self, ui: &mut egui::Ui) {
+        let waiting = self.job.as_ref().is_some_and(|j| j.progress.lock().await_resume);
+        if waiting && ui.button(tr!("Resume")).on_hover_text(tr!("The session was unlocked; carry on clicking")).clicked() {
+            self.resume_after_unlock();
+        }
+    }
+
+    /// Where the next sequence run starts; filled in when a running sequence is stopped.
+    fn start_at_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.use_start_at, tr!("Start at step"))
+                .on_hover_text(tr!("Carry on where an earlier run left off; set when a sequence is stopped"));
+            ui.add_enabled_ui(self.use_start_at, |ui| {
+                ui.add(egui::DragValue::new(&mut self.start_at.step).clamp_range(1..=self.steps.len().max(1)));
+                ui.label(tr!("cycle"));
+                let last_cycle = if self.use_finite_cycles { self.num_cycles } else { u32::MAX };
+                ui.add(egui::DragValue::new(&mut self.start_at.cycle).clamp_range(1..=last_cycle.max(1)));
+            });
+        });
+    }
+
+    /// Hand the running job's step runner a skip/repeat/jump. False if no steps are running yet.
+    fn step_command(&self, command: StepCommand) -> bool {
+        let Some(job) = self.job.as_ref().filter(|_| self.is_running()) else { return false };
+        if job.progress.lock().step_count == 0 { return false; }
+        job.config.lock().step_command = Some(command);
+        true
+    }
+
+    /// Skip / repeat the running step, or jump to another one.
+    fn step_controls_ui(&self, ui: &mut egui::Ui) {
+        let Some(job) = self.job.as_ref().filter(|_| self.is_running()) else { return };
+        let (step, names) = {
+            let p = job.progress.lock();
+            (p.step, p.step_names.clone())
+        };
+        if names.is_empty() { return; }
+        ui.horizontal(|ui| {
+            if ui.button(tr!("⏭ Skip step")).on_hover_text(tr!("Leave this step and go on with the next one")).clicked() {
+                self.step_command(StepCommand::Skip);
+            }
+            if ui.button(tr!("🔁 Repeat step")).on_hover_text(tr!("Start this step over")).clicked() {
+                self.step_command(StepCommand::Repeat);
+            }
+            if names.len() > 1 {
+                let mut jump = None;
+                egui::ComboBox::from_id_source("jump_to_step")
+                    .selected_text(tr!("Jump to…"))
+                    .show_ui(ui, |ui| {
+                        for (i, name) in names.iter().enumerate() {
+                            if ui.selectable_label(i == step, format!("{}. {}", i + 1, name)).clicked() { jump = Some(i); }
+                        }
+                    });
+                if let Some(i) = jump { self.step_command(StepCommand::Jump(i)); }
+            }
+        });
+    }
+
+    /// The most recent step failure of the running job.
+    fn last_error_ui(&self, ui: &mut egui::Ui) {
+        let Some(job) = &self.job else { return };
+        if let Some(e) = &job.progress.lock().last_error { ui.colored_label(Color32::LIGHT_RED, e); }
+    }
+
+    /// Countdown to the next click of a running job and the step it belongs to.
+    fn next_click_ui(&self, ui: &mut egui::Ui) {
+        let Some(job) = self.job.as_ref().filter(|_| self.is_running()) else { return };
+        let p = job.progress.lock();
+        let Some(secs) = p.next_click_in() else { return };
+        let step = p.next_step_name();
+        if step.is_empty() || p.step_names.len() <= 1 {
+            ui.label(tr!("Next click in {}s", format!("{:.1}", secs)));
+        } else {
+            ui.label(tr!("Next click in {}s — {}", format!("{:.1}", secs), step));
+        }
+        ui.ctx().request_repaint_after(std::time::Duration::from_millis(100));
+    }
+
+    /// The last clicks of the current (or last) job, newest first; clicking one marks it on screen.
+    fn history_ui(&mut self, ui: &mut egui::Ui) {
+        let Some(job) = &self.job else { return };
+        let history = job.progress.lock().history.clone();
+        let mut flash = None;
+        ui.collapsing(tr!("Recent clicks ({})", history.len()), |ui| {
+            egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                for (n, c) in history.iter().enumerate().rev() {
+                    let ago = c.at.elapsed().as_secs_f32();
+                    let mut text = tr!("#{} · {}s ago · {} · ({}, {})", n + 1, format!("{:.0}", ago), c.step, c.pos.0, c.pos.1);
+                    if c.missed { text += &tr!(" · missed"); }
+                    if ui.selectable_label(false, text).on_hover_text(tr!("Show on screen")).clicked() {
+                        flash = Some(c.pos);
+                    }
+                }
+            });
+        });
+        if let Some(pos) = flash { self.flash_click(pos, ui.ctx()); }
+    }
+
+    /// Trainer mode: record real mouse movements into the path bank, and choose to replay them.
+    fn path_bank_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing(tr!("Recorded paths ({})", self.path_bank.len()), |ui| {
+            ui.horizontal(|ui| {
+                if self.path_recorder.is_some() {
+                    if ui.button(tr!("⏹ Stop recording ({} new)", self.paths_recorded)).clicked() { self.stop_path_recording(); }
+                } else {
+                    let can = self.input_backend.reads_cursor() && !self.is_running();
+                    let record = ui.add_enabled(can, egui::Button::new(tr!("⏺ Record my movements")))
+                        .on_hover_text(tr!("Move the mouse between spots as you normally would; each movement that ends in a short rest is kept"))
+                        .on_disabled_hover_text(tr!("Needs an input backend that can read the cursor, and no running job"));
+                    if record.clicked() { self.start_path_recording(); }
+                }
+                if !self.path_bank.is_empty() && self.path_recorder.is_none() && ui.button(tr!("Clear")).clicked() {
+                    self.path_bank = Arc::new(PathBank::default());
+                    self.save_path_bank();
+                }
+            });
+            ui.add_enabled(!self.path_bank.is_empty(), egui::Checkbox::new(&mut self.replay_paths, tr!("Replay recorded paths")))
+                .on_hover_text(tr!("Move along your own recorded movements, bent to fit, instead of synthetic curves"));
+        });
+    }
+
+    fn start_path_recording(&mut self) {
+        self.paths_recorded = 0;
+        self.path_recorder = Some(Recorder::start(|| INPUT.lock().mouse_location()));
+        log::info!("Recording mouse paths");
+    }
+
+    fn stop_path_recording(&mut self) {
+        let Some(recorder) = self.path_recorder.take() else { return };
+        self.keep_paths(recorder.stop());
+        log::info!("Recorded {} mouse paths", self.paths_recorded);
+        self.save_path_bank();
+    }
+
+    /// Move finished recordings into the bank while trainer mode is on.
+    fn poll_path_recorder(&mut self, ctx: &egui::Context) {
+        let Some(recorder) = &self.path_recorder else { return };
+        let paths = recorder.take();
+        self.keep_paths(paths);
+        ctx.request_repaint_after(std::time::Duration::from_millis(250));
+    }
+
+    /// Open or close the control socket, the MIDI input and the voice recognizer to match the
+    /// trigger settings, and carry out what came in through them.
+    /// Start the remote viewer when it's turned on (and didn't fail), stop it when it's off.
+    fn poll_viewer(&mut self) {
+        if !self.viewer_settings.enabled {
+            self.viewer = None;
+            return;
+        }
+        if self.viewer.is_some() || self.viewer_error.is_some() { return; }
+        match viewer::Viewer::start(&self.viewer_settings) {
+            Ok(v) => self.viewer = Some(v),
+            Err(e) => {
+                log::error!("{}", e);
+                self.viewer_error = Some(e);
+            }
+        }
+    }
+
+    fn poll_triggers(&mut self, ctx: &egui::Context) {
+        if self.triggers.remote != self.remote.is_some() {
+            let ctx = ctx.clone();
+            self.remote = self.triggers.remote.then(|| daemon::Remote::start(self.control_socket.clone(), move || ctx.request_repaint()));
+        }
+        let device = self.triggers.midi_device.clone();
+        if self.midi.as_ref().map(|m| m.device()) != device.as_deref() {
+            self.midi = None;
+            let failed = self.midi_error.as_ref().is_some_and(|(d, _)| Some(d) == device.as_ref());
+            if let Some(device) = device.filter(|_| !failed) {
+                let ctx = ctx.clone();
+                match MidiInput::open(&device, move || ctx.request_repaint()) {
+                    Ok(m) => {
+                        self.midi = Some(m);
+                        self.midi_error = None;
+                    }
+                    Err(e) => {
+                        log::error!("Could not open MIDI device {}: {}", device.display(), e);
+                        self.midi_error = Some((device, e.to_string()));
+                    }
+                }
+            }
+        }
+        if let Some(remote) = self.remote.take() {
+            remote.answer(|line| daemon::handle(self, line));
+            self.remote = Some(remote);
+        }
+        let notes = self.midi.as_ref().map(|m| m.take()).unwrap_or_default();
+        for note in notes { self.midi_note(note); }
+
+        if !self.triggers.voice.enabled {
+            self.voice = None;
+        } else if self.voice.is_none() && self.voice_error.is_none() {
+            let ctx = ctx.clone();
+            match voice::Listener::start(&self.triggers.voice.command_line(), move || ctx.request_repaint()) {
+                Ok(v) => self.voice = Some(v),
+                Err(e) => {
+                    log::error!("Voice commands: {}", e);
+                    self.voice_error = Some(e);
+                }
+            }
+        }
+        let heard = self.voice.as_ref().map(|v| v.take()).unwrap_or_default();
+        for line in heard { self.voice_heard(line); }
+    }
+
+    /// Stop (or start) if `line`, from the recognizer, says the phrase for it.
+    fn voice_heard(&mut self, line: String) {
+        match self.triggers.voice.hear(&line) {
+            Some(Said::Stop) => {
+                log::warn!("Heard \"{}\": stopping", line);
+                self.stop();
+            }
+            Some(Said::Start) if !self.is_running() => {
+                log::info!("Heard \"{}\": starting", line);
+                self.request_start();
+            }
+            _ => {}
+        }
+        self.last_heard = Some(line);
+    }
+
+    /// Carry out the actions bound to `note`.
+    fn midi_note(&mut self, note: Note) {
+        self.last_note = Some(note);
+        let actions: Vec<TriggerAction> = self.triggers.midi.iter().filter(|b| b.matches(note)).map(|b| b.action.clone()).collect();
+        for action in actions {
+            let result = match &action {
+                // a note is played by hand, so it asks like the Start button does
+                TriggerAction::StartProfile(name) => {
+                    self.stop();
+                    daemon::handle(self, &format!("load {}", name)).map(|_| self.request_start())
+                }
+                _ => daemon::handle(self, &action.command()).map(|_| ()),
+            };
+            match result {
+                Ok(_) => log::info!("MIDI note {} (channel {}): {}", note.note, note.channel, action.command()),
+                Err(e) => log::warn!("MIDI note {} (channel {}): {}: {}", note.note, note.channel, action.command(), e),
+            }
+        }
+    }
+
+    /// Read the cursor and the pixel under it for the status bar, wherever the cursor is.
+    fn poll_cursor_readout(&mut self, ctx: &egui::Context) {
+        if !self.show_cursor_readout || self.compact { return; }
+        ctx.request_repaint_after(READOUT_INTERVAL);
+        if self.cursor_read_at.elapsed() < READOUT_INTERVAL { return; }
+        self.cursor_read_at = Instant::now();
+        // a running job holds the driver for a whole move; keep the last reading until it's done
+        let Some(input) = INPUT.try_lock() else { return };
+        if !input.backend().reads_cursor() || input.error().is_some() {
+            self.cursor_readout = None;
+            return;
+        }
+        let pos = input.mouse_location();
+        drop(input);
+        self.cursor_readout = Some(CursorReadout { pos, color: screenshot::pixel(pos.0, pos.1).ok() });
+    }
+
+    /// The bottom bar: cursor position (virtual screen, then on its display) and the color under it.
+    fn status_bar_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_cursor_readout, tr!("Cursor"))
+                .on_hover_text(tr!("Show where the cursor is and the color under it, in physical pixels"));
+            if !self.show_cursor_readout { return; }
+            let Some(r) = self.cursor_readout else {
+                ui.weak(tr!("can't be read with this input driver"));
+                return;
+            };
+            ui.monospace(format!("{}, {}", r.pos.0, r.pos.1));
+            if let Some(m) = self.monitors.iter().find(|m| m.contains(r.pos)) {
+                ui.weak(format!("{}: {}, {}", m.name, r.pos.0 - m.origin_px.0, r.pos.1 - m.origin_px.1));
+            }
+            if let Some([red, green, blue]) = r.color {
+                ui.separator();
+                color_swatch(ui, [red, green, blue]);
+                ui.monospace(format!("#{:02X}{:02X}{:02X}  rgb({}, {}, {})", red, green, blue, red, green, blue));
+            }
+        });
+    }
+
+    fn keep_paths(&mut self, paths: Vec<path_bank::RecordedPath>) {
+        if paths.is_empty() { return; }
+        let bank = Arc::make_mut(&mut self.path_bank);
+        self.paths_recorded += paths.into_iter().map(|p| bank.add(p)).filter(|&kept| kept).count();
+    }
+
+    fn save_path_bank(&self) {
+        let Some(store) = &self.profiles else { return };
+        if let Err(e) = store.save_path_bank(&self.path_bank) {
+            log::error!("Could not save the path bank: {}", e);
+        }
+    }
+
+    /// Open the overlay just to mark `pos` (physical px, as injected) for a moment.
+    fn flash_click(&mut self, pos: (i32, i32), ctx: &egui::Context) {
+        self.enter_picker(ctx);
+        self.flash = Some((pos, Instant::now() + FLASH_DURATION));
+    }
+
+    /// Recent log lines plus the level and log file switches.
+    fn log_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Log", |ui| {
+            ui.horizontal(|ui| {
+                ui.label(tr!("Level"));
+                let mut level = logging::level();
+                egui::ComboBox::from_id_source("log_level")
+                    .selected_text(level.as_str())
+                    .show_ui(ui, |ui| {
+                        for l in log::LevelFilter::iter() {
+                            ui.selectable_value(&mut level, l, l.as_str());
+                        }
+                    });
+                if level != logging::level() { logging::set_level(level); }
+
+                let mut to_file = logging::file().is_some();
+                if ui.checkbox(&mut to_file, tr!("Write to file")).changed() {
+                    let path = if to_file { self.log_path.clone().or_else(logging::default_file) } else { None };
+                    self.log_error = logging::set_file(path.as_deref()).err().map(|e| tr!("Could not open log file: {}", e));
+                }
+                if let Some(path) = logging::file() { ui.weak(path.display().to_string()); }
+                if ui.button(tr!("Clear")).clicked() { logging::clear_recent(); }
+            });
+            if let Some(e) = &self.log_error { ui.colored_label(Color32::LIGHT_RED, e); }
+            egui::ScrollArea::vertical().max_height(180.0).stick_to_bottom(true).show(ui, |ui| {
+                for line in logging::recent() {
+                    let color = match line.level {
+                        log::Level::Error => Color32::LIGHT_RED,
+                        log::Level::Warn => Color32::YELLOW,
+                        log::Level::Info => ui.visuals().text_color(),
+                        _ => Color32::GRAY,
+                    };
+                    ui.label(egui::RichText::new(line.text).monospace().color(color));
+                }
+            });
+        });
+    }
+
+    /// The whole UI in compact mode: one row with the controls, status and a way back.
+    fn compact_ui(&mut self, ctx: &egui::Context) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                self.controls_ui(ui);
+                ui.separator();
+                self.status_ui(ui);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button("⤢").on_hover_text(tr!("Back to the full window")).clicked() {
+                        self.exit_compact(ctx);
+                    }
+                });
+            });
+        });
+    }
+
+    fn hold_to_run_ui(&mut self, ui: &mut egui::Ui) {
+        ui.add_enabled_ui(hotkeys::available(), |ui| {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.use_hold_to_run, tr!("Hold to run:"));
+                egui::ComboBox::from_id_source("hold_key")
+                    .selected_text(self.hold_key.label())
+                    .show_ui(ui, |ui| {
+                        for k in HotKey::ALL {
+                            ui.selectable_value(&mut self.hold_key, k, k.label());
+                        }
+                    });
+                ui.label(tr!("(clicks only while held)"));
+            });
+        }).response.on_disabled_hover_text(tr!("Global hotkeys need a build with `--features hooks`."));
+    }
+
+    fn wait_for_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.use_wait_for, tr!("Wait for"));
+            ui.add_enabled_ui(self.use_wait_for, |ui| {
+                egui::ComboBox::from_id_source("wait_kind")
+                    .selected_text(i18n::translate(self.wait_for.kind.label()))
+                    .show_ui(ui, |ui| {
+                        for k in [WatchKind::Window, WatchKind::Process] {
+                            ui.selectable_value(&mut self.wait_for.kind, k, i18n::translate(k.label()));
+                        }
+                    });
+                ui.add(egui::TextEdit::singleline(&mut self.wait_for.pattern).hint_text(tr!("name or regex")).desired_width(120.0));
+            });
+        });
+        if !self.use_wait_for { return; }
+        ui.horizontal(|ui| {
+            ui.label(tr!("Timeout"));
+            ui.add(egui::DragValue::new(&mut self.wait_for.timeout_secs).clamp_range(0..=86400).suffix(" s"))
+                .on_hover_text(tr!("0 = wait forever"));
+            ui.checkbox(&mut self.wait_for.stop_when_gone, tr!("Stop when it's gone"));
+        });
+        if let Err(e) = self.wait_for.matcher() { ui.colored_label(Color32::LIGHT_RED, e); }
+    }
+
+    /// Editor fields of a wait-for-image step.
+    fn wait_image_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(tr!("Image"));
+            ui.add(egui::TextEdit::singleline(&mut self.step_image).hint_text(tr!("path to a PNG")).desired_width(200.0));
+            if ui.button(tr!("Capture selection")).on_hover_text(tr!("Save the current selection as the image to wait for")).clicked() {
+                if let Some(path) = self.capture_template() { self.step_image = path; }
+            }
+        });
+        let steps = self.steps.len();
+        let w = &mut self.step_wait;
+        ui.horizontal(|ui| {
+            ui.label(tr!("Timeout"));
+            ui.add(egui::DragValue::new(&mut w.timeout_secs).clamp_range(1..=86400).suffix(" s"));
+            ui.label(tr!("Tolerance"));
+            ui.add(egui::DragValue::new(&mut w.tolerance).clamp_range(0..=255))
+                .on_hover_text(tr!("How far each color channel may differ (0 = exact)"));
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr!("When found:"));
+            branch_ui(ui, "on_found", &mut w.on_found, steps);
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr!("On timeout:"));
+            branch_ui(ui, "on_timeout", &mut w.on_timeout, steps);
+        });
+    }
+
+    /// Save the current selection as a template PNG; returns its path.
+    fn capture_template(&self) -> Option<String> {
+        let dir = portable::config_dir()?.join("templates");
+        let label = if self.step_name.trim().is_empty() { "template" } else { self.step_name.trim() };
+        match screenshot::take(&dir, Some(self.current_bounds()), label) {
+            Ok(path) => {
+                log::info!("Saved template {}", path.display());
+                Some(path.display().to_string())
+            }
+            Err(e) => { log::error!("Could not capture template: {}", e); None }
+        }
+    }
+
+    /// The color at `at` right now, re-read a few times a second while the editor shows it.
+    fn sample_pixel_preview(&mut self, at: (i32, i32), ctx: &egui::Context) -> Option<[u8; 3]> {
+        ctx.request_repaint_after(READOUT_INTERVAL);
+        let moved = self.pixel_preview.is_none_or(|(p, _)| p != at);
+        if moved || self.pixel_preview_at.elapsed() >= READOUT_INTERVAL {
+            self.pixel_preview_at = Instant::now();
+            self.pixel_preview = screenshot::pixel(at.0, at.1).ok().map(|c| (at, c));
+        }
+        self.pixel_preview.filter(|(p, _)| *p == at).map(|(_, c)| c)
+    }
+
+    /// Editor fields of a click step's post-click check.
+    fn verify_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.step_use_verify, tr!("Verify each click"))
+            .on_hover_text(tr!("Check the screen after clicking and retry the click if nothing changed"));
+        if !self.step_use_verify { return; }
+        let is_pixel = matches!(self.step_verify.check, Check::Pixel { .. });
+        ui.horizontal(|ui| {
+            ui.label(tr!("Expect"));
+            if ui.radio(is_pixel, tr!("pixel color")).clicked() && !is_pixel {
+                self.step_verify.check = Check::Pixel { x: 0, y: 0, color: [0, 0, 0] };
+            }
+            if ui.radio(!is_pixel, tr!("image")).clicked() && is_pixel {
+                self.step_verify.check = Check::Image { region: self.current_bounds() };
+            }
+        });
+        let selection = self.current_bounds();
+        let point = self.current_point();
+        let preview = match self.step_verify.check {
+            Check::Pixel { x, y, .. } => self.sample_pixel_preview((x, y), ui.ctx()),
+            Check::Image { .. } => None,
+        };
+        let mut capture = false;
+        let mut pick_color = false;
+        let v = &mut self.step_verify;
+        match &mut v.check {
+            Check::Pixel { x, y, color } => {
+                ui.horizontal(|ui| {
+                    ui.label(tr!("at"));
+                    ui.add(egui::DragValue::new(x));
+                    ui.add(egui::DragValue::new(y));
+                    ui.color_edit_button_srgb(color);
+                    if ui.button(tr!("Sample")).on_hover_text(tr!("Take position and color from the selected point (or the selection's center)")).clicked() {
+                        let (px, py) = point.map_or(selection.center(), |p| (p.x, p.y));
+                        (*x, *y) = (px, py);
+                        match screenshot::pixel(px, py) {
+                            Ok(c) => *color = c,
+                            Err(e) => log::error!("Could not sample the pixel: {}", e),
+                        }
+                    }
+                    pick_color = ui.button(tr!("🎯 Pick")).on_hover_text(tr!("Click anywhere on screen to take that pixel's position and color")).clicked();
+                });
+                if let Some(now) = preview {
+                    ui.horizontal(|ui| {
+                        ui.label(tr!("Now"));
+                        color_swatch(ui, now);
+                        ui.monospace(format!("#{:02X}{:02X}{:02X}", now[0], now[1], now[2]));
+                        let off = color_distance(now, *color);
+                        if off <= v.tolerance {
+                            ui.colored_label(Color32::LIGHT_GREEN, tr!("✔ matches"));
+                        } else {
+                            ui.colored_label(Color32::LIGHT_RED, tr!("✖ off by {}", off));
+                        }
+                    });
+                }
+            }
+            Check::Image { region } => {
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Image"));
+                    ui.add(egui::TextEdit::singleline(&mut v.image).hint_text(tr!("path to a PNG")).desired_width(200.0));
+                    capture = ui.button(tr!("Capture selection")).clicked();
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr!("in [{}..{}]x[{}..{}]", region.min_x, region.max_x, region.min_y, region.max_y));
+                    if ui.button(tr!("Use selection")).clicked() { *region = selection; }
+                });
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label(tr!("Tolerance"));
+            ui.add(egui::Slider::new(&mut v.tolerance, 0..=255));
+            ui.label(tr!("check after"));
+            ui.add(egui::DragValue::new(&mut v.delay_ms).clamp_range(0..=60_000).suffix(" ms"));
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr!("Retries"));
+            ui.add(egui::DragValue::new(&mut v.retries).clamp_range(0..=20));
+            ui.label(tr!("backoff"));
+            ui.add(egui::DragValue::new(&mut v.backoff_ms).clamp_range(0..=60_000).suffix(" ms"))
+                .on_hover_text(tr!("Doubles after every retry"));
+        });
+        if pick_color {
+            self.picking_color = true;
+            self.enter_picker(ui.ctx());
+        }
+        if capture {
+            if let Some(path) = self.capture_template() { self.step_verify.image = path; }
+        }
+    }
+
+    /// The screenshot folder: the configured one, or the default.
+    fn screenshot_dir(&self) -> PathBuf {
+        match self.screenshot_dir.trim() {
+            "" => screenshot::default_dir().unwrap_or_else(|| PathBuf::from("screenshots")),
+            dir => PathBuf::from(dir),
+        }
+    }
+
+    fn steps_take_screenshots(&self) -> bool {
+        self.mode == JobMode::Sequence && self.steps.iter().any(|s| matches!(s.target, StepTarget::Screenshot { .. }))
+    }
+
+    fn screenshot_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.use_screenshot_every, tr!("Screenshot every"));
+            ui.add_enabled(self.use_screenshot_every, egui::DragValue::new(&mut self.screenshot_every).clamp_range(1..=1_000_000));
+            ui.label(tr!("clicks"));
+        });
+        if !self.use_screenshot_every && !self.steps_take_screenshots() { return; }
+        ui.horizontal(|ui| {
+            ui.label(tr!("Folder"));
+            let hint = self.screenshot_dir().display().to_string();
+            ui.add(egui::TextEdit::singleline(&mut self.screenshot_dir).hint_text(hint).desired_width(220.0));
+        });
+    }
+
+    fn events_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.use_events, tr!("Run commands"))
+            .on_hover_text("Placeholders: {event} {clicks} {step} {cycle} {failed} {error}\n\
+                Their values come in as environment variables (AREA_CLICKER_STEP etc.); put {step} and {error} in double quotes");
+        if !self.use_events { return; }
+        egui::Grid::new("event_commands").num_columns(2).show(ui, |ui| {
+            let e = &mut self.events;
+            for (label, cmd) in [("On start", &mut e.on_start), ("On finish", &mut e.on_finish), ("On error", &mut e.on_error)] {
+                ui.label(i18n::translate(label));
+                ui.add(egui::TextEdit::singleline(cmd).hint_text(tr!("shell command")).desired_width(260.0));
+                ui.end_row();
+            }
+            ui.horizontal(|ui| {
+                ui.label(tr!("Every"));
+                ui.add(egui::DragValue::new(&mut e.every_cycles).clamp_range(1..=1_000_000));
+                ui.label(tr!("cycles"));
+            });
+            ui.add(egui::TextEdit::singleline(&mut e.on_cycle).hint_text("e.g. ./rotate-vpn.sh {cycle}").desired_width(260.0));
+            ui.end_row();
+        });
+    }
+
+    fn refocus_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.use_refocus, tr!("Refocus window"))
+                .on_hover_text(tr!("Bring this window to the front before clicking"));
+            ui.add_enabled_ui(self.use_refocus, |ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.refocus.pattern).hint_text(tr!("title or regex")).desired_width(120.0));
+                if let Some((_, title)) = &self.target_window {
+                    if ui.small_button(tr!("Use picked")).on_hover_text(title.as_str()).clicked() {
+                        self.refocus.pattern = format!("^{}$", regex::escape(title));
+                    }
+                }
+                ui.checkbox(&mut self.refocus.every_click, tr!("Before every click"));
+            });
+        });
+        if !self.use_refocus { return; }
+        if let Err(e) = self.refocus.matcher() { ui.colored_label(Color32::LIGHT_RED, e); }
+    }
+
+    fn warm_up_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.use_warm_up, tr!("Warm-up"));
+            if !self.use_warm_up { return; }
+            let w = &mut self.warm_up;
+            ui.label(tr!("first"));
+            ui.add(egui::DragValue::new(&mut w.clicks).speed(1.0).clamp_range(1..=10000));
+            ui.label(tr!("clicks: interval ×"));
+            ui.add(egui::DragValue::new(&mut w.interval_factor).speed(0.05).clamp_range(1.0..=20.0));
+            ui.label(tr!("speed ×"));
+            ui.add(egui::DragValue::new(&mut w.speed_factor).speed(0.01).clamp_range(0.1..=1.0));
+        });
+    }
+
+    fn active_quiet_hours(&self) -> Vec<QuietWindow> {
+        if self.use_quiet_hours { self.quiet_hours.clone() } else { Vec::new() }
+    }
+
+    /// Daily pause windows. Edits reach a running job straight away.
+    fn quiet_hours_ui(&mut self, ui: &mut egui::Ui) {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            changed |= ui.checkbox(&mut self.use_quiet_hours, tr!("Quiet hours"))
+                .on_hover_text(tr!("Pause clicks and key taps during these daily times (local time) and resume afterwards"))
+                .changed();
+            if self.use_quiet_hours && ui.button(tr!("+ Add")).clicked() {
+                self.quiet_hours.push(QuietWindow::default());
+                changed = true;
+            }
+        });
+        if self.use_quiet_hours {
+            fn time(minute: &mut u16) -> egui::DragValue<'_> {
+                egui::DragValue::new(minute)
+                    .speed(1.0)
+                    .clamp_range(0..=24 * 60 - 1)
+                    .custom_formatter(|m, _| quiet::format_time(m as u16))
+                    .custom_parser(|text| quiet::parse_time(text).map(f64::from))
+            }
+            let mut remove = None;
+            for (i, w) in self.quiet_hours.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(tr!("from"));
+                    changed |= ui.add(time(&mut w.start)).changed();
+                    ui.label(tr!("to"));
+                    changed |= ui.add(time(&mut w.end)).changed();
+                    if ui.small_button("🗑").clicked() { remove = Some(i); }
+                });
+            }
+            if let Some(i) = remove {
+                self.quiet_hours.remove(i);
+                changed = true;
+            }
+            if self.quiet_hours.is_empty() { ui.weak(tr!("No windows yet.")); }
+        }
+        if changed && self.is_running() { self.config.lock().quiet_hours = self.active_quiet_hours(); }
+    }
+
+    fn fatigue_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.use_fatigue, tr!("Fatigue"));
+            ui.label(tr!("(slow down over the session)"));
+        });
+        if !self.use_fatigue { return; }
+        let f = &mut self.fatigue;
+        ui.horizontal(|ui| {
+            ui.label(tr!("Per hour: interval +"));
+            ui.add(egui::DragValue::new(&mut f.interval_pct_per_hour).speed(0.5).clamp_range(0.0..=500.0).suffix("%"));
+            ui.label(tr!("speed −"));
+            ui.add(egui::DragValue::new(&mut f.speed_pct_per_hour).speed(0.5).clamp_range(0.0..=90.0).suffix("%"));
+            ui.label(tr!("max"));
+            ui.add(egui::DragValue::new(&mut f.max_pct).speed(0.5).clamp_range(0.0..=500.0).suffix("%"));
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr!("Break chance"));
+            ui.add(egui::DragValue::new(&mut f.break_chance).speed(0.005).clamp_range(0.0..=1.0));
+            ui.label(tr!("for"));
+            ui.add(egui::DragValue::new(&mut f.break_min_secs).speed(1.0).clamp_range(0.0..=3600.0));
+            ui.label(tr!("to"));
+            ui.add(egui::DragValue::new(&mut f.break_max_secs).speed(1.0).clamp_range(0.0..=3600.0));
+            ui.label(tr!("s, recovers"));
+            ui.add(egui::DragValue::new(&mut f.recovery).speed(0.01).clamp_range(0.0..=1.0));
+        });
+    }
+
+    /// App-wide forbidden zones: list, toggle, remove, and add the current selection.
+    fn forbidden_ui(&mut self, ui: &mut egui::Ui) {
+        let enabled = self.forbidden.iter().filter(|z| z.enabled).count();
+        ui.collapsing(tr!("Forbidden zones ({} active)", enabled), |ui| {
+            ui.weak(tr!("Never clicked, in any mode or profile. Points inside are drawn again."));
+            let mut changed = false;
+            let mut remove = None;
+            for (i, z) in self.forbidden.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    changed |= ui.checkbox(&mut z.enabled, "").changed();
+                    let b = z.bounds;
+                    ui.label(format!("{} [{}..{}]x[{}..{}]", z.name, b.min_x, b.max_x, b.min_y, b.max_y));
+                    if ui.small_button("✖").clicked() { remove = Some(i); }
+                });
+            }
+            if let Some(i) = remove {
+                self.forbidden.remove(i);
+                changed = true;
+            }
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.forbidden_name_input).hint_text(tr!("zone name")).desired_width(120.0));
+                if ui.add_enabled(!self.use_point, egui::Button::new(tr!("Forbid selection"))).clicked() {
+                    let name = match self.forbidden_name_input.trim() {
+                        "" => tr!("Zone {}", self.forbidden.len() + 1),
+                        n => n.to_string(),
+                    };
+                    self.forbidden.push(ForbiddenZone { name, bounds: self.current_bounds(), enabled: true });
+                    self.forbidden_name_input.clear();
+                    changed = true;
+                }
+            });
+            if changed { self.update_forbidden(); }
+        });
+    }
+
+    fn limits_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing(tr!("Safety limits"), |ui| {
+            ui.weak(tr!("Apply to every job, whatever its settings."));
+            let mut changed = false;
+            let mut limit = |ui: &mut egui::Ui, label: String, value: &mut Option<u32>, default: u32, unit: String| {
+                ui.horizontal(|ui| {
+                    let mut on = value.is_some();
+                    if ui.checkbox(&mut on, label).changed() {
+                        *value = on.then_some(default);
+                        changed = true;
+                    }
+                    if let Some(v) = value {
+                        changed |= ui.add(egui::DragValue::new(v).clamp_range(1..=1_000_000)).changed();
+                        ui.label(unit);
+                    }
+                });
+            };
+            limit(ui, tr!("At most"), &mut self.limits.per_minute, 300, tr!("clicks a minute"));
+            limit(ui, tr!("Stop after"), &mut self.limits.max_clicks, 10_000, tr!("clicks"));
+            limit(ui, tr!("Stop after"), &mut self.limits.max_minutes, 120, tr!("minutes"));
+            ui.add_enabled_ui(lock::SUPPORTED, |ui| {
+                changed |= ui.checkbox(&mut self.limits.pause_when_locked, tr!("Pause while the screen is locked or the screensaver is on")).changed();
+                if self.limits.pause_when_locked {
+                    changed |= ui.checkbox(&mut self.limits.confirm_unlock, tr!("After unlocking, wait for Resume")).changed();
+                }
+            }).response.on_disabled_hover_text(tr!("The lock screen can't be detected on this platform."));
+            changed |= ui.checkbox(&mut self.limits.pause_when_active, tr!("Pause while I'm using the keyboard or mouse"))
+                .on_hover_text(if activity::SUPPORTED {
+                    tr!("Clicks wait until there's been no input for a while, the click on Start included; the job's own input doesn't count")
+                } else {
+                    tr!("Only mouse movement is noticed on this platform")
+                })
+                .changed();
+            if self.limits.pause_when_active {
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Resume after"));
+                    changed |= ui.add(egui::DragValue::new(&mut self.limits.idle_secs).clamp_range(1..=3600)).changed();
+                    ui.label(tr!("s without input"));
+                });
+            }
+            if changed { self.update_limits(); }
+        });
+    }
+
+    /// Hand the safety limits to the engine (a running job picks them up too) and save them.
+    fn update_limits(&mut self) {
+        self.config.lock().limits = self.limits;
+        let Some(store) = &self.profiles else { return };
+        if let Err(e) = store.save_limits(&self.limits) { log::error!("Could not save safety limits: {}", e); }
+    }
+
+    /// The tick sound and the status dot for every click.
+    fn feedback_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing(tr!("Click feedback"), |ui| {
+            let mut changed = false;
+            changed |= ui.checkbox(&mut self.feedback.sound, tr!("Tick for every click"))
+                .on_hover_text(tr!("Hear that the job is still clicking while you watch another screen"))
+                .changed();
+            if self.feedback.sound {
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Sound command:"));
+                    changed |= ui.add(egui::TextEdit::singleline(&mut self.feedback.command).hint_text(tr!("system beep")))
+                        .on_hover_text(tr!("Run through the shell for every click; leave empty for the system beep"))
+                        .lost_focus();
+                    if self.feedback.command != feedback::DEFAULT_COMMAND && ui.small_button(tr!("Default")).clicked() {
+                        self.feedback.command = feedback::DEFAULT_COMMAND.to_string();
+                        changed = true;
+                    }
+                    if ui.small_button(tr!("Test")).clicked() { self.feedback.click(); }
+                });
+            }
+            changed |= ui.checkbox(&mut self.feedback.dot, tr!("Flash a dot next to the status for every click"))
+                .on_hover_text(tr!("Also shown in the compact window, which stays on top"))
+                .changed();
+            if changed { self.update_feedback(); }
+        });
+    }
+
+    /// Hand the click feedback to the engine (a running job picks it up too) and save it.
+    fn update_feedback(&mut self) {
+        self.config.lock().feedback = self.feedback.clone();
+        let Some(store) = &self.profiles else { return };
+        if let Err(e) = store.save_feedback(&self.feedback) { log::error!("Could not save click feedback: {}", e); }
+    }
+
+    /// The dot lit for a moment after each click, if click feedback asks for it.
+    fn click_dot_ui(&self, ui: &mut egui::Ui) {
+        if !self.feedback.dot { return; }
+        let Some(job) = self.job.as_ref().filter(|_| self.is_running()) else { return };
+        let last = job.progress.lock().history.back().map(|c| c.at);
+        let lit = feedback::dot_lit(last, Instant::now());
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(10.0, 10.0), Sense::hover());
+        let color = if lit { Color32::from_rgb(80, 220, 100) } else { ui.visuals().weak_text_color() };
+        ui.painter().circle_filled(rect.center(), if lit { 4.5 } else { 2.5 }, color);
+        ui.ctx().request_repaint_after(if lit { feedback::DOT_DURATION } else { std::time::Duration::from_millis(100) });
+    }
+
+    /// Gamepad buttons for start/stop, pause and stop, and the stick nudge.
+    fn gamepad_ui(&mut self, ui: &mut egui::Ui) {
+        ui.add_enabled_ui(gamepad::available(), |ui| {
+            for (label, id, button) in [
+                (tr!("Pad start/stop:"), "pad_start", &mut self.pad_start),
+                (tr!("Pad pause:"), "pad_pause", &mut self.pad_pause),
+                (tr!("Pad stop:"), "pad_stop", &mut self.pad_stop),
+            ] {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    egui::ComboBox::from_id_source(id)
+                        .selected_text(button.map_or(tr!("Off"), |b| b.label().to_string()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(button, None, tr!("Off"));
+                            for b in PadButton::ALL {
+                                ui.selectable_value(button, Some(b), b.label());
+                            }
+                        });
+                });
+            }
+            ui.checkbox(&mut self.pad_nudge, tr!("Left stick moves the selection"))
+                .on_hover_text(tr!("Nudge the area or point; a running job follows it"));
+        }).response.on_disabled_hover_text(tr!("Gamepads need a build with `--features gamepad`."));
+    }
+
+    /// Control socket and MIDI notes mapped to actions.
+    fn triggers_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing(tr!("External triggers"), |ui| {
+            let mut changed = false;
+            changed |= ui.checkbox(&mut self.triggers.remote, tr!("Take commands on the control socket"))
+                .on_hover_text(tr!("For `area_clicker send start <profile>`, `send stop` or `send step <n or name>`, e.g. run from a Stream Deck key"))
+                .changed();
+            if self.triggers.remote { ui.weak(self.control_socket.display().to_string()); }
+            ui.horizontal(|ui| {
+                ui.label(tr!("MIDI input:"));
+                let selected = self.triggers.midi_device.as_ref().map_or(tr!("Off"), |d| d.display().to_string());
+                egui::ComboBox::from_id_source("midi_device").selected_text(selected).show_ui(ui, |ui| {
+                    changed |= ui.selectable_value(&mut self.triggers.midi_device, None, tr!("Off")).changed();
+                    for d in triggers::midi_devices() {
+                        let label = d.display().to_string();
+                        changed |= ui.selectable_value(&mut self.triggers.midi_device, Some(d), label).changed();
+                    }
+                });
+                if ui.small_button("⟳").on_hover_text(tr!("Open the device again")).clicked() {
+                    self.midi = None;
+                    self.midi_error = None;
+                }
+            });
+            if let Some((_, e)) = &self.midi_error { ui.colored_label(Color32::RED, e); }
+            if self.midi.is_some() {
+                match self.last_note {
+                    Some(n) => ui.weak(tr!("Last note: {} (channel {})", n.note, n.channel)),
+                    None => ui.weak(tr!("Press a pad or key to see its note.")),
+                };
+            }
+            let mut remove = None;
+            for (i, b) in self.triggers.midi.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Note"));
+                    changed |= ui.add(egui::DragValue::new(&mut b.note).clamp_range(0..=127)).changed();
+                    egui::ComboBox::from_id_source(("midi_channel", i))
+                        .selected_text(b.channel.map_or(tr!("any channel"), |c| tr!("channel {}", c)))
+                        .show_ui(ui, |ui| {
+                            changed |= ui.selectable_value(&mut b.channel, None, tr!("any channel")).changed();
+                            for c in 1..=16 {
+                                changed |= ui.selectable_value(&mut b.channel, Some(c), tr!("channel {}", c)).changed();
+                            }
+                        });
+                    ui.label("→");
+                    egui::ComboBox::from_id_source(("midi_action", i))
+                        .selected_text(i18n::translate(b.action.label()))
+                        .show_ui(ui, |ui| {
+                            let first_profile = self.profile_names.first().cloned().unwrap_or_default();
+                            let first_step = self.steps.first().map(|s| s.name.clone()).unwrap_or_default();
+                            for action in [TriggerAction::StartProfile(first_profile), TriggerAction::Stop, TriggerAction::RunStep(first_step)] {
+                                let current = std::mem::discriminant(&b.action) == std::mem::discriminant(&action);
+                                if ui.selectable_label(current, i18n::translate(action.label())).clicked() && !current {
+                                    b.action = action;
+                                    changed = true;
+                                }
+                            }
+                        });
+                    let target: Option<(Vec<String>, _)> = match &mut b.action {
+                        TriggerAction::StartProfile(name) => Some((self.profile_names.clone(), name)),
+                        TriggerAction::RunStep(name) => Some((self.steps.iter().map(|s| s.name.clone()).collect(), name)),
+                        TriggerAction::Stop => None,
+                    };
+                    if let Some((names, name)) = target {
+                        egui::ComboBox::from_id_source(("midi_target", i)).selected_text(name.as_str()).show_ui(ui, |ui| {
+                            for n in names {
+                                let label = n.clone();
+                                changed |= ui.selectable_value(name, n, label).changed();
+                            }
+                        });
+                    }
+                    if ui.small_button("✖").clicked() { remove = Some(i); }
+                });
+            }
+            if let Some(i) = remove {
+                self.triggers.midi.remove(i);
+                changed = true;
+            }
+            if ui.button(tr!("+ MIDI binding")).on_hover_text(tr!("Starts with the last note played")).clicked() {
+                let (note, channel) = self.last_note.map_or((36, None), |n| (n.note, Some(n.channel)));
+                self.triggers.midi.push(MidiBinding { channel, note, action: TriggerAction::Stop });
+                changed = true;
+            }
+            ui.separator();
+            changed |= self.voice_ui(ui);
+            if changed { self.update_triggers(); }
+        });
+    }
+
+    /// Voice stop settings; true when they changed.
+    fn voice_ui(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        let mut restart = false;
+        ui.horizontal(|ui| {
+            restart |= ui.checkbox(&mut self.triggers.voice.enabled, tr!("Voice stop"))
+                .on_hover_text(tr!("Say the stop phrase to stop the job, for when both hands are busy. Speech is recognized on this computer."))
+                .changed();
+            if self.triggers.voice.enabled && ui.small_button("⟳").on_hover_text(tr!("Start the recognizer again")).clicked() {
+                restart = true;
+            }
+        });
+        if !self.triggers.voice.enabled { return restart; }
+        let voice = &mut self.triggers.voice;
+        // the recognizer is started again once an edit is done, not on every keystroke
+        ui.horizontal(|ui| {
+            ui.label(tr!("Stop phrase:"));
+            restart |= ui.text_edit_singleline(&mut voice.stop_phrase).lost_focus();
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr!("Start phrase:"));
+            changed |= ui.add(egui::TextEdit::singleline(&mut voice.start_phrase).hint_text(tr!("off")))
+                .on_hover_text(tr!("Only works with a recognizer that reports it; the default one listens for the stop phrase alone"))
+                .lost_focus();
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr!("Recognizer:"));
+            restart |= ui.text_edit_singleline(&mut voice.command)
+                .on_hover_text(tr!("A command that prints what it hears, a line at a time; {phrase} becomes the stop phrase"))
+                .lost_focus();
+            if voice.command != voice::DEFAULT_COMMAND && ui.small_button(tr!("Default")).clicked() {
+                voice.command = voice::DEFAULT_COMMAND.to_string();
+                restart = true;
+            }
+        });
+        if let Some(e) = &self.voice_error { ui.colored_label(Color32::RED, e); }
+        if self.voice.is_some() {
+            match &self.last_heard {
+                Some(line) => ui.weak(tr!("Last heard: {}", line)),
+                None => ui.weak(tr!("Listening…")),
+            };
+        }
+        if restart {
+            self.voice = None;
+            self.voice_error = None;
+        }
+        changed || restart
+    }
+
+    /// The remote viewer: how often the target is captured, and where it goes.
+    fn viewer_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing(tr!("Remote viewer"), |ui| {
+            let mut changed = ui.checkbox(&mut self.viewer_settings.enabled, tr!("Capture the clicked area"))
+                .on_hover_text(tr!("A thumbnail of where the job clicks, to check on it from another machine"))
+                .changed();
+            if self.viewer_settings.enabled {
+                let v = &mut self.viewer_settings;
+                // the viewer is started again once an edit is done, not on every keystroke
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Every"));
+                    changed |= ui.add(egui::DragValue::new(&mut v.every_secs).clamp_range(1..=3600).suffix(" s")).changed();
+                    ui.label(tr!("Margin"));
+                    changed |= ui.add(egui::DragValue::new(&mut v.margin).clamp_range(0..=500).suffix(" px")).changed();
+                });
+                ui.horizontal(|ui| {
+                    let mut serve = v.listen.is_some();
+                    changed |= ui.checkbox(&mut serve, tr!("Serve on")).changed();
+                    if serve != v.listen.is_some() {
+                        v.listen = serve.then(|| ViewerSettings::default().listen.unwrap_or_default());
+                    }
+                    if let Some(addr) = &mut v.listen {
+                        changed |= ui.add(egui::TextEdit::singleline(addr).desired_width(140.0))
+                            .on_hover_text(tr!("127.0.0.1 is this machine only; any other address needs a token"))
+                            .lost_focus();
+                    }
+                });
+                if v.listen.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.label(tr!("Token"));
+                        changed |= ui.add(egui::TextEdit::singleline(&mut v.token).password(true).desired_width(140.0))
+                            .on_hover_text(tr!("Asked for in the viewer's address; needed for any address but 127.0.0.1"))
+                            .lost_focus();
+                        if ui.button(tr!("New token")).clicked() {
+                            v.token = viewer::new_token();
+                            changed = true;
+                        }
+                    });
+                }
+                ui.horizontal(|ui| {
+                    let mut save = v.folder.is_some();
+                    changed |= ui.checkbox(&mut save, tr!("Save to folder")).changed();
+                    if save != v.folder.is_some() {
+                        v.folder = save.then(|| screenshot::default_dir().unwrap_or_default().join("viewer"));
+                    }
+                    if let Some(folder) = &mut v.folder {
+                        let mut text = folder.display().to_string();
+                        let edit = ui.add(egui::TextEdit::singleline(&mut text).desired_width(220.0));
+                        if edit.changed() { *folder = PathBuf::from(text); }
+                        changed |= edit.lost_focus();
+                    }
+                });
+                if let Some(addr) = self.viewer.as_ref().and_then(|v| v.addr) {
+                    let token = self.viewer_settings.token.trim();
+                    let url = if token.is_empty() { format!("http://{}/", addr) } else { format!("http://{}/?token={}", addr, token) };
+                    ui.hyperlink_to(format!("http://{}/", addr), url);
+                }
+                if let Some(e) = &self.viewer_error { ui.colored_label(Color32::LIGHT_RED, e); }
+            }
+            if changed {
+                self.viewer = None;
+                self.viewer_error = None;
+                self.update_viewer();
+            }
+        });
+    }
+
+    fn update_viewer(&mut self) {
+        let Some(store) = &self.profiles else { return };
+        if let Err(e) = store.save_viewer(&self.viewer_settings) { log::error!("Could not save the remote viewer settings: {}", e); }
+    }
+
+    /// What the step editor starts a new step with.
+    fn step_defaults_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing(tr!("New steps"), |ui| {
+            let d = &mut self.step_defaults;
+            let mut changed = false;
+            ui.horizontal(|ui| {
+                ui.label(tr!("Clicks"));
+                changed |= ui.add(egui::DragValue::new(&mut d.clicks).speed(1.0).clamp_range(1..=1000000)).changed();
+                ui.label(tr!("Button:"));
+                changed |= ui.radio_value(&mut d.button, ClickButton::Left, tr!("Left")).changed();
+                changed |= ui.radio_value(&mut d.button, ClickButton::Right, tr!("Right")).changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr!("Interval (seconds):"));
+                changed |= ui.add(egui::DragValue::new(&mut d.min_secs).speed(0.1).clamp_range(0.0..=f32::MAX)).changed();
+                ui.label(tr!("to"));
+                changed |= ui.add(egui::DragValue::new(&mut d.max_secs).speed(0.1).clamp_range(0.0..=f32::MAX)).changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr!("Name:"));
+                changed |= ui.add(egui::TextEdit::singleline(&mut d.name).hint_text(tr!("Step {n}")).desired_width(160.0))
+                    .on_hover_text(tr!("Used when a step is added without a name; {n} becomes its number"))
+                    .lost_focus();
+            });
+            if ui.button(tr!("Use the step editor's values")).clicked() {
+                d.clicks = self.step_clicks;
+                d.min_secs = self.step_min_secs;
+                d.max_secs = self.step_max_secs;
+                d.button = if self.step_button_left { ClickButton::Left } else { ClickButton::Right };
+                changed = true;
+            }
+            if changed { self.update_step_defaults(); }
+        });
+    }
+
+    fn update_step_defaults(&mut self) {
+        // a step being edited keeps its values; the blank editor takes the new defaults
+        if self.editing_step_idx.is_none() {
+            let d = &self.step_defaults;
+            self.step_clicks = d.clicks;
+            self.step_min_secs = d.min_secs;
+            self.step_max_secs = d.max_secs;
+            self.step_button_left = d.button == ClickButton::Left;
+        }
+        let Some(store) = &self.profiles else { return };
+        if let Err(e) = store.save_step_defaults(&self.step_defaults) { log::error!("Could not save the step defaults: {}", e); }
+    }
+
+    fn update_triggers(&mut self) {
+        let Some(store) = &self.profiles else { return };
+        if let Err(e) = store.save_triggers(&self.triggers) { log::error!("Could not save triggers: {}", e); }
+    }
+
+    /// What the safety limits would cut short in the job Start would run.
+    fn limit_warnings(&self) -> Vec<String> {
+        let per_minute = |min: f32, max: f32| 60.0 / min.min(max).max(0.01);
+        let longest = self.min_secs.max(self.max_secs);
+        let (rate, clicks, secs) = match self.mode {
+            JobMode::Single => {
+                let n = self.use_finite_clicks.then_some(self.num_clicks);
+                (per_minute(self.min_secs, self.max_secs), n, n.map(|n| longest * n as f32))
+            }
+            JobMode::GridSweep => {
+                let cells = self.grid_cols.max(1) * self.grid_rows.max(1);
+                (per_minute(self.min_secs, self.max_secs), Some(cells), Some(longest * cells as f32))
+            }
+            JobMode::Sequence => {
+                let clicking = || self.steps.iter().filter(|s| s.clicks_mouse());
+                let rate = clicking().map(|s| per_minute(s.min_secs, s.max_secs)).fold(0.0, f32::max);
+                let cycles = self.use_finite_cycles.then_some(self.num_cycles);
+                let clicks = cycles.map(|c| clicking().map(|s| s.clicks).sum::<u32>() * c);
+                (rate, clicks, cycles.map(|c| sequence_duration(&self.steps, c)[2]))
+            }
+            JobMode::AntiAfk => return Vec::new(),
+        };
+        self.limits.warnings(rate, clicks, secs)
+    }
+
+    /// The named area library: save the selection under a name, load or update entries.
+    fn areas_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing(tr!("Areas ({})", self.areas.len()), |ui| {
+            let mut load = None;
+            let mut update = None;
+            let mut delete = None;
+            for (name, b) in &self.areas {
+                let users = self.steps.iter().filter(|s| s.area.as_ref() == Some(name)).count();
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} [{}..{}]x[{}..{}]", name, b.min_x, b.max_x, b.min_y, b.max_y));
+                    if users > 0 { ui.weak(tr!("{} steps", users)); }
+                    if ui.small_button(tr!("Use")).on_hover_text(tr!("Copy into the selection")).clicked() { load = Some(*b); }
+                    if ui.small_button(tr!("Update")).on_hover_text(tr!("Replace with the current selection")).clicked() {
+                        update = Some(name.clone());
+                    }
+                    if ui.small_button("✖").clicked() { delete = Some(name.clone()); }
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.area_name_input).hint_text(tr!("area name")).desired_width(120.0));
+                let name = self.area_name_input.trim().to_string();
+                let label = if self.areas.contains_key(&name) { tr!("Update area") } else { tr!("Save selection as area") };
+                if ui.add_enabled(!name.is_empty() && !self.use_point, egui::Button::new(label)).clicked() {
+                    self.save_area(&name);
+                    self.area_name_input.clear();
+                }
+            });
+            if let Some(b) = load {
+                self.use_point = false;
+                self.bounds_inputs = [b.min_x, b.max_x, b.min_y, b.max_y];
+            }
+            if let Some(name) = update { self.save_area(&name); }
+            if let Some(name) = delete { self.delete_area(&name); }
+        });
+    }
+
+    /// Selection shortcuts and the changes to apply to all selected steps.
+    /// `visible` are the steps the filter currently shows.
+    fn bulk_edit_ui(&mut self, ui: &mut egui::Ui, visible: &[usize]) {
+        ui.horizontal(|ui| {
+            ui.label(tr!("{} selected", self.selected_steps.len()));
+            if ui.small_button(tr!("Select shown")).clicked() { self.selected_steps.extend(visible); }
+            if ui.small_button(tr!("Select none")).clicked() { self.selected_steps.clear(); }
+        });
+        if self.selected_steps.is_empty() { return; }
+        ui.collapsing("Bulk edit selected steps", |ui| {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.bulk.set_interval, tr!("Interval"));
+                ui.add_enabled_ui(self.bulk.set_interval, |ui| {
+                    ui.add(egui::DragValue::new(&mut self.bulk.min_secs).speed(0.1).clamp_range(0.0..=3600.0));
+                    ui.label(tr!("to"));
+                    ui.add(egui::DragValue::new(&mut self.bulk.max_secs).speed(0.1).clamp_range(0.0..=3600.0));
+                    ui.label("s");
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.bulk.set_button, tr!("Button"));
+                ui.add_enabled_ui(self.bulk.set_button, |ui| {
+                    ui.radio_value(&mut self.bulk.button, ClickButton::Left, tr!("Left"));
+                    ui.radio_value(&mut self.bulk.button, ClickButton::Right, tr!("Right"));
+                });
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.bulk.set_clicks, tr!("Clicks"));
+                ui.add_enabled_ui(self.bulk.set_clicks, |ui| {
+                    ui.add(egui::DragValue::new(&mut self.bulk.clicks).speed(1.0).clamp_range(1..=1000000));
+                });
+            });
+            let label = tr!("Apply to {} steps", self.selected_steps.len());
+            if ui.add_enabled(!self.bulk.is_empty(), egui::Button::new(label)).clicked() {
+                self.apply_bulk_edit();
+            }
+        });
+    }
+
+    /// Duplicate the whole sequence shifted by (dx, dy) or onto another monitor, e.g. for a
+    /// second copy of the same app window.
+    fn clone_offset_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing(tr!("Clone with offset"), |ui| {
+            let c = &mut self.clone_offset;
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut c.onto_monitor, false, tr!("Shift by"));
+                ui.add_enabled_ui(!c.onto_monitor, |ui| {
+                    ui.label("dx");
+                    ui.add(egui::DragValue::new(&mut c.dx).speed(1.0));
+                    ui.label("dy");
+                    ui.add(egui::DragValue::new(&mut c.dy).speed(1.0));
+                });
+            });
+            ui.horizontal(|ui| {
+                let several = self.monitors.len() > 1;
+                let onto = ui.add_enabled(several, egui::RadioButton::new(c.onto_monitor, tr!("Move from")))
+                    .on_disabled_hover_text(tr!("Only one monitor is connected"));
+                if onto.clicked() { c.onto_monitor = true; }
+                ui.add_enabled_ui(c.onto_monitor && several, |ui| {
+                    let name = |i: usize| self.monitors.get(i).map_or_else(|| "—".to_string(), |m| format!("{}: {}", i + 1, m.name));
+                    egui::ComboBox::from_id_source("clone_from").selected_text(name(c.from)).show_ui(ui, |ui| {
+                        for i in 0..self.monitors.len() { ui.selectable_value(&mut c.from, i, name(i)); }
+                    });
+                    ui.label(tr!("to"));
+                    egui::ComboBox::from_id_source("clone_to").selected_text(name(c.to)).show_ui(ui, |ui| {
+                        for i in 0..self.monitors.len() { ui.selectable_value(&mut c.to, i, name(i)); }
+                    });
+                });
+            });
+            let ready = self.offset_copies().is_some();
+            ui.horizontal(|ui| {
+                let append = ui.add_enabled(ready, egui::Button::new(tr!("Append copies")))
+                    .on_hover_text(tr!("Add moved copies of all {} steps after the last one", self.steps.len()));
+                if append.clicked() { self.append_offset_copies(); }
+            });
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.clone_offset.profile_name).hint_text(tr!("New profile")).desired_width(120.0));
+                let name = self.clone_offset.profile_name.trim().to_string();
+                let free = !name.is_empty() && !self.profile_names.contains(&name);
+                let save = ui.add_enabled(ready && free && self.profiles.is_some(), egui::Button::new(tr!("Save as profile")))
+                    .on_hover_text(tr!("Save these settings with the moved steps under a new name; this profile stays open"));
+                if save.clicked() {
+                    self.save_offset_copies(&name);
+                    self.clone_offset.profile_name.clear();
+                }
+            });
+        });
+    }
+
+    /// Mark the enabled forbidden zones on the picker overlay.
+    fn draw_forbidden(&self, painter: &egui::Painter, screen_rect: Rect, ctx: &egui::Context) {
+        let ppp = ctx.pixels_per_point().max(0.1);
+        let origin_px = self.picker_origin_px();
+        let to_screen = |px: (i32, i32)| screen_rect.min + physical_to_logical(px, ppp, origin_px, &self.monitors).to_vec2();
+        for z in self.forbidden.iter().filter(|z| z.enabled) {
+            let b = z.bounds;
+            let rect = Rect::from_two_pos(to_screen((b.min_x, b.min_y)), to_screen((b.max_x, b.max_y)));
+            painter.rect_filled(rect, 0.0, Color32::from_rgba_unmultiplied(200, 0, 0, 60));
+            painter.rect_stroke(rect, 0.0, egui::Stroke { width: 1.5, color: Color32::RED });
+            painter.text(rect.left_top() + egui::vec2(3.0, 2.0), egui::Align2::LEFT_TOP, format!("⛔ {}", z.name), egui::FontId::proportional(12.0), Color32::RED);
+        }
+    }
+
+    /// Outline every step's target in its color on the picker overlay.
+    fn draw_step_preview(&self, painter: &egui::Painter, screen_rect: Rect, ctx: &egui::Context) {
+        let ppp = ctx.pixels_per_point().max(0.1);
+        let origin_px = self.picker_origin_px();
+        let to_screen = |px: (i32, i32)| screen_rect.min + physical_to_logical(px, ppp, origin_px, &self.monitors).to_vec2();
+        for (i, s) in self.steps.iter().enumerate() {
+            let color = s.color(i);
+            let (anchor, label) = match s.target {
+                StepTarget::Area | StepTarget::Screenshot { full_screen: false } | StepTarget::WaitImage(_) => {
+                    let b = s.bounds;
+                    let rect = Rect::from_two_pos(to_screen((b.min_x, b.min_y)), to_screen((b.max_x, b.max_y)));
+                    painter.rect_filled(rect, 0.0, color.gamma_multiply(0.15));
+                    painter.rect_stroke(rect, 0.0, egui::Stroke { width: 2.0, color });
+                    let kind = match s.target {
+                        StepTarget::Screenshot { .. } => tr!(" (screenshot)"),
+                        StepTarget::WaitImage(_) => tr!(" (wait for image)"),
+                        _ => String::new(),
+                    };
+                    (rect.left_top(), format!("{}. {}{}", i + 1, s.name, kind))
+                }
+                StepTarget::Point(p) => {
+                    let pos = to_screen((p.x, p.y));
+                    painter.circle_stroke(pos, 6.0 + p.jitter as f32 / ppp, egui::Stroke { width: 2.0, color });
+                    (pos + egui::vec2(8.0, -8.0), format!("{}. {}", i + 1, s.name))
+                }
+                StepTarget::Offset { .. } => continue, // depends on where the previous click lands
+                StepTarget::Screenshot { full_screen: true } => continue,
+            };
+            painter.text(anchor + egui::vec2(2.0, -2.0), egui::Align2::LEFT_BOTTOM, label, egui::FontId::proportional(13.0), color);
+        }
+    }
+
+    /// The sequence as a strip of blocks sized by expected duration; the running step is highlighted.
+    fn timeline_ui(&self, ui: &mut egui::Ui) {
+        let total: f32 = self.steps.iter().map(SequenceStep::expected_secs).sum();
+        if self.steps.is_empty() || total <= 0.0 { return; }
+        let current = (self.is_running() && self.mode == JobMode::Sequence)
+            .then(|| self.job.as_ref().map(|j| j.progress.lock().step))
+            .flatten();
+
+        let (rect, response) = ui.allocate_exact_size(egui::vec2(ui.available_width(), 24.0), Sense::hover());
+        let painter = ui.painter_at(rect);
+        let mut x = rect.left();
+        let mut hovered = None;
+        for (i, s) in self.steps.iter().enumerate() {
+            let w = rect.width() * s.expected_secs() / total;
+            let block = Rect::from_min_max(Pos2::new(x, rect.top()), Pos2::new(x + w, rect.bottom())).shrink2(egui::vec2(1.0, 0.0));
+            x += w;
+            if current == Some(i) {
+                painter.rect_filled(block, 2.0, s.color(i));
+                painter.rect_stroke(block, 2.0, egui::Stroke { width: 2.0, color: Color32::WHITE });
+            } else {
+                painter.rect_filled(block, 2.0, s.color(i).gamma_multiply(0.55));
+            }
+            painter.with_clip_rect(block).text(
+                block.left_center() + egui::vec2(4.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                format!("{}. {}", i + 1, s.name),
+                egui::FontId::proportional(12.0),
+                Color32::WHITE,
+            );
+            if response.hover_pos().is_some_and(|p| block.contains(p)) { hovered = Some(i); }
+        }
+        if let Some(i) = hovered {
+            let s = &self.steps[i];
+            response.on_hover_text(tr!("{}. {} — {} clicks, ~{}s", i + 1, s.name, s.clicks, format!("{:.0}", s.expected_secs())));
+        }
+    }
+
+    /// Min/mean/max run time of the configured sequence, recomputed every frame.
+    fn duration_ui(&self, ui: &mut egui::Ui) {
+        if self.steps.is_empty() { return; }
+        let cycles = if self.use_finite_cycles { self.num_cycles } else { 1 };
+        let [lo, mid, hi] = sequence_duration(&self.steps, cycles);
+        let what = if self.use_finite_cycles { tr!("{} cycles", cycles) } else { tr!("each cycle") };
+        ui.label(tr!(
+            "Estimated duration ({}): {} – {}, typically {}",
+            what, format_duration(lo), format_duration(hi), format_duration(mid),
+        ));
+        if self.use_fatigue || self.use_warm_up {
+            ui.weak(tr!("Fatigue and warm-up slow clicking down and aren't included."));
+        }
+    }
+
+    fn sequence_ui(&mut self, ui: &mut egui::Ui) {
+        let editable = self.editable();
+        ui.group(|ui| {
+            ui.label(tr!("Sequence steps"));
+            let notes = if self.sequence_notes.trim().is_empty() { tr!("Notes") } else { tr!("Notes ✎") };
+            egui::CollapsingHeader::new(notes).id_source("sequence_notes").show(ui, |ui| {
+                ui.add(egui::TextEdit::multiline(&mut self.sequence_notes)
+                    .interactive(editable)
+                    .hint_text(tr!("What this sequence is for, what it expects on screen…"))
+                    .desired_rows(3)
+                    .desired_width(f32::INFINITY));
+            });
+            self.timeline_ui(ui);
+            self.duration_ui(ui);
+            let mut edit = None;
+            let mut remove = None;
+            let mut swap = None;
+            ui.horizontal(|ui| {
+                ui.label(tr!("Filter"));
+                ui.add(egui::TextEdit::singleline(&mut self.step_filter).hint_text(tr!("name, button or bounds")));
+                if !self.step_filter.is_empty() && ui.small_button("✖").clicked() { self.step_filter.clear(); }
+            });
+            let filter = self.step_filter.trim();
+            let mut shown = 0;
+            let mut visible = Vec::new();
+            let mut edited = None;
+            egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                for i in 0..self.steps.len() {
+                    let s = &self.steps[i];
+                    // match the summary only, not the list number in front of it
+                    let number = format!("{}. ", i + 1);
+                    let text = number.clone() + &s.summary();
+                    let ranges: Vec<_> = match_ranges(&text[number.len()..], filter)
+                        .into_iter()
+                        .map(|r| r.start + number.len()..r.end + number.len())
+                        .collect();
+                    if !filter.is_empty() && ranges.is_empty() { continue; }
+                    shown += 1;
+                    visible.push(i);
+                    let mut expanded = self.expanded_steps.contains(&i);
+                    ui.horizontal(|ui| {
+                        let toggle = ui.add_enabled(editable, egui::Button::new(if expanded { "▾" } else { "▸" }).small());
+                        if toggle.on_hover_text(tr!("Edit clicks, interval and button here")).clicked() {
+                            expanded = !expanded;
+                            if expanded { self.expanded_steps.insert(i); } else { self.expanded_steps.remove(&i); }
+                        }
+                        let mut ticked = self.selected_steps.contains(&i);
+                        if ui.add_enabled(editable, egui::Checkbox::new(&mut ticked, "")).on_hover_text(tr!("Select for bulk edit")).changed() {
+                            if ticked { self.selected_steps.insert(i); } else { self.selected_steps.remove(&i); }
+                        }
+                        let (swatch, _) = ui.allocate_exact_size(egui::vec2(10.0, 10.0), Sense::hover());
+                        ui.painter().rect_filled(swatch, 2.0, s.color(i));
+                        let selected = self.editing_step_idx == Some(i) || self.step_cursor == Some(i);
+                        let mut label = ui.add_enabled(editable, egui::SelectableLabel::new(selected, highlighted(&text, &ranges, ui)));
+                        if !s.note.is_empty() {
+                            ui.weak("🗒");
+                            label = label.on_hover_text(s.note.as_str()).on_disabled_hover_text(s.note.as_str());
+                        }
+                        if self.scroll_to_cursor && self.step_cursor == Some(i) {
+                            label.scroll_to_me(None);
+                            self.scroll_to_cursor = false;
+                        }
+                        ui.add_enabled_ui(editable, |ui| {
+                            if label.clicked() || ui.small_button(tr!("Edit")).clicked() { edit = Some(i); }
+                            if ui.small_button("▲").clicked() && i > 0 { swap = Some((i - 1, i)); }
+                            if ui.small_button("▼").clicked() && i + 1 < self.steps.len() { swap = Some((i, i + 1)); }
+                            if ui.small_button("✖").clicked() { remove = Some(i); }
+                        });
+                    });
+                    if expanded && editable && inline_step_ui(ui, &mut self.steps[i]) { edited = Some(i); }
+                }
+                if self.steps.is_empty() {
+                    ui.label(tr!("No steps yet."));
+                } else if shown == 0 {
+                    ui.label(tr!("No steps match the filter."));
+                }
+            });
+            if !filter.is_empty() { ui.weak(tr!("{} of {} steps", shown, self.steps.len())); }
+            if !self.steps.is_empty() {
+                ui.weak(tr!("↑↓ select · Enter edit · Esc back to the list · Del remove · Ctrl+D duplicate · Ctrl+↑↓ move"));
+            }
+            if let Some(i) = edited { self.step_edited_inline(i); }
+            if let Some(i) = edit {
+                self.step_cursor = Some(i);
+                self.edit_step(i);
+            }
+            if let Some((a, b)) = swap { self.swap_steps(a, b); }
+            if let Some(i) = remove { self.remove_step(i); }
+            if !self.steps.is_empty() && editable {
+                self.bulk_edit_ui(ui, &visible);
+                self.clone_offset_ui(ui);
+            }
+        });
+
+        ui.separator();
+
+        ui.add_enabled_ui(editable, |ui| ui.group(|ui| {
+            ui.label(if self.editing_step_idx.is_some() { tr!("Edit step") } else { tr!("Add step") });
+            ui.horizontal(|ui| {
+                ui.label(tr!("Name"));
+                let name = ui.text_edit_singleline(&mut self.step_name);
+                if std::mem::take(&mut self.focus_step_editor) { name.request_focus(); }
+                ui.color_edit_button_srgb(&mut self.step_color).on_hover_text(tr!("Color in the list, timeline and picker"));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr!("Comment"));
+                ui.add(egui::TextEdit::multiline(&mut self.step_note).hint_text(tr!("why this step is here")).desired_rows(1));
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr!("Kind"));
+                egui::ComboBox::from_id_source("step_kind")
+                    .selected_text(self.step_kind.label())
+                    .show_ui(ui, |ui| {
+                        for k in StepKind::ALL { ui.selectable_value(&mut self.step_kind, k, k.label()); }
+                    });
+                if self.step_kind == StepKind::Screenshot { ui.checkbox(&mut self.step_shot_full, tr!("Full screen")); }
+            });
+            if self.step_kind == StepKind::WaitImage {
+                self.wait_image_ui(ui);
+            } else {
+                ui.horizontal(|ui| {
+                    ui.label(if self.step_kind == StepKind::Screenshot { tr!("Shots") } else { tr!("Clicks") });
+                    ui.add(egui::DragValue::new(&mut self.step_clicks).speed(1.0).clamp_range(1..=1000000));
+                    if self.step_kind == StepKind::Click { ui.checkbox(&mut self.step_button_left, tr!("Left button")); }
+                });
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Interval (seconds):"));
+                    ui.add(egui::DragValue::new(&mut self.step_min_secs).speed(0.1));
+                    ui.label(tr!("to"));
+                    ui.add(egui::DragValue::new(&mut self.step_max_secs).speed(0.1));
+                });
+            }
+            if self.step_kind == StepKind::Click {
+                self.verify_ui(ui);
+                let steps = self.steps.len();
+                ui.horizontal(|ui| {
+                    ui.label(tr!("On error: retry"));
+                    ui.add(egui::DragValue::new(&mut self.step_on_error.retries).clamp_range(0..=100))
+                        .on_hover_text(tr!("Bad bounds, failed check, missing window or text"));
+                    ui.label(tr!("times, then"));
+                    branch_ui(ui, "on_error", &mut self.step_on_error.then, steps);
+                });
+            }
+            ui.add_enabled_ui(hotkeys::available(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Hotkey:"));
+                    egui::ComboBox::from_id_source("step_hotkey")
+                        .selected_text(self.step_hotkey.map_or(tr!("Off"), |k| k.label().to_string()))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut self.step_hotkey, None, tr!("Off"));
+                            for k in HotKey::ALL {
+                                ui.selectable_value(&mut self.step_hotkey, Some(k), k.label());
+                            }
+                        })
+                        .response
+                        .on_hover_text(tr!("Pressing it runs just this step once, with its clicks and timing, whether or not a job is running"));
+                    let global = [Some(self.start_key), Some(self.capture_key), self.skip_key, self.repeat_key];
+                    if self.step_hotkey.is_some() && global.contains(&self.step_hotkey) {
+                        ui.colored_label(Color32::YELLOW, tr!("⚠ also bound to a global action"));
+                    }
+                });
+            }).response.on_disabled_hover_text(tr!("Global hotkeys need a build with `--features hooks`."));
+            if self.step_order == StepOrder::Weighted {
+                ui.horizontal(|ui| {
+                    ui.label(tr!("Weight:"));
+                    ui.add(egui::DragValue::new(&mut self.step_weight).speed(0.1).clamp_range(0.0..=1000.0))
+                        .on_hover_text(tr!("How often weighted order draws this step compared to the others; 0 leaves it out"));
+                });
+            }
+            ui.collapsing(tr!("Weighted zones ({})", self.step_zones.len()), |ui| {
+                ui.label(tr!("Fractions (0–1) of the step's area; a zone is picked by weight, then a point inside it."));
+                let mut remove = None;
+                for (i, z) in self.step_zones.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label("x");
+                        ui.add(egui::DragValue::new(&mut z.x0).speed(0.01).clamp_range(0.0..=1.0));
+                        ui.add(egui::DragValue::new(&mut z.x1).speed(0.01).clamp_range(0.0..=1.0));
+                        ui.label("y");
+                        ui.add(egui::DragValue::new(&mut z.y0).speed(0.01).clamp_range(0.0..=1.0));
+                        ui.add(egui::DragValue::new(&mut z.y1).speed(0.01).clamp_range(0.0..=1.0));
+                        ui.label(tr!("weight"));
+                        ui.add(egui::DragValue::new(&mut z.weight).speed(1.0).clamp_range(0.0..=1000.0));
+                        if ui.small_button("✖").clicked() { remove = Some(i); }
+                    });
+                }
+                if let Some(i) = remove { self.step_zones.remove(i); }
+                ui.horizontal(|ui| {
+                    if ui.button(tr!("+ Zone")).clicked() { self.step_zones.push(Zone::full(1.0)); }
+                    if ui.button(tr!("Center band 70/30")).clicked() { self.step_zones = center_band(); }
+                    if ui.button(tr!("Clear")).clicked() { self.step_zones.clear(); }
+                });
+            });
+            if self.step_kind == StepKind::Screenshot {
+                let what = if self.step_shot_full { tr!("every screen") } else { tr!("the current selection") };
+                ui.label(tr!("Saves {} to the screenshot folder.", what));
+            } else if self.step_kind == StepKind::WaitImage {
+                ui.label(tr!("Watches the current selection until the image shows up."));
+            } else {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.step_use_offset, tr!("Relative to previous click:"));
+                    ui.add_enabled_ui(self.step_use_offset, |ui| {
+                        ui.label(tr!("dx"));
+                        ui.add(egui::DragValue::new(&mut self.step_offset[0]));
+                        ui.label(tr!("dy"));
+                        ui.add(egui::DragValue::new(&mut self.step_offset[1]));
+                        ui.label("±");
+                        ui.add(egui::DragValue::new(&mut self.step_offset[2]).clamp_range(0..=200));
+                    });
+                });
+                if !self.step_use_offset {
+                    ui.horizontal(|ui| {
+                        ui.label(tr!("Target:"));
+                        egui::ComboBox::from_id_source("step_area")
+                            .selected_text(self.step_area.as_deref().map_or(tr!("Current selection"), |n| tr!("Area “{}”", n)))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut self.step_area, None, tr!("Current selection"));
+                                for name in self.areas.keys() {
+                                    ui.selectable_value(&mut self.step_area, Some(name.clone()), name);
+                                }
+                            });
+                    });
+                    if self.step_area.is_none() {
+                        ui.label(tr!("Uses the current selection (area or point) as the step's target."));
+                    } else {
+                        ui.label(tr!("Follows the library area: updating the area updates this step."));
+                    }
+                    if !self.use_point {
+                        ui.horizontal(|ui| {
+                            ui.label(tr!("Find text:"));
+                            ui.add(egui::TextEdit::singleline(&mut self.step_text).hint_text(tr!("e.g. Confirm")).desired_width(140.0))
+                                .on_hover_text(tr!("Read the area with OCR (tesseract) and click this word or phrase wherever it is"));
+                        });
+                    }
+                }
+            }
+            ui.horizontal(|ui| {
+                let label = if self.editing_step_idx.is_some() { tr!("Save step") } else { tr!("Add step") };
+                // done with the editor: the keys go back to the step list
+                let save = ui.button(label);
+                if save.clicked() {
+                    self.save_step();
+                    save.surrender_focus();
+                }
+                if self.editing_step_idx.is_some() {
+                    let cancel = ui.button(tr!("Cancel"));
+                    if cancel.clicked() {
+                        self.editing_step_idx = None;
+                        self.reset_step_draft();
+                        cancel.surrender_focus();
+                    }
+                }
+            });
+            self.step_templates_ui(ui);
+        }))
+        .response
+        .on_disabled_hover_text(tr!("The profile is locked; unlock it to edit its steps."));
+    }
+
+    /// The step template library: save the editor's click settings under a name, add steps
+    /// from a template at the editor's target.
+    fn step_templates_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing(tr!("Step templates ({})", self.step_templates.len()), |ui| {
+            let mut insert = None;
+            let mut update = None;
+            let mut delete = None;
+            let adding = self.editing_step_idx.is_none();
+            for (name, t) in &self.step_templates {
+                let users = self.steps.iter().filter(|s| s.template.as_ref() == Some(name)).count();
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} — {}x {:?} {:.1}-{:.1}s", name, t.clicks, t.button, t.min_secs, t.max_secs));
+                    if users > 0 { ui.weak(tr!("{} steps", users)); }
+                    let add = ui.add_enabled(adding, egui::Button::new(tr!("Insert")).small())
+                        .on_hover_text(tr!("Add a step with these settings at the editor's target"))
+                        .on_disabled_hover_text(tr!("Save or cancel the step being edited first."));
+                    if add.clicked() { insert = Some(name.clone()); }
+                    if ui.small_button(tr!("Update")).on_hover_text(tr!("Replace with the editor's settings; linked steps follow")).clicked() {
+                        update = Some(name.clone());
+                    }
+                    if ui.small_button("✖").clicked() { delete = Some(name.clone()); }
+                });
+            }
+            ui.checkbox(&mut self.template_linked, tr!("Inserted steps follow later template edits"));
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.template_name_input).hint_text(tr!("template name")).desired_width(120.0));
+                let name = self.template_name_input.trim().to_string();
+                let label = if self.step_templates.contains_key(&name) { tr!("Update template") } else { tr!("Save editor as template") };
+                if ui.add_enabled(!name.is_empty(), egui::Button::new(label))
+                    .on_hover_text(tr!("Button, clicks, interval, zones, error handling, weight and color; not the target"))
+                    .clicked()
+                {
+                    self.save_template(&name);
+                    self.template_name_input.clear();
+                }
+            });
+            if let Some(name) = insert { self.insert_template(&name); }
+            if let Some(name) = update { self.save_template(&name); }
+            if let Some(name) = delete { self.delete_template(&name); }
+        });
+    }
+}
+
 impl eframe::App for AppState {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // clean exit: nothing to recover next time
+        if let Some(store) = &self.profiles {
+            if let Err(e) = store.clear_recovery() { log::warn!("Could not remove recovery file: {}", e); }
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        self.apply_theme(ctx, frame.info().system_theme);
+        // the listener wakes us up for every press, even while the window is in the background
+        if hotkeys::available() { self.handle_hotkeys(); }
+        if gamepad::available() { self.handle_gamepad(ctx); }
+        if let Some(e) = input::take_failure() { self.input_error = Some(e); }
+        self.poll_ydotool_check();
+
+        self.poll_autostart(ctx);
+        self.poll_window_pick(ctx);
+        self.poll_color_pick(ctx);
+        self.poll_template_pick(ctx);
+        self.poll_test_click(ctx);
+        self.poll_profile_file(ctx);
+        self.poll_path_recorder(ctx);
+        self.poll_triggers(ctx);
+        self.poll_viewer();
+        self.poll_cursor_readout(ctx);
+        if std::mem::take(&mut self.raise_for_start) {
+            // the compact bar has no room for the summary
+            if self.compact { self.exit_compact(ctx); }
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        if self.job.is_some() && !self.is_running() { self.take_run_stats(); }
+        self.update_window_title(ctx);
+        if self.autosave_at.elapsed() >= AUTOSAVE_INTERVAL { self.autosave(); }
+        ctx.request_repaint_after(AUTOSAVE_INTERVAL);
+
         // -------- Picker Overlay --------
+        if self.pick_only && !self.picking_area {
+            self.enter_picker(ctx);
+        }
+        if self.picking_area && ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            if self.pick_only {
+                log::info!("Pick cancelled");
+                ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            } else {
+                self.exit_picker(ctx);
+            }
+        }
         if self.picking_area {
             let screen_rect = ctx.screen_rect();
             let layer_id = egui::LayerId::new(egui::Order::Foreground, egui::Id::new("picker"));
             let painter = egui::Painter::new(ctx.clone(), layer_id, egui::Rect::EVERYTHING);
 
-            // Gray translucent overlay
-            painter.rect_filled(
-                screen_rect,
-                0.0,
-                Color32::from_rgba_premultiplied(128, 128, 128, 100),
-            );
+            // Gray translucent overlay
+            painter.rect_filled(
+                screen_rect,
+                0.0,
+                Color32::from_rgba_premultiplied(128, 128, 128, 100),
+            );
+
+            if let Some((px, until)) = self.flash {
+                let pos = self.overlay_pos(px, ctx.pixels_per_point().max(0.1), screen_rect);
+                let stroke = egui::Stroke { width: 3.0, color: Color32::YELLOW };
+                painter.circle_stroke(pos, 10.0, stroke);
+                painter.circle_stroke(pos, 22.0, egui::Stroke { width: 1.5, color: Color32::YELLOW });
+                painter.text(
+                    pos + egui::vec2(26.0, -26.0),
+                    egui::Align2::LEFT_BOTTOM,
+                    format!("({}, {})", px.0, px.1),
+                    egui::FontId::proportional(16.0),
+                    Color32::WHITE,
+                );
+                let dismissed = egui::Area::new(egui::Id::new("flash_area"))
+                    .order(egui::Order::Foreground)
+                    .show(ctx, |ui| ui.allocate_rect(screen_rect, Sense::click()).clicked())
+                    .inner;
+                if dismissed || Instant::now() >= until {
+                    self.exit_picker(ctx);
+                } else {
+                    ctx.request_repaint_after(std::time::Duration::from_millis(100));
+                }
+                return;
+            }
+
+            if let Some(marker) = self.calibration.as_ref().and_then(|r| r.next_marker()) {
+                let done = self.calibration.as_ref().map_or(0, |r| r.samples.len());
+                let pos = screen_rect.min + egui::vec2(screen_rect.width() * marker.0, screen_rect.height() * marker.1);
+                let stroke = egui::Stroke { width: 2.0, color: Color32::RED };
+                painter.circle_stroke(pos, 12.0, stroke);
+                painter.line_segment([pos - egui::vec2(18.0, 0.0), pos + egui::vec2(18.0, 0.0)], stroke);
+                painter.line_segment([pos - egui::vec2(0.0, 18.0), pos + egui::vec2(0.0, 18.0)], stroke);
+                painter.text(
+                    screen_rect.center_top() + egui::vec2(0.0, 40.0),
+                    egui::Align2::CENTER_TOP,
+                    tr!("Calibration: click the center of the red marker ({}/{}). Esc cancels.", done + 1, calibrate::MARKERS.len()),
+                    egui::FontId::proportional(20.0),
+                    Color32::WHITE,
+                );
+                egui::Area::new(egui::Id::new("calibration_area"))
+                    .order(egui::Order::Foreground)
+                    .show(ctx, |ui| {
+                        let resp = ui.allocate_rect(screen_rect, Sense::click());
+                        if let (true, Some(p)) = (resp.clicked(), resp.interact_pointer_pos()) {
+                            let ppp = ctx.pixels_per_point().max(0.1);
+                            let picked = logical_to_physical(p, ppp, self.picker_origin_px(), &self.monitors);
+                            let injected = INPUT.lock().mouse_location();
+                            self.add_calibration_sample(picked, injected, ctx);
+                        }
+                    });
+                return;
+            }
+
+            if self.picking_color {
+                painter.text(
+                    screen_rect.center_top() + egui::vec2(0.0, 40.0),
+                    egui::Align2::CENTER_TOP,
+                    tr!("Click the pixel whose color the step should check. Esc cancels."),
+                    egui::FontId::proportional(20.0),
+                    Color32::WHITE,
+                );
+                egui::Area::new(egui::Id::new("color_pick_area"))
+                    .order(egui::Order::Foreground)
+                    .show(ctx, |ui| {
+                        let resp = ui.allocate_rect(screen_rect, Sense::click());
+                        if let (true, Some(p)) = (resp.clicked(), resp.interact_pointer_pos()) {
+                            // the screen itself, so no calibration: that's for where clicks land
+                            let ppp = ctx.pixels_per_point().max(0.1);
+                            let px = logical_to_physical(p, ppp, self.picker_origin_px(), &self.monitors);
+                            self.color_pick = Some((px, Instant::now() + PICK_SAMPLE_DELAY));
+                            self.exit_picker(ctx);
+                        }
+                    });
+                return;
+            }
+
+            if self.mode == JobMode::Sequence { self.draw_step_preview(&painter, screen_rect, ctx); }
+            self.draw_forbidden(&painter, screen_rect, ctx);
+
+            // Interaction area
+            egui::Area::new(egui::Id::new("picker_area"))
+                .order(egui::Order::Foreground)
+                .show(ctx, |ui| {
+                    let resp = ui.allocate_rect(screen_rect, Sense::click_and_drag());
+                    let ppp = ctx.pixels_per_point().max(0.1);
+                    let fixed = self.use_fixed_pick_size.then_some(self.fixed_pick_size);
+                    // Shift squares the dragged rectangle
+                    let square = ctx.input(|i| i.modifiers.shift);
+                    let corner = |start: Option<Pos2>, end: Option<Pos2>| match (start, end) {
+                        (Some(a), Some(b)) if square => Some(square_corner(a, b)),
+                        _ => end,
+                    };
+                    let origin_px = self.picker_origin_px();
+                    // the current area is drawn; dragging it or its edges adjusts it instead of starting over
+                    let grip = match (fixed, resp.hover_pos()) {
+                        (None, Some(p)) if self.drag_start.is_none() => grip_at(self.pick_entry_rect(ppp, screen_rect), p),
+                        _ => None,
+                    };
+                    if let Some(g) = self.adjusting.map(|(g, ..)| g).or(grip) { ctx.set_cursor_icon(g.cursor()); }
+                    if resp.drag_started() {
+                        match (grip, resp.interact_pointer_pos()) {
+                            (Some(g), Some(p)) => self.adjusting = Some((g, self.pick_entry, self.picked_px(p, ppp, origin_px))),
+                            _ => {
+                                self.drag_start = resp.interact_pointer_pos();
+                                self.drag_end = self.drag_start;
+                            }
+                        }
+                    }
+                    if resp.dragged() {
+                        self.drag_end = corner(self.drag_start, resp.interact_pointer_pos());
+                    }
+                    let placed = resp.drag_stopped() || resp.clicked();
+                    if let Some((g, from, at)) = self.adjusting {
+                        if let Some(p) = resp.interact_pointer_pos() {
+                            let now = self.picked_px(p, ppp, origin_px);
+                            self.pick_entry = adjust_entry(from, g, (now.0 - at.0, now.1 - at.1));
+                        }
+                        // stays open for more adjusting until the area is confirmed
+                        if resp.drag_stopped() { self.adjusting = None; }
+                    } else if let (true, Some([w, h]), Some(p)) = (placed, fixed, resp.interact_pointer_pos()) {
+                        // fixed size: dragging or clicking only positions the box
+                        self.set_picked_bounds(Bounds::centered(self.picked_px(p, ppp, self.picker_origin_px()), w, h));
+                        self.finish_area_pick(ctx);
+                    } else if resp.drag_stopped() {
+                        self.drag_end = corner(self.drag_start, resp.interact_pointer_pos());
+
+                        // Determine origin_px to add (depends on selected target)
+                        self.set_bounds_from_drag(ppp, origin_px);
+                        self.finish_area_pick(ctx);
+                    } else if resp.clicked() {
+                        // a click without dragging picks a single point
+                        if let Some(p) = resp.interact_pointer_pos() {
+                            self.set_point_from_click(p, ppp, origin_px);
+                        }
+                        self.finish_picking(ctx);
+                    }
+
+                    if self.pick_entry_ui(ctx, &painter, screen_rect) {
+                        let [x, y, w, h] = self.pick_entry;
+                        self.set_picked_bounds(Bounds { min_x: x, max_x: x + w, min_y: y, max_y: y + h });
+                        self.finish_area_pick(ctx);
+                    }
+
+                    let stroke = egui::Stroke { width: 2.0, color: Color32::LIGHT_BLUE };
+                    match (fixed, resp.hover_pos()) {
+                        (Some([w, h]), Some(p)) => {
+                            painter.rect_stroke(Rect::from_center_size(p, egui::vec2(w as f32, h as f32) / ppp), 0.0, stroke);
+                        }
+                        (Some(_), None) => {}
+                        (None, _) => {
+                            if let (Some(a), Some(b)) = (self.drag_start, self.drag_end) {
+                                painter.rect_stroke(Rect::from_two_pos(a, b), 0.0, stroke);
+                            }
+                        }
+                    }
+                });
+
+            return; // Skip main UI while picking; input events bring the next frame
+        }
+
+        if self.compact {
+            self.compact_ui(ctx);
+            return;
+        }
+
+        if self.mode == JobMode::Sequence && !self.confirming_start && self.setup.is_none() && self.pass_prompt.is_none() {
+            self.step_list_keys(ctx);
+        }
+        self.confirm_start_ui(ctx);
+        self.click_preview_ui(ctx);
+        self.dry_run_ui(ctx);
+        self.run_stats_ui(ctx);
+        self.run_history_ui(ctx);
+        self.setup_ui(ctx);
+        self.pass_prompt_ui(ctx);
+        self.pending_load_ui(ctx);
+        self.unlock_prompt_ui(ctx);
+
+        // -------- Main UI --------
+        egui::TopBottomPanel::top("top").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading(tr!("Area Clicker — Multi-Display"));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button(tr!("Compact")).on_hover_text(tr!("Shrink to a small always-on-top control bar")).clicked() {
+                        self.enter_compact(ctx);
+                    }
+                    if ui.button(tr!("History")).on_hover_text(tr!("Past runs with their clicks and run time, by profile and date")).clicked() {
+                        self.history_open = !self.history_open;
+                    }
+                    self.language_ui(ui);
+                    self.theme_ui(ui);
+                    if ui.button(tr!("Tutorial")).on_hover_text(tr!("Walk through picking an area, setting the interval and starting (switches to Single mode)")).clicked() {
+                        self.mode = JobMode::Single;
+                        self.tutorial = Some(Tutorial::default());
+                    }
+                });
+            });
+            self.profiles_ui(ui);
+            self.recovery_ui(ui);
+            self.checkpoint_ui(ui);
+            self.reload_ui(ui);
+        });
+
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| self.status_bar_ui(ui));
+        egui::TopBottomPanel::bottom("log").show(ctx, |ui| self.log_ui(ui));
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // a locked profile's settings are shown but can't be changed
+            let editable = self.editable();
+            ui.horizontal_wrapped(|ui| {
+                ui.vertical(|ui| {
+                    ui.group(|ui| {
+                        ui.horizontal(|ui| {
+                            self.display_ui(ui);
+                        });
+
+                        ui.horizontal(|ui| {
+                            let monitor_id = match self.display_choice {
+                                DisplayChoice::One(i) => self.monitors.get(i).map(|m| m.id),
+                                DisplayChoice::All => None,
+                            };
+                            let calibrate = ui.add_enabled(monitor_id.is_some(), egui::Button::new(tr!("Calibrate…")))
+                                .on_hover_text(tr!("Click a few markers so picked coordinates match where clicks land"))
+                                .on_disabled_hover_text(tr!("Select a single display to calibrate it."));
+                            if calibrate.clicked() { self.start_calibration(ctx); }
+                            if let Some(c) = monitor_id.and_then(|id| self.corrections.get(&id)) {
+                                ui.label(tr!(
+                                    "offset {} scale {}",
+                                    format!("{:+.0},{:+.0}", c.offset_x, c.offset_y),
+                                    format!("{:.3}×{:.3}", c.scale_x, c.scale_y),
+                                ));
+                                if ui.small_button(tr!("Reset")).clicked() {
+                                    if let Some(id) = monitor_id { self.reset_calibration(id); }
+                                }
+                            }
+                        });
+
+                        ui.horizontal(|ui| {
+                            ui.label(tr!("Input:"));
+                            let mut backend = self.input_backend;
+                            egui::ComboBox::from_id_source("input_backend")
+                                .selected_text(i18n::translate(backend.label()))
+                                .show_ui(ui, |ui| {
+                                    for &b in Backend::ALL {
+                                        ui.selectable_value(&mut backend, b, i18n::translate(b.label()));
+                                    }
+                                });
+                            if backend != self.input_backend { self.set_input_backend(backend); }
+                            if self.input_backend == Backend::SendInput
+                                && ui.checkbox(&mut self.hardware_like_input, tr!("Scan-code keys"))
+                                    .on_hover_text(tr!("Send keys as hardware scan codes, for apps that ignore virtual-key input"))
+                                    .changed()
+                            {
+                                INPUT.lock().set_hardware_like(self.hardware_like_input);
+                            }
+                        });
+                        if self.input_backend == Backend::WindowMessages {
+                            ui.horizontal(|ui| {
+                                let title = self.target_window.as_ref().map_or_else(|| tr!("(none)"), |(_, t)| t.clone());
+                                ui.label(tr!("Target window: {}", title));
+                                match self.window_pick_at {
+                                    Some(at) => {
+                                        let left = at.saturating_duration_since(Instant::now()).as_secs_f32().ceil();
+                                        ui.label(tr!("hover it… {} s", left));
+                                    }
+                                    None => {
+                                        if ui.button(tr!("Pick window")).on_hover_text(tr!("Hover the target window within 3 seconds")).clicked() {
+                                            self.window_pick_at = Some(Instant::now() + std::time::Duration::from_secs(3));
+                                        }
+                                    }
+                                }
+                            });
+                        }
+                        if let Some(e) = &self.input_error {
+                            ui.colored_label(Color32::LIGHT_RED, e);
+                        }
+
+                        ui.separator();
+
+                        ui.add_enabled_ui(editable, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(tr!("Selection (px, screen coords):"));
+                                ui.radio_value(&mut self.use_point, false, tr!("Area"));
+                                ui.radio_value(&mut self.use_point, true, tr!("Point"));
+                            });
+                            if self.use_point {
+                                ui.horizontal(|ui| { ui.label("X"); ui.add(egui::DragValue::new(&mut self.point_inputs[0])); });
+                                ui.horizontal(|ui| { ui.label("Y"); ui.add(egui::DragValue::new(&mut self.point_inputs[1])); });
+                                ui.horizontal(|ui| { ui.label(tr!("± jitter")); ui.add(egui::DragValue::new(&mut self.point_inputs[2]).clamp_range(0..=200)); });
+                            } else {
+                                ui.horizontal(|ui| { ui.label(tr!("min X")); ui.add(egui::DragValue::new(&mut self.bounds_inputs[0])); });
+                                ui.horizontal(|ui| { ui.label(tr!("max X")); ui.add(egui::DragValue::new(&mut self.bounds_inputs[1])); });
+                                ui.horizontal(|ui| { ui.label(tr!("min Y")); ui.add(egui::DragValue::new(&mut self.bounds_inputs[2])); });
+                                ui.horizontal(|ui| { ui.label(tr!("max Y")); ui.add(egui::DragValue::new(&mut self.bounds_inputs[3])); });
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(&mut self.follow_cursor, tr!("Center on cursor at start:"));
+                                    ui.add_enabled_ui(self.follow_cursor, |ui| {
+                                        ui.add(egui::DragValue::new(&mut self.follow_size[0]).clamp_range(1..=10000));
+                                        ui.label("×");
+                                        ui.add(egui::DragValue::new(&mut self.follow_size[1]).clamp_range(1..=10000));
+                                    });
+                                });
+                            }
+
+                            ui.horizontal(|ui| {
+                                let pick = ui.button(tr!("Pick (drag a rectangle, or click a point)"));
+                                self.tutorial_mark(TutorialStage::PickArea, pick.rect);
+                                if pick.clicked() {
+                                    self.enter_picker(ctx);
+                                }
+                                if self.mode == JobMode::Sequence {
+                                    ui.checkbox(&mut self.pick_template, tr!("Also save it as the step's reference image"))
+                                        .on_hover_text(tr!("A picked area is captured as a template and checked with image verification after each click"));
+                                }
+                            });
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut self.use_fixed_pick_size, tr!("Fixed size:"))
+                                    .on_hover_text(tr!("The picker places a box of exactly this size where you click or let go; otherwise hold Shift while dragging for a square"));
+                                ui.add_enabled_ui(self.use_fixed_pick_size, |ui| {
+                                    ui.add(egui::DragValue::new(&mut self.fixed_pick_size[0]).clamp_range(1..=10000));
+                                    ui.label("×");
+                                    ui.add(egui::DragValue::new(&mut self.fixed_pick_size[1]).clamp_range(1..=10000));
+                                });
+                            });
+                            self.areas_ui(ui);
+                        });
+                        self.forbidden_ui(ui);
+                        if ui.button(tr!("🎯 Where would it click?"))
+                            .on_hover_text(tr!("Sample {} clicks with the current settings and show them over a screenshot", PREVIEW_CLICKS))
+                            .clicked()
+                        {
+                            self.open_click_preview(ctx);
+                        }
+                        self.limits_ui(ui);
+                        self.feedback_ui(ui);
+                        self.step_defaults_ui(ui);
+
+                        ui.add_enabled_ui(hotkeys::available(), |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(tr!("Start/stop:"));
+                                egui::ComboBox::from_id_source("start_key")
+                                    .selected_text(self.start_key.label())
+                                    .show_ui(ui, |ui| {
+                                        for k in HotKey::ALL {
+                                            ui.selectable_value(&mut self.start_key, k, k.label());
+                                        }
+                                    });
+                            });
+                            ui.horizontal(|ui| {
+                                ui.label(tr!("Capture cursor:"));
+                                egui::ComboBox::from_id_source("capture_key")
+                                    .selected_text(self.capture_key.label())
+                                    .show_ui(ui, |ui| {
+                                        for k in HotKey::ALL {
+                                            ui.selectable_value(&mut self.capture_key, k, k.label());
+                                        }
+                                    });
+                                ui.label(tr!("box ±"));
+                                ui.add(egui::DragValue::new(&mut self.capture_radius).clamp_range(0..=500).suffix(" px"));
+                            });
+                            for (label, id, key) in [
+                                (tr!("Skip step:"), "skip_key", &mut self.skip_key),
+                                (tr!("Repeat step:"), "repeat_key", &mut self.repeat_key),
+                            ] {
+                                ui.horizontal(|ui| {
+                                    ui.label(label);
+                                    egui::ComboBox::from_id_source(id)
+                                        .selected_text(key.map_or(tr!("Off"), |k| k.label().to_string()))
+                                        .show_ui(ui, |ui| {
+                                            ui.selectable_value(key, None, tr!("Off"));
+                                            for k in HotKey::ALL {
+                                                ui.selectable_value(key, Some(k), k.label());
+                                            }
+                                        });
+                                });
+                            }
+                            if self.mode == JobMode::Sequence {
+                                ui.checkbox(&mut self.capture_as_step, tr!("Add each capture as a new step"));
+                            }
+                        }).response.on_disabled_hover_text(tr!("Global hotkeys need a build with `--features hooks`."));
+                        self.gamepad_ui(ui);
+                        self.triggers_ui(ui);
+                        self.viewer_ui(ui);
+                    });
+
+                    ui.separator();
+
+                    ui.group(|ui| {
+                        ui.add_enabled_ui(editable, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(tr!("Mode:"));
+                                ui.radio_value(&mut self.mode, JobMode::Single, tr!("Single"));
+                                ui.radio_value(&mut self.mode, JobMode::Sequence, tr!("Sequence"));
+                                ui.radio_value(&mut self.mode, JobMode::GridSweep, tr!("Grid sweep"));
+                                ui.radio_value(&mut self.mode, JobMode::AntiAfk, tr!("Anti-AFK only"));
+                            });
+                        });
+                    });
+
+                    ui.separator();
+
+                    ui.group(|ui| {
+                        ui.label(tr!("Settings"));
+                        ui.add_enabled_ui(editable, |ui| {
+                            if matches!(self.mode, JobMode::Single | JobMode::GridSweep) {
+                                ui.horizontal(|ui| {
+                                    ui.label(tr!("Click type:"));
+                                    ui.checkbox(&mut self.click_button_left, tr!("Left"));
+                                    let mut right = !self.click_button_left;
+                                    if ui.checkbox(&mut right, tr!("Right")).clicked() { self.click_button_left = !right; }
+                                });
+                                let row = ui.horizontal(|ui| {
+                                    ui.label(tr!("Interval (seconds):"));
+                                    ui.add(egui::DragValue::new(&mut self.min_secs).speed(0.1));
+                                    ui.label(tr!("to"));
+                                    ui.add(egui::DragValue::new(&mut self.max_secs).speed(0.1));
+                                });
+                                self.tutorial_mark(TutorialStage::SetInterval, row.response.rect);
+                            }
+                            if self.mode == JobMode::Single {
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(&mut self.use_finite_clicks, tr!("Limit number of clicks"));
+                                    if self.use_finite_clicks {
+                                        ui.add(egui::DragValue::new(&mut self.num_clicks).speed(1.0).clamp_range(1..=1000000));
+                                    }
+                                });
+                            } else if self.mode == JobMode::GridSweep {
+                                ui.horizontal(|ui| {
+                                    ui.label(tr!("Grid:"));
+                                    ui.add(egui::DragValue::new(&mut self.grid_cols).speed(0.1).clamp_range(1..=100));
+                                    ui.label("×");
+                                    ui.add(egui::DragValue::new(&mut self.grid_rows).speed(0.1).clamp_range(1..=100));
+                                    ui.checkbox(&mut self.grid_serpentine, tr!("Serpentine order"));
+                                });
+                            } else if self.mode == JobMode::Sequence {
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(&mut self.use_finite_cycles, tr!("Limit number of cycles"));
+                                    if self.use_finite_cycles {
+                                        ui.add(egui::DragValue::new(&mut self.num_cycles).speed(1.0).clamp_range(1..=1000000));
+                                    }
+                                });
+                                ui.horizontal(|ui| {
+                                    ui.label(tr!("Step order:"));
+                                    egui::ComboBox::from_id_source("step_order")
+                                        .selected_text(i18n::translate(self.step_order.label()))
+                                        .show_ui(ui, |ui| {
+                                            for o in StepOrder::ALL {
+                                                ui.selectable_value(&mut self.step_order, o, i18n::translate(o.label()));
+                                            }
+                                        });
+                                    ui.label("ℹ").on_hover_text(match self.step_order {
+                                        StepOrder::InOrder => tr!("Each step makes all its clicks before the next one starts"),
+                                        StepOrder::Interleave => tr!("Each pass clicks once for every step that has clicks left, e.g. A B A B A for 3 × A and 2 × B, instead of A A A B B"),
+                                        StepOrder::Weighted => tr!("Every click goes to a step drawn at random by its weight, then waits that step's interval; a cycle makes as many clicks as the steps' counts add up to"),
+                                    });
+                                });
+                                self.start_at_ui(ui);
+                                if ui.button(tr!("🧪 Dry run"))
+                                    .on_hover_text(tr!("Run the sequence in virtual time, without clicking, and show the schedule it would follow"))
+                                    .clicked()
+                                {
+                                    self.start_dry_run();
+                                }
+                            }
+                            self.wait_for_ui(ui);
+                            self.quiet_hours_ui(ui);
+                            ui.checkbox(&mut self.keep_awake, tr!("Keep the computer awake while running"))
+                                .on_hover_text(tr!("Hold off sleep and screen blanking until the job ends"));
+                            if self.mode != JobMode::AntiAfk {
+                                ui.horizontal(|ui| {
+                                    ui.label(tr!("Hold the button for"));
+                                    ui.add(egui::DragValue::new(&mut self.min_hold_ms).clamp_range(0..=2000).suffix(" ms"));
+                                    ui.label(tr!("to"));
+                                    ui.add(egui::DragValue::new(&mut self.max_hold_ms).clamp_range(0..=2000).suffix(" ms"));
+                                }).response.on_hover_text(tr!("Each click presses, waits a random time in this range, then releases"));
+                                ui.checkbox(&mut self.settle_before_click, tr!("Settle on the target before clicking"))
+                                    .on_hover_text(tr!("Arrive a few pixels off and make one or two tiny corrections before pressing"));
+                                ui.checkbox(&mut self.vary_approach, tr!("Vary the approach"))
+                                    .on_hover_text(tr!("Come in from a varied angle and land a little short of the sampled point, inside its area"));
+                                ui.checkbox(&mut self.reclick_misses, tr!("Re-click when a click misses"))
+                                    .on_hover_text(tr!("If the cursor ended up outside the target, move it back and click once more"));
+                                ui.add_enabled(self.input_backend.reads_cursor(), egui::Checkbox::new(&mut self.correct_drift, tr!("Correct pointer drift")))
+                                    .on_hover_text(tr!("Check the real cursor position along the way and steer back, for pointer acceleration or remote-desktop scaling"))
+                                    .on_disabled_hover_text(tr!("The input backend can't read the cursor position"));
+                                self.path_bank_ui(ui);
+                                self.refocus_ui(ui);
+                                self.screenshot_ui(ui);
+                                self.events_ui(ui);
+                                self.hold_to_run_ui(ui);
+                                self.warm_up_ui(ui);
+                                self.fatigue_ui(ui);
+                            }
+                            ui.horizontal(|ui| {
+                                if self.mode == JobMode::AntiAfk {
+                                    ui.label(tr!("Tap key:"));
+                                } else {
+                                    ui.checkbox(&mut self.use_anti_afk, tr!("Anti-AFK tap:"));
+                                }
+                                egui::ComboBox::from_id_source("afk_key")
+                                    .selected_text(self.afk_key.label())
+                                    .show_ui(ui, |ui| {
+                                        for k in AfkKey::ALL {
+                                            ui.selectable_value(&mut self.afk_key, k, k.label());
+                                        }
+                                    });
+                                ui.label(tr!("every"));
+                                ui.add(egui::DragValue::new(&mut self.afk_min_secs).speed(1.0).clamp_range(1.0..=3600.0));
+                                ui.label(tr!("to"));
+                                ui.add(egui::DragValue::new(&mut self.afk_max_secs).speed(1.0).clamp_range(1.0..=3600.0));
+                                ui.label("s");
+                            });
+                        });
+                        ui.horizontal(|ui| {
+                            self.controls_ui(ui);
+                            let mut ask = self.confirm_start;
+                            let toggled = ui.checkbox(&mut ask, tr!("Confirm first"))
+                                .on_hover_text(tr!("Show a summary before starting (--autostart and the control socket skip it)"))
+                                .changed();
+                            if toggled { self.set_confirm_start(ask); }
+                        });
+                        for w in self.limit_warnings() { ui.colored_label(Color32::YELLOW, format!("⚠ {}", w)); }
+                        self.status_ui(ui);
+                        self.history_ui(ui);
+                    });
+                });
+
+                if self.mode == JobMode::Sequence {
+                    ui.vertical(|ui| {
+                        self.sequence_ui(ui);
+                    });
+                }
+            });
+
+            self.diagnostics_ui(ui);
+            self.backup_ui(ui);
+
+            // Preview rectangle
+            let cfg = self.config.lock();
+            if let Some(p) = cfg.point {
+                ui.separator();
+                ui.monospace(tr!("Active point: ({}, {}) ±{} px", p.x, p.y, p.jitter));
+            } else if let Some(b) = cfg.bounds {
+                let info = tr!("Active bounds: x=[{}..{}], y=[{}..{}] ({}x{})",
+                                   b.min_x, b.max_x, b.min_y, b.max_y, b.width(), b.height());
+                ui.separator();
+                ui.monospace(info);
+            }
+        });
+
+        self.tutorial_ui(ctx);
+    }
+}
+
+/// The `export` / `import` subcommands.
+fn transfer_settings(path: &Path, exporting: bool) -> Result<(), String> {
+    let store = ProfileStore::open_default().ok_or("No config directory found; settings can't be stored.")?;
+    if exporting {
+        let bundle = store.export_bundle().map_err(|e| format!("Could not export: {}", e))?;
+        profile::save_bundle(path, &bundle).map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+        println!("Exported {} profiles to {}", bundle.profiles.len() + bundle.encrypted.len(), path.display());
+    } else {
+        let bundle = profile::load_bundle(path).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        store.import_bundle(&bundle).map_err(|e| format!("Could not import: {}", e))?;
+        println!("Imported {} profiles from {}", bundle.profiles.len() + bundle.encrypted.len(), path.display());
+    }
+    Ok(())
+}
+
+/// The `install-task` subcommand: register the runner for `profile` (a saved name or a file).
+fn install_task(profile: &str, at: Option<u16>, name: Option<String>, socket: Option<PathBuf>, print: bool) -> Result<String, String> {
+    let store = ProfileStore::open_default();
+    let file = task::resolve_profile(profile, |n| store.as_ref().and_then(|s| s.path(n).ok()))?;
+    let stem = file.file_stem().map_or(profile.to_string(), |s| s.to_string_lossy().into_owned());
+    let spec = task::TaskSpec {
+        name: name.unwrap_or_else(|| task::default_name(&stem)),
+        exe: std::env::current_exe().map_err(|e| format!("Could not find this program: {}", e))?,
+        profile: file,
+        schedule: at.map_or(task::Schedule::Logon, task::Schedule::Daily),
+        socket,
+        portable: portable::active(),
+    };
+    task::install(&spec, print)
+}
+
+fn main() -> eframe::Result<()> {
+    let mut opts = eframe::NativeOptions::default();
+    let args = Args::parse();
+    let portable = portable::init(args.portable);
+    logging::init(args.log_level);
+    if let Some(dir) = &portable { log::info!("Portable mode: settings are kept in {}", dir.display()); }
+    if let Some(path) = &args.log_file {
+        if let Err(e) = logging::set_file(Some(path)) { log::error!("Could not open log file {}: {}", path.display(), e); }
+    }
+    if let Some(Command::ListDisplays { json }) = args.command {
+        match query_monitors() {
+            Ok(monitors) => print!("{}", displays_table(&monitors, json)),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    if let Some(Command::Simulate { from, to, area, seed }) = args.command {
+        let settings = human_mouse::HumanMouseSettings { rng_seed: seed, ..Default::default() };
+        let path = human_mouse::plan(from, to, area, &settings);
+        println!("{}", serde_json::to_string_pretty(&path).unwrap_or_default());
+        return Ok(());
+    }
+    if let Some(Command::Bench { moves, seed, json }) = args.command {
+        print!("{}", bench::render(&bench::run(moves, &human_mouse::HumanMouseSettings::default(), seed), json));
+        return Ok(());
+    }
+    if let Some(Command::Send { command }) = &args.command {
+        let socket = args.socket.clone().unwrap_or_else(daemon::default_socket);
+        match daemon::send(&socket, &command.join(" ")) {
+            Ok(reply) => println!("{}", if reply.is_empty() { "ok" } else { &reply }),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    if let Some(Command::InstallTask { profile, at, name, print }) = &args.command {
+        match install_task(profile, *at, name.clone(), args.socket.clone(), *print) {
+            Ok(done) => println!("{}", done),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    if let Some(Command::Export { path } | Command::Import { path }) = &args.command {
+        let exporting = matches!(args.command, Some(Command::Export { .. }));
+        if let Err(e) = transfer_settings(path, exporting) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    let pick_only = matches!(args.command, Some(Command::Pick));
+    hotkeys::start_listener();
+    gamepad::start_listener();
+    if args.daemon {
+        let mut state = AppState::new();
+        state.apply_args(&args);
+        if state.autostart_at.take().is_some() {
+            if let Err(e) = daemon::handle(&mut state, "start") { log::error!("Could not start: {}", e); }
+        }
+        let socket = args.socket.clone().unwrap_or_else(daemon::default_socket);
+        if let Err(e) = daemon::run(state, &socket) {
+            log::error!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    // Start as a normal window; we resize/position during picking.
+    opts.viewport.transparent = Some(true);
+    opts.viewport.resizable = Some(true);
+    opts.viewport.inner_size = Some(egui::vec2(740.0, 560.0));
+    opts.viewport.mouse_passthrough = Some(false); // Ensure we capture mouse events
+    opts.follow_system_theme = true; // keeps frame.info().system_theme current for ThemeMode::System
+
+    eframe::run_native(
+        "Area Clicker",
+        opts,
+        Box::new(move |cc| {
+            let ctx = cc.egui_ctx.clone();
+            input::check_ydotool(move || ctx.request_repaint());
+            if pick_only {
+                let mut state = AppState { pick_only, ..AppState::default() };
+                state.set_input_backend(Backend::detect());
+                return Box::new(state);
+            }
+            let ctx = cc.egui_ctx.clone();
+            hotkeys::on_change(move || ctx.request_repaint());
+            let ctx = cc.egui_ctx.clone();
+            gamepad::on_change(move || ctx.request_repaint());
+            let mut state = AppState::new();
+            state.apply_args(&args);
+            state.apply_theme(&cc.egui_ctx, cc.integration_info.system_theme);
+            Box::new(state)
+        }),
+    )
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::step::{grid_cells, StepOutcome};
+    use eframe::egui::Pos2;
+    use std::sync::{Arc, atomic::Ordering};
+    use std::time::Duration;
+
+    #[test]
+    fn test_bounds_validation() {
+        let valid_bounds = Bounds { min_x: 100, max_x: 200, min_y: 100, max_y: 200 };
+        assert!(valid_bounds.is_valid());
+        assert_eq!(valid_bounds.width(), 100);
+        assert_eq!(valid_bounds.height(), 100);
+
+        let invalid_bounds = Bounds { min_x: 200, max_x: 100, min_y: 200, max_y: 100 };
+        assert!(!invalid_bounds.is_valid());
+    }
+
+    #[test]
+    fn test_click_job_creation() {
+        let config = Arc::new(Mutex::new(ClickConfig {
+            bounds: Some(Bounds { min_x: 100, max_x: 200, min_y: 100, max_y: 200 }),
+            button: ClickButton::Left,
+            min_secs: 2.0,
+            max_secs: 4.5,
+            ..ClickConfig::default()
+        }));
+
+        let job = ClickJob::spawn(Arc::clone(&config));
+        assert!(job.running.load(Ordering::Relaxed));
+
+        // Test stopping
+        job.stop();
+        assert!(!job.running.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_app_state_defaults() {
+        let state = AppState::default();
+        assert!(!state.picking_area);
+        assert!(state.drag_start.is_none());
+        assert!(state.drag_end.is_none());
+        assert!(state.click_button_left);
+        assert!(state.job.is_none());
+
+        // input defaults
+        assert_eq!(state.min_secs, 2.0);
+        assert_eq!(state.max_secs, 4.5);
+    }
+
+    /// A display for a fixed test layout, so no test depends on the machine's own monitors.
+    fn monitor(id: u32, origin_px: (i32, i32), size_px: (i32, i32), scale_factor: f32) -> Monitor {
+        Monitor { id, name: String::new(), origin_px, size_px, scale_factor }
+    }
+
+    /// A sequence step clicking `bounds` `clicks` times, one second apart.
+    fn seq_step(name: &str, clicks: u32, bounds: Bounds) -> SequenceStep {
+        SequenceStep { name: name.into(), bounds, clicks, min_secs: 1.0, max_secs: 1.0, ..SequenceStep::default() }
+    }
+
+    #[test]
+    fn test_set_bounds_from_drag_ppp1_origin0() {
+        let mut state = AppState { monitors: vec![monitor(1, (0, 0), (1920, 1080), 1.0)], drag_start: Some(Pos2::new(100.0, 100.0)), ..AppState::default() };
+        state.drag_end   = Some(Pos2::new(200.0, 200.0));
+        state.set_bounds_from_drag(1.0, (0, 0));
+        assert_eq!(state.bounds_inputs, [100, 200, 100, 200]);
+
+        // reverse drag
+        state.drag_start = Some(Pos2::new(200.0, 200.0));
+        state.drag_end   = Some(Pos2::new(100.0, 100.0));
+        state.set_bounds_from_drag(1.0, (0, 0));
+        assert_eq!(state.bounds_inputs, [100, 200, 100, 200]);
+    }
+
+    #[test]
+    fn test_point_from_click_and_jitter() {
+        let mut state = AppState { monitors: vec![monitor(1, (0, 0), (3840, 2160), 2.0)], ..AppState::default() };
+        state.point_inputs[2] = 3;
+        state.set_point_from_click(Pos2::new(10.0, 20.0), 2.0, (100, 0));
+        assert!(state.use_point);
+        assert_eq!(state.config.lock().point, Some(ClickPoint { x: 120, y: 40, jitter: 3 }));
+
+        let mut rng = rand::thread_rng();
+        let p = ClickPoint { x: 0, y: 0, jitter: 3 };
+        for _ in 0..200 {
+            let (x, y) = p.sample(&mut rng);
+            assert!(x * x + y * y <= 16);
+        }
+        assert_eq!(ClickPoint { x: 5, y: 6, jitter: 0 }.sample(&mut rng), (5, 6));
+    }
+
+    #[test]
+    fn test_offset_step_target() {
+        let mut state = AppState { step_use_offset: true, step_offset: [12, -4, 0], ..AppState::default() };
+        state.save_step();
+        assert_eq!(state.steps[0].target, StepTarget::Offset { dx: 12, dy: -4, jitter: 0 });
+
+        state.edit_step(0);
+        assert!(state.step_use_offset);
+        state.step_use_offset = false;
+        state.save_step();
+        assert_eq!(state.steps[0].target, StepTarget::Area);
+    }
+
+    #[test]
+    fn test_profile_store_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("area_clicker_test_{}", std::process::id()));
+        let mut state = AppState { profiles: Some(ProfileStore::new(dir.clone())), ..AppState::default() };
+
+        state.mode = JobMode::GridSweep;
+        state.grid_cols = 7;
+        state.save_profile_as("Daily A");
+        state.duplicate_profile("Daily A", "Daily B");
+        state.rename_profile("Daily A", "Morning");
+        assert_eq!(state.profile_names, vec!["Daily B".to_string(), "Morning".to_string()]);
+        assert_eq!(state.current_profile.as_deref(), Some("Morning"));
+
+        let mut fresh = AppState { profiles: Some(ProfileStore::new(dir.clone())), ..AppState::default() };
+        assert_eq!(fresh.profiles.as_ref().unwrap().last_used().as_deref(), Some("Morning"));
+        fresh.load_profile("Daily B");
+        assert_eq!(fresh.mode, JobMode::GridSweep);
+        assert_eq!(fresh.grid_cols, 7);
+
+        fresh.delete_profile("Daily B");
+        assert_eq!(fresh.profile_names, vec!["Morning".to_string()]);
+        assert!(!profile::valid_name("../evil"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_autosave_and_recovery() {
+        let dir = std::env::temp_dir().join(format!("area_clicker_recovery_{}", std::process::id()));
+        let mut state = AppState { profiles: Some(ProfileStore::new(dir.clone())), ..AppState::default() };
+        state.mode = JobMode::Sequence;
+        state.steps.push(SequenceStep {
+            name: "Open".to_string(),
+            bounds: Bounds { min_x: 0, max_x: 10, min_y: 0, max_y: 10 },
+            max_secs: 1.0,
+            ..SequenceStep::default()
+        });
+        state.step_name = "half typed".to_string();
+        state.autosave();
+
+        // simulate a restart after a crash
+        let store = ProfileStore::new(dir.clone());
+        let recovered = store.load_recovery().expect("recovery file written");
+        let mut fresh = AppState::default();
+        fresh.restore_recovery(recovered);
+        assert_eq!(fresh.mode, JobMode::Sequence);
+        assert_eq!(fresh.steps.len(), 1);
+        assert_eq!(fresh.step_name, "half typed");
+
+        store.clear_recovery().unwrap();
+        assert!(store.load_recovery().is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_cli_args_override_settings() {
+        let args = Args::parse_from(["area_clicker", "--mode", "sequence", "--clicks", "25", "--min-delay-ms", "500", "--autostart"]);
+        let mut state = AppState::default();
+        state.apply_args(&args);
+        assert_eq!(state.mode, JobMode::Sequence);
+        assert!(state.use_finite_clicks);
+        assert_eq!(state.num_clicks, 25);
+        assert!((state.min_secs - 0.5).abs() < f32::EPSILON);
+        assert_eq!(state.max_secs, AppState::default().max_secs); // not given, left alone
+        assert!(state.autostart_at.is_some());
+        state.stop();
+        assert!(state.autostart_at.is_none());
+
+        let args = Args::parse_from(["area_clicker", "--bounds", "400,300,100,-50"]);
+        state.use_point = true;
+        state.apply_args(&args);
+        assert_eq!(state.bounds_inputs, [100, 400, -50, 300]);
+        assert!(!state.use_point);
+        assert!(parse_bounds("1,2,3").is_err());
+        assert!(parse_bounds("1,2,x,4").is_err());
+    }
+
+    #[test]
+    fn test_displays_table() {
+        let monitors = vec![
+            Monitor { id: 1, name: "Display 1 (Primary)".to_string(), origin_px: (0, 0), size_px: (2560, 1440), scale_factor: 1.5 },
+            Monitor { id: 2, name: "Display 2".to_string(), origin_px: (-1920, 0), size_px: (1920, 1080), scale_factor: 1.0 },
+        ];
+        let text = displays_table(&monitors, false);
+        assert_eq!(text.lines().count(), 3);
+        assert!(text.contains("-1920,0") && text.contains("2560x1440"));
+
+        let json: serde_json::Value = serde_json::from_str(&displays_table(&monitors, true)).unwrap();
+        assert_eq!(json[1]["origin_px"], serde_json::json!([-1920, 0]));
+        assert_eq!(json[0]["scale_factor"], 1.5);
+    }
+
+    #[test]
+    fn test_picked_json() {
+        let mut state = AppState {
+            monitors: vec![monitor(1, (-1920, 0), (1920, 1080), 1.0)],
+            drag_start: Some(Pos2::new(10.0, 20.0)),
+            drag_end: Some(Pos2::new(110.0, 70.0)),
+            ..AppState::default()
+        };
+        state.set_bounds_from_drag(1.0, (-1920, 0));
+        let b: serde_json::Value = serde_json::from_str(&state.picked_json()).unwrap();
+        assert_eq!(b, serde_json::json!({ "min_x": -1910, "max_x": -1810, "min_y": 20, "max_y": 70 }));
+
+        state.monitors = vec![monitor(1, (0, 0), (2560, 1440), 2.0)];
+        state.set_point_from_click(Pos2::new(5.0, 6.0), 2.0, (0, 0));
+        let p: serde_json::Value = serde_json::from_str(&state.picked_json()).unwrap();
+        assert_eq!(p, serde_json::json!({ "min_x": 10, "max_x": 10, "min_y": 12, "max_y": 12 }));
+    }
+
+    #[test]
+    fn test_mixed_dpi_picker_coordinates() {
+        // 150% 2560x1440 on the left, 100% 1920x1080 to its right; the window runs at 1.5
+        let monitors = vec![monitor(1, (0, 0), (2560, 1440), 1.5), monitor(2, (2560, 0), (1920, 1080), 1.0)];
+        let ppp = 1.5;
+        assert_eq!(logical_to_physical(Pos2::new(100.0, 100.0), ppp, (0, 0), &monitors), (150, 150));
+        // second monitor starts at 2560/1.5 logical; 300 points into it is 300 px, not 450
+        let start = 2560.0 / 1.5;
+        assert_eq!(logical_to_physical(Pos2::new(start + 300.0, 200.0), ppp, (0, 0), &monitors), (2860, 200));
+        // outside every monitor: plain scaling
+        assert_eq!(logical_to_physical(Pos2::new(10.0, 10.0), 2.0, (-5000, 0), &monitors), (-4980, 20));
+    }
+
+    #[test]
+    fn test_calibration_fit_and_apply() {
+        // injection is shifted by (+3, -2) and 1.25× wider than the picker thinks
+        let truth = Correction { scale_x: 1.25, scale_y: 1.0, offset_x: 3.0, offset_y: -2.0 };
+        let picked = [(100, 100), (900, 100), (900, 700), (100, 700), (500, 400)];
+        let samples: Vec<_> = picked.iter().map(|&p| (p, truth.apply(p))).collect();
+        let fit = Correction::fit(&samples).unwrap();
+        assert_eq!(fit.apply((400, 300)), truth.apply((400, 300)));
+        // a single sample can only give an offset
+        let one = Correction::fit(&[((10, 10), (14, 7))]).unwrap();
+        assert_eq!(one.apply((50, 50)), (54, 47));
+        assert!(Correction::fit(&[]).is_none());
+
+        let mut state = AppState { monitors: vec![monitor(7, (0, 0), (1920, 1080), 1.0)], ..AppState::default() };
+        state.corrections.insert(7, Correction { offset_x: 3.0, offset_y: -2.0, ..Correction::default() });
+        state.set_point_from_click(Pos2::new(100.0, 100.0), 1.0, (0, 0));
+        assert_eq!((state.point_inputs[0], state.point_inputs[1]), (103, 98));
+    }
+
+    #[test]
+    fn test_ydotool_codes() {
+        use crate::input::{button_code, key_code};
+        assert_eq!(key_code(enigo::Key::Shift), Some(42));
+        assert_eq!(key_code(enigo::Key::F15), Some(185));
+        assert_eq!(key_code(enigo::Key::Layout('A')), Some(30));
+        assert_eq!(key_code(enigo::Key::Layout('0')), Some(11));
+        assert_eq!(key_code(enigo::Key::Layout('m')), Some(50));
+        assert_eq!(key_code(enigo::Key::Layout('é')), None);
+        assert_eq!(button_code(enigo::MouseButton::Right), Some(1));
+        assert_eq!(Input::new(Backend::Ydotool).map(|i| i.backend()), Ok(Backend::Ydotool));
+    }
+
+    #[test]
+    fn test_self_test_report() {
+        use crate::human_mouse::{PathEvent, Plan};
+
+        let mut mouse = Plan::at((10, 20));
+        let d = input::self_test_with(&mut mouse, Backend::Enigo, Ok(()));
+        assert_eq!(d.backend, Backend::Enigo);
+        assert_eq!(d.check, Ok(()));
+        assert_eq!((d.start, d.target, d.seen), ((10, 20), (11, 21), (11, 21)));
+        assert_eq!(d.moved, Some(true));
+        assert!(!d.permissions.is_empty());
+        let moves: Vec<_> = mouse.points.iter().filter(|p| p.event == PathEvent::Move).map(|p| (p.x, p.y)).collect();
+        assert_eq!(moves, [(11, 21), (10, 20)]); // put back where it was
+
+        // a cursor that ends up elsewhere, and a backend that can't read it back
+        let mut scaled = Plan { scale: 1.1, ..Plan::at((10, 20)) };
+        assert_eq!(input::self_test_with(&mut scaled, Backend::Enigo, Ok(())).moved, Some(false));
+        let d = input::self_test_with(&mut Plan::at((10, 20)), Backend::Ydotool, Err("ydotoold is not running".into()));
+        assert_eq!(d.moved, None);
+        assert!(d.check.is_err());
+
+        // a backend that couldn't be opened reports why
+        let mut input = Input::unavailable_reporting(Backend::Enigo, "DISPLAY is not set".into(), Box::new(|_| {}));
+        let d = input::self_test(&mut input);
+        assert_eq!(d.check, Err("DISPLAY is not set".to_string()));
+        assert_eq!(d.moved, Some(false));
+    }
+
+    #[test]
+    fn test_window_messages_needs_target() {
+        for b in [Backend::SendInput, Backend::WindowMessages] {
+            assert_eq!(Backend::ALL.contains(&b), cfg!(windows));
+            assert_eq!(b.check().is_ok(), cfg!(windows));
+        }
+        assert!(!Backend::WindowMessages.reads_cursor());
+
+        let mut state = AppState { input_backend: Backend::WindowMessages, ..AppState::default() };
+        state.start();
+        assert!(state.job.is_none());
+        assert!(state.input_error.is_some());
+    }
+
+    #[test]
+    fn test_step_expected_secs() {
+        let step = SequenceStep {
+            name: "a".into(),
+            bounds: Bounds { min_x: 0, max_x: 10, min_y: 0, max_y: 10 },
+            clicks: 10,
+            min_secs: 1.0,
+            max_secs: 3.0,
+            ..SequenceStep::default()
+        };
+        assert_eq!(step.expected_secs(), 20.0);
+    }
+
+    #[test]
+    fn test_sequence_duration() {
+        let area = Bounds { min_x: 0, max_x: 10, min_y: 0, max_y: 10 };
+        let steps = [
+            SequenceStep { max_secs: 3.0, ..seq_step("a", 10, area) },
+            SequenceStep { min_secs: 0.5, max_secs: 0.5, ..seq_step("b", 4, area) },
+        ];
+        assert_eq!(sequence_duration(&steps, 1), [12.0, 22.0, 32.0]);
+        assert_eq!(sequence_duration(&steps, 3), [36.0, 66.0, 96.0]);
+        assert_eq!(format_duration(45.0), "45s");
+        assert_eq!(format_duration(750.0), "12m 30s");
+        assert_eq!(format_duration(7500.0), "2h 05m");
+    }
+
+    #[test]
+    fn test_next_step_name() {
+        let mut p = JobProgress {
+            step: 1,
+            step_count: 2,
+            step_name: "b".into(),
+            cycle: 1,
+            cycles: Some(2),
+            step_names: vec!["a".into(), "b".into()],
+            ..JobProgress::default()
+        };
+        assert_eq!(p.next_step_name(), "b");
+        p.step_finished = true;
+        assert_eq!(p.next_step_name(), "a"); // wraps into cycle 2
+        p.cycle = 2;
+        assert_eq!(p.next_step_name(), "b"); // last cycle: nothing follows
+        p.step = 0;
+        p.step_name = "a".into();
+        assert_eq!(p.next_step_name(), "b");
+        assert!(p.next_click_in().is_none());
+    }
+
+    #[test]
+    fn test_physical_to_logical_roundtrip() {
+        let monitors = vec![
+            Monitor { id: 1, name: "a".into(), origin_px: (0, 0), size_px: (1920, 1080), scale_factor: 1.0 },
+            Monitor { id: 2, name: "b".into(), origin_px: (1920, 0), size_px: (2560, 1440), scale_factor: 2.0 },
+        ];
+        for px in [(100, 200), (1919, 1079), (2000, 300), (4000, 1400)] {
+            let p = physical_to_logical(px, 1.0, (0, 0), &monitors);
+            assert_eq!(logical_to_physical(p, 1.0, (0, 0), &monitors), px);
+        }
+        let c = Correction { scale_x: 1.5, scale_y: 1.0, offset_x: 10.0, offset_y: -4.0 };
+        assert_eq!(c.invert(c.apply((100, 50))), (100, 50));
+    }
+
+    #[test]
+    fn test_log_timestamp_and_rotation() {
+        use std::time::{Duration, SystemTime};
+        assert_eq!(logging::timestamp(SystemTime::UNIX_EPOCH), "1970-01-01 00:00:00");
+        let t = SystemTime::UNIX_EPOCH + Duration::from_secs(1_709_210_096); // leap day
+        assert_eq!(logging::timestamp(t), "2024-02-29 12:34:56");
+
+        let dir = std::env::temp_dir().join(format!("area_clicker_log_test_{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("test.log");
+        let mut file = logging::RotatingFile::open(&path, 20).unwrap();
+        for line in ["first line", "second line", "third line", "fourth line", "fifth line"] {
+            file.write_line(line).unwrap();
+        }
+        let read = |p: &std::path::Path| std::fs::read_to_string(p).unwrap();
+        assert_eq!(read(&path), "fifth line\n");
+        assert_eq!(read(&dir.join("test.log.1")), "fourth line\n");
+        assert_eq!(read(&dir.join("test.log.3")), "second line\n");
+        assert!(!dir.join("test.log.4").exists()); // "first line" rotated out
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_step_filter_matches() {
+        assert_eq!(match_ranges("Open Inventory — 1x Left", "inv"), vec![5..8]);
+        assert_eq!(match_ranges("aAa", "aa"), vec![0..2]);
+        assert_eq!(match_ranges("[10..90]x[20..80]", "..9"), vec![3..6]);
+        assert_eq!(match_ranges("Größe", "GRÖ"), vec![0..4]);
+        assert!(match_ranges("Loot", "").is_empty());
+        assert!(match_ranges("Loot", "right").is_empty());
+    }
+
+    #[test]
+    fn test_bulk_edit_selected_steps() {
+        let mut state = AppState::default();
+        for name in ["a", "b", "c", "d"] {
+            state.steps.push(SequenceStep { max_secs: 2.0, ..seq_step(name, 3, Bounds { min_x: 0, max_x: 10, min_y: 0, max_y: 10 }) });
+        }
+        state.selected_steps.extend([1, 3]);
+        state.remove_step(0); // selection follows the steps: b, d are now 0, 2
+        assert_eq!(state.selected_steps.iter().copied().collect::<Vec<_>>(), vec![0, 2]);
+
+        state.bulk = BulkEdit { set_interval: true, min_secs: 5.0, max_secs: 3.0, set_button: true, button: ClickButton::Right, ..BulkEdit::default() };
+        state.apply_bulk_edit();
+        let b = &state.steps[0];
+        assert_eq!((b.min_secs, b.max_secs, b.button, b.clicks), (3.0, 5.0, ClickButton::Right, 3));
+        let c = &state.steps[1];
+        assert_eq!((c.min_secs, c.max_secs, c.button), (1.0, 2.0, ClickButton::Left));
+        assert_eq!(state.steps[2].button, ClickButton::Right);
+    }
+
+    #[test]
+    fn test_area_library_updates_linked_steps() {
+        let mut state = AppState { bounds_inputs: [10, 20, 30, 40], ..AppState::default() };
+        state.save_area("Inventory");
+        state.step_area = Some("Inventory".into());
+        state.save_step();
+        state.step_area = None;
+        state.bounds_inputs = [0, 5, 0, 5];
+        state.save_step();
+        assert_eq!(state.steps[0].area.as_deref(), Some("Inventory"));
+        assert_eq!(state.steps[1].area, None);
+
+        state.bounds_inputs = [100, 200, 300, 400];
+        state.save_area("Inventory");
+        let moved = Bounds { min_x: 100, max_x: 200, min_y: 300, max_y: 400 };
+        assert_eq!(state.steps[0].bounds, moved);
+        assert_eq!(state.steps[1].bounds, Bounds { min_x: 0, max_x: 5, min_y: 0, max_y: 5 });
+
+        state.delete_area("Inventory");
+        assert_eq!(state.steps[0].area, None);
+        assert_eq!(state.steps[0].bounds, moved);
+    }
+
+    #[test]
+    fn test_step_colors() {
+        let mut state = AppState::default();
+        state.save_step();
+        state.save_step();
+        assert_eq!(state.steps[0].color, Some(STEP_COLORS[0]));
+        assert_eq!(state.steps[1].color, Some(STEP_COLORS[1]));
+        assert_eq!(state.step_color, STEP_COLORS[2]); // next new step
+
+        // untagged steps (older profiles) fall back to their position's color
+        state.steps[1].color = None;
+        let [r, g, b] = STEP_COLORS[1];
+        assert_eq!(state.steps[1].color(1), Color32::from_rgb(r, g, b));
+        state.edit_step(1);
+        assert_eq!(state.step_color, STEP_COLORS[1]);
+    }
+
+    #[test]
+    fn test_step_list_reorder_and_duplicate() {
+        let mut state = AppState::default();
+        for name in ["a", "b", "c"] {
+            state.step_name = name.into();
+            state.save_step();
+        }
+        let names = |s: &AppState| s.steps.iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+        state.selected_steps.insert(0);
+        state.step_cursor = Some(0);
+        state.editing_step_idx = Some(2);
+
+        state.swap_steps(0, 1);
+        assert_eq!(names(&state), ["b", "a", "c"]);
+        assert_eq!((state.step_cursor, state.editing_step_idx), (Some(1), Some(2)));
+        assert!(state.selected_steps.contains(&1));
+
+        state.duplicate_step(1);
+        assert_eq!(names(&state), ["b", "a", "a copy", "c"]);
+        assert_eq!((state.step_cursor, state.editing_step_idx), (Some(2), Some(3)));
+
+        state.step_filter = "copy".into();
+        assert_eq!(state.visible_steps(), vec![2]);
+        state.remove_step(2);
+        assert_eq!(state.step_cursor, None);
+
+        // Enter edits the cursor's step and sends the focus to the editor, whose widgets then
+        // get the keys until Escape drops the focus
+        let key = egui::Event::Key { key: egui::Key::Enter, physical_key: None, pressed: true, repeat: false, modifiers: egui::Modifiers::NONE };
+        let enter = || egui::RawInput { events: vec![key.clone()], ..Default::default() };
+        let ctx = egui::Context::default();
+        (state.step_filter, state.step_cursor) = (String::new(), Some(0));
+        let _ = ctx.run(enter(), |ctx| state.step_list_keys(ctx));
+        assert_eq!(state.editing_step_idx, Some(0));
+        assert!(state.focus_step_editor);
+        state.step_cursor = Some(1);
+        let _ = ctx.run(enter(), |ctx| {
+            ctx.memory_mut(|m| m.request_focus(egui::Id::new("save step")));
+            state.step_list_keys(ctx);
+        });
+        assert_eq!(state.editing_step_idx, Some(0));
+    }
+
+    #[test]
+    fn test_start_summary() {
+        let mut state = AppState { mode: JobMode::Sequence, ..AppState::default() };
+        for (name, button) in [("a", true), ("b", false), ("c", true)] {
+            state.step_name = name.into();
+            state.step_clicks = 4;
+            state.step_button_left = button;
+            state.save_step();
+        }
+        state.use_finite_cycles = true;
+        state.num_cycles = 2;
+        let rows: HashMap<_, _> = state.start_summary().into_iter().collect();
+        assert_eq!(rows["Steps"], "3 steps, 2 cycles");
+        assert_eq!(rows["Clicks"], "24");
+        assert_eq!(rows["Buttons"], "Left × 2 steps, Right × 1 steps");
+        assert!(rows.contains_key("Duration"));
+
+        state.mode = JobMode::Single;
+        state.use_finite_clicks = false;
+        let rows: HashMap<_, _> = state.start_summary().into_iter().collect();
+        assert_eq!(rows["Clicks"], "until stopped");
+        assert!(!rows.contains_key("Duration"));
+
+        // every start by hand asks first, the compact bar and voice included
+        state.compact = true;
+        state.request_start();
+        assert!(state.confirming_start && state.raise_for_start && !state.is_running());
+        state.confirming_start = false;
+        state.triggers.voice.start_phrase = "clicker go".into();
+        state.voice_heard("clicker go".into());
+        assert!(state.confirming_start && !state.is_running());
+
+        let dir = std::env::temp_dir().join(format!("area_clicker_confirm_{}", std::process::id()));
+        state.profiles = Some(ProfileStore::new(dir.clone()));
+        state.set_confirm_start(false);
+        let mut restarted = AppState { profiles: Some(ProfileStore::new(dir.clone())), ..AppState::default() };
+        restarted.load_app_settings();
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(!restarted.confirm_start);
+    }
+
+    #[test]
+    fn test_forbidden_zones_resample() {
+        use crate::sampler::{resample_outside, sample_point};
+        let area = Bounds { min_x: 0, max_x: 10, min_y: 0, max_y: 10 };
+        let forbidden = [Bounds { min_x: 0, max_x: 10, min_y: 0, max_y: 5 }];
+        let mut rng = rand::thread_rng();
+        for _ in 0..200 {
+            let (_, y) = resample_outside(&forbidden, 100, || sample_point(area, &[], &mut rng)).unwrap();
+            assert!(y > 5);
+        }
+        assert_eq!(resample_outside(&[area], 100, || sample_point(area, &[], &mut rng)), None);
+
+        let mut state = AppState { bounds_inputs: [0, 10, 0, 5], ..AppState::default() };
+        state.forbidden.push(ForbiddenZone { name: "top".into(), bounds: state.current_bounds(), enabled: true });
+        state.forbidden.push(ForbiddenZone { name: "off".into(), bounds: area, enabled: false });
+        state.update_forbidden();
+        assert_eq!(state.config.lock().forbidden, forbidden.to_vec());
+    }
+
+    #[test]
+    fn test_wait_for_condition() {
+        let mut wait = WaitFor { kind: WatchKind::Process, pattern: "^no-such-process-[0-9]+$".into(), ..WaitFor::default() };
+        let re = wait.matcher().unwrap();
+        assert!(re.is_match("No-Such-Process-42"));
+        if cfg!(target_os = "linux") {
+            assert_eq!(wait.is_present(&re), Ok(false));
+            let own = std::fs::read_to_string("/proc/self/comm").unwrap();
+            wait.pattern = regex::escape(own.trim());
+            assert_eq!(wait.is_present(&wait.matcher().unwrap()), Ok(true));
+        }
+        wait.pattern = "(".into();
+        assert!(wait.matcher().is_err());
+        wait.pattern = "  ".into();
+        assert!(wait.matcher().is_err());
+
+        let args = Args::parse_from(["area_clicker", "--wait-process", "game", "--wait-timeout", "5", "--stop-when-gone"]);
+        let mut state = AppState::default();
+        state.apply_args(&args);
+        assert!(state.use_wait_for);
+        assert_eq!(state.wait_for, WaitFor { kind: WatchKind::Process, pattern: "game".into(), timeout_secs: 5, stop_when_gone: true });
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_parse_wmctrl_line() {
+        assert_eq!(
+            crate::watch::parse_wmctrl_line("0x03a00007  0 host   Untitled - Notepad  "),
+            Some(("0x03a00007".to_string(), "Untitled - Notepad".to_string()))
+        );
+        assert_eq!(crate::watch::parse_wmctrl_line("0x01 -1 host"), None);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    fn test_refocus_window() {
+        let mut refocus = Refocus { pattern: "notepad".into(), every_click: true };
+        assert!(refocus.matcher().unwrap().is_match("Untitled - Notepad"));
+        refocus.pattern = String::new();
+        assert!(refocus.matcher().is_err());
+
+        let mut state = AppState { use_refocus: true, ..AppState::default() };
+        state.refocus = Refocus { pattern: "^Game$".into(), every_click: true };
+        let profile = state.to_profile();
+        let mut loaded = AppState::default();
+        loaded.apply_profile(profile);
+        assert!(loaded.use_refocus);
+        assert_eq!(loaded.refocus, state.refocus);
+    }
+
+    #[test]
+    fn test_screenshot_step_and_files() {
+        let mut state = AppState { step_kind: StepKind::Screenshot, step_shot_full: true, ..AppState::default() };
+        state.step_name = "Loot: check".into();
+        state.save_step();
+        assert_eq!(state.steps[0].target, StepTarget::Screenshot { full_screen: true });
+        assert!(state.steps[0].summary().contains("screenshot full screen"));
+        state.step_kind = StepKind::Click;
+        state.edit_step(0);
+        assert!(state.step_kind == StepKind::Screenshot && state.step_shot_full);
+
+        let t = std::time::UNIX_EPOCH + std::time::Duration::from_millis(1_760_641_532_042);
+        assert_eq!(screenshot::file_name(t, "Loot: check"), "2025-10-16_19-05-32.042_Loot__check.png");
+        // bounds include their max edges, so a one-pixel area is 1×1
+        assert_eq!(screenshot::pixel_size(Bounds { min_x: 5, max_x: 5, min_y: 0, max_y: 99 }), (1, 100));
+
+        let dir = std::env::temp_dir().join(format!("area_clicker_shots_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.png");
+        let capture = screenshot::Capture { width: 3, height: 2, rgb: (0..18).collect() };
+        screenshot::save_png(&capture, &path).unwrap();
+        let mut reader = png::Decoder::new(std::fs::File::open(&path).unwrap()).read_info().unwrap();
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width, info.height), (3, 2));
+        assert_eq!(&buf[..info.buffer_size()], &capture.rgb[..]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_text_target() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+            4\t1\t1\t1\t1\t0\t10\t20\t200\t30\t-1\t\n\
+            5\t1\t1\t1\t1\t1\t10\t20\t60\t30\t96.1\tPlease\n\
+            5\t1\t1\t1\t1\t2\t80\t20\t70\t30\t95.0\tConfirm.\n\
+            5\t1\t1\t1\t2\t1\t10\t60\t50\t30\t91.3\tCancel\n";
+        let words = ocr::parse_tsv(tsv, (100, 200));
+        assert_eq!(words.len(), 3);
+        assert_eq!(words[1].bounds, Bounds { min_x: 180, max_x: 250, min_y: 220, max_y: 250 });
+        assert_eq!(ocr::find_text(&words, "confirm"), Some(words[1].bounds));
+        assert_eq!(ocr::find_text(&words, "Please  CONFIRM"), Some(Bounds { min_x: 110, max_x: 250, min_y: 220, max_y: 250 }));
+        // phrases don't run across lines
+        assert_eq!(ocr::find_text(&words, "confirm cancel"), None);
+        assert_eq!(ocr::find_text(&words, " "), None);
+
+        let mut state = AppState { step_text: " Confirm ".into(), ..AppState::default() };
+        state.save_step();
+        assert_eq!(state.steps[0].text.as_deref(), Some("Confirm"));
+        assert!(state.step_text.is_empty());
+        state.use_point = true;
+        state.step_text = "Confirm".into();
+        state.save_step();
+        assert_eq!(state.steps[1].text, None);
+    }
+
+    struct ScriptedStep { id: usize, outcomes: std::collections::VecDeque<StepOutcome>, log: Arc<Mutex<Vec<usize>>> }
+
+    impl Step for ScriptedStep {
+        fn name(&self) -> &str { "scripted" }
+        fn execute(&mut self, _ctx: &mut StepCtx) -> StepOutcome {
+            self.log.lock().push(self.id);
+            self.outcomes.pop_front().unwrap_or(StepOutcome::Stop)
+        }
+    }
+
+    #[test]
+    fn test_wait_for_image_branches() {
+        // template matching, exact and within tolerance
+        let mut screen = screenshot::Capture { width: 6, height: 5, rgb: vec![0; 6 * 5 * 3] };
+        for (x, y) in [(3, 2), (4, 2), (3, 3), (4, 3)] { screen.rgb[(y * 6 + x) * 3] = 200; }
+        let needle = screenshot::Capture { width: 2, height: 2, rgb: [200, 0, 0].repeat(4) };
+        assert_eq!(template::find(&screen, &needle, 0), Some((3, 2)));
+        let dimmer = screenshot::Capture { width: 2, height: 2, rgb: [190, 5, 0].repeat(4) };
+        assert_eq!(template::find(&screen, &dimmer, 0), None);
+        assert_eq!(template::find(&screen, &dimmer, 10), Some((3, 2)));
+
+        // larger templates are ruled out by block sums first, which must not lose a match
+        let (w, h) = (60, 40);
+        let rgb = (0..w * h * 3).map(|i| ((i / 3 % w) * 37 + (i / 3 / w) * 91 + i % 3 * 53) as u8 ^ (i * 7 % 13) as u8).collect();
+        let screen = screenshot::Capture { width: w as u32, height: h as u32, rgb };
+        let crop = |(x0, y0): (usize, usize), nw: usize, nh: usize| screenshot::Capture {
+            width: nw as u32,
+            height: nh as u32,
+            rgb: (y0..y0 + nh).flat_map(|y| screen.rgb[(y * w + x0) * 3..(y * w + x0 + nw) * 3].to_vec()).collect(),
+        };
+        let mut needle = crop((23, 17), 12, 9);
+        needle.rgb[40] = needle.rgb[40].wrapping_add(128); // one pixel off is within MAX_MISMATCH
+        assert_eq!(template::find(&screen, &needle, 0), Some((23, 17)));
+        for p in needle.rgb.iter_mut() { *p = p.saturating_add(6); }
+        assert_eq!(template::find(&screen, &needle, 0), None);
+        assert_eq!(template::find(&screen, &needle, 6), Some((23, 17)));
+
+        let path = std::env::temp_dir().join(format!("area_clicker_template_{}.png", std::process::id()));
+        screenshot::save_png(&needle, &path).unwrap();
+        assert_eq!(screenshot::load_png(&path).unwrap().rgb, needle.rgb);
+        std::fs::remove_file(&path).unwrap();
+
+        // skip, jump and stop
+        let running = AtomicBool::new(true);
+        let config = Mutex::new(ClickConfig::default());
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let step = |id, outcomes: Vec<StepOutcome>| Box::new(ScriptedStep { id, outcomes: outcomes.into(), log: Arc::clone(&log) }) as Box<dyn Step>;
+        let mut steps = vec![
+            step(0, vec![StepOutcome::SkipNext]),
+            step(1, vec![StepOutcome::Next]),
+            step(2, vec![StepOutcome::Jump(1), StepOutcome::Stop]),
+        ];
+        run_steps(&mut steps, None, &mut StepCtx::new(&running, &config));
+        assert_eq!(*log.lock(), vec![0, 2, 1, 2]);
+
+        // a missing image takes the timeout branch
+        let mut state = AppState { step_kind: StepKind::WaitImage, step_image: "/no/such/template.png".into(), ..AppState::default() };
+        state.step_wait.on_timeout = Branch::Abort;
+        state.save_step();
+        let StepTarget::WaitImage(w) = state.steps[0].target else { panic!("not a wait step") };
+        assert_eq!(state.steps[0].image.as_deref(), Some("/no/such/template.png"));
+        let mut wait = WaitImageStep::new(state.steps[0].clone(), w);
+        assert!(matches!(wait.execute(&mut StepCtx::new(&running, &config)), StepOutcome::Stop));
+
+        // jumps follow their step when the list changes
+        let mut state = AppState::default();
+        for _ in 0..3 { state.save_step(); }
+        state.steps[0].target = StepTarget::WaitImage(WaitImage { on_found: Branch::Jump(2), on_timeout: Branch::Jump(1), ..WaitImage::default() });
+        state.remove_step(1);
+        assert_eq!(state.steps[0].target, StepTarget::WaitImage(WaitImage { on_found: Branch::Jump(1), on_timeout: Branch::Continue, ..WaitImage::default() }));
+    }
+
+    #[test]
+    fn test_verify_clicks() {
+        let mut state = AppState { step_use_verify: true, ..AppState::default() };
+        state.step_verify = Verify { check: Check::Image { region: Bounds::around((50, 50), 10) }, image: "/no/such.png".into(), retries: 3, ..Verify::default() };
+        state.save_step();
+        assert_eq!(state.steps[0].verify.as_ref(), Some(&state.step_verify));
+        assert!(state.steps[0].summary().ends_with(" · verified"));
+        let json = serde_json::to_string(&state.steps[0]).unwrap();
+        assert_eq!(serde_json::from_str::<SequenceStep>(&json).unwrap().verify, state.steps[0].verify);
+
+        // only click steps are verified
+        state.step_kind = StepKind::Screenshot;
+        state.save_step();
+        assert_eq!(state.steps[1].verify, None);
+
+        let mut image = None;
+        assert!(crate::step::check(&state.step_verify, &mut image).is_err());
+
+        let progress = JobProgress { clicks: 12, failed_steps: 2, ..JobProgress::default() };
+        assert_eq!(progress.summary(), "12 clicks, 2 failed");
+    }
+
+    #[test]
+    fn test_step_error_policy() {
+        let running = AtomicBool::new(true);
+        let config = Mutex::new(ClickConfig::default());
+        let runs = Arc::new(Mutex::new(0));
+        let inverted = Bounds { min_x: 10, max_x: 0, min_y: 10, max_y: 0 };
+        let broken = |then| SequenceStep {
+            min_secs: 0.0,
+            max_secs: 0.0,
+            on_error: OnError { retries: 0, then },
+            ..seq_step("Broken", 3, inverted)
+        };
+
+        // default policy: report and carry on with the next step
+        let mut steps: Vec<Box<dyn Step>> = vec![
+            Box::new(ClickStep::new(broken(Branch::Continue))),
+            Box::new(CountingStep { runs: Arc::clone(&runs), stop_after: None }),
+        ];
+        let mut ctx = StepCtx::new(&running, &config);
+        run_steps(&mut steps, Some(2), &mut ctx);
+        assert_eq!(*runs.lock(), 2);
+        let p = ctx.progress.lock().clone();
+        assert_eq!(p.failed_steps, 2);
+        assert_eq!(p.last_error.as_deref(), Some("Step 'Broken' failed: the step has no valid area"));
+
+        *runs.lock() = 0;
+        let mut steps: Vec<Box<dyn Step>> = vec![
+            Box::new(ClickStep::new(broken(Branch::Abort))),
+            Box::new(CountingStep { runs: Arc::clone(&runs), stop_after: None }),
+        ];
+        run_steps(&mut steps, Some(2), &mut StepCtx::new(&running, &config));
+        assert_eq!(*runs.lock(), 0);
+
+        // jumps in the policy follow their step around
+        let mut state = AppState { steps: vec![broken(Branch::Jump(1)), broken(Branch::Continue)], ..AppState::default() };
+        state.swap_steps(0, 1);
+        assert_eq!(state.steps[1].on_error.then, Branch::Jump(0));
+        assert!(state.steps[1].summary().ends_with(" · on error: go to step 1"));
+    }
+
+    #[test]
+    fn test_event_commands() {
+        let events = EventCommands {
+            on_finish: "notify-send \"Done: {clicks} clicks\"".into(),
+            on_error: "echo \"{step}: {error}\" >> errors.txt".into(),
+            on_cycle: " ./rotate-vpn.sh {cycle} ".into(),
+            every_cycles: 5,
+            ..EventCommands::default()
+        };
+        assert_eq!(events.command(Event::Start, 0), None);
+        assert_eq!(events.command(Event::Cycle, 4), None);
+        assert_eq!(events.command(Event::Cycle, 10), Some("./rotate-vpn.sh {cycle}"));
+
+        let progress = JobProgress {
+            clicks: 428, cycle: 10, step_name: "Loot".into(), last_error: Some("text “Loot” not found".into()),
+            ..JobProgress::default()
+        };
+        let env = crate::events::env(Event::Error, &progress);
+        assert!(env.contains(&("AREA_CLICKER_CLICKS", "428".to_string())));
+        assert!(env.contains(&("AREA_CLICKER_ERROR", "text “Loot” not found".to_string())));
+        #[cfg(unix)]
+        {
+            assert_eq!(crate::events::expand(&events.on_finish), "notify-send \"Done: ${AREA_CLICKER_CLICKS} clicks\"");
+            // values are never parsed as part of the command
+            let progress = JobProgress { step_name: "a; echo pwned $(echo x) `echo y`".into(), ..progress.clone() };
+            let out = std::process::Command::new("sh")
+                .args(["-c", &crate::events::expand("printf '%s|%s' \"{step}\" \"{clicks}\"")])
+                .envs(crate::events::env(Event::Error, &progress))
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8_lossy(&out.stdout), "a; echo pwned $(echo x) `echo y`|428");
+        }
+
+        let mut state = AppState { use_events: true, events: events.clone(), ..AppState::default() };
+        state.start_summary().iter().find(|(label, _)| *label == "Commands").expect("listed");
+        let mut loaded = AppState::default();
+        loaded.apply_profile(state.to_profile());
+        assert!(loaded.use_events);
+        assert_eq!(loaded.events, events);
+
+        // from a file or a bundle, the commands come turned off
+        let dir = std::env::temp_dir().join(format!("area_clicker_events_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("shared.json");
+        profile::save_file(&file, &state.to_profile()).unwrap();
+        let mut from_args = AppState::default();
+        from_args.apply_args(&Args::parse_from(["area_clicker", "--profile", file.to_str().unwrap()]));
+        let mut from_socket = AppState::default();
+        let socket = daemon::handle(&mut from_socket, &format!("load {}", file.display()));
+        let bundle = profile::Bundle { profiles: [("Shared".to_string(), state.to_profile())].into(), ..profile::Bundle::default() };
+        let store = ProfileStore::new(dir.join("store"));
+        let imported = store.import_bundle(&bundle).and_then(|_| store.load("Shared"));
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!((from_args.use_events, &from_args.events), (false, &events));
+        assert!(socket.is_ok());
+        assert!(!from_socket.use_events);
+        assert!(!imported.unwrap().use_events);
+
+        state.events = EventCommands::default();
+        assert!(state.events.is_empty());
+    }
+
+    #[test]
+    fn test_daemon_commands() {
+        let mut state = AppState::default();
+        assert_eq!(daemon::handle(&mut state, "status\n"), Ok("Idle".to_string()));
+        assert_eq!(daemon::handle(&mut state, "stop"), Ok(String::new()));
+        assert!(daemon::handle(&mut state, "load").is_err());
+        assert!(daemon::handle(&mut state, "load some-profile").is_err()); // no profile store in tests
+        assert!(daemon::handle(&mut state, "skip").is_err()); // nothing running
+        assert!(daemon::handle(&mut state, "jump 0").unwrap_err().starts_with("usage"));
+        assert!(daemon::handle(&mut state, "dance").unwrap_err().starts_with("unknown command 'dance'"));
+
+        let dir = std::env::temp_dir().join(format!("area_clicker_daemon_{}", std::process::id()));
+        let path = dir.join("kiosk.json");
+        let saved = AppState { mode: JobMode::GridSweep, grid_cols: 7, ..AppState::default() };
+        crate::profile::save_file(&path, &saved.to_profile()).unwrap();
+        let reply = daemon::handle(&mut state, &format!("load {}", path.display()));
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(reply.is_ok(), "{:?}", reply);
+        assert_eq!((state.mode, state.grid_cols), (JobMode::GridSweep, 7));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_daemon_socket_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("area_clicker_sock_{}", std::process::id())).join("run");
+        let socket = dir.join("area_clicker.sock");
+        let listener = daemon::bind_private(&socket).unwrap();
+        let mode = |p: &std::path::Path| std::fs::metadata(p).unwrap().permissions().mode() & 0o777;
+        assert_eq!((mode(&dir), mode(&socket)), (0o700, 0o600));
+        assert!(daemon::bind_private(&socket).unwrap_err().starts_with("Another daemon"));
+        drop(listener);
+        // our own dead socket is replaced, anything else is left alone
+        drop(daemon::bind_private(&socket).unwrap());
+        std::fs::remove_file(&socket).unwrap();
+        std::fs::write(&socket, "notes").unwrap();
+        assert!(daemon::bind_private(&socket).unwrap_err().contains("remove it by hand"));
+        assert_eq!(std::fs::read_to_string(&socket).unwrap(), "notes");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1); // no staging folder left behind
+        std::fs::remove_dir_all(dir.parent().unwrap()).ok();
+    }
+
+    #[test]
+    fn test_translation_catalogs() {
+        let args = ["3".to_string(), "Login".to_string()];
+        assert_eq!(i18n::fill("{} clicks on {}", &args), "3 clicks on Login");
+        assert_eq!(i18n::fill("{1}: {0} Klicks", &args), "Login: 3 Klicks");
+        assert_eq!(i18n::fill("{x} stays", &args), "{x} stays");
+
+        let de = i18n::parse(include_str!("../locales/de.json")).unwrap();
+        let placeholders = |s: &str| s.matches('{').count();
+        for (en, text) in &de.texts {
+            assert_eq!(placeholders(en), placeholders(text), "placeholders differ for {:?}", en);
+        }
+        let codes: Vec<_> = i18n::available(None).into_iter().map(|l| l.code).collect();
+        assert_eq!(codes, ["en", "de"]);
+    }
+
+    #[test]
+    fn test_theme_selection() {
+        let system = Theme::default();
+        assert!(system.is_dark(None));
+        assert!(system.is_dark(Some(eframe::Theme::Dark)));
+        assert!(!system.is_dark(Some(eframe::Theme::Light)));
+        let light = Theme { mode: ThemeMode::Light, accent: Some([200, 40, 90]) };
+        assert!(!light.is_dark(Some(eframe::Theme::Dark)));
+        let visuals = light.visuals(None);
+        assert!(!visuals.dark_mode);
+        assert_eq!(visuals.selection.bg_fill, Color32::from_rgb(200, 40, 90));
+
+        let json = serde_json::to_string(&light).unwrap();
+        assert_eq!(serde_json::from_str::<Theme>(&json).unwrap(), light);
+        assert_eq!(serde_json::from_str::<Theme>("{}").unwrap(), system);
+    }
+
+    #[test]
+    fn test_setup_wizard_flow() {
+        let mut setup = Setup::default();
+        assert_eq!(setup.page, SetupPage::Permissions);
+        assert!(!setup.can_advance(true, false)); // input must work first
+        assert!(setup.can_advance(false, true));
+        assert_eq!(SetupPage::Permissions.back(), None);
+        assert_eq!(SetupPage::Area.next(), Some(SetupPage::TestClick));
+        assert_eq!(SetupPage::Profile.next(), None);
+
+        setup.go(SetupPage::Area);
+        assert!(!setup.can_advance(false, true));
+        setup.go(SetupPage::TestClick);
+        setup.click = Some(TestClick { target: (100, 200), seen: Some((101, 199)) });
+        assert_eq!(setup.click.unwrap().landed(), Some(true));
+        assert_eq!(TestClick { target: (100, 200), seen: Some((140, 200)) }.landed(), Some(false));
+        assert_eq!(TestClick { target: (100, 200), seen: None }.landed(), None);
+        assert!(!setup.can_advance(true, true)); // the user has to confirm it
+        setup.confirmed = Some(true);
+        assert!(setup.can_advance(true, true));
+        setup.reset_click();
+        assert_eq!((setup.click, setup.confirmed), (None, None));
+
+        setup.go(SetupPage::Profile);
+        setup.profile_name = "  ".into();
+        assert!(!setup.can_advance(true, true));
+
+        // the test click is checked like a job's: none goes into a forbidden zone
+        let everywhere = Bounds { min_x: -100_000, max_x: 100_000, min_y: -100_000, max_y: 100_000 };
+        let forbidden = vec![ForbiddenZone { name: "all".into(), bounds: everywhere, enabled: true }];
+        let mut state = AppState { setup: Some(Setup { click_at: Some(Instant::now()), ..Setup::default() }), forbidden, ..AppState::default() };
+        let ctx = egui::Context::default();
+        state.poll_test_click(&ctx);
+        assert!(state.test_click.is_some());
+        while state.test_click.is_some() {
+            std::thread::sleep(Duration::from_millis(10));
+            state.poll_test_click(&ctx);
+        }
+        let setup = state.setup.unwrap();
+        assert_eq!(setup.click, None);
+        assert!(setup.refused.is_some());
+    }
+
+    #[test]
+    fn test_tutorial_stages() {
+        let mut t = Tutorial::default();
+        let pick = Rect::from_min_size(Pos2::new(10.0, 10.0), egui::vec2(80.0, 20.0));
+        t.mark(TutorialStage::Start, pick); // not this stage's widget
+        assert_eq!(t.target, None);
+        t.mark(TutorialStage::PickArea, pick);
+        assert_eq!(t.target, Some(pick));
+
+        t.advance(TutorialStage::Start); // a late event from another stage doesn't skip ahead
+        assert_eq!(t.stage, TutorialStage::PickArea);
+        t.advance(TutorialStage::PickArea);
+        assert_eq!((t.stage, t.target), (TutorialStage::SetInterval, None));
+        t.advance(TutorialStage::SetInterval);
+        t.advance(TutorialStage::Start);
+        assert_eq!(t.stage, TutorialStage::Done);
+        assert_eq!(TutorialStage::Done.next(), TutorialStage::Done);
+    }
+
+    #[test]
+    fn test_safety_limits() {
+        use crate::limits::RateLimiter;
+        use std::time::Duration;
+
+        let mut rate = RateLimiter::default();
+        let t0 = Instant::now();
+        for i in 0..3 { rate.record(t0 + Duration::from_secs(i)); }
+        assert_eq!(rate.delay(4, t0 + Duration::from_secs(3)), Duration::ZERO);
+        assert_eq!(rate.delay(3, t0 + Duration::from_secs(3)), Duration::from_secs(57)); // until the first drops out
+        assert_eq!(rate.delay(3, t0 + Duration::from_secs(61)), Duration::ZERO);
+
+        let limits = SafetyLimits { per_minute: Some(300), max_clicks: Some(50), max_minutes: Some(10), ..SafetyLimits::default() };
+        assert!(limits.clicks_exceeded(49).is_none());
+        assert!(limits.clicks_exceeded(50).is_some());
+        assert!(limits.run_time_exceeded(Duration::from_secs(600)).is_some());
+
+        // the "0.01 instead of 1" typo
+        let mut state = AppState { limits, min_secs: 0.01, max_secs: 0.02, use_finite_clicks: true, num_clicks: 20, ..AppState::default() };
+        assert_eq!(state.limit_warnings().len(), 1);
+        state.use_finite_clicks = false; // until stopped: both the click and time budget apply
+        assert_eq!(state.limit_warnings().len(), 3);
+        state.min_secs = 1.0;
+        state.max_secs = 2.0;
+        state.limits = SafetyLimits::default();
+        assert!(state.limit_warnings().is_empty());
+
+        // the engine stops a job that's past its run time, even between clicks
+        let running = AtomicBool::new(true);
+        let config = Mutex::new(ClickConfig { limits: SafetyLimits { max_minutes: Some(0), ..limits }, ..ClickConfig::default() });
+        let mut ctx = StepCtx::new(&running, &config);
+        ctx.sleep_ms(100);
+        assert!(!running.load(Ordering::Relaxed));
+        assert!(ctx.progress.lock().last_error.as_deref().unwrap().contains("run time"));
+    }
+
+    #[test]
+    fn test_profile_hot_reload() {
+        use notify::event::{CreateKind, ModifyKind};
+        use notify::{Event as FsEvent, EventKind};
+
+        let dir = std::env::temp_dir().join(format!("area_clicker_watch_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("tuned.json");
+        let woken = Arc::new(Mutex::new(0));
+        let wakes = Arc::clone(&woken);
+        let watch = FileWatch::new(&path, move || *wakes.lock() += 1).unwrap();
+        std::fs::remove_dir_all(&dir).ok();
+        let now = Instant::now();
+        assert!(!watch.take_change_at(now + file_watch::SETTLE));
+        watch.notice(&FsEvent::new(EventKind::Modify(ModifyKind::Any)).add_path(dir.join("other.json"))); // not the watched file
+        assert!(!watch.pending());
+        watch.notice(&FsEvent::new(EventKind::Create(CreateKind::File)).add_path(path.clone()));
+        watch.notice(&FsEvent::new(EventKind::Modify(ModifyKind::Any)).add_path(path.clone()));
+        assert!(watch.pending());
+        assert!(!watch.take_change_at(Instant::now())); // not settled yet
+        let settled = Instant::now() + file_watch::SETTLE;
+        assert!(watch.take_change_at(settled));
+        assert!(!watch.take_change_at(settled)); // once per burst
+        assert!(!watch.pending());
+        assert_eq!(*woken.lock(), 2);
+
+        // a running job switches to the reloaded steps at the end of the cycle
+        let running = AtomicBool::new(true);
+        let config = Mutex::new(ClickConfig { mode: JobMode::AntiAfk, reload: true, ..ClickConfig::default() });
+        let runs = Arc::new(Mutex::new(0));
+        let progress = Arc::new(Mutex::new(JobProgress::default()));
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                let mut steps: Vec<Box<dyn Step>> = vec![Box::new(CountingStep { runs: Arc::clone(&runs), stop_after: None })];
+                run_steps(&mut steps, Some(5), &mut StepCtx::new(&running, &config).with_progress(Arc::clone(&progress)));
+            });
+            // the anti-AFK loop runs until stopped
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while progress.lock().step_names != ["Anti-AFK"] && Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            running.store(false, Ordering::Relaxed);
+        });
+        assert_eq!(*runs.lock(), 1);
+        assert_eq!(progress.lock().step_names, ["Anti-AFK"]);
+        assert!(!config.lock().reload);
+    }
+
+    #[test]
+    fn test_settings_bundle() {
+        let dir = std::env::temp_dir().join(format!("area_clicker_bundle_{}", std::process::id()));
+        let mut state = AppState { profiles: Some(ProfileStore::new(dir.join("old"))), ..AppState::default() };
+        state.min_secs = 4.0;
+        state.save_profile_as("Farm");
+        state.areas.insert("chest".into(), Bounds { min_x: 1, max_x: 5, min_y: 2, max_y: 6 });
+        state.persist_areas();
+        state.limits.max_clicks = Some(50);
+        state.update_limits();
+        state.set_confirm_start(false);
+        state.bundle_path = dir.join("settings.json").display().to_string();
+        assert!(state.export_settings().is_ok());
+
+        let mut fresh = AppState { profiles: Some(ProfileStore::new(dir.join("new"))), ..AppState::default() };
+        fresh.bundle_path = state.bundle_path.clone();
+        let imported = fresh.import_settings();
+        let mut other = AppState { profiles: Some(ProfileStore::new(dir.join("other"))), ..AppState::default() };
+        other.bundle_path = dir.join("missing.json").display().to_string();
+        let missing = other.import_settings();
+        // a bad entry anywhere in the bundle keeps all of it out
+        let mut bundle = ProfileStore::new(dir.join("old")).export_bundle().unwrap();
+        bundle.translations.insert("../de".into(), "{}".into());
+        let bad_store = ProfileStore::new(dir.join("bad"));
+        let bad = bad_store.import_bundle(&bundle);
+        let bad_profiles = bad_store.list();
+        std::fs::remove_dir_all(&dir).ok();
+        assert!(imported.is_ok());
+        assert!(bad.is_err());
+        assert!(bad_profiles.is_empty());
+        assert_eq!(fresh.profile_names, ["Farm"]);
+        assert_eq!(fresh.current_profile.as_deref(), Some("Farm"));
+        assert_eq!(fresh.min_secs, 4.0);
+        assert!(fresh.areas.contains_key("chest"));
+        assert_eq!(fresh.limits.max_clicks, Some(50));
+        assert_eq!(fresh.config.lock().limits.max_clicks, Some(50));
+        assert!(!fresh.confirm_start);
+        assert!(missing.is_err());
+    }
+
+    #[test]
+    fn test_encrypted_profile() {
+        let dir = std::env::temp_dir().join(format!("area_clicker_crypt_{}", std::process::id()));
+        let mut state = AppState { profiles: Some(ProfileStore::new(dir.clone())), ..AppState::default() };
+        state.min_secs = 6.0;
+        state.save_profile_as("Login");
+        state.set_profile_passphrase(Some("hunter2".into()));
+        state.duplicate_profile("Login", "Login copy");
+        let store = ProfileStore::new(dir.clone());
+        let on_disk = std::fs::read_to_string(store.path("Login").unwrap()).unwrap();
+        let plain_load = store.load("Login");
+        let encrypted = store.is_encrypted("Login");
+        let bundle = store.export_bundle().unwrap();
+
+        let mut fresh = AppState { profiles: Some(ProfileStore::new(dir.clone())), ..AppState::default() };
+        fresh.load_profile("Login copy");
+        let asked = fresh.pass_prompt.as_ref().and_then(|p| p.unlock.clone());
+        let wrong = fresh.unlock_profile("Login copy", "hunter3".into());
+        let right = fresh.unlock_profile("Login copy", "hunter2".into());
+        fresh.set_profile_passphrase(None);
+        let decrypted = store.is_encrypted("Login copy");
+        std::fs::remove_dir_all(&dir).ok();
+
+        assert!(!on_disk.contains("min_secs"));
+        assert!(plain_load.is_err());
+        assert!(encrypted);
+        assert!(bundle.profiles.is_empty());
+        assert_eq!(bundle.encrypted.len(), 2);
+        assert_eq!(asked.as_deref(), Some("Login copy"));
+        assert!(wrong.is_err());
+        assert!(right.is_ok());
+        assert_eq!(fresh.min_secs, 6.0);
+        assert!(!decrypted);
+    }
+
+    #[test]
+    fn test_quiet_hours() {
+        let morning = QuietWindow { start: 9 * 60, end: 10 * 60 };
+        assert!(morning.contains(9 * 60));
+        assert!(morning.contains(9 * 60 + 59));
+        assert!(!morning.contains(10 * 60));
+        let night = QuietWindow { start: 22 * 60, end: 6 * 60 };
+        assert!(night.contains(23 * 60) && night.contains(0) && night.contains(5 * 60 + 59));
+        assert!(!night.contains(12 * 60));
+        assert!(!QuietWindow { start: 60, end: 60 }.contains(60));
+        assert_eq!(quiet::active(&[morning, night], 30), Some(night));
+        assert_eq!(quiet::active(&[morning, night], 12 * 60), None);
+
+        assert_eq!(quiet::parse_time("09:30"), Some(570));
+        assert_eq!(quiet::parse_time(" 7 "), Some(420));
+        assert_eq!(quiet::parse_time("24:00"), None);
+        assert_eq!(quiet::parse_time("9:75"), None);
+        assert_eq!(night.label(), "22:00–06:00");
+
+        let mut state = AppState { quiet_hours: vec![morning], ..AppState::default() };
+        assert!(state.active_quiet_hours().is_empty());
+        state.use_quiet_hours = true;
+        let restored = { let mut s = AppState::default(); s.apply_profile(state.to_profile()); s };
+        assert_eq!(restored.active_quiet_hours(), [morning]);
+        assert!(state.start_summary().iter().any(|(label, v)| *label == "Pauses" && v == "09:00–10:00"));
+    }
+
+    #[test]
+    fn test_pause_when_user_active() {
+        use activity::Presence;
+        use std::time::Duration;
+        let secs = Duration::from_secs;
+        let t0 = Instant::now();
+        let at = |s: u64| t0 + secs(s);
+
+        // the last input was our own click: not the user
+        let mut p = Presence::default();
+        p.observe(at(100), Some(secs(2)), Some(secs(2)), Some((10, 10)));
+        assert!(!p.active_within(at(100), secs(30)));
+        // something came in after our click
+        p.observe(at(101), Some(secs(1)), Some(secs(3)), Some((10, 10)));
+        assert!(p.active_within(at(101), secs(30)));
+        assert!(!p.active_within(at(131), secs(30)));
+
+        // no idle time (e.g. Wayland): a cursor moved behind the job's back counts
+        let mut p = Presence::default();
+        p.observe(at(100), None, Some(secs(5)), Some((10, 10)));
+        p.observe(at(101), None, Some(secs(6)), Some((50, 10)));
+        assert!(p.active_within(at(110), secs(30)));
+        // but not one the job moved itself in between
+        let mut p = Presence::default();
+        p.observe(at(100), None, Some(secs(5)), Some((10, 10)));
+        p.observe(at(101), None, Some(Duration::from_millis(300)), Some((50, 10)));
+        assert!(!p.active_within(at(101), secs(30)));
+
+        let saved: SafetyLimits = serde_json::from_str(r#"{ "pause_when_locked": false }"#).unwrap();
+        assert!(!saved.pause_when_active && saved.idle_secs == 30);
+    }
+
+    #[test]
+    fn test_click_feedback() {
+        use std::time::Duration;
+        let saved: ClickFeedback = serde_json::from_str(r#"{ "dot": true }"#).unwrap();
+        assert!(saved.dot && !saved.sound);
+        assert_eq!(saved.command, feedback::DEFAULT_COMMAND);
+
+        let now = Instant::now();
+        assert!(!feedback::dot_lit(None, now));
+        assert!(feedback::dot_lit(Some(now), now + Duration::from_millis(100)));
+        assert!(!feedback::dot_lit(Some(now), now + feedback::DOT_DURATION));
+
+        // the engine picks up the setting like the safety limits
+        let mut state = AppState::default();
+        state.feedback.sound = true;
+        state.update_feedback();
+        assert!(state.config.lock().feedback.sound);
+    }
+
+    #[test]
+    fn test_run_stats() {
+        use crate::stats::StepStats;
+        use std::time::Duration;
+        let t0 = Instant::now();
+        let at = |ms: u64| t0 + Duration::from_millis(ms);
+
+        let mut s = StepStats::default();
+        s.click(at(0), false);
+        assert_eq!(s.mean_interval(), None);
+        s.click(at(1000), true);
+        s.click(at(4000), false);
+        assert_eq!((s.clicks, s.misses), (3, 1));
+        assert!((s.mean_interval().unwrap() - 2.0).abs() < 1e-9);
+        assert!((s.stdev_interval().unwrap() - 1.0).abs() < 1e-9);
+
+        // time goes to whichever step was running, the current one up to now
+        let mut p = JobProgress {
+            step_names: vec!["Open, then wait".to_string(), "Loot".to_string()],
+            step_stats: vec![s, StepStats::default()],
+            started: Some(t0),
+            clicks: 3,
+            ..JobProgress::default()
+        };
+        p.switch_step(0, at(0));
+        p.switch_step(1, at(5000));
+        p.retried();
+        let stats = p.run_stats(at(6000));
+        assert_eq!(stats.steps[0].1.time, Duration::from_secs(5));
+        assert_eq!(stats.steps[1].1.time, Duration::from_secs(1));
+        assert_eq!(stats.steps[1].1.retries, 1);
+        assert_eq!(stats.duration, Duration::from_secs(6));
+        assert_eq!(stats.bottleneck(), Some("Open, then wait"));
+        let csv = stats.to_csv();
+        assert!(csv.lines().nth(1).unwrap().starts_with("\"Open, then wait\",3,2.000,1.000,1,0,5.000"));
+
+        // taken once when the job ends; a job without clicks has nothing to show
+        let mut state = AppState::default();
+        let progress = Arc::new(Mutex::new(p));
+        state.job = Some(ClickJob { running: Arc::new(AtomicBool::new(false)), progress, reported: false, config: Arc::clone(&state.config) });
+        state.take_run_stats();
+        assert_eq!(state.run_stats.take().map(|s| s.clicks), Some(3));
+        state.take_run_stats();
+        assert!(state.run_stats.is_none());
+        state.job = Some(ClickJob { running: Arc::new(AtomicBool::new(false)), progress: Arc::default(), reported: false, config: Arc::clone(&state.config) });
+        state.take_run_stats();
+        assert!(state.run_stats.is_none());
+    }
+
+    #[test]
+    fn test_run_history() {
+        use chrono::{Local, NaiveDate, TimeZone};
+        use std::time::Duration;
+        let path = std::env::temp_dir().join(format!("area_clicker_history_{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let history = History::open(&path).unwrap();
+        let run = |day: u32, profile: Option<&str>, clicks: u32| RunRecord {
+            started: Local.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap(),
+            profile: profile.map(str::to_string),
+            duration: Duration::from_secs(60),
+            clicks,
+            misses: 0,
+            failed_steps: 0,
+            cycles: 1,
+        };
+        history.add(&run(1, Some("Farm"), 100)).unwrap();
+        history.add(&run(9, Some("Farm"), 40)).unwrap();
+        history.add(&run(10, None, 7)).unwrap();
+        history.add(&run(10, Some("Mine"), 5)).unwrap();
+
+        let day = |d| NaiveDate::from_ymd_opt(2026, 3, d).unwrap();
+        let week = HistoryFilter::last_days(Some("Farm".to_string()), 6, day(10));
+        let runs = history.runs(&week).unwrap();
+        assert_eq!(runs, vec![run(9, Some("Farm"), 40)]);
+        let all = history.runs(&HistoryFilter::default()).unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[3], run(1, Some("Farm"), 100)); // newest first
+        assert_eq!(history::totals(&all), (152, Duration::from_secs(240)));
+        let one_day = HistoryFilter { from: Some(day(10)), to: Some(day(10)), ..HistoryFilter::default() };
+        assert_eq!(history.runs(&one_day).unwrap().len(), 2);
+        assert_eq!(history.profiles().unwrap(), vec!["Farm".to_string(), "Mine".to_string()]);
+
+        assert_eq!(history::parse_day(" 2026-03-10 "), Ok(Some(day(10))));
+        assert_eq!(history::parse_day(""), Ok(None));
+        assert!(history::parse_day("10/03/2026").is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_remote_viewer() {
+        use std::io::{Read, Write};
+        let (status, kind, _) = viewer::respond("/thumbnail.jpg?t=1", Some(vec![1, 2, 3]), 5, "");
+        assert_eq!((status, kind), ("200 OK", "image/jpeg"));
+        assert_eq!(viewer::respond("/thumbnail.jpg", None, 5, "").0, "404 Not Found");
+        assert_eq!(viewer::respond("/../secrets", None, 5, "").0, "404 Not Found");
+        let page = String::from_utf8(viewer::respond("/", None, 7, "").2).unwrap();
+        assert!(page.contains("content=\"7\"") && page.contains("/thumbnail.jpg\""));
+
+        // with a token, every request has to bring it
+        assert_eq!(viewer::respond("/thumbnail.jpg", Some(vec![1]), 5, "s3cret").0, "403 Forbidden");
+        assert_eq!(viewer::respond("/?token=guess", None, 5, "s3cret").0, "403 Forbidden");
+        assert_eq!(viewer::respond("/thumbnail.jpg?t=1&token=s3cret", Some(vec![1]), 5, "s3cret").0, "200 OK");
+        let page = String::from_utf8(viewer::respond("/?token=s3cret", None, 7, "s3cret").2).unwrap();
+        assert!(page.contains("/thumbnail.jpg?token=s3cret"));
+        assert_eq!(viewer::new_token().len(), 24);
+
+        let saved: ViewerSettings = serde_json::from_str(r#"{ "enabled": true }"#).unwrap();
+        assert_eq!(saved.listen.as_deref(), Some("127.0.0.1:8765"));
+
+        let capture = screenshot::Capture { width: 16, height: 8, rgb: vec![200; 16 * 8 * 3] };
+        assert!(screenshot::encode_jpeg(&capture, 80).unwrap().starts_with(&[0xFF, 0xD8]));
+
+        // only a loopback address goes without a token
+        let open = ViewerSettings { enabled: true, listen: Some("0.0.0.0:0".to_string()), ..ViewerSettings::default() };
+        assert!(viewer::Viewer::start(&open).is_err());
+
+        // served over plain HTTP; stopping it frees the address again
+        let settings = ViewerSettings { enabled: true, listen: Some("127.0.0.1:0".to_string()), ..ViewerSettings::default() };
+        let running = viewer::Viewer::start(&settings).unwrap();
+        let addr = running.addr.unwrap();
+        let mut stream = std::net::TcpStream::connect(addr).unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\nHost: x\r\n\r\n").unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        assert!(reply.starts_with("HTTP/1.1 200 OK"));
+        drop(running);
+        let again = ViewerSettings { listen: Some(addr.to_string()), ..settings };
+        assert!(viewer::Viewer::start(&again).is_ok());
+    }
+
+    #[test]
+    fn test_install_task() {
+        let spec = task::TaskSpec {
+            name: task::default_name("Farm run/2"),
+            exe: PathBuf::from("/opt/area clicker/area_clicker"),
+            profile: PathBuf::from("/home/u/profiles/100%.json"),
+            schedule: task::Schedule::Daily(9 * 60 + 30),
+            socket: None,
+            portable: false,
+        };
+        assert_eq!(spec.name, "area_clicker-Farm_run_2");
+        assert_eq!(spec.args(), ["--daemon", "--profile", "/home/u/profiles/100%.json", "--autostart"]);
+        let schtasks = spec.schtasks_args();
+        assert_eq!(&schtasks[..4], ["/Create", "/F", "/TN", "area_clicker-Farm_run_2"]);
+        assert!(schtasks[5].starts_with("\"/opt/area clicker/area_clicker\" --daemon"));
+        assert_eq!(&schtasks[6..], ["/SC", "DAILY", "/ST", "09:30"]);
+
+        let units = spec.systemd_units();
+        assert_eq!(units.len(), 2);
+        assert_eq!(units[0].0, "area_clicker-Farm_run_2.service");
+        assert!(units[0].1.contains("ExecStart=\"/opt/area clicker/area_clicker\" --daemon --profile /home/u/profiles/100%%.json --autostart\n"));
+        assert!(!units[0].1.contains("[Install]"));
+        assert!(units[1].1.contains("OnCalendar=*-*-* 09:30:00"));
+        assert_eq!(spec.systemd_enable(), "area_clicker-Farm_run_2.timer");
+
+        let logon = task::TaskSpec { schedule: task::Schedule::Logon, socket: Some(PathBuf::from("/tmp/s")), portable: true, ..spec };
+        assert_eq!(&logon.schtasks_args()[6..], ["/SC", "ONLOGON"]);
+        assert!(logon.args().ends_with(&["--socket".to_string(), "/tmp/s".to_string(), "--portable".to_string()]));
+        let units = logon.systemd_units();
+        assert_eq!(units.len(), 1);
+        assert!(units[0].1.contains("WantedBy=default.target"));
+        assert_eq!(logon.systemd_enable(), "area_clicker-Farm_run_2.service");
+
+        assert!(task::resolve_profile("no such profile", |_| None).is_err());
+    }
+
+    #[test]
+    fn test_portable_detect() {
+        let dir = std::env::temp_dir().join(format!("area_clicker_portable_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(portable::detect(&dir, false), None);
+        assert_eq!(portable::detect(&dir, true), Some(dir.join("config")));
+        std::fs::write(dir.join(portable::FLAG_FILE), "").unwrap();
+        assert_eq!(portable::detect(&dir, false), Some(dir.join("config")));
+        assert!(Args::parse_from(["area_clicker", "--portable"]).portable);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_step_defaults() {
+        let step_defaults = StepDefaults { clicks: 2, min_secs: 3.0, max_secs: 4.0, button: ClickButton::Right, name: "Tap {n}".into() };
+        let mut state = AppState { step_defaults, ..AppState::default() };
+        state.reset_step_draft();
+        state.save_step();
+        let s = &state.steps[0];
+        assert_eq!((s.name.as_str(), s.clicks, s.min_secs, s.max_secs, s.button), ("Tap 1", 2, 3.0, 4.0, ClickButton::Right));
+
+        // the editor goes back to the defaults after each step, edited or added
+        state.step_clicks = 9;
+        state.save_step();
+        assert_eq!((state.steps[1].clicks, state.step_clicks), (9, 2));
+        state.edit_step(1);
+        assert_eq!(state.step_clicks, 9);
+        state.save_step();
+        assert_eq!(state.step_clicks, 2);
+        assert_eq!(StepDefaults::default().step_name(3), "Step 3");
+
+        let dir = std::env::temp_dir().join(format!("area_clicker_step_defaults_{}", std::process::id()));
+        let store = ProfileStore::new(dir.clone());
+        assert_eq!(store.load_step_defaults(), StepDefaults::default());
+        store.save_step_defaults(&state.step_defaults).unwrap();
+        assert_eq!(store.load_step_defaults(), state.step_defaults);
+        assert_eq!(store.export_bundle().unwrap().step_defaults, state.step_defaults);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_step_templates() {
+        let mut state = AppState { step_clicks: 2, step_button_left: false, ..AppState::default() };
+        state.save_template("slow right-click");
+        state.bounds_inputs = [10, 20, 30, 40];
+        state.insert_template("slow right-click");
+        state.template_linked = false;
+        state.insert_template("slow right-click");
+        let s = &state.steps[0];
+        assert_eq!((s.clicks, s.button, s.template.as_deref()), (2, ClickButton::Right, Some("slow right-click")));
+        assert_eq!(s.bounds, Bounds { min_x: 10, max_x: 20, min_y: 30, max_y: 40 });
+        assert_eq!(s.name, "slow right-click 1");
+        assert_eq!(state.steps[1].template, None);
+        assert!(s.summary().ends_with(" · template slow right-click"));
+
+        // template edits reach linked steps only
+        state.step_clicks = 7;
+        state.save_template("slow right-click");
+        assert_eq!((state.steps[0].clicks, state.steps[1].clicks), (7, 2));
+
+        // renaming a linked step keeps the link, changing its clicks drops it
+        state.edit_step(0);
+        state.step_name = "Confirm".into();
+        state.save_step();
+        assert_eq!(state.steps[0].template.as_deref(), Some("slow right-click"));
+        state.edit_step(0);
+        state.step_clicks = 1;
+        state.save_step();
+        assert_eq!(state.steps[0].template, None);
+
+        state.steps[1].template = Some("slow right-click".into());
+        state.delete_template("slow right-click");
+        assert_eq!((state.steps[1].template.clone(), state.steps[1].clicks), (None, 2));
+    }
+
+    #[test]
+    fn test_inline_step_edit() {
+        let mut state = AppState::default();
+        state.save_template("fast");
+        for _ in 0..3 { state.insert_template("fast"); }
+        state.expanded_steps.extend([0, 2]);
+        state.edit_step(1);
+
+        state.steps[1].clicks = 11;
+        state.steps[1].button = ClickButton::Right;
+        state.step_edited_inline(1);
+        assert_eq!(state.steps[1].template, None);
+        assert_eq!((state.step_clicks, state.step_button_left), (11, false));
+        state.save_step();
+        assert_eq!(state.steps[1].clicks, 11);
+
+        // a step whose settings still match keeps its link
+        state.step_edited_inline(0);
+        assert_eq!(state.steps[0].template.as_deref(), Some("fast"));
+
+        state.swap_steps(1, 2);
+        assert_eq!(state.expanded_steps.iter().copied().collect::<Vec<_>>(), vec![0, 1]);
+        state.remove_step(0);
+        assert_eq!(state.expanded_steps.iter().copied().collect::<Vec<_>>(), vec![0]);
+        state.duplicate_step(0);
+        assert_eq!(state.expanded_steps.iter().copied().collect::<Vec<_>>(), vec![0]);
+    }
+
+    #[test]
+    fn test_step_and_sequence_notes() {
+        let mut state = AppState { step_note: "right-click twice: the first one only focuses the window\n".into(), ..AppState::default() };
+        state.save_step();
+        state.save_step();
+        assert_eq!(state.steps[0].note, "right-click twice: the first one only focuses the window");
+        assert_eq!(state.steps[1].note, "");
+        assert!(!serde_json::to_string(&state.steps[1]).unwrap().contains("note"));
+        state.edit_step(0);
+        assert_eq!(state.step_note, state.steps[0].note);
+
+        state.sequence_notes = "Nightly farm run".into();
+        let json = serde_json::to_string(&state.to_profile()).unwrap();
+        let mut fresh = AppState::default();
+        fresh.apply_profile(serde_json::from_str(&json).unwrap());
+        assert_eq!(fresh.sequence_notes, "Nightly farm run");
+        assert_eq!(fresh.steps[0].note, state.steps[0].note);
+    }
+
+    #[test]
+    fn test_profile_diff_before_load() {
+        let mut state = AppState::default();
+        for name in ["Open", "Loot", "Close"] {
+            state.step_name = name.into();
+            state.save_step();
+        }
+        let before = state.to_profile();
+        let mut after = before.clone();
+        after.min_secs = 9.0;
+        after.steps.remove(2);
+        after.steps[1].clicks = 7;
+        after.steps.push(SequenceStep { name: "Sell".into(), ..after.steps[0].clone() });
+        let d = diff::profiles(&before, &after);
+        assert_eq!(d.settings, vec![diff::FieldChange { field: "min secs".into(), from: "2.0".into(), to: "9.0".into() }]);
+        assert_eq!(d.steps, vec![
+            diff::StepChange::Changed { index: 1, name: "Loot".into(), fields: vec![diff::FieldChange { field: "clicks".into(), from: "5".into(), to: "7".into() }] },
+            diff::StepChange::Added { index: 2, name: "Sell".into() },
+            diff::StepChange::Removed { index: 2, name: "Close".into() },
+        ]);
+        assert!(diff::profiles(&before, &before).is_empty());
+
+        // unsaved edits hold a load back until the diff is answered
+        let dir = std::env::temp_dir().join(format!("area_clicker_diff_{}", std::process::id()));
+        let store = ProfileStore::new(dir.clone());
+        store.save("Farm", &after).unwrap();
+        state.profiles = Some(store);
+        assert!(state.has_unsaved_changes());
+        state.load_profile("Farm");
+        assert_eq!(state.current_profile, None);
+        assert!(state.pending_load.as_ref().is_some_and(|p| p.diff == d));
+        state.save_profile_as("Mine");
+        assert!(!state.has_unsaved_changes());
+        state.pending_load = None;
+        state.load_profile("Farm");
+        assert!(state.pending_load.is_none());
+        assert_eq!((state.current_profile.as_deref(), state.min_secs), (Some("Farm"), 9.0));
+
+        // a file changed on disk is reloaded right away only if nothing was edited since
+        state.auto_reload = true;
+        state.changed_profile = Some(before.clone());
+        state.review_reload();
+        assert!(state.pending_load.is_none());
+        assert_eq!(state.steps.len(), 3);
+        state.min_secs = 1.0;
+        state.changed_profile = Some(after.clone());
+        state.review_reload();
+        assert!(matches!(state.pending_load, Some(PendingLoad { target: LoadTarget::Reload, .. })));
+        assert_eq!(state.min_secs, 1.0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_locked_profile() {
+        let dir = std::env::temp_dir().join(format!("area_clicker_lock_{}", std::process::id()));
+        let store = ProfileStore::new(dir.clone());
+        let mut state = AppState { step_name: "Loot".into(), ..AppState::default() };
+        state.save_step();
+        state.profiles = Some(store);
+        state.save_profile_as("Farm");
+        assert!(state.editable());
+
+        state.lock_profile();
+        assert!(!state.editable());
+        let saved = state.profiles.as_ref().unwrap().load("Farm").unwrap();
+        assert!(saved.locked);
+
+        // loading it again starts locked, even after an unlock
+        state.unlocked = true;
+        assert!(state.editable());
+        state.apply_profile(saved);
+        assert!(state.profile_locked && !state.unlocked && !state.editable());
+
+        // removing the lock is saved with the profile
+        state.profile_locked = false;
+        assert!(!state.to_profile().locked);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_clone_sequence_with_offset() {
+        let mut state = AppState::default();
+        for name in ["Open", "Loot"] {
+            state.step_name = name.into();
+            state.save_step();
+        }
+        state.steps[0].bounds = Bounds { min_x: 100, max_x: 200, min_y: 50, max_y: 80 };
+        state.steps[0].area = Some("Chest".into());
+        state.steps[1].target = StepTarget::Point(ClickPoint { x: 300, y: 400, jitter: 2 });
+        state.steps[1].on_error.then = Branch::Jump(0);
+
+        state.clone_offset.dx = 960;
+        state.append_offset_copies();
+        assert_eq!(state.steps.len(), 4);
+        assert_eq!(state.steps[2].bounds, Bounds { min_x: 1060, max_x: 1160, min_y: 50, max_y: 80 });
+        assert_eq!((state.steps[2].name.as_str(), state.steps[2].area.as_deref()), ("Open copy", None));
+        assert_eq!(state.steps[3].target, StepTarget::Point(ClickPoint { x: 1260, y: 400, jitter: 2 }));
+        assert_eq!(state.steps[3].on_error.then, Branch::Jump(2));
+        assert_eq!(state.steps[1].on_error.then, Branch::Jump(0));
+
+        // onto a monitor of a different size: same relative spot
+        state.monitors = vec![monitor(1, (0, 0), (1920, 1080), 1.0), monitor(2, (1920, 0), (3840, 2160), 1.0)];
+        state.clone_offset.onto_monitor = true;
+        let copies = state.offset_copies().unwrap();
+        assert_eq!(copies[0].bounds, Bounds { min_x: 2120, max_x: 2320, min_y: 100, max_y: 160 });
+        state.clone_offset.to = 5;
+        assert!(state.offset_copies().is_none());
+    }
+
+    #[test]
+    fn test_legacy_verify_on_fail() {
+        let mut step = serde_json::to_value(SequenceStep { verify: Some(Verify::default()), ..SequenceStep::default() }).unwrap();
+        step.as_object_mut().unwrap().remove("on_error");
+        step["verify"]["on_fail"] = serde_json::json!("Abort");
+        let profile: Profile = serde_json::from_value(serde_json::json!({ "steps": [step] })).unwrap();
+        assert_eq!(profile.steps[0].on_error, OnError { retries: 0, then: Branch::Abort });
+
+        let saved = serde_json::to_string(&profile.steps[0]).unwrap();
+        assert!(!saved.contains("on_fail"));
+        assert_eq!(serde_json::from_str::<SequenceStep>(&saved).unwrap().on_error.then, Branch::Abort);
+    }
+
+    #[test]
+    fn test_pause_when_locked() {
+        assert_eq!(lock::session_path("c2"), "/org/freedesktop/login1/session/c2");
+        assert_eq!(lock::session_path("2"), "/org/freedesktop/login1/session/_32");
+        let saved: SafetyLimits = serde_json::from_str(r#"{ "per_minute": 100 }"#).unwrap();
+        assert!(saved.pause_when_locked && !saved.confirm_unlock);
+
+        let mut state = AppState::default();
+        assert!(!state.resume_after_unlock());
+        let progress = Arc::new(Mutex::new(JobProgress { await_resume: true, ..JobProgress::default() }));
+        state.job = Some(ClickJob { running: Arc::new(AtomicBool::new(true)), progress: Arc::clone(&progress), reported: false, config: Arc::clone(&state.config) });
+        assert!(state.resume_after_unlock());
+        assert!(!progress.lock().await_resume);
+        assert!(!state.resume_after_unlock());
+    }
+
+    #[test]
+    fn test_keep_awake() {
+        let mut state = AppState::default();
+        assert!(state.keep_awake);
+        let mut cfg = ClickConfig::default();
+        state.write_config(&mut cfg);
+        assert!(cfg.keep_awake);
+        state.keep_awake = false;
+        let restored = { let mut s = AppState::default(); s.apply_profile(state.to_profile()); s };
+        assert!(!restored.keep_awake);
+        let old: Profile = serde_json::from_str("{}").unwrap();
+        assert!(old.keep_awake);
+
+        #[cfg(not(windows))]
+        {
+            let (program, args) = power::helper_command(42);
+            if cfg!(target_os = "linux") {
+                assert_eq!(program, "systemd-inhibit");
+                assert_eq!(args.last().map(String::as_str), Some("cat"));
+            } else {
+                assert_eq!(args, ["-dims", "-w", "42"]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_unavailable_input() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let report = move |message: String| { let _ = tx.send(message); };
+        let mut input = Input::unavailable_reporting(Backend::Ydotool, "ydotoold is not running".to_string(), Box::new(report));
+        assert_eq!(input.backend(), Backend::Ydotool);
+        assert_eq!(input.error(), Some("ydotoold is not running"));
+        input.mouse_click(enigo::MouseButton::Left);
+        assert_eq!(rx.try_recv().as_deref(), Ok("No input sent: ydotoold is not running"));
+        assert_eq!(Input::new(Backend::SendInput).is_ok(), cfg!(windows));
+
+        #[cfg(target_os = "linux")]
+        if std::env::var_os("DISPLAY").is_none() {
+            assert!(Input::new(Backend::Enigo).is_err());
+        }
+    }
+
+    #[test]
+    fn test_click_miss_detection() {
+        use crate::human_mouse::on_target;
+        let b = Bounds { min_x: 100, max_x: 200, min_y: 100, max_y: 200 };
+        assert!(on_target(Some(b), (150, 150), (101, 199)));
+        assert!(!on_target(Some(b), (150, 150), (99, 150)));
+        assert!(on_target(None, (50, 50), (52, 48)));
+        assert!(!on_target(None, (50, 50), (54, 50)));
+
+        let progress = JobProgress { clicks: 10, misses: 2, ..JobProgress::default() };
+        assert_eq!(progress.summary(), "10 clicks, 2 missed");
+
+        let mut state = AppState::default();
+        assert!(!state.reclick_misses);
+        state.reclick_misses = true;
+        let mut cfg = ClickConfig::default();
+        state.write_config(&mut cfg);
+        assert!(cfg.reclick_misses);
+        let restored = { let mut s = AppState::default(); s.apply_profile(state.to_profile()); s };
+        assert!(restored.reclick_misses);
+    }
+
+    #[test]
+    fn test_drift_correction() {
+        use crate::human_mouse::{human_move_and_click, steer, HumanMouseSettings, Plan};
+        use enigo::MouseButton;
+
+        assert_eq!(steer((0, 0), (100, 100), (101, 99)), (0, 0));
+        assert_eq!(steer((0, 0), (100, 100), (110, 95)), (-10, 5));
+        assert_eq!(steer((-10, 5), (100, 100), (103, 100)), (-13, 5));
+
+        let mut settings = HumanMouseSettings {
+            avg_speed: 5000.0, overshoot_chance: 0.0, min_pause_ms: 0, max_pause_ms: 0, rng_seed: Some(7),
+            ..HumanMouseSettings::default()
+        };
+        // a remote desktop that scales every position it is sent by 10%
+        let mut mouse = Plan { scale: 1.1, ..Plan::at((0, 0)) };
+        let landed = human_move_and_click(&mut mouse, (0, 0), (400, 300), None, &settings, MouseButton::Left);
+        assert_eq!(landed, (440, 330));
+
+        settings.checkpoint_steps = Some(5);
+        let landed = human_move_and_click(&mut mouse, (0, 0), (400, 300), None, &settings, MouseButton::Left);
+        assert!((landed.0 - 400).abs() <= 1 && (landed.1 - 300).abs() <= 1, "{:?}", landed);
+    }
+
+    #[test]
+    fn test_target_approach() {
+        use crate::human_mouse::{approach_direction, landing_point, HumanMouseSettings};
+        use rand::{rngs::StdRng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(3);
+        // off unless asked for
+        let plain = HumanMouseSettings::default();
+        assert_eq!(landing_point((0, 150), (150, 150), Some(Bounds { min_x: 100, max_x: 200, min_y: 100, max_y: 200 }), &plain, &mut rng), (150, 150));
+        let settings = HumanMouseSettings { approach_angle_deg: 30.0, undershoot: 0.15, ..plain };
+        let b = Bounds { min_x: 100, max_x: 200, min_y: 100, max_y: 200 };
+        let (from, to) = ((0, 150), (150, 150));
+        let max_cos = settings.approach_angle_deg.to_radians().cos();
+        for _ in 0..200 {
+            // short of the sampled point, on the side the motion came from, still inside
+            let at = landing_point(from, to, Some(b), &settings, &mut rng);
+            assert!(b.contains(at) && at.0 <= to.0 && at.0 >= 135 && at.1 == 150, "{:?}", at);
+
+            let (ax, ay) = approach_direction(from, to, &settings, &mut rng).unwrap();
+            assert!(((ax * ax + ay * ay).sqrt() - 1.0).abs() < 1e-4);
+            assert!(-ax >= max_cos - 1e-4, "({}, {})", ax, ay); // within the angle of "back toward from"
+        }
+        assert_eq!(landing_point(from, to, None, &settings, &mut rng), to);
+        assert_eq!(approach_direction(to, to, &settings, &mut rng), None);
+
+        // the landing point is what's checked against forbidden zones, kept in its zone and recorded
+        let running = AtomicBool::new(true);
+        let forbidden = vec![Bounds { min_x: 0, max_x: 1000, min_y: 100, max_y: 120 }];
+        let config = Mutex::new(ClickConfig { vary_approach: true, forbidden, ..ClickConfig::default() });
+        let mut ctx = StepCtx::new(&running, &config).dry();
+        let zones = [Zone { x0: 0.0, y0: 0.0, x1: 0.5, y1: 1.0, weight: 1.0 }];
+        for _ in 0..100 {
+            let at = ctx.click_in(b, &zones, ClickButton::Left).unwrap();
+            assert!(at.0 >= 100 && at.0 <= 150 && at.1 > 120 && at.1 <= 200, "{:?}", at);
+        }
+        let clicks: Vec<_> = ctx.dry_run.unwrap().entries.into_iter().filter_map(|e| match e.event {
+            DryRunEvent::Click(at) => Some(at),
+            _ => None,
+        }).collect();
+        assert_eq!(clicks.len(), 100);
+        assert!(clicks.iter().all(|&(x, y)| x <= 150 && y > 120));
+    }
+
+    #[test]
+    fn test_settle_before_click() {
+        use crate::human_mouse::{human_move_and_click, settle_point, HumanMouseSettings, PathEvent, Plan};
+        use enigo::MouseButton;
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let settings = HumanMouseSettings { settle_moves: 2, undershoot: 0.0, overshoot_chance: 0.0, rng_seed: Some(5), ..HumanMouseSettings::default() };
+        let mut rng = StdRng::seed_from_u64(5);
+        let b = Bounds { min_x: 100, max_x: 110, min_y: 100, max_y: 110 };
+        for _ in 0..100 {
+            let p = settle_point((100, 105), Some(b), &settings, &mut rng);
+            assert!(b.contains(p) && (p.0 - 100).abs() <= 3 && (p.1 - 105).abs() <= 3);
+            assert_ne!(settle_point((50, 50), None, &settings, &mut rng), (50, 50));
+        }
+
+        let mut mouse = Plan::at((0, 0));
+        let landed = human_move_and_click(&mut mouse, (0, 0), (300, 200), None, &settings, MouseButton::Left);
+        assert!((landed.0 - 300).abs() <= 1 && (landed.1 - 200).abs() <= 1, "{:?}", landed);
+        // the main path ends a few px off; the corrections close the gap
+        let moves = mouse.points.iter().filter(|p| p.event == PathEvent::Move);
+        let near: Vec<_> = moves.filter(|p| (p.x - 300).abs() <= 4 && (p.y - 200).abs() <= 4).collect();
+        assert!(near.len() > 3);
+    }
 
-            // Interaction area
-            egui::Area::new(egui::Id::new("picker_area"))
-                .order(egui::Order::Foreground)
-                .show(ctx, |ui| {
-                    let resp = ui.allocate_rect(screen_rect, Sense::click_and_drag());
-                    if resp.drag_started() {
-                        self.drag_start = resp.interact_pointer_pos();
-                        self.drag_end = self.drag_start;
-                    }
-                    if resp.dragged() {
-                        self.drag_end = resp.interact_pointer_pos();
-                    }
-                    if resp.drag_stopped() {
-                        self.drag_end = resp.interact_pointer_pos();
+    #[test]
+    fn test_click_called_off_mid_move() {
+        use crate::human_mouse::{human_move_and_click_while, simulate, HumanMouseSettings, PathEvent, Plan};
+        use enigo::MouseButton;
+        use std::cell::Cell;
 
-                        // Determine origin_px to add (depends on selected target)
-                        let origin_px = match self.display_choice {
-                            DisplayChoice::All => {
-                                let (min_x, min_y, _max_x, _max_y) = union_rect(&self.monitors);
-                                (min_x, min_y)
-                            }
-                            DisplayChoice::One(i) => {
-                                self.monitors.get(i).map(|m| m.origin_px).unwrap_or((0, 0))
-                            }
-                        };
-                        let ppp = ctx.pixels_per_point().max(0.1);
-                        self.set_bounds_from_drag(ppp, origin_px);
-                        self.exit_picker(ctx);
-                    }
+        let settings = HumanMouseSettings { settle_moves: 2, overshoot_chance: 1.0, rng_seed: Some(3), ..HumanMouseSettings::default() };
+        let run = |checks_passed: u32| {
+            let (mut mouse, asked) = (Plan::at((0, 0)), Cell::new(0));
+            let go_on = || { asked.set(asked.get() + 1); asked.get() <= checks_passed };
+            let landed = simulate(|| human_move_and_click_while(&mut mouse, (0, 0), (300, 200), None, &settings, MouseButton::Left, go_on));
+            (landed, mouse.points, asked.get())
+        };
+        let (landed, full, checks) = run(u32::MAX);
+        assert!(landed.is_some() && full.iter().any(|p| p.event == PathEvent::Down));
+        // let go after the overshoot: the cursor stops there and nothing is pressed
+        let (landed, cut, _) = run(0);
+        assert_eq!(landed, None);
+        assert!(cut.iter().all(|p| p.event == PathEvent::Move) && cut.len() < full.len() - 2);
+        // let go just before the press
+        let (landed, last, _) = run(checks - 1);
+        assert_eq!(landed, None);
+        assert!(last.iter().all(|p| p.event == PathEvent::Move));
+    }
 
-                    if let (Some(a), Some(b)) = (self.drag_start, self.drag_end) {
-                        let rect = Rect::from_two_pos(a, b);
-                        let stroke = egui::Stroke { width: 2.0, color: Color32::LIGHT_BLUE };
-                        painter.rect_stroke(rect, 0.0, stroke);
-                    }
-                });
+    #[test]
+    fn test_click_hold_duration() {
+        use crate::human_mouse::{press, HumanMouseSettings, PathEvent, Plan};
+        use enigo::MouseButton;
 
-            ctx.request_repaint();
-            return; // Skip main UI while picking
-        }
+        let settings = HumanMouseSettings { min_hold_ms: 40, max_hold_ms: 40, ..HumanMouseSettings::default() };
+        let mut mouse = Plan::at((0, 0));
+        press(&mut mouse, MouseButton::Left, &settings, &mut rand::thread_rng());
+        let [down, up] = mouse.points[..] else { panic!("clicks must press and release separately") };
+        assert_eq!((down.event, up.event), (PathEvent::Down, PathEvent::Up));
+        assert!(up.ms - down.ms >= 40.0);
 
-        // -------- Main UI --------
-        egui::TopBottomPanel::top("top").show(ctx, |ui| {
-            ui.heading("Area Clicker — Multi-Display");
-        });
+        let mut state = AppState::default();
+        assert_eq!((state.min_hold_ms, state.max_hold_ms), (20, 120));
+        (state.min_hold_ms, state.max_hold_ms) = (90, 30);
+        let mut cfg = ClickConfig::default();
+        state.write_config(&mut cfg);
+        assert_eq!((cfg.min_hold_ms, cfg.max_hold_ms), (30, 90));
+        let restored = { let mut s = AppState::default(); s.apply_profile(state.to_profile()); s };
+        assert_eq!((restored.min_hold_ms, restored.max_hold_ms), (90, 30));
+    }
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.horizontal_wrapped(|ui| {
-                ui.vertical(|ui| {
-                    ui.group(|ui| {
-                        ui.horizontal(|ui| {
-                            ui.label("Target display:");
-                            egui::ComboBox::from_id_source("display_select")
-                                .selected_text(match self.display_choice {
-                                    DisplayChoice::All => "All displays".into(),
-                                    DisplayChoice::One(i) => self.monitors.get(i)
-                                        .map(|m| m.name.clone())
-                                        .unwrap_or_else(|| "Unknown".into()),
-                                })
-                                .show_ui(ui, |ui| {
-                                    ui.selectable_value(&mut self.display_choice, DisplayChoice::All, "All displays");
-                                    for (i, m) in self.monitors.iter().enumerate() {
-                                        ui.selectable_value(&mut self.display_choice, DisplayChoice::One(i), &m.name);
-                                    }
-                                });
+    #[test]
+    fn test_path_bank() {
+        use crate::path_bank::{fit, Segmenter};
+        use rand::{rngs::StdRng, SeedableRng};
 
-                            if ui.button("↻ Refresh").clicked() {
-                                self.refresh_monitors();
-                            }
-                        });
+        // a move right, a rest, then a short twitch that isn't worth keeping
+        let mut seg = Segmenter::default();
+        let mut cuts = Vec::new();
+        let mut feed = |ms: u32, pos: (i32, i32)| cuts.extend(seg.push(ms, pos));
+        feed(0, (0, 0));
+        for i in 1..=10 { feed(i * 8, (i as i32 * 20, i as i32 * 2)); }
+        for i in 11..=30 { feed(i * 8, (200, 20)); }
+        feed(248, (205, 20));
+        for i in 32..=60 { feed(i * 8, (205, 20)); }
+        assert_eq!(cuts.len(), 2);
+        assert_eq!(cuts[0].samples.first(), Some(&(0, 0, 0)));
+        assert_eq!(cuts[0].samples.last(), Some(&(80, 200, 20)));
 
-                        ui.separator();
+        let mut bank = PathBank::default();
+        assert!(bank.add(cuts[0].clone()));
+        assert!(!bank.add(cuts[1].clone()));
+        assert_eq!(bank.len(), 1);
 
-                        ui.label("Selection (px, screen coords)");
-                        ui.horizontal(|ui| { ui.label("min X"); ui.add(egui::DragValue::new(&mut self.bounds_inputs[0])); });
-                        ui.horizontal(|ui| { ui.label("max X"); ui.add(egui::DragValue::new(&mut self.bounds_inputs[1])); });
-                        ui.horizontal(|ui| { ui.label("min Y"); ui.add(egui::DragValue::new(&mut self.bounds_inputs[2])); });
-                        ui.horizontal(|ui| { ui.label("max Y"); ui.add(egui::DragValue::new(&mut self.bounds_inputs[3])); });
+        // rotated and scaled onto a move straight down; ends land exactly
+        let points = fit(&cuts[0], (500, 500), (500, 900), 0.0, 1.0);
+        assert_eq!(points.first().map(|p| (p.1, p.2)), Some((500, 500)));
+        assert_eq!(points.last().map(|p| (p.1, p.2)), Some((500, 900)));
+        assert!(points.windows(2).all(|w| w[0].0 <= w[1].0));
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!(bank.variant((0, 0), (10, 10), &mut rng).is_none());
+        let v = bank.variant((100, 100), (400, 100), &mut rng).unwrap();
+        assert_eq!(v.last().map(|p| (p.1, p.2)), Some((400, 100)));
 
-                        if ui.button("Pick Area (drag a rectangle)").clicked() {
-                            self.enter_picker(ctx);
-                        }
-                    });
+        let dir = std::env::temp_dir().join(format!("area_clicker_paths_{}", std::process::id()));
+        let store = ProfileStore::new(dir.clone());
+        assert!(store.load_path_bank().is_empty());
+        store.save_path_bank(&bank).unwrap();
+        let loaded = store.load_path_bank();
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(loaded.paths, bank.paths);
 
-                    ui.separator();
+        let mut state = AppState { replay_paths: true, ..AppState::default() };
+        let mut cfg = ClickConfig::default();
+        state.write_config(&mut cfg);
+        assert!(cfg.path_bank.is_none()); // nothing recorded yet
+        state.path_bank = Arc::new(bank);
+        state.write_config(&mut cfg);
+        assert_eq!(cfg.path_bank.map(|b| b.len()), Some(1));
+    }
 
-                    ui.group(|ui| {
-                        ui.label("Settings");
-                        ui.horizontal(|ui| {
-                            ui.label("Click type:");
-                            ui.checkbox(&mut self.click_button_left, "Left");
-                            let mut right = !self.click_button_left;
-                            if ui.checkbox(&mut right, "Right").clicked() { self.click_button_left = !right; }
-                        });
-                        ui.horizontal(|ui| {
-                            ui.label("Interval (seconds):");
-                            ui.add(egui::DragValue::new(&mut self.min_secs).speed(0.1));
-                            ui.label("to");
-                            ui.add(egui::DragValue::new(&mut self.max_secs).speed(0.1));
-                        });
-                        ui.horizontal(|ui| {
-                            ui.checkbox(&mut self.use_finite_clicks, "Limit number of clicks");
-                            if self.use_finite_clicks {
-                                ui.add(egui::DragValue::new(&mut self.num_clicks).speed(1.0).clamp_range(1..=1000000));
-                            }
-                        });
-                        ui.horizontal(|ui| {
-                            if ui.button("Start").clicked() { self.start(); }
-                            if ui.button("Pause").clicked() { self.pause(); }
-                            if ui.button("Stop").clicked() { self.stop(); }
-                        });
+    #[test]
+    fn test_plan_path() {
+        use crate::human_mouse::{plan, HumanMouseSettings, PathEvent};
+        let started = Instant::now();
+        let settings = HumanMouseSettings { rng_seed: Some(4), ..HumanMouseSettings::default() };
+        let path = plan((0, 0), (300, 200), None, &settings);
+        assert!(started.elapsed() < Duration::from_millis(100));
+        assert_eq!(path, plan((0, 0), (300, 200), None, &settings));
+        assert!(path.windows(2).all(|w| w[0].ms <= w[1].ms));
+        let events: Vec<_> = path.iter().rev().take(2).map(|p| (p.event, p.x, p.y)).collect();
+        assert_eq!(events, [(PathEvent::Up, 300, 200), (PathEvent::Down, 300, 200)]);
+        assert!(path[..path.len() - 2].iter().all(|p| p.event == PathEvent::Move));
 
-                        if let Some(job) = &self.job {
-                            let running = job.running.load(Ordering::Relaxed);
-                            ui.label(format!("Status: {}", if running {"Running"} else {"Stopped"}));
-                        } else {
-                            ui.label("Status: Stopped");
-                        }
-                    });
-                });
-            });
+        let area = Bounds { min_x: 280, max_x: 320, min_y: 180, max_y: 220 };
+        let down = plan((0, 0), (300, 200), Some(area), &settings).into_iter().find(|p| p.event == PathEvent::Down).unwrap();
+        assert!(area.contains((down.x, down.y)));
 
-            // Preview rectangle
-            if let Some(b) = self.config.lock().bounds {
-                let info = format!("Active bounds: x=[{}..{}], y=[{}..{}] ({}x{})",
-                                   b.min_x, b.max_x, b.min_y, b.max_y, b.width(), b.height());
-                ui.separator();
-                ui.monospace(info);
+        assert_eq!(parse_point("-5, 12"), Ok((-5, 12)));
+        assert!(parse_point("5").is_err());
+    }
+
+    #[test]
+    fn test_mouse_bench() {
+        let started = Instant::now();
+        let settings = human_mouse::HumanMouseSettings::default();
+        let report = bench::run(60, &settings, 3);
+        assert!(started.elapsed() < Duration::from_secs(5)); // simulated, not slept
+        assert_eq!(report.moves, 60);
+        for s in [report.duration_ms, report.peak_speed, report.path_ratio, report.jitter_peak_hz] {
+            assert!(s.p5 <= s.p50 && s.p50 <= s.p95 && s.mean > 0.0, "{:?}", s);
+        }
+        assert!(report.path_ratio.p5 >= 0.99);
+        assert!((report.jitter_bands.iter().sum::<f32>() - 1.0).abs() < 1e-3);
+        assert_eq!(bench::run(60, &settings, 3).duration_ms, report.duration_ms);
+        assert!(bench::render(&report, false).contains("peak speed"));
+
+        // 12 Hz sampled at 100 Hz
+        let wave: Vec<f32> = (0..100).map(|i| (2.0 * std::f32::consts::PI * 12.0 * i as f32 / 100.0).sin()).collect();
+        let (peak, bands) = bench::spectrum(&wave, 0.01);
+        assert!((peak - 12.0).abs() < 0.5);
+        assert!(bands[2] > bands[0] + bands[1] + bands[3]);
+    }
+
+    /// Records its index each time it runs; the first time, it asks for `command` and waits.
+    struct CommandStep { id: usize, log: Arc<Mutex<Vec<usize>>>, command: Option<StepCommand> }
+
+    impl Step for CommandStep {
+        fn name(&self) -> &str { "command" }
+        fn execute(&mut self, ctx: &mut StepCtx) -> StepOutcome {
+            self.log.lock().push(self.id);
+            match self.command.take() {
+                Some(c) => {
+                    ctx.config.lock().step_command = Some(c);
+                    StepOutcome::Continue
+                }
+                None => StepOutcome::Next,
             }
-        });
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use eframe::egui::Pos2;
-    use std::sync::{Arc, atomic::Ordering};
-    use std::time::Duration;
+    #[test]
+    fn test_step_commands() {
+        let running = AtomicBool::new(true);
+        let config = Mutex::new(ClickConfig::default());
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let step = |id, command| -> Box<dyn Step> { Box::new(CommandStep { id, log: Arc::clone(&log), command }) };
+
+        let mut steps = vec![
+            step(0, Some(StepCommand::Skip)),
+            step(1, Some(StepCommand::Repeat)),
+            step(2, Some(StepCommand::Jump(0))),
+        ];
+        run_steps(&mut steps, Some(1), &mut StepCtx::new(&running, &config));
+        assert_eq!(*log.lock(), vec![0, 1, 1, 2, 0, 1, 2]);
+
+        // a jump past the end lands on the last step
+        log.lock().clear();
+        let mut steps = vec![step(0, Some(StepCommand::Jump(9))), step(1, None)];
+        run_steps(&mut steps, Some(1), &mut StepCtx::new(&running, &config));
+        assert_eq!(*log.lock(), vec![0, 1]);
+        assert_eq!(config.lock().step_command, None);
+    }
 
     #[test]
-    fn test_bounds_validation() {
-        let valid_bounds = Bounds { min_x: 100, max_x: 200, min_y: 100, max_y: 200 };
-        assert!(valid_bounds.is_valid());
-        assert_eq!(valid_bounds.width(), 100);
-        assert_eq!(valid_bounds.height(), 100);
+    fn test_resume_sequence_position() {
+        let running = AtomicBool::new(true);
+        let config = Mutex::new(ClickConfig { start_at: (1, 1), ..ClickConfig::default() });
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut steps: Vec<Box<dyn Step>> = (0..3).map(|id| Box::new(CommandStep { id, log: Arc::clone(&log), command: None }) as Box<dyn Step>).collect();
+        run_steps(&mut steps, Some(3), &mut StepCtx::new(&running, &config));
+        assert_eq!(*log.lock(), vec![1, 2, 0, 1, 2]);
 
-        let invalid_bounds = Bounds { min_x: 200, max_x: 100, min_y: 200, max_y: 100 };
-        assert!(!invalid_bounds.is_valid());
+        // a position remembered on stop is offered again when the profile is loaded
+        let dir = std::env::temp_dir().join(format!("area_clicker_positions_{}", std::process::id()));
+        let store = ProfileStore::new(dir.clone());
+        store.save("long run", &AppState::default().to_profile()).unwrap();
+        let mut state = AppState { profiles: Some(ProfileStore::new(dir.clone())), ..AppState::default() };
+        state.load_profile("long run");
+        assert!(!state.use_start_at);
+        state.store_position(Some(SequencePosition { step: 3, cycle: 12 }));
+        let mut fresh = AppState { profiles: Some(ProfileStore::new(dir.clone())), ..AppState::default() };
+        fresh.load_profile("long run");
+        assert!(fresh.use_start_at);
+        assert_eq!(fresh.start_at, SequencePosition { step: 3, cycle: 12 });
+        fresh.store_position(None);
+        assert!(store.load_positions().is_empty());
+        let _ = std::fs::remove_dir_all(dir);
     }
 
     #[test]
-    fn test_click_job_creation() {
-        let config = Arc::new(Mutex::new(ClickConfig {
-            bounds: Some(Bounds { min_x: 100, max_x: 200, min_y: 100, max_y: 200 }),
-            button: ClickButton::Left,
-            min_secs: 2.0,
-            max_secs: 4.5,
+    fn test_cycle_checkpoints() {
+        let dir = std::env::temp_dir().join(format!("area_clicker_checkpoint_{}", std::process::id()));
+        let store = ProfileStore::new(dir.clone());
+        let running = AtomicBool::new(true);
+        let carried = Checkpoint { profile: Some("overnight".to_string()), cycle: 4, clicks: 30, step_clicks: vec![10, 20], ..Checkpoint::default() };
+        let config = Mutex::new(ClickConfig { start_at: (0, 4), checkpoint: Some((store.clone(), carried)), ..ClickConfig::default() });
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let mut steps: Vec<Box<dyn Step>> = (0..2).map(|id| Box::new(CommandStep { id, log: Arc::clone(&log), command: None }) as Box<dyn Step>).collect();
+        run_steps(&mut steps, Some(6), &mut StepCtx::new(&running, &config));
+        let saved = store.load_checkpoint().expect("checkpoint written at the cycle boundary");
+        assert_eq!((saved.profile.as_deref(), saved.cycle, saved.clicks), (Some("overnight"), 6, 30));
+        assert_eq!(saved.step_clicks, vec![10, 20]);
+
+        // after a crash the app offers it, and continuing picks up at the next cycle
+        let mut state = AppState { profiles: Some(ProfileStore::new(dir.clone())), ..AppState::default() };
+        state.pending_checkpoint = store.load_checkpoint();
+        let c = state.pending_checkpoint.take().unwrap();
+        state.continue_checkpoint(c);
+        assert_eq!(state.start_at, SequencePosition { step: 1, cycle: 7 });
+        assert!(state.continued.is_none(), "the carried counts went into the job");
+        state.stop();
+        store.clear_checkpoint().unwrap();
+        assert!(store.load_checkpoint().is_none());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_color_distance() {
+        assert_eq!(color_distance([10, 200, 30], [10, 200, 30]), 0);
+        assert_eq!(color_distance([10, 200, 30], [14, 190, 31]), 10);
+        assert_eq!(color_distance([0, 0, 0], [255, 255, 255]), 255);
+
+        // leaving the picker (e.g. Esc) ends color picking too
+        let mut state = AppState { picking_area: true, picking_color: true, ..AppState::default() };
+        state.exit_picker(&egui::Context::default());
+        assert!(!state.picking_area && !state.picking_color);
+    }
+
+    #[test]
+    fn test_picker_square_and_fixed_size() {
+        let start = egui::pos2(100.0, 100.0);
+        assert_eq!(square_corner(start, egui::pos2(160.0, 120.0)), egui::pos2(160.0, 160.0));
+        assert_eq!(square_corner(start, egui::pos2(90.0, 40.0)), egui::pos2(40.0, 40.0));
+        assert_eq!(square_corner(start, egui::pos2(130.0, 70.0)), egui::pos2(130.0, 70.0));
+
+        let mut state = AppState::default();
+        state.set_picked_bounds(Bounds::centered((500, 300), 64, 48));
+        assert_eq!(state.bounds_inputs, [468, 532, 276, 324]);
+        assert!(!state.use_point);
+    }
+
+    #[test]
+    fn test_picker_entry_starts_from_selection() {
+        let mut state = AppState { bounds_inputs: [10, 60, 20, 50], ..AppState::default() };
+        state.enter_picker(&egui::Context::default());
+        assert_eq!(state.pick_entry, [10, 20, 50, 30]);
+    }
+
+    #[test]
+    fn test_picker_adjusts_existing_selection() {
+        let rect = Rect::from_min_max(egui::pos2(100.0, 100.0), egui::pos2(200.0, 150.0));
+        assert_eq!(grip_at(rect, egui::pos2(150.0, 125.0)), Some(Grip::MOVE));
+        assert_eq!(grip_at(rect, egui::pos2(50.0, 125.0)), None);
+        let left = Grip { left: true, right: false, top: false, bottom: false };
+        assert_eq!(grip_at(rect, egui::pos2(97.0, 125.0)), Some(left));
+        let corner = Grip { left: false, right: true, top: false, bottom: true };
+        assert_eq!(grip_at(rect, egui::pos2(203.0, 148.0)), Some(corner));
+
+        assert_eq!(adjust_entry([100, 100, 100, 50], Grip::MOVE, (10, -5)), [110, 95, 100, 50]);
+        assert_eq!(adjust_entry([100, 100, 100, 50], corner, (20, 10)), [100, 100, 120, 60]);
+        // dragging an edge past the opposite one flips the area instead of making it negative
+        assert_eq!(adjust_entry([100, 100, 100, 50], left, (130, 0)), [200, 100, 30, 50]);
+    }
+
+    #[test]
+    fn test_picker_negative_origin_monitors() {
+        // 100% 1920x1080 left of a 150% 2560x1440 primary; the window starts at the union's left edge
+        let monitors = vec![monitor(1, (0, 0), (2560, 1440), 1.5), monitor(2, (-1920, 0), (1920, 1080), 1.0)];
+        let (ppp, origin) = (1.5, (-1920, 0));
+        // the left monitor is 1920 points wide, so the primary starts there, not at 1920 / 1.5
+        assert_eq!(monitor_rect(&monitors[0], ppp, origin, &monitors).min, Pos2::new(1920.0, 0.0));
+        assert_eq!(logical_to_physical(Pos2::new(100.0, 50.0), ppp, origin, &monitors), (-1820, 50));
+        assert_eq!(logical_to_physical(Pos2::new(1500.0, 50.0), ppp, origin, &monitors), (-420, 50));
+        assert_eq!(logical_to_physical(Pos2::new(2020.0, 50.0), ppp, origin, &monitors), (150, 75));
+        for px in [(-1920, 0), (-1, 1079), (0, 0), (2559, 1439)] {
+            assert_eq!(logical_to_physical(physical_to_logical(px, ppp, origin, &monitors), ppp, origin, &monitors), px);
+        }
+        // one above the primary at the same scale maps straight through
+        let monitors = vec![monitor(1, (0, 0), (1920, 1080), 1.0), monitor(2, (200, -1080), (1920, 1080), 1.0)];
+        assert_eq!(logical_to_physical(Pos2::new(300.0, 100.0), 1.0, (0, -1080), &monitors), (300, -980));
+        assert_eq!(physical_to_logical((300, -980), 1.0, (0, -1080), &monitors), Pos2::new(300.0, 100.0));
+    }
+
+    #[test]
+    fn test_simulated_click_preview() {
+        use rand::{rngs::StdRng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(3);
+        let area = Bounds { min_x: 0, max_x: 99, min_y: 0, max_y: 99 };
+        let forbidden = [Bounds { min_x: 0, max_x: 49, min_y: 0, max_y: 99 }];
+        let cfg = ClickConfig { bounds: Some(area), ..ClickConfig::default() };
+        let targets = click_targets(&cfg);
+        assert_eq!(targets, vec![(0, ClickTarget::Area(area, Vec::new()))]);
+        let (points, skipped) = simulate_clicks(&targets, &forbidden, PREVIEW_CLICKS, &mut rng);
+        assert_eq!((points.len(), skipped), (PREVIEW_CLICKS, 0));
+        assert!(points.iter().all(|&(_, p)| area.contains(p) && !forbidden[0].contains(p)));
+
+        // a sequence alternates over its clicking steps; a point inside a forbidden zone is skipped
+        let point = ClickPoint { x: 10, y: 10, jitter: 0 };
+        let cfg = ClickConfig {
+            mode: JobMode::Sequence,
+            steps: vec![
+                seq_step("area", 1, area),
+                SequenceStep { target: StepTarget::Screenshot { full_screen: false }, ..seq_step("shot", 1, area) },
+                SequenceStep { target: StepTarget::Point(point), ..seq_step("point", 1, area) },
+            ],
+            ..ClickConfig::default()
+        };
+        let targets = click_targets(&cfg);
+        assert_eq!(targets.iter().map(|(i, _)| *i).collect::<Vec<_>>(), vec![0, 2]);
+        let (points, skipped) = simulate_clicks(&targets, &forbidden, 10, &mut rng);
+        assert_eq!((points.len(), skipped), (5, 5));
+        assert!(points.iter().all(|&(i, _)| i == 0));
+        assert!(simulate_clicks(&[], &forbidden, 10, &mut rng).0.is_empty());
+    }
+
+    #[test]
+    fn test_dry_run_schedule() {
+        use crate::step::DRY_RUN_MAX_EVENTS;
+        let area = Bounds { min_x: 0, max_x: 99, min_y: 0, max_y: 99 };
+        let sequence = |steps, cycles| ClickConfig { mode: JobMode::Sequence, steps, cycles, ..ClickConfig::default() };
+        let events = |r: &DryRunReport| r.run.entries.iter().map(|e| (e.step, std::mem::discriminant(&e.event))).collect::<Vec<_>>();
+        let (step_ev, click, cycle_end) = (
+            std::mem::discriminant(&DryRunEvent::Step),
+            std::mem::discriminant(&DryRunEvent::Click((0, 0))),
+            std::mem::discriminant(&DryRunEvent::CycleEnd),
+        );
+
+        // one cycle runs every step once, and takes the intervals' time without sleeping
+        let started = Instant::now();
+        let report = dry_run(sequence(vec![seq_step("a", 2, area), seq_step("b", 1, area)], Some(1)));
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert_eq!(events(&report), vec![(0, step_ev), (0, click), (0, click), (1, step_ev), (1, click), (1, cycle_end)]);
+        assert_eq!((report.cycles(), report.clicks, report.run.now_ms), (1, 3, 3000));
+        assert_eq!(report.run.per_step(2), vec![(2, 2000), (1, 1000)]);
+        assert!(report.run.entries.iter().all(|e| e.cycle == 1) && report.stopped().is_none());
+
+        // the image is taken as found; three cycles end three times
+        let target = StepTarget::WaitImage(WaitImage { on_found: Branch::SkipNext, ..WaitImage::default() });
+        let wait = SequenceStep { target, ..seq_step("wait", 1, area) };
+        let report = dry_run(sequence(vec![wait, seq_step("skipped", 1, area), seq_step("c", 1, area)], Some(3)));
+        assert_eq!((report.cycles(), report.clicks), (3, 3));
+        assert_eq!(report.run.per_step(3).iter().map(|t| t.0).collect::<Vec<_>>(), vec![0, 0, 3]);
+
+        // a failing step that aborts ends the run; endless cycles stop at the event limit
+        let mut broken = seq_step("broken", 1, area);
+        broken.bounds = Bounds { min_x: 10, max_x: 0, min_y: 10, max_y: 0 };
+        broken.on_error.then = Branch::Abort;
+        let report = dry_run(sequence(vec![seq_step("a", 1, area), broken], None));
+        assert_eq!((report.cycles(), report.clicks, report.failed_steps), (0, 1, 1));
+        assert_eq!(report.stopped(), Some("step 'broken' ended the job"));
+        let report = dry_run(sequence(vec![seq_step("a", 1, area)], None));
+        assert!(report.run.entries.len() > DRY_RUN_MAX_EVENTS && report.stopped().is_some());
+    }
+
+    #[test]
+    fn test_cycle_schedule() {
+        use crate::step::CycleSchedule;
+        let cycles = |total, done| CycleSchedule::new(total, done).collect::<Vec<_>>();
+        assert_eq!(cycles(Some(0), 0), Vec::<u32>::new());
+        assert_eq!(cycles(Some(1), 0), vec![0]);
+        assert_eq!(cycles(Some(3), 0), vec![0, 1, 2]);
+        // continuing after cycles already done
+        assert_eq!(cycles(Some(3), 2), vec![2]);
+        assert_eq!(cycles(Some(3), 3), Vec::<u32>::new());
+        assert_eq!(cycles(Some(3), 7), Vec::<u32>::new());
+        assert_eq!(CycleSchedule::new(None, 0).take(5).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        assert_eq!(CycleSchedule::new(None, u32::MAX).take(2).collect::<Vec<_>>(), vec![u32::MAX, u32::MAX]);
+        // a reload changing the total keeps the cycles already run
+        let mut schedule = CycleSchedule::new(Some(5), 0);
+        assert_eq!((schedule.next(), schedule.next()), (Some(0), Some(1)));
+        schedule.set_cycles(Some(3));
+        assert_eq!(schedule.collect::<Vec<_>>(), vec![2]);
+
+        // the runner repeats a one-click sequence exactly that many times
+        let step = seq_step("a", 1, Bounds { min_x: 0, max_x: 99, min_y: 0, max_y: 99 });
+        for n in [0, 1, 2, 10] {
+            let cfg = ClickConfig { mode: JobMode::Sequence, steps: vec![step.clone()], cycles: Some(n), ..ClickConfig::default() };
+            let report = dry_run(cfg);
+            assert_eq!((report.cycles(), report.clicks), (n as usize, n), "cycles = Some({})", n);
+        }
+        let cfg = ClickConfig { mode: JobMode::Sequence, steps: vec![step.clone()], cycles: Some(3), start_at: (0, 1), ..ClickConfig::default() };
+        assert_eq!(dry_run(cfg).clicks, 2);
+        let cfg = ClickConfig { mode: JobMode::Sequence, steps: vec![step], cycles: None, ..ClickConfig::default() };
+        assert!(dry_run(cfg).cycles() > 1000);
+    }
+
+    #[test]
+    fn test_interleaved_sequence() {
+        let (left, right) = (Bounds { min_x: 0, max_x: 9, min_y: 0, max_y: 9 }, Bounds { min_x: 100, max_x: 109, min_y: 0, max_y: 9 });
+        let cfg = |step_order| ClickConfig {
+            mode: JobMode::Sequence,
+            steps: vec![seq_step("a", 3, left), seq_step("b", 2, right)],
+            cycles: Some(2),
+            step_order,
+            ..ClickConfig::default()
+        };
+        let order = |report: &DryRunReport| report.run.entries.iter()
+            .filter(|e| matches!(e.event, DryRunEvent::Click(_)))
+            .map(|e| e.step_name.as_str())
+            .collect::<String>();
+        assert_eq!(order(&dry_run(cfg(StepOrder::InOrder))), "aaabbaaabb");
+        let report = dry_run(cfg(StepOrder::Interleave));
+        assert_eq!(order(&report), "ababaababa");
+        assert_eq!((report.cycles(), report.clicks), (2, 10));
+        // the clicks still land in their own step's area
+        assert!(report.run.entries.iter().all(|e| match e.event {
+            DryRunEvent::Click((x, _)) => (x < 100) == (e.step_name == "a"),
+            _ => true,
         }));
+        // starting at the second step begins the rotation there
+        let report = dry_run(ClickConfig { cycles: Some(1), start_at: (1, 0), ..cfg(StepOrder::Interleave) });
+        assert_eq!(order(&report), "babaa");
+    }
 
-        let job = ClickJob::spawn(Arc::clone(&config));
-        assert!(job.running.load(Ordering::Relaxed));
+    #[test]
+    fn test_weighted_sequence() {
+        let area = Bounds { min_x: 0, max_x: 9, min_y: 0, max_y: 9 };
+        let cfg = |steps| ClickConfig {
+            mode: JobMode::Sequence,
+            steps,
+            cycles: Some(5),
+            step_order: StepOrder::Weighted,
+            ..ClickConfig::default()
+        };
+        // a cycle makes as many clicks as the counts add up to; a zero weight leaves its step out
+        let report = dry_run(cfg(vec![
+            seq_step("a", 10, area),
+            SequenceStep { weight: 3.0, ..seq_step("b", 30, area) },
+            SequenceStep { weight: 0.0, ..seq_step("c", 20, area) },
+        ]));
+        assert_eq!((report.cycles(), report.clicks), (5, 200));
+        let per_step = report.run.per_step(3);
+        assert_eq!(per_step[2].0, 0);
+        assert!(per_step[0].0 > 0 && per_step[1].0 > per_step[0].0, "{:?}", per_step);
+        // nothing to draw from ends the job
+        let report = dry_run(cfg(vec![SequenceStep { weight: 0.0, ..seq_step("a", 10, area) }, seq_step("b", 0, area)]));
+        assert_eq!(report.clicks, 0);
+        assert!(report.stopped().is_some());
+    }
 
-        // Test stopping
-        job.stop();
-        assert!(!job.running.load(Ordering::Relaxed));
+    #[test]
+    fn test_step_hotkey_burst() {
+        let step = SequenceStep {
+            name: "burst".into(),
+            bounds: Bounds { min_x: 0, max_x: 9, min_y: 0, max_y: 9 },
+            clicks: 4,
+            max_secs: 0.5,
+            hotkey: Some(HotKey::F3),
+            ..SequenceStep::default()
+        };
+        let job = ClickConfig {
+            mode: JobMode::Single,
+            cycles: None,
+            step_order: StepOrder::Weighted,
+            hold_to_run: Some(HotKey::F9),
+            start_at: (2, 5),
+            ..ClickConfig::default()
+        };
+        let burst = job.burst(step.clone());
+        assert_eq!((burst.mode, burst.cycles, burst.step_order, burst.hold_to_run), (JobMode::Sequence, Some(1), StepOrder::InOrder, None));
+        assert_eq!(burst.start_at, (0, 0));
+        // the step's own clicks with its own timing, once
+        let report = dry_run(burst);
+        assert_eq!((report.cycles(), report.clicks), (1, 4));
+        assert_eq!(report.run.now_ms, 2000);
+        let saved: SequenceStep = serde_json::from_str(&serde_json::to_string(&step).unwrap()).unwrap();
+        assert_eq!(saved.hotkey, Some(HotKey::F3));
+        assert!(saved.summary().ends_with(" · ⌨ F3"));
     }
 
     #[test]
-    fn test_app_state_defaults() {
-        let state = AppState::default();
-        assert!(!state.picking_area);
-        assert!(state.drag_start.is_none());
-        assert!(state.drag_end.is_none());
-        assert!(state.click_button_left);
-        assert!(state.job.is_none());
+    fn test_external_triggers() {
+        // running status, a clock tick in the middle and a note-on with velocity 0 (a release)
+        let bytes = [0x91, 36, 100, 0xF8, 38, 90, 36, 0, 0xC0, 5, 0x80, 40, 0, 0xF0, 1, 2, 0xF7, 40, 64];
+        let mut parser = triggers::MidiParser::default();
+        let notes: Vec<Note> = bytes.iter().filter_map(|&b| parser.push(b)).collect();
+        assert_eq!(notes, [Note { channel: 2, note: 36 }, Note { channel: 2, note: 38 }]);
 
-        // input defaults
-        assert_eq!(state.min_secs, 2.0);
-        assert_eq!(state.max_secs, 4.5);
+        let binding = MidiBinding { channel: None, note: 36, action: TriggerAction::RunStep("Collect".into()) };
+        assert!(binding.matches(notes[0]) && !binding.matches(notes[1]));
+        assert!(!MidiBinding { channel: Some(1), ..binding.clone() }.matches(notes[0]));
+        assert_eq!(binding.action.command(), "step Collect");
+        assert_eq!(TriggerAction::StartProfile("Farm".into()).command(), "start Farm");
+
+        let mut state = AppState::default();
+        assert_eq!(daemon::handle(&mut state, "step Collect"), Err("no step 'Collect'".to_string()));
+        assert!(daemon::handle(&mut state, "step").unwrap_err().starts_with("usage"));
+        assert!(daemon::handle(&mut state, "start Farm").is_err()); // no profile store in tests
+        assert!(daemon::handle(&mut state, "quit").is_err());
+        state.triggers.midi = vec![binding, MidiBinding { channel: Some(2), note: 38, action: TriggerAction::Stop }];
+        state.midi_note(Note { channel: 2, note: 38 });
+        assert_eq!(state.last_note, Some(Note { channel: 2, note: 38 }));
     }
 
     #[test]
-    fn test_set_bounds_from_drag_ppp1_origin0() {
+    fn test_voice_stop() {
+        let mut v = voice::Voice::default();
+        assert_eq!(v.hear("Clicker, STOP!"), Some(Said::Stop));
+        assert_eq!(v.hear("the clicker stopped"), None);
+        assert_eq!(v.hear("clicker start"), None); // no start phrase set
+        v.start_phrase = "clicker start".into();
+        assert_eq!(v.hear("okay clicker start"), Some(Said::Start));
+        assert_eq!(v.hear("clicker start clicker stop"), Some(Said::Stop));
+        assert!(v.command_line().contains("-keyphrase \"clicker stop\""));
+        assert!(!voice::contains_phrase("anything", "  "));
+
+        let triggers: Triggers = serde_json::from_str(r#"{"remote": true}"#).unwrap();
+        assert_eq!(triggers.voice, voice::Voice::default());
+
         let mut state = AppState::default();
-        state.drag_start = Some(Pos2::new(100.0, 100.0));
-        state.drag_end   = Some(Pos2::new(200.0, 200.0));
-        state.set_bounds_from_drag(1.0, (0, 0));
-        assert_eq!(state.bounds_inputs, [100, 200, 100, 200]);
+        state.triggers.voice = v;
+        state.voice_heard("clicker stop".into());
+        assert!(!state.is_running());
+        assert_eq!(state.last_heard.as_deref(), Some("clicker stop"));
+    }
 
-        // reverse drag
-        state.drag_start = Some(Pos2::new(200.0, 200.0));
-        state.drag_end   = Some(Pos2::new(100.0, 100.0));
-        state.set_bounds_from_drag(1.0, (0, 0));
-        assert_eq!(state.bounds_inputs, [100, 200, 100, 200]);
+    #[test]
+    fn test_gamepad_nudge() {
+        assert_eq!(gamepad::dead_zone(0.1), 0.0);
+        assert_eq!(gamepad::dead_zone(1.0), 1.0);
+        assert!((gamepad::dead_zone(-0.6) + 0.5).abs() < 1e-6);
+
+        let mut state = AppState { use_point: true, point_inputs: [100, 100, 0], ..AppState::default() };
+        state.nudge_selection((0.6, -0.3));
+        assert_eq!(state.point_inputs, [100, 100, 0]);
+        state.nudge_selection((0.6, -0.8));
+        assert_eq!(state.point_inputs, [101, 99, 0]);
+        assert_eq!(state.config.lock().point.map(|p| (p.x, p.y)), Some((101, 99)));
+
+        state.use_point = false;
+        state.bounds_inputs = [10, 20, 30, 40];
+        state.nudge_selection((-2.0, 3.5)); // on top of the (0.2, -0.1) left over
+        assert_eq!(state.bounds_inputs, [9, 19, 33, 43]);
+
+        state.pad_start = Some(PadButton::Start);
+        state.pad_nudge = true;
+        let profile = state.to_profile();
+        let json = serde_json::to_string(&profile).unwrap();
+        let back: Profile = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.pad_start, Some(PadButton::Start));
+        assert!(back.pad_nudge && back.pad_stop.is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_control_socket_in_window() {
+        let socket = std::env::temp_dir().join(format!("area_clicker_remote_{}.sock", std::process::id()));
+        let remote = daemon::Remote::start(socket.clone(), || {});
+        let client = {
+            let socket = socket.clone();
+            std::thread::spawn(move || {
+                for _ in 0..100 {
+                    if socket.exists() { return daemon::send(&socket, "status"); }
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                }
+                Err("the socket never showed up".to_string())
+            })
+        };
+        while !client.is_finished() {
+            remote.answer(|line| Ok(format!("got {}", line)));
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(client.join().unwrap(), Ok("got status".to_string()));
+        drop(remote);
+        for _ in 0..100 {
+            if !socket.exists() { break; }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        assert!(!socket.exists());
+    }
+
+    #[test]
+    fn test_compact_mode_toggle() {
+        let ctx = egui::Context::default();
+        let mut state = AppState::default();
+        let mut input = egui::RawInput::default();
+        input.viewports.entry(egui::ViewportId::ROOT).or_default().inner_rect = Some(Rect::from_min_size(Pos2::ZERO, egui::vec2(900.0, 700.0)));
+        let commands = |out: egui::FullOutput| out.viewport_output[&egui::ViewportId::ROOT].commands.clone();
+
+        let entered = commands(ctx.run(input.clone(), |ctx| state.enter_compact(ctx)));
+        assert!(entered.contains(&egui::ViewportCommand::WindowLevel(WindowLevel::AlwaysOnTop)));
+        assert!(entered.contains(&egui::ViewportCommand::InnerSize(egui::vec2(460.0, 36.0))));
+        // the bar shows only the controls, the status and the way back
+        let out = ctx.run(input.clone(), |ctx| state.compact_ui(ctx));
+        let texts: Vec<String> = out.shapes.iter().filter_map(|c| match &c.shape {
+            egui::Shape::Text(t) => Some(t.galley.text().to_string()),
+            _ => None,
+        }).collect();
+        assert_eq!(texts, ["Start", "Pause", "Stop", "Status: Stopped", "⤢"]);
+
+        // leaving it gives the window back the size it had
+        let left = commands(ctx.run(input, |ctx| state.exit_compact(ctx)));
+        assert!(!state.compact);
+        assert!(left.contains(&egui::ViewportCommand::WindowLevel(WindowLevel::Normal)));
+        assert!(left.contains(&egui::ViewportCommand::InnerSize(egui::vec2(900.0, 700.0))));
+        let fallback = commands(ctx.run(egui::RawInput::default(), |ctx| { state.enter_compact(ctx); state.exit_compact(ctx) }));
+        assert!(fallback.contains(&egui::ViewportCommand::InnerSize(egui::vec2(740.0, 560.0))));
+    }
+
+    #[test]
+    fn test_progress_summary() {
+        let mut p = JobProgress { step: 2, step_count: 7, cycle: 12, cycles: None, clicks: 428, ..Default::default() };
+        assert_eq!(p.summary(), "step 3/7, cycle 12, 428 clicks");
+        p.cycles = Some(20);
+        assert_eq!(p.summary(), "step 3/7, cycle 12/20, 428 clicks");
+        let single = JobProgress { step_count: 1, cycle: 1, cycles: Some(1), clicks: 5, ..Default::default() };
+        assert_eq!(single.summary(), "5 clicks");
+    }
+
+    #[test]
+    fn test_bounds_around() {
+        let c = Bounds::centered((50, 60), 100, 40);
+        assert_eq!((c.min_x, c.max_x, c.min_y, c.max_y), (0, 100, 40, 80));
+
+        let b = Bounds::around((50, 60), 5);
+        assert_eq!((b.min_x, b.max_x, b.min_y, b.max_y), (45, 55, 55, 65));
+        assert!(b.contains((50, 60)));
+        assert!(!Bounds::around((0, 0), 0).is_valid());
+    }
+
+    #[test]
+    fn test_save_and_edit_step() {
+        let mut state = AppState { bounds_inputs: [10, 20, 30, 40], ..AppState::default() };
+        state.save_step();
+        assert_eq!(state.steps.len(), 1);
+        assert_eq!(state.steps[0].name, "Step 1");
+        assert_eq!(state.steps[0].clicks, 5);
+
+        state.edit_step(0);
+        state.step_clicks = 9;
+        state.save_step();
+        assert_eq!(state.steps.len(), 1);
+        assert_eq!(state.steps[0].clicks, 9);
+        assert!(state.editing_step_idx.is_none());
+    }
+
+    #[test]
+    fn test_anti_afk_mode_config() {
+        let mut state = AppState { mode: JobMode::AntiAfk, afk_key: AfkKey::F15, ..AppState::default() };
+        state.start();
+        state.stop();
+
+        let cfg = state.config.lock().clone();
+        assert_eq!(cfg.anti_afk.map(|a| a.key), Some(AfkKey::F15));
+        let (steps, cycles) = build_steps(&cfg);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(cycles, None);
+    }
+
+    #[test]
+    fn test_grid_cells_cover_bounds() {
+        let b = Bounds { min_x: 0, max_x: 99, min_y: 0, max_y: 49 };
+        let cells = grid_cells(b, 3, 2, false);
+        assert_eq!(cells.len(), 6);
+        assert_eq!((cells[0].min_x, cells[0].max_x), (0, 32));
+        assert_eq!((cells[1].min_x, cells[1].max_x), (33, 65));
+        assert_eq!((cells[2].min_x, cells[2].max_x), (66, 99));
+        assert_eq!((cells[3].min_y, cells[3].max_y), (24, 49));
+
+        // serpentine: the second row runs right-to-left
+        let snake = grid_cells(b, 3, 2, true);
+        assert_eq!(snake[3].min_x, 66);
+        assert_eq!(snake[5].min_x, 0);
+
+        // more cells than pixels still yields non-inverted cells
+        let tiny = grid_cells(Bounds { min_x: 0, max_x: 2, min_y: 0, max_y: 2 }, 5, 1, false);
+        assert!(tiny.iter().all(|c| c.min_x <= c.max_x));
+    }
+
+    #[test]
+    fn test_weighted_zones_sampling() {
+        use rand::SeedableRng;
+        let b = Bounds { min_x: 0, max_x: 299, min_y: 0, max_y: 299 };
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+
+        // all weight on the left third
+        let zones = [Zone { x0: 0.0, y0: 0.0, x1: 1.0 / 3.0, y1: 1.0, weight: 1.0 }, Zone::full(0.0)];
+        for _ in 0..200 {
+            let (x, _) = crate::sampler::sample_point(b, &zones, &mut rng);
+            assert!(x <= 100);
+        }
+
+        // 70/30 center band: well over half the points land in the middle third
+        let zones = center_band();
+        let hits = (0..1000)
+            .filter(|_| (100..=200).contains(&crate::sampler::sample_point(b, &zones, &mut rng).1))
+            .count();
+        assert!(hits > 650, "center hits: {}", hits);
+    }
+
+    #[test]
+    fn test_fatigue_factors() {
+        let f = Fatigue { interval_pct_per_hour: 20.0, speed_pct_per_hour: 10.0, max_pct: 30.0, ..Fatigue::default() };
+        assert_eq!(f.interval_factor(0.0), 1.0);
+        assert!((f.interval_factor(1.0) - 1.2).abs() < 1e-6);
+        assert!((f.speed_factor(2.0) - 0.8).abs() < 1e-6);
+        // both drifts are capped at max_pct
+        assert!((f.interval_factor(10.0) - 1.3).abs() < 1e-6);
+        assert!((f.speed_factor(10.0) - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_warm_up_eases_to_normal() {
+        let w = WarmUp { clicks: 4, interval_factor: 3.0, speed_factor: 0.5 };
+        assert_eq!(w.interval_factor(0), 3.0);
+        assert_eq!(w.interval_factor(2), 2.0);
+        assert_eq!(w.interval_factor(4), 1.0);
+        assert_eq!(w.interval_factor(100), 1.0);
+        assert_eq!(w.speed_factor(0), 0.5);
+        assert_eq!(w.speed_factor(4), 1.0);
+
+        // a one-click warm-up stretches the wait after the first click and no later one
+        let step = SequenceStep { name: "a".into(), bounds: Bounds { min_x: 0, max_x: 9, min_y: 0, max_y: 9 }, clicks: 3, min_secs: 1.0, max_secs: 1.0, ..SequenceStep::default() };
+        let warm_up = Some(WarmUp { clicks: 1, interval_factor: 3.0, speed_factor: 0.5 });
+        let report = dry_run(ClickConfig { mode: JobMode::Sequence, steps: vec![step], cycles: Some(1), warm_up, ..ClickConfig::default() });
+        let clicks_at = report.run.entries.iter().filter(|e| matches!(e.event, DryRunEvent::Click(_))).map(|e| e.at_ms).collect::<Vec<_>>();
+        assert_eq!(clicks_at, vec![0, 3000, 4000]);
+    }
+
+    struct CountingStep { runs: Arc<Mutex<u32>>, stop_after: Option<u32> }
+
+    impl Step for CountingStep {
+        fn name(&self) -> &str { "counting" }
+        fn execute(&mut self, _ctx: &mut StepCtx) -> StepOutcome {
+            let mut runs = self.runs.lock();
+            *runs += 1;
+            if self.stop_after == Some(*runs) { StepOutcome::Stop } else { StepOutcome::Next }
+        }
+    }
+
+    #[test]
+    fn test_run_steps_custom_step() {
+        let running = AtomicBool::new(true);
+        let config = Mutex::new(ClickConfig::default());
+        let runs = Arc::new(Mutex::new(0));
+
+        let mut steps: Vec<Box<dyn Step>> = vec![
+            Box::new(CountingStep { runs: Arc::clone(&runs), stop_after: None }),
+            Box::new(CountingStep { runs: Arc::clone(&runs), stop_after: None }),
+        ];
+        run_steps(&mut steps, Some(3), &mut StepCtx::new(&running, &config));
+        assert_eq!(*runs.lock(), 6);
+
+        *runs.lock() = 0;
+        let mut steps: Vec<Box<dyn Step>> = vec![
+            Box::new(CountingStep { runs: Arc::clone(&runs), stop_after: Some(4) }),
+        ];
+        run_steps(&mut steps, None, &mut StepCtx::new(&running, &config));
+        assert_eq!(*runs.lock(), 4);
     }
 
     #[test]
@@ -592,6 +9590,7 @@ mod tests {
             button: ClickButton::Left,
             min_secs: 0.1,
             max_secs: 0.2,
+            ..ClickConfig::default()
         }));
 
         let job = ClickJob::spawn(Arc::clone(&config));
@@ -600,24 +9599,3 @@ mod tests {
         assert!(!job.running.load(Ordering::Relaxed));
     }
 }
-
-fn main() -> eframe::Result<()> {
-    let mut opts = eframe::NativeOptions::default();
-    let _args = Args::parse(); // Arguments will be used later
-
-    // Start as a normal window; we resize/position during picking.
-    opts.viewport.transparent = Some(true);
-    opts.viewport.resizable = Some(true);
-    opts.viewport.mouse_passthrough = Some(false); // Ensure we capture mouse events
-    opts.follow_system_theme = true;
-
-    eframe::run_native(
-        "Area Clicker",
-        opts,
-        Box::new(|cc| {
-            cc.egui_ctx.set_visuals(egui::Visuals::dark());
-            Box::<AppState>::default()
-        }),
-    )
-
-}
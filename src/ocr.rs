@@ -0,0 +1,80 @@
+//! Text targets: OCR a search region with the `tesseract` command-line tool and find where a
+//! word or phrase is on screen, so steps survive buttons moving between app versions.
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::human_mouse::Bounds;
+use crate::screenshot;
+
+/// One recognized word, in screen coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Word {
+    pub text: String,
+    pub bounds: Bounds,
+    /// (block, paragraph, line): words of a phrase must share it.
+    pub line: (u32, u32, u32),
+}
+
+/// OCR `region` of the screen.
+pub fn read_words(region: Bounds) -> Result<Vec<Word>, String> {
+    let capture = screenshot::capture(Some(region))?;
+    let mut png = Vec::new();
+    screenshot::write_png(&capture, &mut png).map_err(|e| e.to_string())?;
+
+    let mut child = Command::new("tesseract")
+        .args(["stdin", "stdout", "tsv"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Text targets need the tesseract command ({})", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(&png).map_err(|e| format!("Could not send the image to tesseract: {}", e))?;
+    }
+    let out = child.wait_with_output().map_err(|e| format!("tesseract failed: {}", e))?;
+    if !out.status.success() {
+        return Err(format!("tesseract failed: {}", String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    Ok(parse_tsv(&String::from_utf8_lossy(&out.stdout), (region.min_x, region.min_y)))
+}
+
+/// Words from tesseract's TSV output, moved by `origin` (the capture's top-left corner).
+pub fn parse_tsv(tsv: &str, (ox, oy): (i32, i32)) -> Vec<Word> {
+    // level page block par line word left top width height conf text
+    tsv.lines()
+        .skip(1)
+        .filter_map(|l| {
+            let cols: Vec<&str> = l.splitn(12, '\t').collect();
+            if cols.len() < 12 || cols[0] != "5" || cols[11].trim().is_empty() { return None; }
+            let n = |i: usize| cols[i].parse::<i32>().ok();
+            let (left, top, width, height) = (n(6)?, n(7)?, n(8)?, n(9)?);
+            Some(Word {
+                text: cols[11].trim().to_string(),
+                bounds: Bounds { min_x: ox + left, max_x: ox + left + width, min_y: oy + top, max_y: oy + top + height },
+                line: (n(2)? as u32, n(3)? as u32, n(4)? as u32),
+            })
+        })
+        .collect()
+}
+
+/// Lowercase and strip surrounding punctuation, so "Confirm." matches "confirm".
+fn normalize(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase()
+}
+
+/// Bounds of the first occurrence of `needle` (one or more words, on one line) in `words`.
+pub fn find_text(words: &[Word], needle: &str) -> Option<Bounds> {
+    let wanted: Vec<String> = needle.split_whitespace().map(normalize).filter(|w| !w.is_empty()).collect();
+    if wanted.is_empty() { return None; }
+    words.windows(wanted.len()).find_map(|run| {
+        let matches = run.iter().all(|w| w.line == run[0].line)
+            && run.iter().zip(&wanted).all(|(w, n)| normalize(&w.text) == *n);
+        matches.then(|| run.iter().skip(1).fold(run[0].bounds, |b, w| Bounds {
+            min_x: b.min_x.min(w.bounds.min_x),
+            max_x: b.max_x.max(w.bounds.max_x),
+            min_y: b.min_y.min(w.bounds.min_y),
+            max_y: b.max_y.max(w.bounds.max_y),
+        }))
+    })
+}
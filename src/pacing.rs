@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Gradual slow-down over a long session: intervals get longer and mouse movement slower
+/// as the job runs, and an occasional break takes part of that fatigue away again.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Fatigue {
+    /// Interval increase per hour of running, in percent.
+    pub interval_pct_per_hour: f32,
+    /// Mouse speed decrease per hour of running, in percent.
+    pub speed_pct_per_hour: f32,
+    /// Cap for both drifts, in percent.
+    pub max_pct: f32,
+    /// Chance (0–1) after each click to take a break.
+    pub break_chance: f32,
+    pub break_min_secs: f32,
+    pub break_max_secs: f32,
+    /// Share (0–1) of the accumulated fatigue a break removes.
+    pub recovery: f32,
+}
+
+impl Default for Fatigue {
+    fn default() -> Self {
+        Self {
+            interval_pct_per_hour: 15.0,
+            speed_pct_per_hour: 10.0,
+            max_pct: 50.0,
+            break_chance: 0.02,
+            break_min_secs: 20.0,
+            break_max_secs: 90.0,
+            recovery: 0.6,
+        }
+    }
+}
+
+impl Fatigue {
+    fn drift(&self, pct_per_hour: f32, hours: f32) -> f32 {
+        (pct_per_hour * hours).clamp(0.0, self.max_pct.max(0.0)) / 100.0
+    }
+
+    /// Multiplier for the click interval after `hours` of fatigue (>= 1).
+    pub fn interval_factor(&self, hours: f32) -> f32 {
+        1.0 + self.drift(self.interval_pct_per_hour, hours)
+    }
+
+    /// Multiplier for mouse speed after `hours` of fatigue (<= 1, never below 0.1).
+    pub fn speed_factor(&self, hours: f32) -> f32 {
+        (1.0 - self.drift(self.speed_pct_per_hour, hours)).max(0.1)
+    }
+}
+
+/// Extra-slow start: the first `clicks` clicks of a job begin at the given interval and speed
+/// multipliers and ease linearly back to the configured values.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WarmUp {
+    pub clicks: u32,
+    /// Interval multiplier for the very first click (>= 1).
+    pub interval_factor: f32,
+    /// Mouse speed multiplier for the very first click (<= 1).
+    pub speed_factor: f32,
+}
+
+impl Default for WarmUp {
+    fn default() -> Self { Self { clicks: 10, interval_factor: 2.5, speed_factor: 0.6 } }
+}
+
+impl WarmUp {
+    /// How far through the warm-up `done` clicks are, 0.0 (start) to 1.0 (finished).
+    fn progress(&self, done: u32) -> f32 {
+        if self.clicks == 0 { 1.0 } else { (done as f32 / self.clicks as f32).min(1.0) }
+    }
+
+    pub fn interval_factor(&self, done: u32) -> f32 {
+        let start = self.interval_factor.max(1.0);
+        start + (1.0 - start) * self.progress(done)
+    }
+
+    pub fn speed_factor(&self, done: u32) -> f32 {
+        let start = self.speed_factor.clamp(0.1, 1.0);
+        start + (1.0 - start) * self.progress(done)
+    }
+}
+
+/// How tired the engine currently is, measured as hours since a "fresh" reference point.
+pub struct FatigueClock {
+    fresh_at: Instant,
+}
+
+impl FatigueClock {
+    pub fn new() -> Self { Self { fresh_at: Instant::now() } }
+
+    pub fn hours(&self) -> f32 { self.fresh_at.elapsed().as_secs_f32() / 3600.0 }
+
+    /// Count `by` as already worked, for runs in virtual time.
+    pub fn advance(&mut self, by: Duration) {
+        if let Some(t) = self.fresh_at.checked_sub(by) { self.fresh_at = t; }
+    }
+
+    /// Forget `recovery` (0–1) of the fatigue accumulated so far.
+    pub fn recover(&mut self, recovery: f32) {
+        let tired = self.fresh_at.elapsed();
+        self.fresh_at += Duration::from_secs_f32(tired.as_secs_f32() * recovery.clamp(0.0, 1.0));
+    }
+}
@@ -0,0 +1,170 @@
+//! Recorded mouse paths. A trainer mode samples the user's real cursor movements into a path
+//! bank; playback then bends a recorded path onto the move at hand instead of drawing a
+//! synthetic curve, since real movements beat any easing function for realism.
+
+use parking_lot::Mutex;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// One cursor sample: milliseconds since the movement started, and the position.
+pub type Sample = (u32, i32, i32);
+
+/// Movements shorter than this (px, start to end) aren't kept or replayed.
+pub const MIN_LENGTH: f32 = 40.0;
+
+/// Most paths kept; the oldest go first.
+pub const MAX_PATHS: usize = 500;
+
+/// How often the recorder reads the cursor.
+const SAMPLE_MS: u64 = 8;
+
+/// A movement ends once the cursor has rested this long.
+const REST_MS: u32 = 120;
+
+/// Largest sideways bend of a replayed path, as a share of the move's length.
+const MAX_WARP: f32 = 0.06;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecordedPath {
+    pub samples: Vec<Sample>,
+}
+
+impl RecordedPath {
+    fn start(&self) -> (f32, f32) { self.samples.first().map_or((0.0, 0.0), |s| (s.1 as f32, s.2 as f32)) }
+    fn end(&self) -> (f32, f32) { self.samples.last().map_or((0.0, 0.0), |s| (s.1 as f32, s.2 as f32)) }
+
+    /// Straight-line distance from the first sample to the last.
+    pub fn length(&self) -> f32 {
+        let ((x0, y0), (x1, y1)) = (self.start(), self.end());
+        (x1 - x0).hypot(y1 - y0)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PathBank {
+    pub paths: Vec<RecordedPath>,
+}
+
+impl PathBank {
+    /// Keep `path` if it's long enough to be worth replaying. Returns whether it was kept.
+    pub fn add(&mut self, path: RecordedPath) -> bool {
+        if path.samples.len() < 3 || path.length() < MIN_LENGTH { return false; }
+        self.paths.push(path);
+        if self.paths.len() > MAX_PATHS { self.paths.remove(0); }
+        true
+    }
+
+    pub fn len(&self) -> usize { self.paths.len() }
+
+    pub fn is_empty(&self) -> bool { self.paths.is_empty() }
+
+    /// A recorded path for a move of `distance` px: one between half and double that long if
+    /// there is one, any otherwise.
+    pub fn pick(&self, distance: f32, rng: &mut impl Rng) -> Option<&RecordedPath> {
+        let similar: Vec<_> = self.paths.iter().filter(|p| (0.5..=2.0).contains(&(distance / p.length()))).collect();
+        if similar.is_empty() {
+            return (!self.paths.is_empty()).then(|| &self.paths[rng.gen_range(0..self.paths.len())]);
+        }
+        Some(similar[rng.gen_range(0..similar.len())])
+    }
+
+    /// A varied replay of a fitting recorded path for the move `from` → `to`, as
+    /// `(ms, x, y)` points; `None` for short moves or an empty bank.
+    pub fn variant(&self, from: (i32, i32), to: (i32, i32), rng: &mut impl Rng) -> Option<Vec<(u64, i32, i32)>> {
+        let distance = ((to.0 - from.0) as f32).hypot((to.1 - from.1) as f32);
+        if distance < MIN_LENGTH { return None; }
+        let path = self.pick(distance, rng)?;
+        Some(fit(path, from, to, rng.gen_range(-MAX_WARP..=MAX_WARP), rng.gen_range(0.85..=1.15)))
+    }
+}
+
+/// Map `path` onto the move `from` → `to`: rotate and scale it so its ends land there, bend it
+/// sideways by `warp` times the move's length at the middle, and stretch its timing by
+/// `tempo` (and by the square root of the scale, as longer moves take only a bit longer).
+pub fn fit(path: &RecordedPath, from: (i32, i32), to: (i32, i32), warp: f32, tempo: f32) -> Vec<(u64, i32, i32)> {
+    let (sx, sy) = path.start();
+    let (rx, ry) = { let (ex, ey) = path.end(); (ex - sx, ey - sy) };
+    let (vx, vy) = ((to.0 - from.0) as f32, (to.1 - from.1) as f32);
+    let r2 = (rx * rx + ry * ry).max(1.0);
+    // the complex ratio v / r: rotation and scale in one
+    let (kx, ky) = ((vx * rx + vy * ry) / r2, (vy * rx - vx * ry) / r2);
+    let scale = kx.hypot(ky);
+    let (nx, ny) = { let d = vx.hypot(vy).max(1.0); (-vy / d, vx / d) };
+    let bend = warp * vx.hypot(vy);
+    path.samples
+        .iter()
+        .map(|&(ms, x, y)| {
+            let (px, py) = (x as f32 - sx, y as f32 - sy);
+            // progress along the recorded move, for the bend's sine
+            let s = ((px * rx + py * ry) / r2).clamp(0.0, 1.0);
+            let b = bend * (std::f32::consts::PI * s).sin();
+            let qx = from.0 as f32 + kx * px - ky * py + nx * b;
+            let qy = from.1 as f32 + ky * px + kx * py + ny * b;
+            ((ms as f32 * tempo * scale.sqrt()).round() as u64, qx.round() as i32, qy.round() as i32)
+        })
+        .collect()
+}
+
+/// Cuts a stream of cursor samples into movements at pauses.
+#[derive(Default)]
+pub struct Segmenter {
+    prev: Option<(u32, (i32, i32))>,
+    current: Vec<Sample>,
+}
+
+impl Segmenter {
+    /// Feed the cursor position at `ms`; returns a movement once the cursor has come to rest.
+    pub fn push(&mut self, ms: u32, pos: (i32, i32)) -> Option<RecordedPath> {
+        let (prev_ms, prev_pos) = self.prev.replace((ms, pos))?;
+        if pos != prev_pos {
+            if self.current.is_empty() { self.current.push((prev_ms, prev_pos.0, prev_pos.1)); }
+            self.current.push((ms, pos.0, pos.1));
+            return None;
+        }
+        if ms.saturating_sub(self.current.last()?.0) < REST_MS { return None; }
+        let t0 = self.current[0].0;
+        Some(RecordedPath { samples: self.current.drain(..).map(|(t, x, y)| (t - t0, x, y)).collect() })
+    }
+}
+
+/// Trainer mode: reads the cursor on its own thread and collects the movements it sees.
+pub struct Recorder {
+    running: Arc<AtomicBool>,
+    recorded: Arc<Mutex<Vec<RecordedPath>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Recorder {
+    /// Start sampling `read` (the cursor position).
+    pub fn start(read: impl Fn() -> (i32, i32) + Send + 'static) -> Self {
+        let running = Arc::new(AtomicBool::new(true));
+        let recorded = Arc::new(Mutex::new(Vec::new()));
+        let thread = {
+            let (running, recorded) = (Arc::clone(&running), Arc::clone(&recorded));
+            std::thread::spawn(move || {
+                let started = Instant::now();
+                let mut segmenter = Segmenter::default();
+                while running.load(Ordering::Relaxed) {
+                    if let Some(path) = segmenter.push(started.elapsed().as_millis() as u32, read()) {
+                        recorded.lock().push(path);
+                    }
+                    std::thread::sleep(Duration::from_millis(SAMPLE_MS));
+                }
+            })
+        };
+        Self { running, recorded, thread: Some(thread) }
+    }
+
+    /// Movements finished since the last call.
+    pub fn take(&self) -> Vec<RecordedPath> { std::mem::take(&mut *self.recorded.lock()) }
+
+    /// Stop sampling; returns the movements not yet taken.
+    pub fn stop(mut self) -> Vec<RecordedPath> {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(t) = self.thread.take() { let _ = t.join(); }
+        self.take()
+    }
+}
@@ -0,0 +1,32 @@
+//! Portable mode: with `--portable`, or a `portable.flag` file next to the executable, profiles,
+//! settings, logs and captures are kept in a `config/` folder beside the executable instead of
+//! the platform config directory, so the tool can run from a USB stick on any machine.
+
+use parking_lot::Mutex;
+use std::path::{Path, PathBuf};
+
+/// A file with this name beside the executable turns portable mode on.
+pub const FLAG_FILE: &str = "portable.flag";
+
+/// The portable config folder; `None` = not portable.
+static DIR: Mutex<Option<PathBuf>> = parking_lot::const_mutex(None);
+
+/// Turn portable mode on if `forced` or the flag file is there; returns the folder used.
+pub fn init(forced: bool) -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    let dir = detect(exe.parent()?, forced)?;
+    *DIR.lock() = Some(dir.clone());
+    Some(dir)
+}
+
+/// The config folder for an executable in `exe_dir`, if it runs portable.
+pub fn detect(exe_dir: &Path, forced: bool) -> Option<PathBuf> {
+    (forced || exe_dir.join(FLAG_FILE).is_file()).then(|| exe_dir.join("config"))
+}
+
+pub fn active() -> bool { DIR.lock().is_some() }
+
+/// Where everything is stored: the portable folder, else `<config>/area_clicker`.
+pub fn config_dir() -> Option<PathBuf> {
+    DIR.lock().clone().or_else(|| dirs::config_dir().map(|d| d.join("area_clicker")))
+}
@@ -0,0 +1,74 @@
+//! Keeps the computer and its display awake while a job runs, so the machine doesn't go to
+//! sleep (and take the job with it) halfway through a long run.
+
+/// Holds off sleep and display blanking until dropped.
+pub struct KeepAwake {
+    /// `systemd-inhibit` / `caffeinate`, holding the inhibitor for as long as it runs.
+    #[cfg(not(windows))]
+    helper: std::process::Child,
+}
+
+impl KeepAwake {
+    /// On Windows the request belongs to the calling thread, so keep the guard on the thread
+    /// that runs the job.
+    pub fn new() -> Result<Self, String> {
+        #[cfg(windows)]
+        {
+            use windows_sys::Win32::System::Power::{
+                SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+            };
+            if unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED) } == 0 {
+                return Err("Windows refused to keep the system awake".to_string());
+            }
+            Ok(Self {})
+        }
+        #[cfg(not(windows))]
+        {
+            use std::process::{Command, Stdio};
+            let (program, args) = helper_command(std::process::id());
+            let mut helper = Command::new(program)
+                .args(&args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+                .map_err(|e| format!("Keeping the system awake needs {} ({})", program, e))?;
+            // a helper that can't take the inhibitor gives up right away
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            if let Ok(Some(status)) = helper.try_wait() {
+                return Err(format!("{} exited ({})", program, status));
+            }
+            Ok(Self { helper })
+        }
+    }
+}
+
+impl Drop for KeepAwake {
+    fn drop(&mut self) {
+        #[cfg(windows)]
+        unsafe {
+            windows_sys::Win32::System::Power::SetThreadExecutionState(windows_sys::Win32::System::Power::ES_CONTINUOUS);
+        }
+        #[cfg(not(windows))]
+        {
+            // closing stdin ends `cat`, and with it the inhibitor
+            drop(self.helper.stdin.take());
+            #[cfg(not(target_os = "linux"))]
+            let _ = self.helper.kill();
+            let _ = self.helper.wait();
+        }
+    }
+}
+
+/// The helper that holds the inhibitor for process `pid`. On Linux it runs `cat` on our stdin
+/// pipe, which ends when we close it or exit, so a crash can't leave the inhibitor behind;
+/// `caffeinate -w` does the same by watching our pid.
+#[cfg(not(windows))]
+pub fn helper_command(pid: u32) -> (&'static str, Vec<String>) {
+    if cfg!(target_os = "linux") {
+        let args = ["--what=idle:sleep", "--who=Area Clicker", "--why=A click job is running", "--mode=block", "cat"];
+        ("systemd-inhibit", args.map(str::to_string).to_vec())
+    } else {
+        ("caffeinate", vec!["-dims".to_string(), "-w".to_string(), pid.to_string()])
+    }
+}
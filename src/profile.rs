@@ -0,0 +1,832 @@
+//! Named profiles: snapshots of the editable settings, stored as JSON files under the
+//! user's config directory (`<config>/area_clicker/profiles/<name>.json`).
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::calibrate::Correction;
+use crate::crypt::{self, Sealed};
+use crate::events::EventCommands;
+use crate::feedback::ClickFeedback;
+use crate::gamepad::PadButton;
+use crate::hotkeys::HotKey;
+use crate::human_mouse::Bounds;
+use crate::limits::SafetyLimits;
+use crate::path_bank::PathBank;
+use crate::pacing::{Fatigue, WarmUp};
+use crate::quiet::QuietWindow;
+use crate::sampler::Zone;
+use crate::theme::Theme;
+use crate::triggers::Triggers;
+use crate::viewer::ViewerSettings;
+use crate::watch::{Refocus, WaitFor};
+use crate::{AfkKey, JobMode, OnError, SequenceStep, StepDefaults, StepKind, StepOrder, StepTemplate, Verify, WaitImage};
+
+/// Everything a profile remembers. Missing fields (older files) fall back to the defaults.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub bounds: [i32; 4], // min_x, max_x, min_y, max_y
+    pub use_point: bool,
+    pub point: [i32; 3],  // x, y, jitter
+    pub follow_cursor: bool,
+    pub follow_size: [i32; 2],
+    pub use_fixed_pick_size: bool,
+    pub fixed_pick_size: [i32; 2],
+    pub click_button_left: bool,
+    pub min_secs: f32,
+    pub max_secs: f32,
+    pub use_finite_clicks: bool,
+    pub num_clicks: u32,
+
+    pub mode: JobMode,
+    pub steps: Vec<SequenceStep>,
+    pub use_finite_cycles: bool,
+    pub num_cycles: u32,
+    pub step_order: StepOrder,
+    /// What the sequence is for and how it's meant to be used.
+    pub notes: String,
+
+    pub grid_cols: u32,
+    pub grid_rows: u32,
+    pub grid_serpentine: bool,
+
+    pub use_fatigue: bool,
+    pub fatigue: Fatigue,
+    pub use_warm_up: bool,
+    pub warm_up: WarmUp,
+    pub use_quiet_hours: bool,
+    pub quiet_hours: Vec<QuietWindow>,
+
+    pub use_hold_to_run: bool,
+    pub hold_key: HotKey,
+    pub keep_awake: bool,
+    pub reclick_misses: bool,
+    pub correct_drift: bool,
+    pub settle_before_click: bool,
+    pub vary_approach: bool,
+    pub min_hold_ms: u64,
+    pub max_hold_ms: u64,
+    pub replay_paths: bool,
+    pub use_wait_for: bool,
+    pub wait_for: WaitFor,
+    pub use_refocus: bool,
+    pub refocus: Refocus,
+    pub use_screenshot_every: bool,
+    pub screenshot_every: u32,
+    pub screenshot_dir: String, // empty = default folder
+    pub use_events: bool,
+    pub events: EventCommands,
+    pub start_key: HotKey,
+    pub capture_key: HotKey,
+    pub skip_key: Option<HotKey>,
+    pub repeat_key: Option<HotKey>,
+    pub capture_radius: i32,
+    pub capture_as_step: bool,
+    pub pad_start: Option<PadButton>,
+    pub pad_pause: Option<PadButton>,
+    pub pad_stop: Option<PadButton>,
+    pub pad_nudge: bool,
+    pub pick_template: bool,
+
+    pub use_anti_afk: bool,
+    pub afk_key: AfkKey,
+    pub afk_min_secs: f32,
+    pub afk_max_secs: f32,
+
+    /// Read-only: the editing controls stay greyed out until the profile is unlocked.
+    pub locked: bool,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            bounds: [100, 400, 100, 400],
+            use_point: false,
+            point: [250, 250, 0],
+            follow_cursor: false,
+            follow_size: [100, 100],
+            use_fixed_pick_size: false,
+            fixed_pick_size: [100, 100],
+            click_button_left: true,
+            min_secs: 2.0,
+            max_secs: 4.5,
+            use_finite_clicks: false,
+            num_clicks: 100,
+
+            mode: JobMode::Single,
+            steps: Vec::new(),
+            use_finite_cycles: true,
+            num_cycles: 1,
+            step_order: StepOrder::InOrder,
+            notes: String::new(),
+
+            grid_cols: 4,
+            grid_rows: 4,
+            grid_serpentine: true,
+
+            use_fatigue: false,
+            fatigue: Fatigue::default(),
+            use_warm_up: false,
+            warm_up: WarmUp::default(),
+            use_quiet_hours: false,
+            quiet_hours: Vec::new(),
+
+            use_hold_to_run: false,
+            hold_key: HotKey::F9,
+            keep_awake: true,
+            reclick_misses: false,
+            correct_drift: false,
+            settle_before_click: false,
+            vary_approach: false,
+            min_hold_ms: 20,
+            max_hold_ms: 120,
+            replay_paths: false,
+            use_wait_for: false,
+            wait_for: WaitFor::default(),
+            use_refocus: false,
+            refocus: Refocus::default(),
+            use_screenshot_every: false,
+            screenshot_every: 100,
+            screenshot_dir: String::new(),
+            use_events: false,
+            events: EventCommands::default(),
+            start_key: HotKey::F6,
+            capture_key: HotKey::F8,
+            skip_key: None,
+            repeat_key: None,
+            capture_radius: 5,
+            pad_start: None,
+            pad_pause: None,
+            pad_stop: None,
+            pad_nudge: false,
+            capture_as_step: false,
+            pick_template: false,
+
+            use_anti_afk: false,
+            afk_key: AfkKey::Shift,
+            afk_min_secs: 30.0,
+            afk_max_secs: 90.0,
+
+            locked: false,
+        }
+    }
+}
+
+impl Profile {
+    /// Turn the event commands off until the user turns them back on. Profiles from a file or a
+    /// settings bundle may come from anyone, and their commands would run with the next start.
+    /// True if there were commands to turn off.
+    pub fn disarm_events(&mut self) -> bool {
+        let armed = self.use_events && !self.events.is_empty();
+        self.use_events = false;
+        armed
+    }
+}
+
+/// The sequence step editor's inputs, which aren't part of a profile until saved as a step.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StepDraft {
+    pub editing: Option<usize>, // index of the step being edited
+    pub name: String,
+    pub clicks: u32,
+    pub min_secs: f32,
+    pub max_secs: f32,
+    pub button_left: bool,
+    pub zones: Vec<Zone>,
+    pub use_offset: bool,
+    pub offset: [i32; 3],
+    pub kind: StepKind,
+    pub shot_full_screen: bool,
+    pub image: String,
+    pub wait: WaitImage,
+    pub use_verify: bool,
+    pub verify: Verify,
+    pub on_error: OnError,
+    pub text: String,
+    pub area: Option<String>, // library area the step will use instead of the selection
+    pub color: Option<[u8; 3]>,
+    pub weight: f32,
+    pub hotkey: Option<HotKey>,
+    pub note: String,
+}
+
+impl Default for StepDraft {
+    fn default() -> Self {
+        Self {
+            editing: None,
+            name: String::new(),
+            clicks: 5,
+            min_secs: 0.5,
+            max_secs: 1.5,
+            button_left: true,
+            zones: Vec::new(),
+            use_offset: false,
+            offset: [0, 30, 0],
+            kind: StepKind::Click,
+            shot_full_screen: false,
+            image: String::new(),
+            wait: WaitImage::default(),
+            use_verify: false,
+            verify: Verify::default(),
+            on_error: OnError::default(),
+            text: String::new(),
+            area: None,
+            color: None,
+            weight: 1.0,
+            hotkey: None,
+            note: String::new(),
+        }
+    }
+}
+
+/// Autosaved working state, offered back after a crash or forced quit.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Recovery {
+    pub profile: Option<String>, // profile that was active, if any
+    pub settings: Profile,
+    pub draft: StepDraft,
+}
+
+/// Progress of a sequence run, saved at every cycle boundary so a run cut short by a crash or
+/// reboot can be continued.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Checkpoint {
+    pub profile: Option<String>, // profile the run was started from, if any
+    pub settings: Profile,
+    /// Cycles finished.
+    pub cycle: u32,
+    pub clicks: u32,
+    /// Clicks made by each step, in step order.
+    pub step_clicks: Vec<u32>,
+}
+
+/// Where a sequence was stopped: 1-based step and cycle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SequencePosition {
+    pub step: usize,
+    pub cycle: u32,
+}
+
+impl Default for SequencePosition {
+    fn default() -> Self { Self { step: 1, cycle: 1 } }
+}
+
+/// An area that must never be clicked, whatever the job says.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ForbiddenZone {
+    pub name: String,
+    pub bounds: Bounds,
+    #[serde(default = "enabled")]
+    pub enabled: bool,
+}
+
+fn enabled() -> bool { true }
+
+/// Read a profile from any JSON file.
+pub fn load_file(path: &Path) -> io::Result<Profile> { load_file_with(path, None) }
+
+/// Read a profile file, decrypting it with `passphrase` if it was saved encrypted.
+pub fn load_file_with(path: &Path, passphrase: Option<&str>) -> io::Result<Profile> {
+    let text = std::fs::read_to_string(path)?;
+    let text = match serde_json::from_str::<Sealed>(&text) {
+        Ok(sealed) => {
+            let denied = |e: String| io::Error::new(io::ErrorKind::PermissionDenied, e);
+            let passphrase = passphrase.ok_or_else(|| denied("profile is encrypted; a passphrase is needed".to_string()))?;
+            let plain = crypt::open(&sealed, passphrase).map_err(denied)?;
+            String::from_utf8(plain).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        }
+        Err(_) => text,
+    };
+    serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Write a profile as pretty JSON, creating parent directories as needed.
+pub fn save_file(path: &Path, profile: &Profile) -> io::Result<()> { save_file_with(path, profile, None) }
+
+/// Like [`save_file`], encrypted with `passphrase` if one is given.
+pub fn save_file_with(path: &Path, profile: &Profile, passphrase: Option<&str>) -> io::Result<()> {
+    if let Some(dir) = path.parent() { std::fs::create_dir_all(dir)?; }
+    let mut text = serde_json::to_string_pretty(profile).map_err(io::Error::other)?;
+    if let Some(passphrase) = passphrase {
+        let sealed = crypt::seal(text.as_bytes(), passphrase).map_err(io::Error::other)?;
+        text = serde_json::to_string_pretty(&sealed).map_err(io::Error::other)?;
+    }
+    std::fs::write(path, text)
+}
+
+/// The encrypted contents of a profile file, if it is encrypted.
+fn read_sealed(path: &Path) -> Option<Sealed> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+/// Everything set up in the app, as one file for moving to another machine. Display calibration
+/// is left out: it only fits the monitors it was measured on.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bundle {
+    /// Profiles by name, hotkeys and human-like settings included.
+    pub profiles: BTreeMap<String, Profile>,
+    /// Encrypted profiles, copied as they are; they still need their passphrase after import.
+    pub encrypted: BTreeMap<String, Sealed>,
+    pub last_profile: Option<String>,
+    pub areas: BTreeMap<String, Bounds>,
+    pub step_templates: BTreeMap<String, StepTemplate>,
+    pub forbidden: Vec<ForbiddenZone>,
+    pub limits: SafetyLimits,
+    pub feedback: ClickFeedback,
+    pub triggers: Triggers,
+    pub viewer: ViewerSettings,
+    pub step_defaults: StepDefaults,
+    pub theme: Theme,
+    pub language: Option<String>,
+    pub auto_reload: bool,
+    #[serde(default = "enabled")]
+    pub confirm_start: bool,
+    /// User-made translations: language code → catalog JSON as found in the locales directory.
+    pub translations: BTreeMap<String, String>,
+}
+
+/// Read a settings bundle written by [`save_bundle`].
+pub fn load_bundle(path: &Path) -> io::Result<Bundle> {
+    let text = std::fs::read_to_string(path)?;
+    serde_json::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+pub fn save_bundle(path: &Path, bundle: &Bundle) -> io::Result<()> {
+    if let Some(dir) = path.parent() { std::fs::create_dir_all(dir)?; }
+    let text = serde_json::to_string_pretty(bundle).map_err(io::Error::other)?;
+    std::fs::write(path, text)
+}
+
+/// The directory of named profiles plus the "last used" marker.
+#[derive(Clone, Debug)]
+pub struct ProfileStore {
+    dir: PathBuf,
+}
+
+impl ProfileStore {
+    pub fn new(dir: PathBuf) -> Self { Self { dir } }
+
+    /// The config folder (see `portable::config_dir`), or `None` if the platform has no config
+    /// directory.
+    pub fn open_default() -> Option<Self> {
+        crate::portable::config_dir().map(Self::new)
+    }
+
+    fn profiles_dir(&self) -> PathBuf { self.dir.join("profiles") }
+
+    pub fn path(&self, name: &str) -> io::Result<PathBuf> {
+        if !valid_name(name) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid profile name '{}'", name)));
+        }
+        Ok(self.profiles_dir().join(format!("{}.json", name)))
+    }
+
+    /// Names of all stored profiles, sorted.
+    pub fn list(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(self.profiles_dir()) else { return Vec::new() };
+        let mut names: Vec<String> = entries
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|x| x == "json"))
+            .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+            .collect();
+        names.sort_by_key(|n| n.to_lowercase());
+        names
+    }
+
+    pub fn load(&self, name: &str) -> io::Result<Profile> { load_file(&self.path(name)?) }
+
+    pub fn load_with(&self, name: &str, passphrase: Option<&str>) -> io::Result<Profile> {
+        load_file_with(&self.path(name)?, passphrase)
+    }
+
+    pub fn save(&self, name: &str, profile: &Profile) -> io::Result<()> { save_file(&self.path(name)?, profile) }
+
+    pub fn save_with(&self, name: &str, profile: &Profile, passphrase: Option<&str>) -> io::Result<()> {
+        save_file_with(&self.path(name)?, profile, passphrase)
+    }
+
+    /// Whether the profile was saved with a passphrase.
+    pub fn is_encrypted(&self, name: &str) -> bool { self.path(name).ok().and_then(|p| read_sealed(&p)).is_some() }
+
+    pub fn rename(&self, from: &str, to: &str) -> io::Result<()> {
+        let (from_path, to_path) = (self.path(from)?, self.path(to)?);
+        if to_path.exists() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("profile '{}' already exists", to)));
+        }
+        let was_last = self.last_used().as_deref() == Some(from);
+        std::fs::rename(from_path, to_path)?;
+        if was_last { self.set_last_used(to)?; }
+        Ok(())
+    }
+
+    pub fn duplicate(&self, from: &str, to: &str) -> io::Result<()> {
+        if self.path(to)?.exists() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("profile '{}' already exists", to)));
+        }
+        // copied as is, so an encrypted profile stays encrypted
+        std::fs::copy(self.path(from)?, self.path(to)?).map(|_| ())
+    }
+
+    pub fn delete(&self, name: &str) -> io::Result<()> { std::fs::remove_file(self.path(name)?) }
+
+    /// Profile loaded most recently, if it still exists.
+    pub fn last_used(&self) -> Option<String> {
+        let name = std::fs::read_to_string(self.dir.join("last_profile")).ok()?;
+        let name = name.trim().to_string();
+        self.path(&name).ok()?.exists().then_some(name)
+    }
+
+    pub fn set_last_used(&self, name: &str) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.dir.join("last_profile"), name)
+    }
+
+    /// UI language code picked in the language menu, if any.
+    pub fn language(&self) -> Option<String> {
+        let code = std::fs::read_to_string(self.dir.join("language")).ok()?;
+        Some(code.trim().to_string()).filter(|c| !c.is_empty())
+    }
+
+    pub fn set_language(&self, code: &str) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.dir.join("language"), code)
+    }
+
+    /// Whether the first-run setup wizard was finished or skipped.
+    pub fn setup_done(&self) -> bool { self.dir.join("setup_done").exists() }
+
+    pub fn set_setup_done(&self) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.dir.join("setup_done"), "")
+    }
+
+    /// Whether profiles are reloaded as soon as their file is edited outside the app.
+    pub fn auto_reload(&self) -> bool { self.dir.join("auto_reload").exists() }
+
+    pub fn set_auto_reload(&self, on: bool) -> io::Result<()> {
+        let path = self.dir.join("auto_reload");
+        if !on { return std::fs::remove_file(path).or_else(|e| if e.kind() == io::ErrorKind::NotFound { Ok(()) } else { Err(e) }); }
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(path, "")
+    }
+
+    /// Whether starting by hand shows the job summary first (on unless turned off).
+    pub fn confirm_start(&self) -> bool { !self.dir.join("skip_start_summary").exists() }
+
+    pub fn set_confirm_start(&self, on: bool) -> io::Result<()> {
+        let path = self.dir.join("skip_start_summary");
+        if on { return std::fs::remove_file(path).or_else(|e| if e.kind() == io::ErrorKind::NotFound { Ok(()) } else { Err(e) }); }
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(path, "")
+    }
+
+    /// The run history database.
+    pub fn history_path(&self) -> PathBuf { self.dir.join("history.sqlite") }
+
+    /// Where user-made translations (`<code>.json`) are picked up from.
+    pub fn locales_dir(&self) -> PathBuf { self.dir.join("locales") }
+
+    /// Theme and accent color; the default (follow the system) if none was saved.
+    pub fn load_theme(&self) -> Theme {
+        let Ok(text) = std::fs::read_to_string(self.dir.join("theme.json")) else { return Theme::default() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable theme file: {}", e);
+            Theme::default()
+        })
+    }
+
+    pub fn save_theme(&self, theme: &Theme) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(theme).map_err(io::Error::other)?;
+        std::fs::write(self.dir.join("theme.json"), text)
+    }
+
+    /// Per-monitor coordinate corrections, keyed by monitor id. Empty if none were saved.
+    pub fn load_calibration(&self) -> HashMap<u32, Correction> {
+        let Ok(text) = std::fs::read_to_string(self.dir.join("calibration.json")) else { return HashMap::new() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable calibration file: {}", e);
+            HashMap::new()
+        })
+    }
+
+    pub fn save_calibration(&self, corrections: &HashMap<u32, Correction>) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(corrections).map_err(io::Error::other)?;
+        std::fs::write(self.dir.join("calibration.json"), text)
+    }
+
+    /// The named area library, shared by all profiles. Empty if none was saved.
+    pub fn load_areas(&self) -> BTreeMap<String, Bounds> {
+        let Ok(text) = std::fs::read_to_string(self.dir.join("areas.json")) else { return BTreeMap::new() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable area library: {}", e);
+            BTreeMap::new()
+        })
+    }
+
+    pub fn save_areas(&self, areas: &BTreeMap<String, Bounds>) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(areas).map_err(io::Error::other)?;
+        std::fs::write(self.dir.join("areas.json"), text)
+    }
+
+    /// App-wide forbidden zones. Empty if none were saved.
+    pub fn load_step_templates(&self) -> BTreeMap<String, StepTemplate> {
+        let Ok(text) = std::fs::read_to_string(self.dir.join("step_templates.json")) else { return BTreeMap::new() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable step templates file: {}", e);
+            BTreeMap::new()
+        })
+    }
+
+    pub fn save_step_templates(&self, templates: &BTreeMap<String, StepTemplate>) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(templates).map_err(io::Error::other)?;
+        std::fs::write(self.dir.join("step_templates.json"), text)
+    }
+
+    pub fn load_forbidden(&self) -> Vec<ForbiddenZone> {
+        let Ok(text) = std::fs::read_to_string(self.dir.join("forbidden_zones.json")) else { return Vec::new() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable forbidden zones file: {}", e);
+            Vec::new()
+        })
+    }
+
+    pub fn save_forbidden(&self, zones: &[ForbiddenZone]) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(zones).map_err(io::Error::other)?;
+        std::fs::write(self.dir.join("forbidden_zones.json"), text)
+    }
+
+    /// App-wide safety limits; the defaults if none were saved.
+    pub fn load_limits(&self) -> SafetyLimits {
+        let Ok(text) = std::fs::read_to_string(self.dir.join("limits.json")) else { return SafetyLimits::default() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable safety limits file: {}", e);
+            SafetyLimits::default()
+        })
+    }
+
+    pub fn save_limits(&self, limits: &SafetyLimits) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(limits).map_err(io::Error::other)?;
+        std::fs::write(self.dir.join("limits.json"), text)
+    }
+
+    /// App-wide click feedback; off if none was saved.
+    pub fn load_feedback(&self) -> ClickFeedback {
+        let Ok(text) = std::fs::read_to_string(self.dir.join("feedback.json")) else { return ClickFeedback::default() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable click feedback file: {}", e);
+            ClickFeedback::default()
+        })
+    }
+
+    pub fn save_feedback(&self, feedback: &ClickFeedback) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(feedback).map_err(io::Error::other)?;
+        std::fs::write(self.dir.join("feedback.json"), text)
+    }
+
+    /// App-wide external triggers; none if none were saved.
+    pub fn load_triggers(&self) -> Triggers {
+        let Ok(text) = std::fs::read_to_string(self.dir.join("triggers.json")) else { return Triggers::default() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable triggers file: {}", e);
+            Triggers::default()
+        })
+    }
+
+    pub fn save_triggers(&self, triggers: &Triggers) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(triggers).map_err(io::Error::other)?;
+        std::fs::write(self.dir.join("triggers.json"), text)
+    }
+
+    /// App-wide remote viewer settings; off if none were saved.
+    pub fn load_viewer(&self) -> ViewerSettings {
+        let Ok(text) = std::fs::read_to_string(self.dir.join("viewer.json")) else { return ViewerSettings::default() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable remote viewer file: {}", e);
+            ViewerSettings::default()
+        })
+    }
+
+    pub fn save_viewer(&self, viewer: &ViewerSettings) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(viewer).map_err(io::Error::other)?;
+        std::fs::write(self.dir.join("viewer.json"), text)
+    }
+
+    pub fn load_step_defaults(&self) -> StepDefaults {
+        let Ok(text) = std::fs::read_to_string(self.dir.join("step_defaults.json")) else { return StepDefaults::default() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable step defaults file: {}", e);
+            StepDefaults::default()
+        })
+    }
+
+    pub fn save_step_defaults(&self, defaults: &StepDefaults) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(defaults).map_err(io::Error::other)?;
+        std::fs::write(self.dir.join("step_defaults.json"), text)
+    }
+
+    /// Mouse paths recorded in trainer mode. Empty if none were saved.
+    pub fn load_path_bank(&self) -> PathBank {
+        let Ok(text) = std::fs::read_to_string(self.dir.join("paths.json")) else { return PathBank::default() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable path bank: {}", e);
+            PathBank::default()
+        })
+    }
+
+    pub fn save_path_bank(&self, bank: &PathBank) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string(bank).map_err(io::Error::other)?;
+        std::fs::write(self.dir.join("paths.json"), text)
+    }
+
+    /// Where each profile's sequence was last stopped, by profile name. Empty if none were saved.
+    pub fn load_positions(&self) -> BTreeMap<String, SequencePosition> {
+        let Ok(text) = std::fs::read_to_string(self.dir.join("positions.json")) else { return BTreeMap::new() };
+        serde_json::from_str(&text).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable sequence positions file: {}", e);
+            BTreeMap::new()
+        })
+    }
+
+    pub fn save_positions(&self, positions: &BTreeMap<String, SequencePosition>) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(positions).map_err(io::Error::other)?;
+        std::fs::write(self.dir.join("positions.json"), text)
+    }
+
+    /// Collect every profile and app-wide setting into one [`Bundle`].
+    pub fn export_bundle(&self) -> io::Result<Bundle> {
+        let (mut profiles, mut encrypted) = (BTreeMap::new(), BTreeMap::new());
+        for name in self.list() {
+            if let Some(sealed) = read_sealed(&self.path(&name)?) {
+                encrypted.insert(name, sealed);
+                continue;
+            }
+            let profile = self.load(&name).map_err(|e| io::Error::new(e.kind(), format!("profile '{}': {}", name, e)))?;
+            profiles.insert(name, profile);
+        }
+        let mut translations = BTreeMap::new();
+        if let Ok(entries) = std::fs::read_dir(self.locales_dir()) {
+            for path in entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.extension().is_some_and(|x| x == "json")) {
+                let Some(code) = path.file_stem().and_then(|s| s.to_str()).map(str::to_string) else { continue };
+                translations.insert(code, std::fs::read_to_string(&path)?);
+            }
+        }
+        Ok(Bundle {
+            profiles,
+            encrypted,
+            last_profile: self.last_used(),
+            areas: self.load_areas(),
+            step_templates: self.load_step_templates(),
+            forbidden: self.load_forbidden(),
+            limits: self.load_limits(),
+            feedback: self.load_feedback(),
+            triggers: self.load_triggers(),
+            viewer: self.load_viewer(),
+            step_defaults: self.load_step_defaults(),
+            theme: self.load_theme(),
+            language: self.language(),
+            auto_reload: self.auto_reload(),
+            confirm_start: self.confirm_start(),
+            translations,
+        })
+    }
+
+    /// Write a [`Bundle`] into this store. Profiles, areas, step templates and translations with
+    /// the same name are replaced, others are kept; forbidden zones are added unless an identical one exists;
+    /// limits, click feedback, triggers, remote viewer, step defaults, theme, language, auto-reload and the start
+    /// summary setting are taken from the bundle.
+    /// Imported profiles come with their event commands turned off (see [`Profile::disarm_events`]).
+    pub fn import_bundle(&self, bundle: &Bundle) -> io::Result<()> {
+        // check everything before writing anything, so a bad entry can't leave it half applied
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidInput, e);
+        for name in bundle.profiles.keys().chain(bundle.encrypted.keys()) { self.path(name)?; }
+        for (code, text) in &bundle.translations {
+            if !valid_name(code) { return Err(invalid(format!("invalid language code '{}'", code))); }
+            crate::i18n::parse(text).map_err(|e| invalid(format!("translation '{}': {}", code, e)))?;
+        }
+        let encrypted = bundle.encrypted.iter()
+            .map(|(name, sealed)| Ok((name, serde_json::to_string_pretty(sealed).map_err(io::Error::other)?)))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        for (name, profile) in &bundle.profiles {
+            let mut profile = profile.clone();
+            profile.disarm_events();
+            self.save(name, &profile)?;
+        }
+        for (name, text) in encrypted {
+            std::fs::create_dir_all(self.profiles_dir())?;
+            std::fs::write(self.path(name)?, text)?;
+        }
+        let known = |n: &str| bundle.profiles.contains_key(n) || bundle.encrypted.contains_key(n);
+        if let Some(name) = bundle.last_profile.as_deref().filter(|n| known(n)) {
+            self.set_last_used(name)?;
+        }
+        let mut areas = self.load_areas();
+        areas.extend(bundle.areas.clone());
+        self.save_areas(&areas)?;
+        let mut templates = self.load_step_templates();
+        templates.extend(bundle.step_templates.clone());
+        self.save_step_templates(&templates)?;
+        let mut zones = self.load_forbidden();
+        for zone in &bundle.forbidden {
+            if !zones.contains(zone) { zones.push(zone.clone()); }
+        }
+        self.save_forbidden(&zones)?;
+        self.save_limits(&bundle.limits)?;
+        self.save_feedback(&bundle.feedback)?;
+        self.save_triggers(&bundle.triggers)?;
+        self.save_viewer(&bundle.viewer)?;
+        self.save_step_defaults(&bundle.step_defaults)?;
+        self.save_theme(&bundle.theme)?;
+        if let Some(code) = &bundle.language { self.set_language(code)?; }
+        self.set_auto_reload(bundle.auto_reload)?;
+        self.set_confirm_start(bundle.confirm_start)?;
+        for (code, text) in &bundle.translations {
+            std::fs::create_dir_all(self.locales_dir())?;
+            std::fs::write(self.locales_dir().join(format!("{}.json", code)), text)?;
+        }
+        Ok(())
+    }
+
+    fn recovery_path(&self) -> PathBuf { self.dir.join("recovery.json") }
+
+    /// Write the recovery file. Goes through a temp file so a crash mid-write can't corrupt it.
+    pub fn save_recovery(&self, text: &str) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let tmp = self.dir.join("recovery.json.tmp");
+        std::fs::write(&tmp, text)?;
+        std::fs::rename(tmp, self.recovery_path())
+    }
+
+    /// State left behind by a session that didn't exit cleanly, if any.
+    pub fn load_recovery(&self) -> Option<Recovery> {
+        let text = std::fs::read_to_string(self.recovery_path()).ok()?;
+        match serde_json::from_str(&text) {
+            Ok(r) => Some(r),
+            Err(e) => { log::warn!("Ignoring unreadable recovery file: {}", e); None }
+        }
+    }
+
+    fn checkpoint_path(&self) -> PathBuf { self.dir.join("checkpoint.json") }
+
+    /// Write the run checkpoint, through a temp file like the recovery file.
+    pub fn save_checkpoint(&self, checkpoint: &Checkpoint) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let text = serde_json::to_string_pretty(checkpoint).map_err(io::Error::other)?;
+        let tmp = self.dir.join("checkpoint.json.tmp");
+        std::fs::write(&tmp, text)?;
+        std::fs::rename(tmp, self.checkpoint_path())
+    }
+
+    /// The checkpoint of a run that never got to finish or be stopped, if any.
+    pub fn load_checkpoint(&self) -> Option<Checkpoint> {
+        let text = std::fs::read_to_string(self.checkpoint_path()).ok()?;
+        match serde_json::from_str(&text) {
+            Ok(c) => Some(c),
+            Err(e) => { log::warn!("Ignoring unreadable checkpoint file: {}", e); None }
+        }
+    }
+
+    /// Remove the checkpoint; called when a run ends.
+    pub fn clear_checkpoint(&self) -> io::Result<()> {
+        match std::fs::remove_file(self.checkpoint_path()) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Remove the recovery file; called on a clean exit.
+    pub fn clear_recovery(&self) -> io::Result<()> {
+        match std::fs::remove_file(self.recovery_path()) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Profile names become file names, so keep them to something every OS accepts.
+pub fn valid_name(name: &str) -> bool {
+    !name.trim().is_empty()
+        && name.trim() == name
+        && !name.starts_with('.')
+        && !name.chars().any(|c| matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') || c.is_control())
+}
@@ -0,0 +1,50 @@
+//! Quiet hours: daily time windows (local time) during which a running job holds its clicks
+//! and key taps, e.g. while the target service is down for maintenance. The job thread checks
+//! them before every click and carries on by itself once the window is over.
+
+use chrono::Timelike;
+use serde::{Deserialize, Serialize};
+
+/// `start..end` in minutes after midnight. A window whose end is earlier than its start runs
+/// past midnight (22:00–06:00).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuietWindow {
+    pub start: u16,
+    pub end: u16,
+}
+
+impl Default for QuietWindow {
+    fn default() -> Self { Self { start: 9 * 60, end: 10 * 60 } }
+}
+
+impl QuietWindow {
+    pub fn contains(&self, minute: u16) -> bool {
+        if self.start <= self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+
+    pub fn label(&self) -> String { format!("{}–{}", format_time(self.start), format_time(self.end)) }
+}
+
+/// The window `minute` falls in, if any.
+pub fn active(windows: &[QuietWindow], minute: u16) -> Option<QuietWindow> {
+    windows.iter().copied().find(|w| w.contains(minute))
+}
+
+/// Local wall-clock time in minutes after midnight.
+pub fn now_minute() -> u16 {
+    let now = chrono::Local::now();
+    (now.hour() * 60 + now.minute()) as u16
+}
+
+pub fn format_time(minute: u16) -> String { format!("{:02}:{:02}", minute / 60 % 24, minute % 60) }
+
+/// `HH:MM` (or just `HH`) → minutes after midnight.
+pub fn parse_time(text: &str) -> Option<u16> {
+    let (h, m) = text.trim().split_once(':').unwrap_or((text.trim(), "0"));
+    let (h, m): (u16, u16) = (h.trim().parse().ok()?, m.trim().parse().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
+}
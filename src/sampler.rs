@@ -0,0 +1,98 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::human_mouse::Bounds;
+
+/// A single target pixel, spread by up to `jitter` px in any direction.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ClickPoint {
+    pub x: i32,
+    pub y: i32,
+    pub jitter: i32,
+}
+
+impl ClickPoint {
+    /// Random point within the jitter circle (uniform over its area).
+    pub fn sample(&self, rng: &mut impl Rng) -> (i32, i32) {
+        if self.jitter <= 0 { return (self.x, self.y); }
+        let r = self.jitter as f32 * rng.gen::<f32>().sqrt();
+        let theta = rng.gen_range(0.0..std::f32::consts::TAU);
+        (self.x + (r * theta.cos()).round() as i32, self.y + (r * theta.sin()).round() as i32)
+    }
+}
+
+/// A weighted part of an area, as fractions (0.0–1.0) of the area's width and height.
+/// Zones may overlap; a point in an overlap can be reached through either zone.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Zone {
+    pub x0: f32, pub y0: f32,
+    pub x1: f32, pub y1: f32,
+    pub weight: f32,
+}
+
+impl Zone {
+    /// The whole area.
+    pub fn full(weight: f32) -> Self { Self { x0: 0.0, y0: 0.0, x1: 1.0, y1: 1.0, weight } }
+
+    /// Map the fractional rect onto `b`, in pixels.
+    pub fn resolve(&self, b: Bounds) -> Bounds {
+        let (x0, x1) = order(self.x0, self.x1);
+        let (y0, y1) = order(self.y0, self.y1);
+        let lerp = |min: i32, max: i32, t: f32| min + ((max - min) as f32 * t.clamp(0.0, 1.0)).round() as i32;
+        Bounds {
+            min_x: lerp(b.min_x, b.max_x, x0), max_x: lerp(b.min_x, b.max_x, x1),
+            min_y: lerp(b.min_y, b.max_y, y0), max_y: lerp(b.min_y, b.max_y, y1),
+        }
+    }
+}
+
+fn order(a: f32, b: f32) -> (f32, f32) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+/// Preset: 70% of clicks in the middle third (horizontal band), 30% anywhere in the area.
+pub fn center_band() -> Vec<Zone> {
+    vec![
+        Zone { x0: 0.0, y0: 1.0 / 3.0, x1: 1.0, y1: 2.0 / 3.0, weight: 70.0 },
+        Zone::full(30.0),
+    ]
+}
+
+/// Index drawn at random in proportion to `weights` (negative ones count as zero).
+/// `None` if no weight is positive.
+pub fn pick_weighted(weights: &[f32], rng: &mut impl Rng) -> Option<usize> {
+    let total: f32 = weights.iter().map(|w| w.max(0.0)).sum();
+    if total <= 0.0 { return None; }
+    let mut roll = rng.gen_range(0.0..total);
+    for (i, w) in weights.iter().enumerate() {
+        let w = w.max(0.0);
+        if roll < w { return Some(i); }
+        roll -= w;
+    }
+    weights.iter().rposition(|&w| w > 0.0)
+}
+
+/// Pick the zone to sample from, by weight. `None` if there are no usable zones.
+fn pick_zone<'a>(zones: &'a [Zone], rng: &mut impl Rng) -> Option<&'a Zone> {
+    let weights: Vec<f32> = zones.iter().map(|z| z.weight).collect();
+    pick_weighted(&weights, rng).map(|i| &zones[i])
+}
+
+/// Draw points with `sample` until one lies outside every `forbidden` area.
+/// `None` if `attempts` draws in a row were all forbidden.
+pub fn resample_outside(forbidden: &[Bounds], attempts: u32, mut sample: impl FnMut() -> (i32, i32)) -> Option<(i32, i32)> {
+    (0..attempts).map(|_| sample()).find(|&p| !forbidden.iter().any(|f| f.contains(p)))
+}
+
+/// The part of `b` to click in: a zone chosen by weight, in pixels, or all of `b` without
+/// zones (or if all weights are zero).
+pub fn sample_area(b: Bounds, zones: &[Zone], rng: &mut impl Rng) -> Bounds {
+    pick_zone(zones, rng).map_or(b, |z| z.resolve(b))
+}
+
+/// Random point inside `b`. With zones, a zone is chosen by weight first and the point is
+/// uniform inside it; without zones (or if all weights are zero) the whole area is uniform.
+pub fn sample_point(b: Bounds, zones: &[Zone], rng: &mut impl Rng) -> (i32, i32) {
+    let area = sample_area(b, zones, rng);
+    (rng.gen_range(area.min_x..=area.max_x), rng.gen_range(area.min_y..=area.max_y))
+}
@@ -0,0 +1,203 @@
+//! Screenshots of the whole (virtual) screen or one area, saved as timestamped PNGs so a
+//! long unattended run leaves a record of what the screen looked like.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::human_mouse::Bounds;
+
+/// A captured image, 8-bit RGB, rows top to bottom.
+pub struct Capture {
+    pub width: u32,
+    pub height: u32,
+    pub rgb: Vec<u8>,
+}
+
+/// `screenshots` in the config folder, used when no folder is configured.
+pub fn default_dir() -> Option<PathBuf> {
+    crate::portable::config_dir().map(|d| d.join("screenshots"))
+}
+
+/// E.g. `2026-10-16_19-05-32.123_Loot.png` (UTC). Characters that don't belong in a file
+/// name are replaced by `_`.
+pub fn file_name(t: SystemTime, label: &str) -> String {
+    let millis = t.duration_since(SystemTime::UNIX_EPOCH).map_or(0, |d| d.subsec_millis());
+    let stamp = crate::logging::timestamp(t).replace(' ', "_").replace(':', "-");
+    let label: String = label
+        .chars()
+        .take(40)
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    if label.is_empty() { format!("{}.{:03}.png", stamp, millis) } else { format!("{}.{:03}_{}.png", stamp, millis, label) }
+}
+
+/// Capture `area` (or every screen, with `None`) and save it in `dir`. Returns the file written.
+pub fn take(dir: &Path, area: Option<Bounds>, label: &str) -> Result<PathBuf, String> {
+    let capture = capture(area)?;
+    std::fs::create_dir_all(dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    let path = dir.join(file_name(SystemTime::now(), label));
+    save_png(&capture, &path)?;
+    Ok(path)
+}
+
+pub fn save_png(capture: &Capture, path: &Path) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| format!("Could not create {}: {}", path.display(), e))?;
+    write_png(capture, BufWriter::new(file)).map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+pub fn write_png(capture: &Capture, out: impl Write) -> Result<(), png::EncodingError> {
+    let mut encoder = png::Encoder::new(out, capture.width, capture.height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&capture.rgb)
+}
+
+/// Encode `capture` as a JPEG of `quality` (1–100), for when size matters more than exact pixels.
+pub fn encode_jpeg(capture: &Capture, quality: u8) -> Result<Vec<u8>, String> {
+    let size = |n: u32| u16::try_from(n).map_err(|_| format!("{} px is too large for a JPEG", n));
+    let mut out = Vec::new();
+    jpeg_encoder::Encoder::new(&mut out, quality)
+        .encode(&capture.rgb, size(capture.width)?, size(capture.height)?, jpeg_encoder::ColorType::Rgb)
+        .map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+/// Read a PNG (any color type) as 8-bit RGB; transparency is dropped.
+pub fn load_png(path: &Path) -> Result<Capture, String> {
+    let file = File::open(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+    let mut decoder = png::Decoder::new(BufReader::new(file));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf).map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+    let pixels = &buf[..info.buffer_size()];
+    let rgb = match info.color_type {
+        png::ColorType::Rgb => pixels.to_vec(),
+        png::ColorType::Rgba => pixels.chunks_exact(4).flat_map(|p| [p[0], p[1], p[2]]).collect(),
+        png::ColorType::Grayscale => pixels.iter().flat_map(|&g| [g, g, g]).collect(),
+        png::ColorType::GrayscaleAlpha => pixels.chunks_exact(2).flat_map(|p| [p[0], p[0], p[0]]).collect(),
+        png::ColorType::Indexed => return Err(format!("Unexpected palette image in {}", path.display())),
+    };
+    Ok(Capture { width: info.width, height: info.height, rgb })
+}
+
+/// Width and height in pixels of `b`, whose max edges are part of it like its min edges.
+pub fn pixel_size(b: Bounds) -> (i32, i32) { (b.max_x - b.min_x + 1, b.max_y - b.min_y + 1) }
+
+/// Grab the pixels of `area` (inclusive, in screen coordinates), or of the whole virtual screen.
+pub fn capture(area: Option<Bounds>) -> Result<Capture, String> {
+    if let Some(b) = area {
+        let (w, h) = pixel_size(b);
+        if w <= 0 || h <= 0 { return Err("The screenshot area is empty".to_string()); }
+    }
+    #[cfg(windows)]
+    { win::capture(area) }
+    #[cfg(target_os = "linux")]
+    { x11::capture(area) }
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        let _ = area;
+        Err("Screenshots aren't supported on this platform yet".to_string())
+    }
+}
+
+/// The color of the screen pixel at (`x`, `y`).
+pub fn pixel(x: i32, y: i32) -> Result<[u8; 3], String> {
+    let c = capture(Some(Bounds { min_x: x, max_x: x, min_y: y, max_y: y }))?;
+    match c.rgb[..] {
+        [r, g, b, ..] => Ok([r, g, b]),
+        _ => Err(format!("No pixel at ({}, {})", x, y)),
+    }
+}
+
+/// Turn 32-bit little-endian BGRX pixels (what both GDI and X11 hand out) into RGB.
+#[cfg_attr(not(any(windows, target_os = "linux")), allow(dead_code))]
+fn bgrx_to_rgb(bgrx: &[u8]) -> Vec<u8> {
+    bgrx.chunks_exact(4).flat_map(|p| [p[2], p[1], p[0]]).collect()
+}
+
+#[cfg(target_os = "linux")]
+mod x11 {
+    use x11rb::connection::Connection;
+    use x11rb::errors::ReplyError;
+    use x11rb::protocol::xproto::{ConnectionExt, ImageFormat, ImageOrder};
+
+    use super::{bgrx_to_rgb, pixel_size, Capture};
+    use crate::human_mouse::Bounds;
+
+    pub fn capture(area: Option<Bounds>) -> Result<Capture, String> {
+        let (conn, screen_num) = x11rb::connect(None).map_err(|e| format!("Could not connect to the X server: {}", e))?;
+        let setup = conn.setup();
+        let screen = &setup.roots[screen_num];
+        let bpp = setup.pixmap_formats.iter().find(|f| f.depth == screen.root_depth).map(|f| f.bits_per_pixel);
+        if bpp != Some(32) || setup.image_byte_order != ImageOrder::LSB_FIRST {
+            return Err(format!("Unsupported X display format (depth {})", screen.root_depth));
+        }
+        // clip to the root window; X refuses requests that reach outside it
+        let full = Bounds { min_x: 0, max_x: screen.width_in_pixels as i32 - 1, min_y: 0, max_y: screen.height_in_pixels as i32 - 1 };
+        let b = area.map_or(full, |a| Bounds {
+            min_x: a.min_x.max(0),
+            max_x: a.max_x.min(full.max_x),
+            min_y: a.min_y.max(0),
+            max_y: a.max_y.min(full.max_y),
+        });
+        let (w, h) = pixel_size(b);
+        if w <= 0 || h <= 0 { return Err("The screenshot area is off screen".to_string()); }
+        let reply = conn
+            .get_image(ImageFormat::Z_PIXMAP, screen.root, b.min_x as i16, b.min_y as i16, w as u16, h as u16, !0)
+            .map_err(ReplyError::from)
+            .and_then(|c| c.reply())
+            .map_err(|e| format!("Could not read the screen: {}", e))?;
+        Ok(Capture { width: w as u32, height: h as u32, rgb: bgrx_to_rgb(&reply.data) })
+    }
+}
+
+#[cfg(windows)]
+mod win {
+    use windows_sys::Win32::Graphics::Gdi::{
+        BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC, GetDIBits, ReleaseDC,
+        SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, CAPTUREBLT, DIB_RGB_COLORS, SRCCOPY,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetSystemMetrics, SM_CXVIRTUALSCREEN, SM_CYVIRTUALSCREEN, SM_XVIRTUALSCREEN, SM_YVIRTUALSCREEN,
+    };
+
+    use super::{bgrx_to_rgb, pixel_size, Capture};
+    use crate::human_mouse::Bounds;
+
+    pub fn capture(area: Option<Bounds>) -> Result<Capture, String> {
+        let b = area.unwrap_or_else(|| unsafe {
+            let (x, y) = (GetSystemMetrics(SM_XVIRTUALSCREEN), GetSystemMetrics(SM_YVIRTUALSCREEN));
+            Bounds { min_x: x, max_x: x + GetSystemMetrics(SM_CXVIRTUALSCREEN) - 1, min_y: y, max_y: y + GetSystemMetrics(SM_CYVIRTUALSCREEN) - 1 }
+        });
+        let (w, h) = pixel_size(b);
+        let mut bgrx = vec![0u8; w as usize * h as usize * 4];
+        unsafe {
+            let screen = GetDC(std::ptr::null_mut());
+            if screen.is_null() { return Err("Could not get the screen device context".to_string()); }
+            let mem = CreateCompatibleDC(screen);
+            let bitmap = CreateCompatibleBitmap(screen, w, h);
+            let old = SelectObject(mem, bitmap);
+            let copied = BitBlt(mem, 0, 0, w, h, screen, b.min_x, b.min_y, SRCCOPY | CAPTUREBLT) != 0;
+            let mut info: BITMAPINFO = std::mem::zeroed();
+            info.bmiHeader = BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: w,
+                biHeight: -h, // negative: rows top to bottom
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB,
+                ..std::mem::zeroed()
+            };
+            SelectObject(mem, old);
+            let lines = GetDIBits(mem, bitmap, 0, h as u32, bgrx.as_mut_ptr().cast(), &mut info, DIB_RGB_COLORS);
+            DeleteObject(bitmap);
+            DeleteDC(mem);
+            ReleaseDC(std::ptr::null_mut(), screen);
+            if !copied || lines != h { return Err("Could not copy the screen".to_string()); }
+        }
+        Ok(Capture { width: w as u32, height: h as u32, rgb: bgrx_to_rgb(&bgrx) })
+    }
+}
@@ -0,0 +1,90 @@
+//! First-run setup wizard: check that input works, choose a display, pick a test area, watch
+//! one supervised click land and save the result as a starter profile. The pages are drawn by
+//! `AppState::setup_ui`; this holds where the user is in the flow.
+
+use std::time::{Duration, Instant};
+
+/// Time to take the hands off the mouse before the test click.
+pub const TEST_CLICK_DELAY: Duration = Duration::from_secs(3);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Page { Permissions, Displays, Area, TestClick, Profile }
+
+impl Page {
+    pub const ALL: [Page; 5] = [Page::Permissions, Page::Displays, Page::Area, Page::TestClick, Page::Profile];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Page::Permissions => "Check input permissions",
+            Page::Displays => "Choose a display",
+            Page::Area => "Pick a test area",
+            Page::TestClick => "Try a click",
+            Page::Profile => "Save a starter profile",
+        }
+    }
+
+    pub fn index(self) -> usize { Page::ALL.iter().position(|&p| p == self).unwrap_or(0) }
+
+    pub fn next(self) -> Option<Page> { Page::ALL.get(self.index() + 1).copied() }
+
+    pub fn back(self) -> Option<Page> { self.index().checked_sub(1).map(|i| Page::ALL[i]) }
+}
+
+/// Outcome of the supervised test click.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TestClick {
+    /// Where the click was aimed, in physical pixels.
+    pub target: (i32, i32),
+    /// Where the cursor was afterwards, if the backend can read it.
+    pub seen: Option<(i32, i32)>,
+}
+
+impl TestClick {
+    /// The cursor ended up on target, or `None` when it can't be read back.
+    pub fn landed(&self) -> Option<bool> { self.seen.map(|(x, y)| (x - self.target.0).abs() <= 2 && (y - self.target.1).abs() <= 2) }
+}
+
+#[derive(Clone, Debug)]
+pub struct Setup {
+    pub page: Page,
+    /// Test click countdown, while one is pending.
+    pub click_at: Option<Instant>,
+    pub click: Option<TestClick>,
+    /// Why the last test click wasn't sent, if it wasn't.
+    pub refused: Option<String>,
+    /// The user's answer to "did it land on the marker?".
+    pub confirmed: Option<bool>,
+    pub profile_name: String,
+}
+
+impl Default for Setup {
+    fn default() -> Self {
+        Self { page: Page::Permissions, click_at: None, click: None, refused: None, confirmed: None, profile_name: "Starter".to_string() }
+    }
+}
+
+impl Setup {
+    /// Whether Next is available on the current page.
+    pub fn can_advance(&self, has_target: bool, input_ok: bool) -> bool {
+        match self.page {
+            Page::Permissions => input_ok,
+            Page::Displays => true,
+            Page::Area => has_target,
+            Page::TestClick => self.confirmed == Some(true),
+            Page::Profile => !self.profile_name.trim().is_empty(),
+        }
+    }
+
+    pub fn go(&mut self, page: Page) {
+        self.page = page;
+        self.click_at = None;
+    }
+
+    /// Forget the last test click, e.g. after picking a new area or calibrating.
+    pub fn reset_click(&mut self) {
+        self.click_at = None;
+        self.click = None;
+        self.refused = None;
+        self.confirmed = None;
+    }
+}
@@ -0,0 +1,93 @@
+//! Per-step run statistics: clicks, the time between a step's clicks, misses, retries and how
+//! long each step ran, gathered while a job runs and shown (and exportable as CSV) once it ends,
+//! to find the steps that hold a sequence up.
+
+use std::io;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// What one step did during a run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StepStats {
+    pub clicks: u32,
+    /// Clicks that landed outside their target.
+    pub misses: u32,
+    /// Error-policy and click-check retries.
+    pub retries: u32,
+    /// Time the step was the running one.
+    pub time: Duration,
+    /// Count, sum and sum of squares of the seconds between the step's clicks.
+    intervals: (u32, f64, f64),
+    last_click: Option<Instant>,
+}
+
+impl StepStats {
+    pub fn click(&mut self, at: Instant, missed: bool) {
+        self.clicks += 1;
+        if missed { self.misses += 1; }
+        if let Some(last) = self.last_click {
+            let secs = at.saturating_duration_since(last).as_secs_f64();
+            let (n, sum, sq) = &mut self.intervals;
+            *n += 1;
+            *sum += secs;
+            *sq += secs * secs;
+        }
+        self.last_click = Some(at);
+    }
+
+    /// Average seconds between the step's clicks; `None` before its second click.
+    pub fn mean_interval(&self) -> Option<f64> {
+        let (n, sum, _) = self.intervals;
+        (n > 0).then(|| sum / n as f64)
+    }
+
+    /// Standard deviation of the seconds between the step's clicks.
+    pub fn stdev_interval(&self) -> Option<f64> {
+        let (n, _, sq) = self.intervals;
+        let mean = self.mean_interval()?;
+        Some((sq / n as f64 - mean * mean).max(0.0).sqrt())
+    }
+}
+
+/// The statistics of a finished run, step by step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunStats {
+    pub steps: Vec<(String, StepStats)>,
+    pub clicks: u32,
+    pub misses: u32,
+    pub failed_steps: u32,
+    /// Cycles started.
+    pub cycles: u32,
+    /// From the start to the end of the job.
+    pub duration: Duration,
+}
+
+impl RunStats {
+    /// The slowest step by running time, if any step ran at all.
+    pub fn bottleneck(&self) -> Option<&str> {
+        self.steps.iter().filter(|(_, s)| !s.time.is_zero()).max_by_key(|(_, s)| s.time).map(|(name, _)| name.as_str())
+    }
+
+    /// One row per step; intervals and times in seconds.
+    pub fn to_csv(&self) -> String {
+        let secs = |v: Option<f64>| v.map(|v| format!("{:.3}", v)).unwrap_or_default();
+        let mut out = String::from("step,clicks,mean_interval_s,stdev_interval_s,misses,retries,time_s\n");
+        for (name, s) in &self.steps {
+            out += &format!(
+                "{},{},{},{},{},{},{:.3}\n",
+                csv_field(name), s.clicks, secs(s.mean_interval()), secs(s.stdev_interval()), s.misses, s.retries, s.time.as_secs_f64(),
+            );
+        }
+        out
+    }
+
+    pub fn save_csv(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) { std::fs::create_dir_all(dir)?; }
+        std::fs::write(path, self.to_csv())
+    }
+}
+
+/// Quote a CSV field if it needs it.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) { format!("\"{}\"", text.replace('"', "\"\"")) } else { text.to_string() }
+}
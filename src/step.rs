@@ -0,0 +1,1473 @@
+use enigo::{KeyboardControllable, MouseButton, MouseControllable};
+use parking_lot::Mutex;
+use rand::{rngs::ThreadRng, Rng};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::activity::{self, Presence};
+use crate::events::{self, Event};
+use crate::hotkeys;
+use crate::input;
+use crate::human_mouse::{human_move_and_click_while, landing_point, on_target, Bounds, HumanMouseSettings};
+use crate::limits::{self, RateLimiter};
+use crate::stats::{RunStats, StepStats};
+use crate::viewer;
+use crate::lock::{Blocked, LockWatch};
+use crate::pacing::FatigueClock;
+use crate::quiet;
+use crate::sampler::{pick_weighted, resample_outside, sample_area, ClickPoint, Zone};
+use crate::ocr;
+use crate::screenshot;
+use crate::watch::{activate_window, Activated, WaitFor};
+use crate::screenshot::Capture;
+use crate::template;
+use crate::{Branch, Check, ClickButton, ClickConfig, GridSweep, OnError, SequenceStep, StepOrder, StepTarget, Verify, WaitImage, INPUT};
+
+/// What the runner should do after a call to [`Step::execute`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum StepOutcome {
+    /// The step has more work; call `execute` again.
+    Continue,
+    /// The step is finished for this cycle; move on to the next one.
+    Next,
+    /// End the whole job.
+    Stop,
+    /// Skip the step after this one.
+    SkipNext,
+    /// Carry on at step n (0-based); past the end finishes the cycle.
+    Jump(usize),
+}
+
+/// Asked for from the UI, a hotkey or the daemon while a job runs; the runner acts on it
+/// before the step's next `execute`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepCommand {
+    /// Leave the current step and go on with the next one.
+    Skip,
+    /// Start the current step over.
+    Repeat,
+    /// Carry on at step n (0-based).
+    Jump(usize),
+}
+
+/// Why a sequence step couldn't do its job; handled by the step's [`OnError`] policy.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StepError {
+    InvalidBounds,
+    /// Relative step, but nothing was clicked yet.
+    NoPreviousClick,
+    /// Every candidate point was in a forbidden zone.
+    NoAllowedPoint,
+    TextNotFound(String),
+    Ocr(String),
+    /// The refocus target window isn't there.
+    WindowMissing(String),
+    /// The click check kept failing (or couldn't run).
+    CheckFailed(String),
+}
+
+impl std::fmt::Display for StepError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::InvalidBounds => write!(f, "the step has no valid area"),
+            Self::NoPreviousClick => write!(f, "relative to the previous click, but nothing was clicked yet"),
+            Self::NoAllowedPoint => write!(f, "no allowed point outside the forbidden zones"),
+            Self::TextNotFound(text) => write!(f, "text “{}” not found", text),
+            Self::Ocr(e) => write!(f, "OCR failed: {}", e),
+            Self::WindowMissing(e) => write!(f, "{}", e),
+            Self::CheckFailed(e) => write!(f, "click check failed: {}", e),
+        }
+    }
+}
+
+/// Return `(a, b)` as `(min, max)` so users can type the interval either way round.
+fn ordered(a: f32, b: f32) -> (f32, f32) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+/// Where the move toward `to` starts: the last click, or “outside the square” so we can test
+/// re-entry.
+fn start_point(last: Option<(i32, i32)>, to: (i32, i32), bounds: Option<Bounds>) -> (i32, i32) {
+    last.unwrap_or_else(|| match bounds {
+        Some(b) => (b.min_x - 40, b.min_y - 40),
+        None => (to.0 - 40, to.1 - 40),
+    })
+}
+
+/// With a varied approach: how far off the straight line (degrees) the cursor may come in,
+/// and how far short of the aim (share of the target's size) it may land.
+const APPROACH_ANGLE_DEG: f32 = 30.0;
+const UNDERSHOOT: f32 = 0.15;
+
+/// Draws per click before giving up on finding a point outside the forbidden zones.
+pub const RESAMPLE_ATTEMPTS: u32 = 100;
+
+/// How often a wait-for-image step looks at the screen.
+const IMAGE_POLL_MS: u64 = 500;
+
+/// OCR passes over a text target's region before giving up on the step.
+const OCR_ATTEMPTS: u32 = 3;
+
+/// Pause after raising the target window, so the first click doesn't land mid-animation.
+const REFOCUS_SETTLE_MS: u64 = 150;
+
+/// How often the lock screen check runs; it may start a process.
+const LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// With drift correction on, the real cursor is read back every this many path steps (~100ms).
+const DRIFT_CHECKPOINT_STEPS: usize = 10;
+
+/// Pause before a failed step tries again under its error policy.
+const ERROR_RETRY_MS: u64 = 1000;
+
+/// How many recent clicks [`JobProgress::history`] keeps.
+pub const HISTORY_LEN: usize = 100;
+
+/// A dry run stops after recording this many events, so an endless job still ends.
+pub const DRY_RUN_MAX_EVENTS: usize = 5000;
+
+/// A dry run stops after this much virtual time.
+pub const DRY_RUN_MAX_MS: u64 = 7 * 24 * 3600 * 1000;
+
+/// Something that happened in a dry run.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DryRunEvent {
+    /// The runner started (or went back to) a step.
+    Step,
+    Click((i32, i32)),
+    Screenshot,
+    /// A condition a real run would wait for or check, taken as met, e.g. `image found`.
+    Assumed(String),
+    /// A step gave up under its error policy.
+    Failed(String),
+    /// A cycle was finished.
+    CycleEnd,
+    /// The job ended before running out of cycles.
+    Stopped(String),
+}
+
+/// One event of a dry run, with the virtual time and the step it happened in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DryRunEntry {
+    pub at_ms: u64,
+    /// 1-based.
+    pub cycle: u32,
+    pub step: usize,
+    pub step_name: String,
+    pub event: DryRunEvent,
+}
+
+/// A job run in virtual time: sleeps only move the clock on, nothing is clicked, captured or
+/// launched, and conditions on the screen or other windows are taken as met. What would have
+/// happened is recorded, for checking a sequence's schedule without waiting it out.
+#[derive(Clone, Debug, Default)]
+pub struct DryRun {
+    /// Virtual time since the start, ms.
+    pub now_ms: u64,
+    pub entries: Vec<DryRunEntry>,
+}
+
+impl DryRun {
+    /// Clicks made and virtual time spent by each of `step_count` steps.
+    pub fn per_step(&self, step_count: usize) -> Vec<(u32, u64)> {
+        let mut totals = vec![(0, 0); step_count];
+        for (e, next) in self.entries.iter().zip(self.entries.iter().skip(1).map(|n| n.at_ms).chain([self.now_ms])) {
+            let Some(t) = totals.get_mut(e.step) else { continue };
+            if matches!(e.event, DryRunEvent::Click(_)) { t.0 += 1; }
+            if e.event != DryRunEvent::CycleEnd { t.1 += next - e.at_ms; }
+        }
+        totals
+    }
+}
+
+/// One performed click, for the recent-clicks list.
+#[derive(Clone, Debug)]
+pub struct ClickRecord {
+    pub at: Instant,
+    pub step: String,
+    pub pos: (i32, i32),
+    /// The cursor wasn't on the target when the button went down (before any re-click).
+    pub missed: bool,
+}
+
+/// Live progress of a running job, shared with the UI.
+#[derive(Clone, Debug, Default)]
+pub struct JobProgress {
+    /// Index of the running step.
+    pub step: usize,
+    pub step_count: usize,
+    pub step_name: String,
+    /// 1-based cycle number.
+    pub cycle: u32,
+    pub cycles: Option<u32>,
+    pub clicks: u32,
+    /// Clicks that landed outside their target.
+    pub misses: u32,
+    /// Names of all steps, in run order.
+    pub step_names: Vec<String>,
+    /// Clicks made by each step, in step order.
+    pub step_clicks: Vec<u32>,
+    /// The running step has made its last click and is only waiting out the interval.
+    pub step_finished: bool,
+    /// When the current wait ends and the next click is due; `None` while not waiting.
+    pub next_click_at: Option<Instant>,
+    /// The last [`HISTORY_LEN`] clicks, oldest first.
+    pub history: VecDeque<ClickRecord>,
+    /// What the job is waiting for before its first click, e.g. `window title “Notepad”`.
+    pub waiting_for: Option<String>,
+    /// Steps given up on after their error policy ran out of retries.
+    pub failed_steps: u32,
+    /// The last step failure, e.g. `Step 'Loot' failed: text “Loot” not found`.
+    pub last_error: Option<String>,
+    /// The session was unlocked and the job waits for the user to resume; the UI clears it.
+    pub await_resume: bool,
+    /// When the job started, and what each step did since, in step order.
+    pub started: Option<Instant>,
+    pub step_stats: Vec<StepStats>,
+    /// When the running step was entered, for the time spent in each step.
+    pub step_entered: Option<Instant>,
+}
+
+impl JobProgress {
+    /// Short human-readable summary, e.g. "step 3/7, cycle 12, 428 clicks".
+    pub fn summary(&self) -> String {
+        if let Some(what) = &self.waiting_for { return format!("waiting for {}", what); }
+        let mut parts = Vec::new();
+        if self.step_count > 1 {
+            parts.push(format!("step {}/{}", self.step + 1, self.step_count));
+        }
+        match self.cycles {
+            Some(1) => {}
+            Some(n) => parts.push(format!("cycle {}/{}", self.cycle, n)),
+            None if self.cycle > 0 => parts.push(format!("cycle {}", self.cycle)),
+            None => {}
+        }
+        parts.push(format!("{} clicks", self.clicks));
+        if self.misses > 0 { parts.push(format!("{} missed", self.misses)); }
+        if self.failed_steps > 0 { parts.push(format!("{} failed", self.failed_steps)); }
+        parts.join(", ")
+    }
+
+    /// Name of the step the next click belongs to: the following step (wrapping into the
+    /// next cycle) once the running one is finished.
+    pub fn next_step_name(&self) -> &str {
+        if !self.step_finished || self.step_names.is_empty() { return &self.step_name; }
+        let next = self.step + 1;
+        if next < self.step_names.len() { return &self.step_names[next]; }
+        match self.cycles {
+            Some(n) if self.cycle >= n => &self.step_name,
+            _ => &self.step_names[0],
+        }
+    }
+
+    /// Make step `i` the running one from `now` on, adding the time since the last switch to
+    /// the step that ran until now.
+    pub fn switch_step(&mut self, i: usize, now: Instant) {
+        let (step, entered) = (self.step, self.step_entered.replace(now));
+        if let (Some(at), Some(s)) = (entered, self.step_stats.get_mut(step)) {
+            s.time += now.saturating_duration_since(at);
+        }
+        self.step = i;
+    }
+
+    /// Count a retry of the running step.
+    pub fn retried(&mut self) {
+        let step = self.step;
+        if let Some(s) = self.step_stats.get_mut(step) { s.retries += 1; }
+    }
+
+    /// The run's statistics as of `now`, the running step's time up to then included.
+    pub fn run_stats(&self, now: Instant) -> RunStats {
+        let mut stats = self.step_stats.clone();
+        if let (Some(at), Some(s)) = (self.step_entered, stats.get_mut(self.step)) {
+            s.time += now.saturating_duration_since(at);
+        }
+        RunStats {
+            steps: self.step_names.iter().cloned().zip(stats).collect(),
+            clicks: self.clicks,
+            misses: self.misses,
+            failed_steps: self.failed_steps,
+            cycles: self.cycle,
+            duration: self.started.map_or(Duration::ZERO, |at| now.saturating_duration_since(at)),
+        }
+    }
+
+    /// Seconds until the next click, if the job is waiting for one.
+    pub fn next_click_in(&self) -> Option<f32> {
+        self.next_click_at.map(|at| at.saturating_duration_since(Instant::now()).as_secs_f32())
+    }
+}
+
+/// Everything a step needs from the engine while it runs.
+pub struct StepCtx<'a> {
+    pub running: &'a AtomicBool,
+    /// Live job config; edits from the UI show up here while the job runs.
+    pub config: &'a Mutex<ClickConfig>,
+    pub rng: ThreadRng,
+    /// Where the last click landed, used as the start of the next movement.
+    pub last_pos: Option<(i32, i32)>,
+    /// When the next anti-AFK key tap is due.
+    next_key_tap: Option<Instant>,
+    fatigue: FatigueClock,
+    /// Clicks performed so far in this job.
+    pub clicks: u32,
+    pub progress: Arc<Mutex<JobProgress>>,
+    /// Window/process whose disappearance ends the job, and when to look for it next.
+    watch: Option<(WaitFor, regex::Regex, Instant)>,
+    /// Why the last refocus didn't find its window; `None` when it did (or isn't configured).
+    pub window_error: Option<String>,
+    /// When the job started, for the run time safety limit.
+    started: Instant,
+    /// Recent clicks for the per-minute limit; shared by the whole session, private in a dry run.
+    rate: Arc<Mutex<RateLimiter>>,
+    /// Last lock screen check and what it found.
+    lock_state: Option<(Instant, Option<Blocked>)>,
+    lock_watch: LockWatch,
+    /// What's been seen of the user, for pausing while they're at the computer.
+    presence: Presence,
+    /// Set for a dry run: virtual time, and what the job would have done.
+    pub dry_run: Option<DryRun>,
+}
+
+impl<'a> StepCtx<'a> {
+    pub fn new(running: &'a AtomicBool, config: &'a Mutex<ClickConfig>) -> Self {
+        Self {
+            running, config, rng: rand::thread_rng(), last_pos: None, next_key_tap: None,
+            fatigue: FatigueClock::new(), clicks: 0, progress: Arc::default(), watch: None,
+            window_error: None, started: Instant::now(), rate: Arc::clone(&limits::SESSION_RATE),
+            lock_state: None, lock_watch: LockWatch::default(), presence: Presence::default(), dry_run: None,
+        }
+    }
+
+    /// Run as a dry run (see [`DryRun`]).
+    pub fn dry(mut self) -> Self {
+        self.dry_run = Some(DryRun::default());
+        self.rate = Arc::default();
+        self
+    }
+
+    /// Note `event` in the dry run, if this is one; stops the run once it has recorded enough.
+    pub fn record(&mut self, event: DryRunEvent) {
+        let Some(dry) = &mut self.dry_run else { return };
+        let (cycle, step, step_name) = {
+            let p = self.progress.lock();
+            (p.cycle, p.step, p.step_name.clone())
+        };
+        dry.entries.push(DryRunEntry { at_ms: dry.now_ms, cycle, step, step_name: step_name.clone(), event });
+        if dry.entries.len() >= DRY_RUN_MAX_EVENTS && self.running.load(Ordering::Relaxed) {
+            let event = DryRunEvent::Stopped(format!("{} events recorded", DRY_RUN_MAX_EVENTS));
+            dry.entries.push(DryRunEntry { at_ms: dry.now_ms, cycle, step, step_name, event });
+            self.running.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Time since the job started; virtual in a dry run.
+    fn elapsed(&self) -> Duration {
+        self.dry_run.as_ref().map_or_else(|| self.started.elapsed(), |d| Duration::from_millis(d.now_ms))
+    }
+
+    /// Report progress into `progress` instead of a private one.
+    pub fn with_progress(mut self, progress: Arc<Mutex<JobProgress>>) -> Self {
+        progress.lock().started = Some(self.started);
+        self.progress = progress;
+        self
+    }
+
+    pub fn is_running(&self) -> bool { self.running.load(Ordering::Relaxed) }
+
+    /// Human-move to a random point inside `bounds` (weighted by `zones`, if any) and click it.
+    /// Returns the clicked point, or `None` if the whole area seems to be forbidden.
+    /// The landing point (see [`landing_point`]) is part of the draw, so it is what the
+    /// forbidden zones are checked against and what gets recorded, and it stays in its zone.
+    pub fn click_in(&mut self, bounds: Bounds, zones: &[Zone], button: ClickButton) -> Option<(i32, i32)> {
+        let forbidden = self.config.lock().forbidden.clone();
+        let settings = self.human_settings();
+        let (rng, from) = (&mut self.rng, self.last_pos);
+        let Some(to) = resample_outside(&forbidden, RESAMPLE_ATTEMPTS, || {
+            let area = sample_area(bounds, zones, rng);
+            let aim = (rng.gen_range(area.min_x..=area.max_x), rng.gen_range(area.min_y..=area.max_y));
+            landing_point(start_point(from, aim, Some(bounds)), aim, Some(area), &settings, rng)
+        }) else {
+            log::warn!("No allowed point found in [{}..{}]x[{}..{}]; click skipped", bounds.min_x, bounds.max_x, bounds.min_y, bounds.max_y);
+            return None;
+        };
+        self.click_at(to, Some(bounds), button);
+        Some(to)
+    }
+
+    /// Human-move to a point target (within its jitter) and click it. Returns the clicked
+    /// point, or `None` if it lies in a forbidden zone.
+    pub fn click_point(&mut self, point: ClickPoint, button: ClickButton) -> Option<(i32, i32)> {
+        let forbidden = self.config.lock().forbidden.clone();
+        let rng = &mut self.rng;
+        let Some(to) = resample_outside(&forbidden, RESAMPLE_ATTEMPTS, || point.sample(rng)) else {
+            log::warn!("Point ({}, {}) is in a forbidden zone; click skipped", point.x, point.y);
+            return None;
+        };
+        self.click_at(to, None, button);
+        Some(to)
+    }
+
+    /// Human-move to `to` (optionally kept inside `bounds`) and click. Refuses points in a
+    /// forbidden zone, whatever the caller sampled. A click that missed is made once more if
+    /// the job re-clicks misses, counted and checked like any other.
+    pub fn click_at(&mut self, to: (i32, i32), bounds: Option<Bounds>, button: ClickButton) {
+        if self.click_once(to, bounds, button) != Some(true) || !self.config.lock().reclick_misses { return; }
+        log::info!("Re-clicking ({}, {})", to.0, to.1);
+        if self.click_once(to, bounds, button) == Some(true) { log::warn!("Re-click at ({}, {}) missed too", to.0, to.1); }
+    }
+
+    /// One click for [`Self::click_at`]: whether it missed, or `None` if it wasn't made.
+    fn click_once(&mut self, to: (i32, i32), bounds: Option<Bounds>, button: ClickButton) -> Option<bool> {
+        if self.config.lock().forbidden.iter().any(|f| f.contains(to)) {
+            log::warn!("Refusing to click ({}, {}): inside a forbidden zone", to.0, to.1);
+            return None;
+        }
+        let missed = if self.dry_run.is_some() {
+            if !self.check_limits(true) { return None; }
+            self.record(DryRunEvent::Click(to));
+            false
+        } else {
+            self.press_at(to, bounds, button)?
+        };
+
+        // remember where we ended up
+        self.last_pos = Some(to);
+        self.clicks += 1;
+        self.rate.lock().record(Instant::now());
+        {
+            let mut p = self.progress.lock();
+            p.clicks = self.clicks;
+            if missed { p.misses += 1; }
+            p.next_click_at = None;
+            if p.history.len() >= HISTORY_LEN { p.history.pop_front(); }
+            let step = p.step;
+            if let Some(n) = p.step_clicks.get_mut(step) { *n += 1; }
+            if let Some(s) = p.step_stats.get_mut(step) { s.click(Instant::now(), missed); }
+            let step = p.step_name.clone();
+            p.history.push_back(ClickRecord { at: Instant::now(), step, pos: to, missed });
+        }
+        if self.dry_run.is_none() {
+            self.config.lock().feedback.click();
+            viewer::set_target(Some(bounds.unwrap_or_else(|| Bounds::around(to, 0))));
+        }
+        let every = self.config.lock().screenshot_every;
+        if every.is_some_and(|n| self.clicks.is_multiple_of(n)) {
+            self.screenshot(None, &format!("click-{}", self.clicks));
+        }
+        Some(missed)
+    }
+
+    /// Wait until clicking is allowed, then move there and press. Returns whether the click
+    /// missed, or `None` if the job was stopped (or hit a limit) while waiting, or the hold key
+    /// was let go of before the press.
+    fn press_at(&mut self, to: (i32, i32), bounds: Option<Bounds>, button: ClickButton) -> Option<bool> {
+        if !self.wait_while_locked() { return None; }
+        if !self.wait_while_user_active() { return None; }
+        if !self.wait_out_quiet_hours() { return None; }
+        if !self.wait_for_hold_key() { return None; }
+        if !self.wait_for_rate_limit() { return None; }
+        if self.config.lock().refocus.as_ref().is_some_and(|r| r.every_click) { self.refocus(); }
+        let mut en = INPUT.lock();
+
+        let from = start_point(self.last_pos, to, bounds);
+
+        let button = match button {
+            ClickButton::Left => MouseButton::Left,
+            ClickButton::Right => MouseButton::Right,
+        };
+
+        let settings = self.human_settings();
+        // in hold-to-run mode, letting go of the key during the move calls the click off
+        let hold = self.config.lock().hold_to_run;
+        let held = || hold.is_none_or(hotkeys::is_held);
+        let Some(landed) = human_move_and_click_while(&mut *en, from, to, bounds, &settings, button, held) else {
+            log::info!("Hold key released before the click; click skipped");
+            return None;
+        };
+        // backends that can't read the cursor just echo the target back
+        let missed = en.backend().reads_cursor() && !on_target(bounds, to, landed);
+        if missed { log::warn!("Click at ({}, {}) missed: the cursor was at ({}, {})", to.0, to.1, landed.0, landed.1); }
+        Some(missed)
+    }
+
+    /// Find `text` inside `region` by OCR, retrying a few times in case it's still loading.
+    pub fn locate_text(&mut self, region: Bounds, text: &str) -> Result<Bounds, StepError> {
+        if self.dry_run.is_some() {
+            self.record(DryRunEvent::Assumed(format!("text “{}” found", text)));
+            return Ok(region);
+        }
+        for attempt in 1..=OCR_ATTEMPTS {
+            let words = ocr::read_words(region).map_err(StepError::Ocr)?;
+            if let Some(found) = ocr::find_text(&words, text) { return Ok(found); }
+            if attempt < OCR_ATTEMPTS { self.sleep_ms(1000); }
+            if !self.is_running() { break; }
+        }
+        Err(StepError::TextNotFound(text.to_string()))
+    }
+
+    /// Run the job's command for `event`, if one is set (and due, for cycle events).
+    /// Save the run's progress after `cycle` cycles, if the job keeps a checkpoint.
+    pub fn save_checkpoint(&self, cycle: u32) {
+        if self.dry_run.is_some() { return; }
+        let Some((store, mut checkpoint)) = self.config.lock().checkpoint.clone() else { return };
+        checkpoint.cycle = cycle;
+        checkpoint.clicks += self.clicks;
+        checkpoint.step_clicks = self.progress.lock().step_clicks.clone();
+        if let Err(e) = store.save_checkpoint(&checkpoint) { log::warn!("Could not save the checkpoint: {}", e); }
+    }
+
+    pub fn fire(&self, event: Event) {
+        if self.dry_run.is_some() { return; }
+        let Some(commands) = self.config.lock().events.clone() else { return };
+        let progress = self.progress.lock().clone();
+        if let Some(cmd) = commands.command(event, progress.cycle) {
+            events::run(event, events::expand(cmd), events::env(event, &progress));
+        }
+    }
+
+    /// Save a screenshot of `area` (or every screen) to the configured folder. Failures are
+    /// logged; the job carries on.
+    pub fn screenshot(&mut self, area: Option<Bounds>, label: &str) {
+        if self.dry_run.is_some() { return self.record(DryRunEvent::Screenshot); }
+        let dir = self.config.lock().screenshot_dir.clone();
+        match screenshot::take(&dir, area, label) {
+            Ok(path) => log::info!("Saved screenshot {}", path.display()),
+            Err(e) => log::warn!("Screenshot failed: {}", e),
+        }
+    }
+
+    /// In hold-to-run mode, block until the hold key is down. Returns false if the job
+    /// was stopped while waiting.
+    fn wait_for_hold_key(&self) -> bool {
+        let mut waiting = false;
+        loop {
+            let Some(key) = self.config.lock().hold_to_run else { return true; };
+            if hotkeys::is_held(key) {
+                if waiting { log::info!("Hold key {} down, resuming", key.label()); }
+                return true;
+            }
+            if !self.is_running() { return false; }
+            if !waiting {
+                log::info!("Paused until {} is held", key.label());
+                waiting = true;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+
+    /// Whether the session is locked or the screensaver is on, looked up at most once a second.
+    fn screen_blocked(&mut self) -> Option<Blocked> {
+        if !self.config.lock().limits.pause_when_locked { return None; }
+        if let Some((at, state)) = self.lock_state {
+            if at.elapsed() < LOCK_CHECK_INTERVAL { return state; }
+        }
+        let state = self.lock_watch.blocked();
+        self.lock_state = Some((Instant::now(), state));
+        state
+    }
+
+    /// Hold the click while the session is locked or the screensaver is on and, if the safety
+    /// settings ask for it, until the user resumes after unlocking. Returns false if the job
+    /// was stopped while waiting.
+    fn wait_while_locked(&mut self) -> bool {
+        let Some(blocked) = self.screen_blocked() else { return true };
+        log::info!("Pausing: {}", blocked.label());
+        {
+            let mut p = self.progress.lock();
+            p.waiting_for = Some("the session to be unlocked".to_string());
+            p.next_click_at = None;
+        }
+        while self.screen_blocked().is_some() {
+            self.sleep_ms(500);
+            if !self.is_running() { return false; }
+        }
+        if self.config.lock().limits.confirm_unlock {
+            log::info!("Unlocked; waiting for Resume");
+            {
+                let mut p = self.progress.lock();
+                p.waiting_for = Some("Resume after unlocking".to_string());
+                p.await_resume = true;
+            }
+            while self.progress.lock().await_resume {
+                self.sleep_ms(200);
+                if !self.is_running() { return false; }
+            }
+        }
+        log::info!("Unlocked; resuming");
+        self.progress.lock().waiting_for = None;
+        true
+    }
+
+    /// Whether the safety settings ask to pause while the user is at the computer and they've
+    /// used the keyboard or mouse within the idle period.
+    fn user_active(&mut self) -> bool {
+        let limits = self.config.lock().limits;
+        if !limits.pause_when_active || self.dry_run.is_some() { return false; }
+        let cursor = {
+            let en = INPUT.lock();
+            en.backend().reads_cursor().then(|| en.mouse_location())
+        };
+        let now = Instant::now();
+        self.presence.observe(now, activity::idle(), input::since_sent(), cursor);
+        self.presence.active_within(now, Duration::from_secs(limits.idle_secs as u64))
+    }
+
+    /// Hold the click while the user is at the computer (see [`Self::user_active`]). Returns
+    /// false if the job was stopped while waiting.
+    fn wait_while_user_active(&mut self) -> bool {
+        if !self.user_active() { return true; }
+        let idle_secs = self.config.lock().limits.idle_secs;
+        log::info!("Pausing: keyboard or mouse in use");
+        {
+            let mut p = self.progress.lock();
+            p.waiting_for = Some(format!("{} s without keyboard or mouse input", idle_secs));
+            p.next_click_at = None;
+        }
+        while self.user_active() {
+            self.sleep_ms(500);
+            if !self.is_running() { return false; }
+        }
+        log::info!("No input for {} s; resuming", idle_secs);
+        self.progress.lock().waiting_for = None;
+        true
+    }
+
+    /// Hold the click while a quiet hours window is on. Returns false if the job was stopped
+    /// while waiting.
+    fn wait_out_quiet_hours(&mut self) -> bool {
+        let mut quiet = None;
+        loop {
+            let windows = self.config.lock().quiet_hours.clone();
+            let Some(window) = quiet::active(&windows, quiet::now_minute()) else { break };
+            if quiet != Some(window) {
+                log::info!("Quiet hours {}: pausing", window.label());
+                let mut p = self.progress.lock();
+                p.waiting_for = Some(format!("quiet hours to end at {}", quiet::format_time(window.end)));
+                p.next_click_at = None;
+                quiet = Some(window);
+            }
+            self.sleep_ms(1000);
+            if !self.is_running() { return false; }
+        }
+        if quiet.is_some() {
+            log::info!("Quiet hours over; resuming");
+            self.progress.lock().waiting_for = None;
+        }
+        true
+    }
+
+    /// Stop the job if it has run past the run time safety limit or, `before_click`, used up
+    /// its click budget. Returns false if it did.
+    fn check_limits(&mut self, before_click: bool) -> bool {
+        let limits = self.config.lock().limits;
+        let clicks = if before_click { limits.clicks_exceeded(self.clicks) } else { None };
+        let Some(why) = clicks.or_else(|| limits.run_time_exceeded(self.elapsed())) else { return true };
+        if self.is_running() {
+            log::warn!("Stopping: {}", why);
+            self.record(DryRunEvent::Stopped(why.clone()));
+            self.progress.lock().last_error = Some(why);
+            self.running.store(false, Ordering::Relaxed);
+        }
+        false
+    }
+
+    /// Hold off the next click while the per-minute safety limit is used up. Returns false
+    /// if the job was stopped meanwhile or hit another limit.
+    fn wait_for_rate_limit(&mut self) -> bool {
+        if !self.check_limits(true) { return false; }
+        let Some(per_minute) = self.config.lock().limits.per_minute else { return true };
+        let wait = self.rate.lock().delay(per_minute, Instant::now());
+        if !wait.is_zero() {
+            log::warn!("Safety limit of {} clicks a minute reached; waiting {:.1}s", per_minute, wait.as_secs_f32());
+            self.announce_next_click(wait.as_secs_f32());
+            self.sleep_ms(wait.as_millis() as u64);
+        }
+        self.is_running()
+    }
+
+    /// Mouse movement settings for the next move, slowed down by warm-up and fatigue.
+    pub fn human_settings(&self) -> HumanMouseSettings {
+        let mut settings = HumanMouseSettings::default();
+        let cfg = self.config.lock();
+        if let Some(w) = cfg.warm_up {
+            settings.avg_speed *= w.speed_factor(self.clicks);
+        }
+        if let Some(f) = cfg.fatigue {
+            settings.avg_speed *= f.speed_factor(self.fatigue.hours());
+        }
+        settings.checkpoint_steps = cfg.correct_drift.then_some(DRIFT_CHECKPOINT_STEPS);
+        if cfg.settle_before_click { settings.settle_moves = 2; }
+        if cfg.vary_approach {
+            settings.approach_angle_deg = APPROACH_ANGLE_DEG;
+            settings.undershoot = UNDERSHOOT;
+        }
+        (settings.min_hold_ms, settings.max_hold_ms) = (cfg.min_hold_ms, cfg.max_hold_ms);
+        settings.path_bank = cfg.path_bank.clone();
+        settings
+    }
+
+    /// Sleep a random duration between `min_secs` and `max_secs`, waking early if the job stops.
+    /// Warm-up and fatigue stretch the interval; fatigue may insert a break first.
+    pub fn sleep_between(&mut self, min_secs: f32, max_secs: f32) {
+        let (warm_up, fatigue) = {
+            let cfg = self.config.lock();
+            (cfg.warm_up, cfg.fatigue)
+        };
+        let (min_s, max_s) = ordered(min_secs, max_secs);
+        let base = self.rng.gen_range(min_s..=max_s);
+        // the wait after a click is part of that click's warm-up, so count it as not yet done
+        let mut factor = warm_up.map_or(1.0, |w| w.interval_factor(self.clicks.saturating_sub(1)));
+        if let Some(f) = fatigue {
+            if self.rng.gen::<f32>() < f.break_chance {
+                let (min_b, max_b) = ordered(f.break_min_secs, f.break_max_secs);
+                let secs = self.rng.gen_range(min_b..=max_b).max(0.0);
+                // rough until the break is over and the recovered interval is known
+                self.announce_next_click(secs + base * factor * f.interval_factor(self.fatigue.hours()));
+                log::info!("Fatigue: taking a {:.0}s break", secs);
+                self.sleep_ms((secs * 1000.0) as u64);
+                self.fatigue.recover(f.recovery);
+            }
+            factor *= f.interval_factor(self.fatigue.hours());
+        }
+
+        let wait = (base * factor).max(0.01);
+        self.announce_next_click(wait);
+        self.sleep_ms((wait * 1000.0) as u64);
+    }
+
+    fn announce_next_click(&self, secs: f32) {
+        self.progress.lock().next_click_at = Some(Instant::now() + Duration::from_secs_f32(secs.max(0.0)));
+    }
+
+    /// Block until the configured window/process exists. Returns false if the job was
+    /// stopped, the wait timed out, or the condition can't be checked.
+    pub fn wait_for_start(&mut self) -> bool {
+        let Some(wait) = self.config.lock().wait_for.clone() else { return true };
+        if self.dry_run.is_some() {
+            self.record(DryRunEvent::Assumed(wait.describe()));
+            return true;
+        }
+        let re = match wait.matcher() {
+            Ok(re) => re,
+            Err(e) => { log::error!("Bad start condition: {}", e); return false; }
+        };
+        let started = Instant::now();
+        loop {
+            match wait.is_present(&re) {
+                Ok(true) => break,
+                Ok(false) => {}
+                Err(e) => {
+                    log::error!("Can't check start condition: {}", e);
+                    self.progress.lock().waiting_for = None;
+                    return false;
+                }
+            }
+            if self.progress.lock().waiting_for.is_none() {
+                log::info!("Waiting for {}", wait.describe());
+                self.progress.lock().waiting_for = Some(wait.describe());
+            }
+            if wait.timeout_secs > 0 && started.elapsed() >= Duration::from_secs(wait.timeout_secs as u64) {
+                log::warn!("Gave up waiting for {} after {}s", wait.describe(), wait.timeout_secs);
+                self.progress.lock().waiting_for = None;
+                return false;
+            }
+            self.sleep_ms(500);
+            if !self.is_running() { return false; }
+        }
+        log::info!("Found {}; starting", wait.describe());
+        self.progress.lock().waiting_for = None;
+        if wait.stop_when_gone { self.watch = Some((wait, re, Instant::now())); }
+        true
+    }
+
+    /// Bring the configured target window to the front. Problems end up in
+    /// [`window_error`](Self::window_error) for sequence steps to handle; other modes log
+    /// them and click anyway.
+    pub fn refocus(&mut self) {
+        let Some(refocus) = self.config.lock().refocus.clone() else {
+            self.window_error = None;
+            return;
+        };
+        if self.dry_run.is_some() {
+            self.window_error = None;
+            return self.record(DryRunEvent::Assumed(format!("window “{}” in front", refocus.pattern)));
+        }
+        self.window_error = match refocus.matcher().and_then(|re| activate_window(&re)) {
+            Ok(Activated::AlreadyActive) => None,
+            Ok(Activated::Raised) => {
+                // give the window a moment to come up before clicking on it
+                self.sleep_ms(REFOCUS_SETTLE_MS);
+                None
+            }
+            Ok(Activated::NotFound) => Some(format!("no window matching “{}” to refocus", refocus.pattern)),
+            Err(e) => Some(format!("can't refocus: {}", e)),
+        };
+        if let Some(e) = &self.window_error { log::warn!("{}", e); }
+    }
+
+    /// With "stop when gone", end the job once the watched window/process disappears.
+    /// Looks at most once a second.
+    fn check_watch(&mut self) {
+        let Some((wait, re, next)) = &mut self.watch else { return };
+        if Instant::now() < *next { return; }
+        *next = Instant::now() + Duration::from_secs(1);
+        if let Ok(false) = wait.is_present(re) {
+            log::info!("{} is gone; stopping", wait.describe());
+            self.running.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Sleep in 50 ms slices so a stop request (or a step command) is noticed quickly.
+    /// Anti-AFK key taps are sent from here, so they happen alongside clicks.
+    pub fn sleep_ms(&mut self, ms: u64) {
+        if let Some(dry) = &mut self.dry_run {
+            dry.now_ms += ms;
+            self.fatigue.advance(Duration::from_millis(ms));
+            if dry.now_ms >= DRY_RUN_MAX_MS && self.is_running() {
+                self.record(DryRunEvent::Stopped(format!("{} h of virtual time", DRY_RUN_MAX_MS / 3_600_000)));
+                self.running.store(false, Ordering::Relaxed);
+            }
+            self.check_limits(false);
+            return;
+        }
+        for _ in 0..ms / 50 {
+            if !self.is_running() || !self.check_limits(false) { return; }
+            if self.config.lock().step_command.is_some() { return; }
+            self.tap_key_if_due();
+            self.check_watch();
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        if !ms.is_multiple_of(50) { std::thread::sleep(Duration::from_millis(ms % 50)); }
+    }
+
+    /// Send the anti-AFK key if one is configured and its random interval has elapsed.
+    fn tap_key_if_due(&mut self) {
+        let (afk, quiet_hours) = {
+            let cfg = self.config.lock();
+            (cfg.anti_afk, cfg.quiet_hours.clone())
+        };
+        let Some(afk) = afk else {
+            self.next_key_tap = None;
+            return;
+        };
+        if self.screen_blocked().is_some() || self.progress.lock().await_resume || self.user_active() {
+            self.next_key_tap = None;
+            return;
+        }
+        if !quiet_hours.is_empty() && quiet::active(&quiet_hours, quiet::now_minute()).is_some() {
+            self.next_key_tap = None;
+            return;
+        }
+        let now = Instant::now();
+        let due = self.next_key_tap.is_some_and(|t| now >= t);
+        if due {
+            INPUT.lock().key_click(afk.key.to_enigo());
+            log::debug!("Anti-AFK tap: {}", afk.key.label());
+        }
+        if due || self.next_key_tap.is_none() {
+            let (min_s, max_s) = ordered(afk.min_secs, afk.max_secs);
+            let wait = self.rng.gen_range(min_s..=max_s).max(1.0);
+            self.next_key_tap = Some(now + Duration::from_secs_f32(wait));
+        }
+    }
+}
+
+/// A unit of work run by the click engine.
+///
+/// The runner calls [`Step::reset`] at the start of every cycle, then calls
+/// [`Step::execute`] until it returns something other than [`StepOutcome::Continue`].
+/// Each `execute` should do one small action (typically one click plus its delay)
+/// so stop requests stay responsive.
+pub trait Step: Send {
+    fn name(&self) -> &str;
+    fn reset(&mut self) {}
+    fn execute(&mut self, ctx: &mut StepCtx) -> StepOutcome;
+
+    /// How often weighted order draws this step relative to the others.
+    fn weight(&self) -> f32 { 1.0 }
+
+    /// Turns the step adds to a weighted cycle.
+    fn turns(&self) -> u32 { 1 }
+
+    /// One turn in weighted order: the whole step by default. Returns the outcome that ended it.
+    fn turn(&mut self, ctx: &mut StepCtx) -> StepOutcome {
+        self.reset();
+        while ctx.is_running() {
+            match self.execute(ctx) {
+                StepOutcome::Continue => {}
+                outcome => return outcome,
+            }
+        }
+        StepOutcome::Continue
+    }
+}
+
+/// Single mode: clicks inside the live config bounds until the click budget is used up.
+pub struct SingleAreaStep {
+    remaining: Option<u32>,
+}
+
+impl SingleAreaStep {
+    pub fn new(finite_clicks: Option<u32>) -> Self { Self { remaining: finite_clicks } }
+}
+
+impl Step for SingleAreaStep {
+    fn name(&self) -> &str { "Single area" }
+
+    fn execute(&mut self, ctx: &mut StepCtx) -> StepOutcome {
+        // Check if we've completed our finite clicks
+        if let Some(0) = self.remaining { return StepOutcome::Next; }
+
+        let cfg = ctx.config.lock().clone();
+        if let Some(p) = cfg.point {
+            ctx.click_point(p, cfg.button);
+        } else if let Some(b) = cfg.bounds.filter(|b| b.is_valid()) {
+            ctx.click_in(b, &[], cfg.button);
+        } else {
+            ctx.sleep_ms(200);
+            return StepOutcome::Continue;
+        }
+
+        // Update click counter if we're using finite clicks
+        if let Some(ref mut remaining) = self.remaining {
+            *remaining = remaining.saturating_sub(1);
+        }
+
+        ctx.sleep_between(cfg.min_secs, cfg.max_secs);
+        StepOutcome::Continue
+    }
+}
+
+/// Sequence mode: a fixed number of clicks inside the step's own bounds.
+pub struct ClickStep {
+    step: SequenceStep,
+    done: u32,
+    /// Error-policy retries used up this cycle.
+    retried: u32,
+    /// The verify image, loaded on first use.
+    verify_image: Option<Capture>,
+}
+
+impl ClickStep {
+    pub fn new(step: SequenceStep) -> Self { Self { step, done: 0, retried: 0, verify_image: None } }
+
+    /// Click the step's target once.
+    fn click(&self, ctx: &mut StepCtx) -> Result<(), StepError> {
+        if let Some(e) = &ctx.window_error { return Err(StepError::WindowMissing(e.clone())); }
+        let s = &self.step;
+        let clicked = match s.target {
+            StepTarget::Area if !s.bounds.is_valid() => return Err(StepError::InvalidBounds),
+            StepTarget::Area => match &s.text {
+                None => ctx.click_in(s.bounds, &s.zones, s.button),
+                Some(text) => {
+                    let found = ctx.locate_text(s.bounds, text)?;
+                    ctx.click_in(found, &[], s.button)
+                }
+            },
+            StepTarget::Point(p) => ctx.click_point(p, s.button),
+            StepTarget::Offset { dx, dy, jitter } => {
+                let (x, y) = ctx.last_pos.ok_or(StepError::NoPreviousClick)?;
+                ctx.click_point(ClickPoint { x: x + dx, y: y + dy, jitter }, s.button)
+            }
+            StepTarget::Screenshot { full_screen } => {
+                if !full_screen && !s.bounds.is_valid() { return Err(StepError::InvalidBounds); }
+                ctx.screenshot((!full_screen).then_some(s.bounds), &s.name);
+                return Ok(());
+            }
+            StepTarget::WaitImage(_) => return Ok(()), // run by WaitImageStep
+        };
+        clicked.map(|_| ()).ok_or(StepError::NoAllowedPoint)
+    }
+
+    /// Check the screen after a click, clicking again with backoff while the check fails.
+    fn verify(&mut self, verify: &Verify, ctx: &mut StepCtx) -> Result<(), StepError> {
+        for attempt in 0..=verify.retries {
+            ctx.sleep_ms(verify.delay_ms);
+            if !ctx.is_running() { return Ok(()); }
+            if ctx.dry_run.is_some() {
+                ctx.record(DryRunEvent::Assumed("click check passed".to_string()));
+                return Ok(());
+            }
+            match check(verify, &mut self.verify_image) {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                Err(e) => return Err(StepError::CheckFailed(e)),
+            }
+            if attempt == verify.retries { break; }
+            let backoff = verify.backoff_ms.saturating_mul(1 << attempt.min(16));
+            log::info!("Step '{}': click didn't take, retry {}/{} in {} ms", self.step.name, attempt + 1, verify.retries, backoff);
+            ctx.progress.lock().retried();
+            ctx.sleep_ms(backoff);
+            if !ctx.is_running() { return Ok(()); }
+            self.click(ctx)?;
+        }
+        Err(StepError::CheckFailed(format!("still failing after {} retries", verify.retries)))
+    }
+
+    /// Apply the step's error policy: try again while retries are left, then give up and branch.
+    fn fail(&mut self, err: StepError, ctx: &mut StepCtx) -> StepOutcome {
+        let OnError { retries, then } = self.step.on_error;
+        if self.retried < retries {
+            self.retried += 1;
+            log::warn!("Step '{}': {}; retry {}/{}", self.step.name, err, self.retried, retries);
+            ctx.progress.lock().retried();
+            ctx.sleep_ms(ERROR_RETRY_MS);
+            return StepOutcome::Continue;
+        }
+        let msg = format!("Step '{}' failed: {}", self.step.name, err);
+        log::error!("{}; {}", msg, then.label());
+        ctx.record(DryRunEvent::Failed(err.to_string()));
+        let mut p = ctx.progress.lock();
+        p.failed_steps += 1;
+        p.last_error = Some(msg);
+        p.step_finished = true;
+        drop(p);
+        ctx.fire(Event::Error);
+        branch_outcome(then)
+    }
+}
+
+/// Whether the screen shows what `verify` expects. `image` caches the verify image.
+pub fn check(verify: &Verify, image: &mut Option<Capture>) -> Result<bool, String> {
+    match verify.check {
+        Check::Pixel { x, y, color } => Ok(color_distance(screenshot::pixel(x, y)?, color) <= verify.tolerance),
+        Check::Image { region } => {
+            if image.is_none() { *image = Some(screenshot::load_png(Path::new(&verify.image))?); }
+            let screen = screenshot::capture(Some(region))?;
+            Ok(image.as_ref().is_some_and(|t| template::find(&screen, t, verify.tolerance).is_some()))
+        }
+    }
+}
+
+/// Largest difference between the channels of two colors; a pixel check passes when it's
+/// within the tolerance.
+pub fn color_distance(a: [u8; 3], b: [u8; 3]) -> u8 {
+    a.iter().zip(b).map(|(x, y)| x.abs_diff(y)).max().unwrap_or(0)
+}
+
+/// The runner outcome for a sequence branch.
+fn branch_outcome(branch: Branch) -> StepOutcome {
+    match branch {
+        Branch::Continue => StepOutcome::Next,
+        Branch::SkipNext => StepOutcome::SkipNext,
+        Branch::Abort => StepOutcome::Stop,
+        Branch::Jump(n) => StepOutcome::Jump(n),
+    }
+}
+
+impl Step for ClickStep {
+    fn name(&self) -> &str { &self.step.name }
+
+    fn reset(&mut self) {
+        self.done = 0;
+        self.retried = 0;
+    }
+
+    fn execute(&mut self, ctx: &mut StepCtx) -> StepOutcome {
+        if self.done >= self.step.clicks { return StepOutcome::Next; }
+        if let Err(e) = self.click(ctx) { return self.fail(e, ctx); }
+        if let Some(verify) = self.step.verify.clone() {
+            if let Err(e) = self.verify(&verify, ctx) { return self.fail(e, ctx); }
+        }
+        let (min_secs, max_secs) = (self.step.min_secs, self.step.max_secs);
+        self.done += 1;
+        if self.done >= self.step.clicks { ctx.progress.lock().step_finished = true; }
+        ctx.sleep_between(min_secs, max_secs);
+        StepOutcome::Continue
+    }
+
+    fn weight(&self) -> f32 { self.step.weight }
+
+    fn turns(&self) -> u32 { self.step.clicks }
+
+    /// A single click (after any retries) and the step's interval.
+    fn turn(&mut self, ctx: &mut StepCtx) -> StepOutcome {
+        self.reset();
+        while ctx.is_running() {
+            match self.execute(ctx) {
+                StepOutcome::Continue if self.done > 0 => break,
+                StepOutcome::Continue => {}
+                outcome => return outcome,
+            }
+        }
+        StepOutcome::Continue
+    }
+}
+
+/// Sequence mode: watch the step's bounds until its template image appears or the timeout
+/// runs out, then branch.
+pub struct WaitImageStep {
+    step: SequenceStep,
+    wait: WaitImage,
+    started: Option<Instant>,
+    template: Option<Capture>,
+}
+
+impl WaitImageStep {
+    pub fn new(step: SequenceStep, wait: WaitImage) -> Self { Self { step, wait, started: None, template: None } }
+
+    fn branch(&self, branch: Branch, ctx: &mut StepCtx) -> StepOutcome {
+        let mut p = ctx.progress.lock();
+        p.waiting_for = None;
+        p.step_finished = true;
+        branch_outcome(branch)
+    }
+}
+
+impl Step for WaitImageStep {
+    fn name(&self) -> &str { &self.step.name }
+
+    fn reset(&mut self) { self.started = None; }
+
+    fn weight(&self) -> f32 { self.step.weight }
+
+    fn execute(&mut self, ctx: &mut StepCtx) -> StepOutcome {
+        if ctx.dry_run.is_some() {
+            ctx.record(DryRunEvent::Assumed("image found".to_string()));
+            return self.branch(self.wait.on_found, ctx);
+        }
+        let image = self.step.image.clone().unwrap_or_default();
+        let started = *self.started.get_or_insert_with(Instant::now);
+        if self.template.is_none() {
+            match screenshot::load_png(Path::new(&image)) {
+                Ok(t) => self.template = Some(t),
+                Err(e) => {
+                    log::error!("Step '{}': {}", self.step.name, e);
+                    ctx.sleep_ms(IMAGE_POLL_MS); // don't spin if the branch loops back here
+                    return self.branch(self.wait.on_timeout, ctx);
+                }
+            }
+        }
+        let template = self.template.as_ref().expect("loaded above");
+        match screenshot::capture(Some(self.step.bounds)) {
+            Ok(screen) if template::find(&screen, template, self.wait.tolerance).is_some() => {
+                log::info!("Step '{}': image found after {:.1}s", self.step.name, started.elapsed().as_secs_f32());
+                return self.branch(self.wait.on_found, ctx);
+            }
+            Ok(_) => {}
+            Err(e) => {
+                log::error!("Step '{}': {}", self.step.name, e);
+                ctx.sleep_ms(IMAGE_POLL_MS);
+                return self.branch(self.wait.on_timeout, ctx);
+            }
+        }
+        if started.elapsed() >= Duration::from_secs(self.wait.timeout_secs as u64) {
+            log::warn!("Step '{}': image not found within {}s", self.step.name, self.wait.timeout_secs);
+            return self.branch(self.wait.on_timeout, ctx);
+        }
+        ctx.progress.lock().waiting_for = Some(format!("image {}", self.step.name));
+        ctx.sleep_ms(IMAGE_POLL_MS);
+        StepOutcome::Continue
+    }
+}
+
+/// Split `b` into `cols` × `rows` cells, row by row (top to bottom).
+/// With `serpentine`, every other row runs right-to-left.
+pub fn grid_cells(b: Bounds, cols: u32, rows: u32, serpentine: bool) -> Vec<Bounds> {
+    let cols = cols.max(1) as i64;
+    let rows = rows.max(1) as i64;
+    // edge i of n, spread evenly over [min, max]; the last cell ends exactly at max
+    let edge = |min: i32, max: i32, i: i64, n: i64| (min as i64 + (max as i64 - min as i64) * i / n) as i32;
+    let span = |min: i32, max: i32, i: i64, n: i64| {
+        let lo = edge(min, max, i, n);
+        let hi = if i + 1 == n { max } else { (edge(min, max, i + 1, n) - 1).max(lo) };
+        (lo, hi)
+    };
+
+    let mut cells = Vec::with_capacity((cols * rows) as usize);
+    for r in 0..rows {
+        let (min_y, max_y) = span(b.min_y, b.max_y, r, rows);
+        let row: Vec<Bounds> = (0..cols)
+            .map(|c| {
+                let (min_x, max_x) = span(b.min_x, b.max_x, c, cols);
+                Bounds { min_x, max_x, min_y, max_y }
+            })
+            .collect();
+        if serpentine && r % 2 == 1 {
+            cells.extend(row.into_iter().rev());
+        } else {
+            cells.extend(row);
+        }
+    }
+    cells
+}
+
+/// Grid sweep mode: one click at a random point of every grid cell.
+pub struct GridSweepStep {
+    cells: Vec<Bounds>,
+    next: usize,
+}
+
+impl GridSweepStep {
+    pub fn new(bounds: Option<Bounds>, grid: GridSweep) -> Self {
+        let cells = match bounds {
+            Some(b) if b.is_valid() => grid_cells(b, grid.cols, grid.rows, grid.serpentine),
+            _ => Vec::new(),
+        };
+        Self { cells, next: 0 }
+    }
+}
+
+impl Step for GridSweepStep {
+    fn name(&self) -> &str { "Grid sweep" }
+
+    fn reset(&mut self) { self.next = 0; }
+
+    fn execute(&mut self, ctx: &mut StepCtx) -> StepOutcome {
+        let Some(&cell) = self.cells.get(self.next) else { return StepOutcome::Next; };
+        self.next += 1;
+
+        // button and timing stay live like Single mode; the cells are fixed at start
+        let (button, min_secs, max_secs) = {
+            let cfg = ctx.config.lock();
+            (cfg.button, cfg.min_secs, cfg.max_secs)
+        };
+        ctx.click_in(cell, &[], button);
+        ctx.sleep_between(min_secs, max_secs);
+        StepOutcome::Continue
+    }
+}
+
+/// Anti-AFK mode: no clicks, just idle so [`StepCtx::sleep_ms`] can send the key taps.
+pub struct KeyTapStep;
+
+impl Step for KeyTapStep {
+    fn name(&self) -> &str { "Anti-AFK" }
+
+    fn execute(&mut self, ctx: &mut StepCtx) -> StepOutcome {
+        ctx.sleep_ms(500);
+        StepOutcome::Continue
+    }
+}
+
+/// Show step `i` of `step_count` as the running one in cycle `cycle` (0-based) of `cycles`.
+fn enter_step(ctx: &mut StepCtx, i: usize, step_count: usize, name: &str, (cycle, cycles): (u32, Option<u32>)) {
+    {
+        let mut p = ctx.progress.lock();
+        p.switch_step(i, Instant::now());
+        p.step_count = step_count;
+        p.step_name = name.to_string();
+        p.cycle = cycle + 1;
+        p.cycles = cycles;
+        p.step_finished = false;
+    }
+    ctx.record(DryRunEvent::Step);
+}
+
+/// One interleaved cycle: an `execute` (one click, for click steps) of every unfinished step in
+/// turn, starting at step `first`, until all of them are finished. Branches finish the step that
+/// took them; skip, repeat and jump commands act on the rotation. Returns false if the job ended.
+fn run_interleaved(steps: &mut [Box<dyn Step>], first: usize, cycle: (u32, Option<u32>), ctx: &mut StepCtx) -> bool {
+    let step_count = steps.len();
+    steps.iter_mut().for_each(|s| s.reset());
+    let mut finished = vec![false; step_count];
+    let mut i = first.min(step_count - 1);
+    while finished.contains(&false) {
+        if finished[i] {
+            i = (i + 1) % step_count;
+            continue;
+        }
+        if !ctx.is_running() { return false; }
+        let step = &mut steps[i];
+        log::debug!("Interleaved turn of step '{}' (cycle {})", step.name(), cycle.0 + 1);
+        enter_step(ctx, i, step_count, step.name(), cycle);
+        ctx.refocus();
+        let command = ctx.config.lock().step_command.take();
+        match command {
+            Some(StepCommand::Skip) => {
+                log::info!("Skipping step '{}'", step.name());
+                finished[i] = true;
+            }
+            Some(StepCommand::Repeat) => {
+                log::info!("Repeating step '{}'", step.name());
+                step.reset();
+            }
+            Some(StepCommand::Jump(n)) => {
+                i = n.min(step_count - 1);
+                log::info!("Jumping to step {}", i + 1);
+                steps[i].reset();
+                finished[i] = false;
+                continue;
+            }
+            None => match step.execute(ctx) {
+                StepOutcome::Continue => {}
+                StepOutcome::Stop => {
+                    ctx.record(DryRunEvent::Stopped(format!("step '{}' ended the job", step.name())));
+                    return false;
+                }
+                StepOutcome::Next | StepOutcome::SkipNext | StepOutcome::Jump(_) => finished[i] = true,
+            },
+        }
+        i = (i + 1) % step_count;
+    }
+    true
+}
+
+/// One weighted cycle: as many turns as the steps' [`Step::turns`] add up to, each given to a
+/// step drawn by [`Step::weight`] (see [`Step::turn`]). A skip command passes on the next draw,
+/// repeat gives the next turn to the last step again and jump to the chosen one. Returns false
+/// if the job ended.
+fn run_weighted(steps: &mut [Box<dyn Step>], cycle: (u32, Option<u32>), ctx: &mut StepCtx) -> bool {
+    let step_count = steps.len();
+    let weights: Vec<f32> = steps.iter().map(|s| if s.turns() > 0 { s.weight().max(0.0) } else { 0.0 }).collect();
+    let turns: u32 = steps.iter().zip(&weights).filter(|(_, &w)| w > 0.0).map(|(s, _)| s.turns()).sum();
+    if turns == 0 {
+        log::error!("Weighted order: no step has both a weight and clicks");
+        ctx.record(DryRunEvent::Stopped("no step to draw".to_string()));
+        ctx.running.store(false, Ordering::Relaxed);
+        return false;
+    }
+    let mut last = None;
+    let mut forced = None;
+    for _ in 0..turns {
+        if !ctx.is_running() { return false; }
+        let command = ctx.config.lock().step_command.take();
+        match command {
+            Some(StepCommand::Skip) => {
+                log::info!("Skipping a weighted draw");
+                continue;
+            }
+            Some(StepCommand::Repeat) => forced = last,
+            Some(StepCommand::Jump(n)) => forced = Some(n.min(step_count - 1)),
+            None => {}
+        }
+        let Some(i) = forced.take().or_else(|| pick_weighted(&weights, &mut ctx.rng)) else { break };
+        last = Some(i);
+        let step = &mut steps[i];
+        log::debug!("Weighted turn of step '{}' (cycle {})", step.name(), cycle.0 + 1);
+        enter_step(ctx, i, step_count, step.name(), cycle);
+        ctx.refocus();
+        if step.turn(ctx) == StepOutcome::Stop {
+            ctx.record(DryRunEvent::Stopped(format!("step '{}' ended the job", step.name())));
+            return false;
+        }
+    }
+    true
+}
+
+/// The cycles a sequence runs, as 0-based cycle numbers: from the cycles already done up to
+/// `cycles` (`None` = forever). `Some(1)` runs exactly one cycle and `Some(0)` none.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CycleSchedule {
+    next: u32,
+    cycles: Option<u32>,
+}
+
+impl CycleSchedule {
+    /// `done` cycles are counted as already run, e.g. when continuing a stopped sequence.
+    pub fn new(cycles: Option<u32>, done: u32) -> Self { Self { next: done, cycles } }
+
+    pub fn cycles(&self) -> Option<u32> { self.cycles }
+
+    /// Change the total, e.g. after the profile was reloaded; the cycles run so far still count.
+    pub fn set_cycles(&mut self, cycles: Option<u32>) { self.cycles = cycles; }
+}
+
+impl Iterator for CycleSchedule {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        if self.cycles.is_some_and(|n| self.next >= n) { return None; }
+        let cycle = self.next;
+        // an endless schedule stays on its last number rather than ending
+        self.next = self.next.saturating_add(1);
+        Some(cycle)
+    }
+}
+
+/// Run `steps` in order, `cycles` times (`None` = until stopped), starting at the config's
+/// `start_at`. The config's `step_order` can have the steps take turns instead (see
+/// [`run_interleaved`] and [`run_weighted`]). When the config is marked as reloaded, the steps are rebuilt from it
+/// between cycles.
+pub fn run_steps(steps: &mut Vec<Box<dyn Step>>, cycles: Option<u32>, ctx: &mut StepCtx) {
+    if steps.is_empty() { return; }
+    let mut step_count = steps.len();
+    let (first_step, done, mut step_clicks) = {
+        let cfg = ctx.config.lock();
+        let carried = cfg.checkpoint.as_ref().map(|(_, c)| c.step_clicks.clone()).unwrap_or_default();
+        (cfg.start_at.0, cfg.start_at.1, carried)
+    };
+    step_clicks.resize(step_count, 0);
+    {
+        let mut p = ctx.progress.lock();
+        p.step_names = steps.iter().map(|s| s.name().to_string()).collect();
+        p.step_clicks = step_clicks;
+        p.step_stats = vec![StepStats::default(); step_count];
+    }
+    let mut first_step = first_step.min(step_count - 1);
+    if first_step > 0 || done > 0 {
+        log::info!("Starting at step {}, cycle {}", first_step + 1, done + 1);
+    }
+    let mut schedule = CycleSchedule::new(cycles, done);
+    'job: while let Some(cycle) = schedule.next() {
+        let mut i = std::mem::take(&mut first_step);
+        let order = ctx.config.lock().step_order;
+        let ran = match order {
+            StepOrder::InOrder => None,
+            StepOrder::Interleave => Some(run_interleaved(steps, i, (cycle, schedule.cycles()), ctx)),
+            StepOrder::Weighted => Some(run_weighted(steps, (cycle, schedule.cycles()), ctx)),
+        };
+        match ran {
+            Some(false) => break 'job,
+            Some(true) => i = step_count,
+            None => {}
+        }
+        while let Some(step) = steps.get_mut(i) {
+            log::info!("Running step '{}' (cycle {})", step.name(), cycle + 1);
+            enter_step(ctx, i, step_count, step.name(), (cycle, schedule.cycles()));
+            step.reset();
+            ctx.refocus();
+            i = loop {
+                if !ctx.is_running() { break 'job; }
+                let command = ctx.config.lock().step_command.take();
+                match command {
+                    Some(StepCommand::Skip) => {
+                        log::info!("Skipping step '{}'", step.name());
+                        break i + 1;
+                    }
+                    Some(StepCommand::Repeat) => {
+                        log::info!("Repeating step '{}'", step.name());
+                        break i;
+                    }
+                    Some(StepCommand::Jump(n)) => {
+                        let n = n.min(step_count - 1);
+                        log::info!("Jumping to step {}", n + 1);
+                        break n;
+                    }
+                    None => {}
+                }
+                match step.execute(ctx) {
+                    StepOutcome::Continue => {}
+                    StepOutcome::Next => break i + 1,
+                    StepOutcome::SkipNext => break i + 2,
+                    StepOutcome::Jump(n) => break n,
+                    StepOutcome::Stop => {
+                        ctx.record(DryRunEvent::Stopped(format!("step '{}' ended the job", step.name())));
+                        break 'job;
+                    }
+                }
+            };
+        }
+        ctx.record(DryRunEvent::CycleEnd);
+        ctx.save_checkpoint(cycle + 1);
+        ctx.fire(Event::Cycle);
+        let reloaded = {
+            let mut cfg = ctx.config.lock();
+            std::mem::take(&mut cfg.reload).then(|| crate::build_steps(&cfg))
+        };
+        if let Some((rebuilt, new_cycles)) = reloaded.filter(|(s, _)| !s.is_empty()) {
+            log::info!("Running the reloaded profile from cycle {}", cycle + 2);
+            *steps = rebuilt;
+            step_count = steps.len();
+            schedule.set_cycles(new_cycles);
+            let mut p = ctx.progress.lock();
+            p.step_names = steps.iter().map(|s| s.name().to_string()).collect();
+            p.step_clicks.resize(step_count, 0);
+            p.step_stats.resize(step_count, StepStats::default());
+        }
+    }
+}
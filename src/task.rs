@@ -0,0 +1,151 @@
+//! `install-task`: have the system start the headless runner (`--daemon --profile <file>
+//! --autostart`) at logon or at a time each day: a Task Scheduler task on Windows, a systemd
+//! user unit (plus a timer for daily starts) elsewhere.
+
+use std::path::{Path, PathBuf};
+
+use crate::quiet::format_time;
+
+/// When the system starts the runner.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Schedule {
+    Logon,
+    /// Every day at this many minutes after midnight.
+    Daily(u16),
+}
+
+impl Schedule {
+    pub fn label(self) -> String {
+        match self {
+            Schedule::Logon => "at logon".to_string(),
+            Schedule::Daily(minute) => format!("daily at {}", format_time(minute)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TaskSpec {
+    /// Task or unit name, e.g. `area_clicker-Farm`.
+    pub name: String,
+    pub exe: PathBuf,
+    pub profile: PathBuf,
+    pub schedule: Schedule,
+    /// Control socket for the runner; `None` = the default one.
+    pub socket: Option<PathBuf>,
+    /// Pass `--portable`, so the runner reads the same config folder.
+    pub portable: bool,
+}
+
+impl TaskSpec {
+    /// The runner's arguments.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = vec!["--daemon".to_string(), "--profile".to_string(), self.profile.display().to_string(), "--autostart".to_string()];
+        if let Some(socket) = &self.socket {
+            args.push("--socket".to_string());
+            args.push(socket.display().to_string());
+        }
+        if self.portable { args.push("--portable".to_string()); }
+        args
+    }
+
+    /// `schtasks` arguments that create (or replace) the task.
+    pub fn schtasks_args(&self) -> Vec<String> {
+        let command = std::iter::once(&self.exe.display().to_string()).chain(&self.args()).map(|a| quote_windows(a)).collect::<Vec<_>>().join(" ");
+        let mut args = vec!["/Create".to_string(), "/F".to_string(), "/TN".to_string(), self.name.clone(), "/TR".to_string(), command];
+        match self.schedule {
+            Schedule::Logon => args.extend(["/SC", "ONLOGON"].map(str::to_string)),
+            Schedule::Daily(minute) => args.extend(["/SC".to_string(), "DAILY".to_string(), "/ST".to_string(), format_time(minute)]),
+        }
+        args
+    }
+
+    /// File names and contents of the systemd user units: a service, plus a timer for daily
+    /// starts.
+    pub fn systemd_units(&self) -> Vec<(String, String)> {
+        let exec = std::iter::once(&self.exe.display().to_string()).chain(&self.args()).map(|a| quote_systemd(a)).collect::<Vec<_>>().join(" ");
+        let install = match self.schedule {
+            Schedule::Logon => "\n[Install]\nWantedBy=default.target\n",
+            Schedule::Daily(_) => "",
+        };
+        let service = format!(
+            "[Unit]\nDescription=Area Clicker profile runner ({})\nAfter=graphical-session.target\n\n\
+             [Service]\nExecStart={}\nRestart=no\n{}",
+            self.schedule.label(), exec, install,
+        );
+        let mut units = vec![(format!("{}.service", self.name), service)];
+        if let Schedule::Daily(minute) = self.schedule {
+            let timer = format!(
+                "[Unit]\nDescription=Start {}.service daily at {}\n\n[Timer]\nOnCalendar=*-*-* {}:00\nPersistent=true\n\n[Install]\nWantedBy=timers.target\n",
+                self.name, format_time(minute), format_time(minute),
+            );
+            units.push((format!("{}.timer", self.name), timer));
+        }
+        units
+    }
+
+    /// The unit that gets enabled.
+    pub fn systemd_enable(&self) -> String {
+        match self.schedule {
+            Schedule::Logon => format!("{}.service", self.name),
+            Schedule::Daily(_) => format!("{}.timer", self.name),
+        }
+    }
+}
+
+/// `area_clicker-<profile>`, with anything but letters, digits, `-` and `_` made `_`.
+pub fn default_name(profile: &str) -> String {
+    let clean: String = profile.chars().map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect();
+    format!("area_clicker-{}", clean)
+}
+
+/// Register the task, or with `print_only` just show what would be registered. Returns what
+/// to tell the user.
+pub fn install(spec: &TaskSpec, print_only: bool) -> Result<String, String> {
+    if cfg!(windows) {
+        let args = spec.schtasks_args();
+        if print_only { return Ok(format!("schtasks {}", args.iter().map(|a| quote_windows(a)).collect::<Vec<_>>().join(" "))); }
+        run("schtasks", &args)?;
+        return Ok(format!("Registered task '{}' ({})", spec.name, spec.schedule.label()));
+    }
+    let units = spec.systemd_units();
+    if print_only {
+        return Ok(units.iter().map(|(file, text)| format!("# {}\n{}", file, text)).collect::<Vec<_>>().join("\n"));
+    }
+    let dir = dirs::config_dir().ok_or("No config directory found")?.join("systemd").join("user");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Could not create {}: {}", dir.display(), e))?;
+    for (file, text) in &units {
+        let path = dir.join(file);
+        std::fs::write(&path, text).map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    }
+    let enable = spec.systemd_enable();
+    run("systemctl", &["--user".to_string(), "daemon-reload".to_string()])?;
+    run("systemctl", &["--user".to_string(), "enable".to_string(), enable.clone()])?;
+    Ok(format!("Installed {} in {} ({}); it's enabled and starts next time", enable, dir.display(), spec.schedule.label()))
+}
+
+fn run(program: &str, args: &[String]) -> Result<(), String> {
+    let out = std::process::Command::new(program).args(args).output().map_err(|e| format!("Could not run {}: {}", program, e))?;
+    if out.status.success() { return Ok(()); }
+    Err(format!("{} failed ({}): {}", program, out.status, String::from_utf8_lossy(&out.stderr).trim()))
+}
+
+/// A saved profile's file, or `arg` itself if it's a file.
+pub fn resolve_profile(arg: &str, saved: impl Fn(&str) -> Option<PathBuf>) -> Result<PathBuf, String> {
+    let path = Path::new(arg);
+    if path.is_file() { return std::path::absolute(path).map_err(|e| e.to_string()); }
+    saved(arg).filter(|p| p.is_file()).ok_or_else(|| format!("no saved profile or profile file '{}'", arg))
+}
+
+fn quote_windows(arg: &str) -> String {
+    if arg.is_empty() || arg.contains([' ', '\t', '"']) { format!("\"{}\"", arg.replace('"', "\\\"")) } else { arg.to_string() }
+}
+
+/// `%` starts a specifier in unit files, quoted or not.
+fn quote_systemd(arg: &str) -> String {
+    let arg = arg.replace('%', "%%");
+    if arg.is_empty() || arg.contains([' ', '\t', '"', '\\', '\'']) {
+        format!("\"{}\"", arg.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        arg
+    }
+}
@@ -0,0 +1,116 @@
+//! Template matching: find where a reference image (e.g. a cropped screenshot of a button)
+//! shows up inside a capture of the screen.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::screenshot::Capture;
+
+/// Share of template pixels allowed to differ, for anti-aliasing and cursor blink noise.
+const MAX_MISMATCH: f32 = 0.02;
+
+/// Side of the pixel blocks whose sums are compared before any single pixel.
+const BLOCK: usize = 4;
+
+/// Top-left corner of the first spot (row by row) where `needle` appears in `haystack`.
+/// A pixel matches when each channel is within `tolerance`; up to [`MAX_MISMATCH`] of them
+/// may be off.
+///
+/// Checking every pixel at every spot is too slow for a region polled twice a second, so most
+/// spots are ruled out by [`BLOCK`]×[`BLOCK`] block sums first (see [`Blocks::rule_out`]).
+/// Those never rule out a match, so the answer is the same.
+pub fn find(haystack: &Capture, needle: &Capture, tolerance: u8) -> Option<(u32, u32)> {
+    let (hw, hh, nw, nh) = (haystack.width as usize, haystack.height as usize, needle.width as usize, needle.height as usize);
+    if nw == 0 || nh == 0 || nw > hw || nh > hh { return None; }
+    let allowed = (nw * nh) as f32 * MAX_MISMATCH;
+    let pixels = distinct_first(needle.rgb.chunks_exact(3).enumerate().map(|(i, p)| {
+        (i % nw, i / nw, [p[0] as u16, p[1] as u16, p[2] as u16])
+    }).collect());
+    let blocks = (nw >= BLOCK && nh >= BLOCK && tolerance < u8::MAX).then(|| Blocks::new(haystack, needle, tolerance));
+    let tolerance = tolerance as u16;
+    for y in 0..=hh - nh {
+        for x in 0..=hw - nw {
+            if blocks.as_ref().is_some_and(|b| b.rule_out((x, y), allowed)) { continue; }
+            let mut off = 0;
+            let matched = pixels.iter().all(|&(nx, ny, color)| {
+                let i = ((y + ny) * hw + x + nx) * 3;
+                if (0..3).any(|c| (haystack.rgb[i + c] as u16).abs_diff(color[c]) > tolerance) { off += 1; }
+                off as f32 <= allowed
+            });
+            if matched { return Some((x as u32, y as u32)); }
+        }
+    }
+    None
+}
+
+/// `cells` (x, y, color), the ones least like the most common color first: those rule a
+/// wrong spot out fastest, e.g. where a button was cropped with some background and the
+/// screen is mostly that background.
+fn distinct_first(mut cells: Vec<(usize, usize, [u16; 3])>) -> Vec<(usize, usize, [u16; 3])> {
+    let mut counts = HashMap::new();
+    for &(_, _, color) in &cells { *counts.entry(color).or_insert(0) += 1; }
+    if let Some((common, _)) = counts.into_iter().max_by_key(|&(_, n)| n) {
+        cells.sort_by_key(|&(_, _, color)| Reverse((0..3).map(|c| color[c].abs_diff(common[c])).max()));
+    }
+    cells
+}
+
+/// The needle cut into [`BLOCK`]×[`BLOCK`] blocks, and the sums of every such block of the
+/// haystack, by top-left corner.
+struct Blocks {
+    /// (x, y, per-channel sum) of the needle's blocks, in [`distinct_first`] order.
+    needle: Vec<(usize, usize, [u16; 3])>,
+    sums: Vec<[u16; 3]>,
+    /// Corners per row in `sums`.
+    width: usize,
+    tolerance: u16,
+}
+
+impl Blocks {
+    fn new(haystack: &Capture, needle: &Capture, tolerance: u8) -> Self {
+        let (sums, width) = (block_sums(needle), needle.width as usize - BLOCK + 1);
+        let (cols, rows) = (needle.width as usize / BLOCK, needle.height as usize / BLOCK);
+        let cells = (0..rows).flat_map(|y| (0..cols).map(move |x| (x * BLOCK, y * BLOCK)));
+        let needle = distinct_first(cells.map(|(x, y)| (x, y, sums[y * width + x])).collect());
+        Self { needle, sums: block_sums(haystack), width: haystack.width as usize - BLOCK + 1, tolerance: tolerance as u16 }
+    }
+
+    /// Whether the needle can't be at `(x, y)`. A block whose sum is off by `d` more than
+    /// `tolerance` per pixel allows holds at least `d / (255 - tolerance)` pixels that are
+    /// off, so once those add up to more than `allowed`, no pixel needs checking.
+    fn rule_out(&self, (x, y): (usize, usize), allowed: f32) -> bool {
+        let slack = (BLOCK * BLOCK) as u16 * self.tolerance;
+        let per_pixel = 255 - self.tolerance;
+        let mut off = 0;
+        for &(bx, by, want) in &self.needle {
+            let got = self.sums[(y + by) * self.width + x + bx];
+            let excess = (0..3).map(|c| got[c].abs_diff(want[c]).saturating_sub(slack)).max().unwrap_or(0);
+            off += excess.div_ceil(per_pixel);
+            if off as f32 > allowed { return true; }
+        }
+        false
+    }
+}
+
+/// Per-channel sums of the [`BLOCK`]×[`BLOCK`] block at every top-left corner with room for
+/// one, row by row.
+fn block_sums(image: &Capture) -> Vec<[u16; 3]> {
+    let (w, h) = (image.width as usize, image.height as usize);
+    let cols = w - BLOCK + 1;
+    let mut across = vec![[0u16; 3]; cols * h];
+    for y in 0..h {
+        for x in 0..cols {
+            let row = (y * w + x) * 3;
+            for p in image.rgb[row..row + BLOCK * 3].chunks_exact(3) {
+                for (sum, &v) in across[y * cols + x].iter_mut().zip(p) { *sum += v as u16; }
+            }
+        }
+    }
+    let mut sums = vec![[0u16; 3]; cols * (h - BLOCK + 1)];
+    for (i, sum) in sums.iter_mut().enumerate() {
+        for dy in 0..BLOCK {
+            for c in 0..3 { sum[c] += across[i + dy * cols][c]; }
+        }
+    }
+    sums
+}
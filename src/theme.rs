@@ -0,0 +1,58 @@
+//! Light/dark theme and accent color, picked in the top bar and kept in `theme.json`.
+
+use eframe::egui::{self, Color32, Visuals};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThemeMode {
+    /// Follow the desktop's light/dark setting (dark if it can't be read).
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl ThemeMode {
+    pub const ALL: [ThemeMode; 3] = [ThemeMode::System, ThemeMode::Light, ThemeMode::Dark];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeMode::System => "System",
+            ThemeMode::Light => "Light",
+            ThemeMode::Dark => "Dark",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    pub mode: ThemeMode,
+    /// Selection and link color; `None` keeps egui's blue.
+    pub accent: Option<[u8; 3]>,
+}
+
+impl Theme {
+    /// Whether to draw dark, given the desktop's theme (if known).
+    pub fn is_dark(&self, system: Option<eframe::Theme>) -> bool {
+        match self.mode {
+            ThemeMode::System => system != Some(eframe::Theme::Light),
+            ThemeMode::Light => false,
+            ThemeMode::Dark => true,
+        }
+    }
+
+    pub fn visuals(&self, system: Option<eframe::Theme>) -> Visuals {
+        let mut visuals = if self.is_dark(system) { Visuals::dark() } else { Visuals::light() };
+        if let Some([r, g, b]) = self.accent {
+            let accent = Color32::from_rgb(r, g, b);
+            visuals.selection.bg_fill = accent;
+            visuals.hyperlink_color = accent;
+            visuals.widgets.hovered.bg_stroke.color = accent;
+            visuals.widgets.active.bg_stroke.color = accent;
+        }
+        visuals
+    }
+
+    pub fn apply(&self, ctx: &egui::Context, system: Option<eframe::Theme>) { ctx.set_visuals(self.visuals(system)); }
+}
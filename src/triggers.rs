@@ -0,0 +1,179 @@
+//! External triggers: buttons on a MIDI controller mapped to actions, so a pad or a Stream
+//! Deck (through a MIDI bridge, or by running `area_clicker send …` from a key) can start a
+//! profile, stop the job or fire a single step. The actions are carried out as control socket
+//! commands (see [`crate::daemon`]).
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+
+use crate::voice::Voice;
+
+/// What a trigger does.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TriggerAction {
+    /// Load the named profile and start it (stopping whatever runs).
+    StartProfile(String),
+    Stop,
+    /// Run the named step of the loaded profile once, job or no job.
+    RunStep(String),
+}
+
+impl TriggerAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TriggerAction::StartProfile(_) => "Start profile",
+            TriggerAction::Stop => "Stop",
+            TriggerAction::RunStep(_) => "Run step",
+        }
+    }
+
+    /// The control socket command that carries the action out.
+    pub fn command(&self) -> String {
+        match self {
+            TriggerAction::StartProfile(name) => format!("start {}", name),
+            TriggerAction::Stop => "stop".to_string(),
+            TriggerAction::RunStep(name) => format!("step {}", name),
+        }
+    }
+}
+
+/// A MIDI note mapped to an action.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MidiBinding {
+    /// 1–16; `None` = any channel.
+    pub channel: Option<u8>,
+    /// 0–127, e.g. 36 for the first pad of many controllers.
+    pub note: u8,
+    pub action: TriggerAction,
+}
+
+impl MidiBinding {
+    pub fn matches(&self, note: Note) -> bool {
+        self.note == note.note && self.channel.is_none_or(|c| c == note.channel)
+    }
+}
+
+/// App-wide trigger settings.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Triggers {
+    /// Serve the control socket while the window is open, for `area_clicker send`.
+    pub remote: bool,
+    /// Raw MIDI device the notes are read from, e.g. `/dev/snd/midiC1D0`.
+    pub midi_device: Option<PathBuf>,
+    pub midi: Vec<MidiBinding>,
+    pub voice: Voice,
+}
+
+/// A pressed key of a MIDI controller: a note-on with a velocity above 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Note {
+    /// 1–16.
+    pub channel: u8,
+    pub note: u8,
+}
+
+/// Picks note-ons out of a raw MIDI byte stream, running status included.
+#[derive(Default)]
+pub struct MidiParser {
+    status: Option<u8>,
+    data: Vec<u8>,
+}
+
+impl MidiParser {
+    /// Feed one byte; returns a note once its message is complete.
+    pub fn push(&mut self, byte: u8) -> Option<Note> {
+        match byte {
+            // real-time messages may turn up anywhere and leave the running status alone
+            0xF8..=0xFF => return None,
+            0x80..=0xEF => {
+                self.status = Some(byte);
+                self.data.clear();
+                return None;
+            }
+            // system messages (sysex and the rest) cancel the running status
+            0xF0..=0xF7 => {
+                self.status = None;
+                self.data.clear();
+                return None;
+            }
+            _ => {}
+        }
+        let status = self.status?;
+        self.data.push(byte);
+        let len = if matches!(status & 0xF0, 0xC0 | 0xD0) { 1 } else { 2 };
+        if self.data.len() < len { return None; }
+        let data = std::mem::take(&mut self.data);
+        (status & 0xF0 == 0x90 && data[1] > 0).then_some(Note { channel: (status & 0x0F) + 1, note: data[0] })
+    }
+}
+
+/// Raw MIDI devices found on this system (ALSA's on Linux); empty elsewhere.
+pub fn midi_devices() -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    for (dir, prefix) in [("/dev/snd", "midiC"), ("/dev", "midi")] {
+        let Ok(entries) = std::fs::read_dir(dir) else { continue };
+        found.extend(
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_name().to_str().is_some_and(|n| n.starts_with(prefix)))
+                .map(|e| e.path()),
+        );
+    }
+    found.sort();
+    found
+}
+
+/// Reads notes from a raw MIDI device on its own thread.
+pub struct MidiInput {
+    device: PathBuf,
+    notes: Arc<Mutex<Vec<Note>>>,
+    /// Cleared when the input is dropped; the thread ends after its next byte.
+    open: Arc<AtomicBool>,
+}
+
+impl MidiInput {
+    /// Open `device`; `wake` is called (from the reading thread) for every note.
+    pub fn open(device: &Path, wake: impl Fn() + Send + 'static) -> std::io::Result<Self> {
+        let mut file = std::fs::File::open(device)?;
+        let notes = Arc::new(Mutex::new(Vec::new()));
+        let open = Arc::new(AtomicBool::new(true));
+        {
+            let (notes, open, device) = (Arc::clone(&notes), Arc::clone(&open), device.to_path_buf());
+            std::thread::spawn(move || {
+                let mut parser = MidiParser::default();
+                let mut buf = [0u8; 64];
+                while open.load(Ordering::Relaxed) {
+                    let n = match file.read(&mut buf) {
+                        Ok(0) => break,
+                        Ok(n) => n,
+                        Err(e) => {
+                            log::error!("MIDI input {} failed: {}", device.display(), e);
+                            break;
+                        }
+                    };
+                    let found: Vec<Note> = buf[..n].iter().filter_map(|&b| parser.push(b)).collect();
+                    if !found.is_empty() && open.load(Ordering::Relaxed) {
+                        notes.lock().extend(found);
+                        wake();
+                    }
+                }
+                log::info!("Stopped reading MIDI from {}", device.display());
+            });
+        }
+        log::info!("Reading MIDI from {}", device.display());
+        Ok(Self { device: device.to_path_buf(), notes, open })
+    }
+
+    pub fn device(&self) -> &Path { &self.device }
+
+    /// Notes played since the last call, oldest first.
+    pub fn take(&self) -> Vec<Note> { std::mem::take(&mut *self.notes.lock()) }
+}
+
+impl Drop for MidiInput {
+    fn drop(&mut self) { self.open.store(false, Ordering::Relaxed); }
+}
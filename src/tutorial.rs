@@ -0,0 +1,56 @@
+//! Tutorial mode: highlights the widgets of a basic job one at a time (pick an area → set the
+//! interval → start) with a callout explaining each. Widgets report where they were drawn with
+//! [`Tutorial::mark`]; `AppState::tutorial_ui` paints the overlay on top of the finished frame.
+
+use eframe::egui::Rect;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage { PickArea, SetInterval, Start, Done }
+
+impl Stage {
+    /// Stages that point at a widget, in order.
+    pub const STEPS: [Stage; 3] = [Stage::PickArea, Stage::SetInterval, Stage::Start];
+
+    pub fn text(self) -> &'static str {
+        match self {
+            Stage::PickArea => "Click Pick, then drag a rectangle over the area to click (or click a single point).",
+            Stage::SetInterval => "Set how many seconds to wait between clicks. Each wait is drawn at random from this range. Click Next when you're happy with it.",
+            Stage::Start => "Press Start. Clicks begin right away; Stop (or the start/stop hotkey) ends them.",
+            Stage::Done => "That's all it takes. Profiles, sequences and the other options build on these three steps.",
+        }
+    }
+
+    pub fn next(self) -> Stage {
+        match self {
+            Stage::PickArea => Stage::SetInterval,
+            Stage::SetInterval => Stage::Start,
+            Stage::Start | Stage::Done => Stage::Done,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Tutorial {
+    pub stage: Stage,
+    /// Where the current stage's widget was drawn this frame, if it was.
+    pub target: Option<Rect>,
+}
+
+impl Default for Tutorial {
+    fn default() -> Self { Self { stage: Stage::PickArea, target: None } }
+}
+
+impl Tutorial {
+    /// Record `rect` as the highlight, if `stage` is the current one.
+    pub fn mark(&mut self, stage: Stage, rect: Rect) {
+        if self.stage == stage { self.target = Some(self.target.map_or(rect, |r| r.union(rect))); }
+    }
+
+    /// Move on, if `stage` is the current one (so late or repeated events don't skip ahead).
+    pub fn advance(&mut self, stage: Stage) {
+        if self.stage == stage {
+            self.stage = stage.next();
+            self.target = None;
+        }
+    }
+}
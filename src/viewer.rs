@@ -0,0 +1,208 @@
+//! Remote viewer: a JPEG thumbnail of the area the job is clicking, captured every few seconds
+//! and served over HTTP (`http://<address>/?token=…` shows it, refreshing itself;
+//! `/thumbnail.jpg?token=…` is the image) and/or written to a folder as `latest.jpg`, to check
+//! from another machine that the job still clicks the right thing. Anything but a loopback
+//! address needs a token, since the thumbnail shows part of the screen.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::human_mouse::Bounds;
+use crate::screenshot;
+
+/// Where the running job clicks, set with every click; `None` while no job runs.
+static TARGET: Mutex<Option<Bounds>> = parking_lot::const_mutex(None);
+
+pub fn set_target(target: Option<Bounds>) { *TARGET.lock() = target; }
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewerSettings {
+    pub enabled: bool,
+    /// Seconds between captures.
+    pub every_secs: u32,
+    /// Screen around the target that is captured too, px.
+    pub margin: i32,
+    /// Address the thumbnail is served on; `None` = not served. Only this machine can reach
+    /// `127.0.0.1`; any other address needs `token`.
+    pub listen: Option<String>,
+    /// Asked for in every request as `?token=…`; empty = none, which only a loopback address allows.
+    pub token: String,
+    /// Folder `latest.jpg` is written to; `None` = not saved.
+    pub folder: Option<PathBuf>,
+}
+
+impl Default for ViewerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false, every_secs: 5, margin: 20, listen: Some("127.0.0.1:8765".to_string()), token: String::new(),
+            folder: None,
+        }
+    }
+}
+
+/// A random token for [`ViewerSettings::token`].
+pub fn new_token() -> String {
+    use rand::distributions::{Alphanumeric, DistString};
+    Alphanumeric.sample_string(&mut rand::thread_rng(), 24)
+}
+
+/// JPEG quality of the thumbnail.
+const QUALITY: u8 = 80;
+
+/// The latest thumbnail, JPEG encoded.
+type Latest = Arc<Mutex<Option<Vec<u8>>>>;
+
+/// A running viewer: the capture thread and, if the thumbnail is served, the HTTP server.
+/// Dropping it stops both.
+pub struct Viewer {
+    stop: Arc<AtomicBool>,
+    server: Option<JoinHandle<()>>,
+    /// Where the server listens, once bound.
+    pub addr: Option<SocketAddr>,
+}
+
+impl Viewer {
+    pub fn start(settings: &ViewerSettings) -> Result<Self, String> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let latest = Latest::default();
+        let listener = match settings.listen.as_deref().map(str::trim).filter(|a| !a.is_empty()) {
+            Some(addr) => {
+                let listener = TcpListener::bind(addr).map_err(|e| format!("Could not listen on {}: {}", addr, e))?;
+                let local = listener.local_addr().map_err(|e| e.to_string())?;
+                if !local.ip().is_loopback() && settings.token.trim().is_empty() {
+                    return Err(format!("Serving on {} shows the screen to other machines; set a token first.", addr));
+                }
+                listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+                Some(listener)
+            }
+            None => None,
+        };
+        let addr = listener.as_ref().and_then(|l| l.local_addr().ok());
+        let server = listener.map(|listener| {
+            let (stop, latest) = (Arc::clone(&stop), Arc::clone(&latest));
+            let (refresh, token) = (settings.every_secs.max(1), settings.token.trim().to_string());
+            std::thread::spawn(move || serve(listener, &stop, &latest, refresh, &token))
+        });
+        {
+            let (stop, settings) = (Arc::clone(&stop), settings.clone());
+            std::thread::spawn(move || capture_loop(&settings, &stop, &latest));
+        }
+        if let Some(addr) = addr { log::info!("Serving the target thumbnail on http://{}/", addr); }
+        Ok(Self { stop, server, addr })
+    }
+}
+
+impl Drop for Viewer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // let go of the address before returning, so a restarted viewer can bind it again
+        if let Some(server) = self.server.take() { let _ = server.join(); }
+    }
+}
+
+/// Capture the target every `every_secs` until stopped.
+fn capture_loop(settings: &ViewerSettings, stop: &AtomicBool, latest: &Mutex<Option<Vec<u8>>>) {
+    let every = Duration::from_secs(settings.every_secs.max(1) as u64);
+    let mut next = Instant::now();
+    while !stop.load(Ordering::Relaxed) {
+        if Instant::now() < next {
+            std::thread::sleep(Duration::from_millis(100));
+            continue;
+        }
+        next = Instant::now() + every;
+        let Some(target) = *TARGET.lock() else { continue };
+        let area = Bounds {
+            min_x: target.min_x - settings.margin,
+            max_x: target.max_x + settings.margin,
+            min_y: target.min_y - settings.margin,
+            max_y: target.max_y + settings.margin,
+        };
+        let jpeg = match screenshot::capture(Some(area)).and_then(|c| screenshot::encode_jpeg(&c, QUALITY)) {
+            Ok(jpeg) => jpeg,
+            Err(e) => {
+                log::debug!("Could not capture the viewer thumbnail: {}", e);
+                continue;
+            }
+        };
+        if let Some(dir) = &settings.folder {
+            // written next to it first, so a reader never sees half an image
+            let tmp = dir.join("latest.jpg.tmp");
+            let written = std::fs::create_dir_all(dir)
+                .and_then(|_| std::fs::write(&tmp, &jpeg))
+                .and_then(|_| std::fs::rename(&tmp, dir.join("latest.jpg")));
+            if let Err(e) = written { log::warn!("Could not save the viewer thumbnail in {}: {}", dir.display(), e); }
+        }
+        *latest.lock() = Some(jpeg);
+    }
+}
+
+fn serve(listener: TcpListener, stop: &AtomicBool, latest: &Mutex<Option<Vec<u8>>>, refresh: u32, token: &str) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = answer(stream, latest, refresh, token) { log::debug!("Viewer request failed: {}", e); }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => std::thread::sleep(Duration::from_millis(100)),
+            Err(e) => {
+                log::error!("The viewer stopped serving: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+fn answer(stream: TcpStream, latest: &Mutex<Option<Vec<u8>>>, refresh: u32, token: &str) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let path = line.split_whitespace().nth(1).unwrap_or("/").to_string();
+    // read the headers too: closing with unread input would reset the connection
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 { header.clear(); }
+    let jpeg = latest.lock().clone();
+    let (status, content_type, body) = respond(&path, jpeg, refresh, token);
+    let mut out = &stream;
+    write!(
+        out,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status, content_type, body.len(),
+    )?;
+    out.write_all(&body)?;
+    out.flush()
+}
+
+/// Status line, content type and body for a GET of `path`, which has to carry `token` unless
+/// it's empty.
+pub fn respond(path: &str, jpeg: Option<Vec<u8>>, refresh: u32, token: &str) -> (&'static str, &'static str, Vec<u8>) {
+    let (route, query) = path.split_once('?').unwrap_or((path, ""));
+    let given = query.split('&').find_map(|pair| pair.strip_prefix("token="));
+    if !token.is_empty() && given != Some(token) {
+        return ("403 Forbidden", "text/plain", b"a valid token is needed".to_vec());
+    }
+    let query = if token.is_empty() { String::new() } else { format!("?token={}", token) };
+    match route {
+        "/" => {
+            let page = format!(
+                "<!doctype html><html><head><title>Area Clicker</title><meta http-equiv=\"refresh\" content=\"{}\"></head>\
+                 <body style=\"background:#222;color:#ccc\"><img src=\"/thumbnail.jpg{}\" alt=\"no capture yet\"></body></html>",
+                refresh, query,
+            );
+            ("200 OK", "text/html; charset=utf-8", page.into_bytes())
+        }
+        "/thumbnail.jpg" => match jpeg {
+            Some(jpeg) => ("200 OK", "image/jpeg", jpeg),
+            None => ("404 Not Found", "text/plain", b"no capture yet: nothing is being clicked".to_vec()),
+        },
+        _ => ("404 Not Found", "text/plain", b"not found".to_vec()),
+    }
+}
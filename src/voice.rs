@@ -0,0 +1,125 @@
+//! Voice triggers: a spoken phrase ("clicker stop") as one more way to abort a job when both
+//! hands are busy. Recognition is left to a speech recognizer command running on this machine
+//! (`pocketsphinx_continuous` spotting just the phrase, by default), so no audio leaves it.
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
+use std::process::{Child, Command, Stdio};
+use std::sync::Arc;
+
+/// Spots the stop phrase only; `{phrase}` is filled in.
+pub const DEFAULT_COMMAND: &str =
+    "pocketsphinx_continuous -inmic yes -keyphrase \"{phrase}\" -kws_threshold 1e-20 -logfn /dev/null";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Voice {
+    pub enabled: bool,
+    pub stop_phrase: String,
+    /// Empty = starting by voice is off. Needs a recognizer that reports it too.
+    pub start_phrase: String,
+    /// Recognizer that prints what it hears, a line per utterance; `{phrase}` becomes the stop
+    /// phrase.
+    pub command: String,
+}
+
+impl Default for Voice {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            stop_phrase: "clicker stop".to_string(),
+            start_phrase: String::new(),
+            command: DEFAULT_COMMAND.to_string(),
+        }
+    }
+}
+
+/// What a heard line asks for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Said {
+    Stop,
+    Start,
+}
+
+impl Voice {
+    pub fn command_line(&self) -> String {
+        self.command.replace("{phrase}", &normalize(&self.stop_phrase))
+    }
+
+    /// The phrase in `line`, if any; a line with both counts as stop.
+    pub fn hear(&self, line: &str) -> Option<Said> {
+        if contains_phrase(line, &self.stop_phrase) { return Some(Said::Stop); }
+        contains_phrase(line, &self.start_phrase).then_some(Said::Start)
+    }
+}
+
+/// Lowercase words, punctuation dropped: "Clicker, STOP!" → "clicker stop".
+pub fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether `line` says `phrase` as whole words ("clicker stopped" doesn't say "clicker stop").
+pub fn contains_phrase(line: &str, phrase: &str) -> bool {
+    let phrase = normalize(phrase);
+    !phrase.is_empty() && format!(" {} ", normalize(line)).contains(&format!(" {} ", phrase))
+}
+
+/// A running recognizer, its output read on its own thread. Dropping it ends the recognizer.
+pub struct Listener {
+    child: Child,
+    heard: Arc<Mutex<Vec<String>>>,
+}
+
+impl Listener {
+    /// Run `command`; `wake` is called (from the reading thread) for every line it prints.
+    pub fn start(command: &str, wake: impl Fn() + Send + 'static) -> Result<Self, String> {
+        #[cfg(windows)]
+        let mut cmd = Command::new("cmd");
+        #[cfg(windows)]
+        cmd.args(["/C", command]);
+        #[cfg(not(windows))]
+        let mut cmd = Command::new("sh");
+        #[cfg(not(windows))]
+        cmd.args(["-c", command]);
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Could not run the recognizer: {}", e))?;
+        // a missing recognizer or microphone ends it right away
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        if let Ok(Some(status)) = child.try_wait() {
+            return Err(format!("The recognizer exited ({})", status));
+        }
+        let heard = Arc::new(Mutex::new(Vec::new()));
+        if let Some(stdout) = child.stdout.take() {
+            let heard = Arc::clone(&heard);
+            std::thread::spawn(move || {
+                for line in std::io::BufReader::new(stdout).lines().map_while(Result::ok) {
+                    if line.trim().is_empty() { continue; }
+                    heard.lock().push(line.trim().to_string());
+                    wake();
+                }
+                log::info!("Voice recognizer stopped");
+            });
+        }
+        log::info!("Listening for voice commands: {}", command);
+        Ok(Self { child, heard })
+    }
+
+    /// Lines heard since the last call, oldest first.
+    pub fn take(&self) -> Vec<String> { std::mem::take(&mut *self.heard.lock()) }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
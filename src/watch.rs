@@ -0,0 +1,245 @@
+//! Start conditions: hold the first click until a window or process shows up, and
+//! optionally end the job once it's gone again. Also brings a target window to the front
+//! before clicking.
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum WatchKind { Window, Process }
+
+impl WatchKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            WatchKind::Window => "window title",
+            WatchKind::Process => "process name",
+        }
+    }
+}
+
+/// What to wait for. `pattern` is a case-insensitive regex matched anywhere in the window
+/// title or process name (so a plain name works too).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaitFor {
+    pub kind: WatchKind,
+    pub pattern: String,
+    pub timeout_secs: u32, // 0 = wait forever
+    pub stop_when_gone: bool,
+}
+
+impl Default for WaitFor {
+    fn default() -> Self {
+        Self { kind: WatchKind::Window, pattern: String::new(), timeout_secs: 60, stop_when_gone: false }
+    }
+}
+
+impl WaitFor {
+    pub fn matcher(&self) -> Result<Regex, String> {
+        if self.pattern.trim().is_empty() { return Err("Enter a name or pattern to wait for.".to_string()); }
+        name_regex(&self.pattern)
+    }
+
+    /// Whether a matching window/process currently exists.
+    pub fn is_present(&self, re: &Regex) -> Result<bool, String> {
+        let names = match self.kind {
+            WatchKind::Window => window_titles()?,
+            WatchKind::Process => process_names()?,
+        };
+        Ok(names.iter().any(|n| re.is_match(n)))
+    }
+
+    /// E.g. `window title “Notepad”`.
+    pub fn describe(&self) -> String { format!("{} “{}”", self.kind.label(), self.pattern) }
+}
+
+/// The window to bring to the front before clicking, by title (same matching as [`WaitFor`]).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Refocus {
+    pub pattern: String,
+    /// Refocus before every click instead of only when a step starts.
+    pub every_click: bool,
+}
+
+impl Refocus {
+    pub fn matcher(&self) -> Result<Regex, String> {
+        if self.pattern.trim().is_empty() { return Err("Enter the title of the window to refocus.".to_string()); }
+        name_regex(&self.pattern)
+    }
+}
+
+fn name_regex(pattern: &str) -> Result<Regex, String> {
+    RegexBuilder::new(pattern).case_insensitive(true).build().map_err(|e| e.to_string())
+}
+
+/// What [`activate_window`] did.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Activated {
+    /// The window was already in front.
+    AlreadyActive,
+    Raised,
+    NotFound,
+}
+
+/// Titles of the visible top-level windows.
+pub fn window_titles() -> Result<Vec<String>, String> {
+    Ok(windows()?.into_iter().map(|(_, title)| title).collect())
+}
+
+/// Bring the first visible window whose title matches `re` to the front, restoring it if
+/// it's minimized.
+pub fn activate_window(re: &Regex) -> Result<Activated, String> {
+    let Some((id, title)) = windows()?.into_iter().find(|(_, t)| re.is_match(t)) else {
+        return Ok(Activated::NotFound);
+    };
+    #[cfg(windows)]
+    { win::activate(id, &title) }
+    #[cfg(not(windows))]
+    {
+        if active_window_id().is_some_and(|active| parse_x11_id(&id) == Some(active)) {
+            return Ok(Activated::AlreadyActive);
+        }
+        let out = std::process::Command::new("wmctrl")
+            .args(["-i", "-a", &id])
+            .output()
+            .map_err(|e| format!("Activating windows needs wmctrl ({})", e))?;
+        if !out.status.success() {
+            return Err(format!("wmctrl could not activate “{}”: {}", title, String::from_utf8_lossy(&out.stderr).trim()));
+        }
+        Ok(Activated::Raised)
+    }
+}
+
+/// `(handle, title)` of the visible top-level windows.
+#[cfg(windows)]
+fn windows() -> Result<Vec<(isize, String)>, String> { Ok(win::windows()) }
+
+/// `(id, title)` of the top-level windows; the id is what `wmctrl -i` takes.
+#[cfg(not(windows))]
+fn windows() -> Result<Vec<(String, String)>, String> {
+    // X11 only; there's no general way to list other apps' windows on Wayland
+    let out = std::process::Command::new("wmctrl")
+        .arg("-l")
+        .output()
+        .map_err(|e| format!("Listing windows needs wmctrl ({})", e))?;
+    if !out.status.success() {
+        return Err(format!("wmctrl failed: {}", String::from_utf8_lossy(&out.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&out.stdout).lines().filter_map(parse_wmctrl_line).collect())
+}
+
+/// `(id, title)` from a `wmctrl -l` line: `<id> <desktop> <host> <title>`, where the columns
+/// may be padded with several spaces.
+#[cfg(not(windows))]
+pub fn parse_wmctrl_line(line: &str) -> Option<(String, String)> {
+    let mut rest = line.trim_start();
+    let mut cols = Vec::new();
+    for _ in 0..3 {
+        let (col, tail) = rest.split_once(char::is_whitespace)?;
+        cols.push(col);
+        rest = tail.trim_start();
+    }
+    Some((cols[0].to_string(), rest.trim_end().to_string()))
+}
+
+/// The focused X11 window, via `xprop`. `None` if that can't be told.
+#[cfg(not(windows))]
+fn active_window_id() -> Option<u64> {
+    let out = std::process::Command::new("xprop").args(["-root", "_NET_ACTIVE_WINDOW"]).output().ok()?;
+    // "_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007"
+    parse_x11_id(String::from_utf8_lossy(&out.stdout).rsplit(' ').next()?.trim())
+}
+
+/// `0x03a00007` → `0x3a00007`; wmctrl pads ids, xprop doesn't.
+#[cfg(not(windows))]
+fn parse_x11_id(id: &str) -> Option<u64> {
+    u64::from_str_radix(id.strip_prefix("0x")?, 16).ok()
+}
+
+/// Executable names of the running processes.
+pub fn process_names() -> Result<Vec<String>, String> {
+    #[cfg(windows)]
+    { win::process_names() }
+    #[cfg(target_os = "linux")]
+    {
+        let entries = std::fs::read_dir("/proc").map_err(|e| format!("Could not read /proc: {}", e))?;
+        Ok(entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().bytes().all(|b| b.is_ascii_digit()))
+            .filter_map(|e| std::fs::read_to_string(e.path().join("comm")).ok())
+            .map(|c| c.trim().to_string())
+            .collect())
+    }
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        let out = std::process::Command::new("ps")
+            .args(["-A", "-o", "comm="])
+            .output()
+            .map_err(|e| format!("Could not run ps: {}", e))?;
+        Ok(String::from_utf8_lossy(&out.stdout)
+            .lines()
+            .map(|l| l.rsplit('/').next().unwrap_or(l).trim().to_string())
+            .collect())
+    }
+}
+
+#[cfg(windows)]
+mod win {
+    use windows_sys::Win32::Foundation::{CloseHandle, BOOL, HWND, INVALID_HANDLE_VALUE, LPARAM};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetForegroundWindow, GetWindowTextW, IsIconic, IsWindowVisible, SetForegroundWindow, ShowWindow,
+        SW_RESTORE,
+    };
+
+    use super::Activated;
+
+    unsafe extern "system" fn collect_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let windows = &mut *(lparam as *mut Vec<(isize, String)>);
+        if IsWindowVisible(hwnd) != 0 {
+            let mut buf = [0u16; 256];
+            let len = GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32);
+            if len > 0 { windows.push((hwnd as isize, String::from_utf16_lossy(&buf[..len as usize]))); }
+        }
+        1 // keep enumerating
+    }
+
+    pub fn windows() -> Vec<(isize, String)> {
+        let mut windows: Vec<(isize, String)> = Vec::new();
+        unsafe { EnumWindows(Some(collect_window), &mut windows as *mut Vec<(isize, String)> as LPARAM) };
+        windows
+    }
+
+    pub fn activate(hwnd: isize, title: &str) -> Result<Activated, String> {
+        let hwnd = hwnd as HWND;
+        unsafe {
+            if GetForegroundWindow() == hwnd && IsIconic(hwnd) == 0 { return Ok(Activated::AlreadyActive); }
+            if IsIconic(hwnd) != 0 { ShowWindow(hwnd, SW_RESTORE); }
+            // Windows only lets us take the foreground in some situations (e.g. we own it, or
+            // the user hasn't touched the input since); otherwise it just flashes the taskbar
+            if SetForegroundWindow(hwnd) == 0 || GetForegroundWindow() != hwnd {
+                return Err(format!("Windows would not bring “{}” to the front", title));
+            }
+        }
+        Ok(Activated::Raised)
+    }
+
+    pub fn process_names() -> Result<Vec<String>, String> {
+        let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+        if snapshot == INVALID_HANDLE_VALUE { return Err("Could not list processes".to_string()); }
+        let mut names = Vec::new();
+        let mut entry: PROCESSENTRY32W = unsafe { std::mem::zeroed() };
+        entry.dwSize = std::mem::size_of::<PROCESSENTRY32W>() as u32;
+        let mut more = unsafe { Process32FirstW(snapshot, &mut entry) } != 0;
+        while more {
+            let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+            names.push(String::from_utf16_lossy(&entry.szExeFile[..len]));
+            more = unsafe { Process32NextW(snapshot, &mut entry) } != 0;
+        }
+        unsafe { CloseHandle(snapshot) };
+        Ok(names)
+    }
+}